@@ -1,6 +1,16 @@
 use criterion::{BatchSize, BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
-use primer_scout::{Primer, ScanOptions, scan_sequence};
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use primer_scout::sink::{HitSink, PerPrimerSink, TsvColumns, TsvSink};
+use primer_scout::{
+    DEFAULT_FASTA_LINE_WIDTH, Hit, MemoryProfile, Primer, ScanAlgorithm, ScanOptions, Scanner,
+    XorShift64, scan_references, scan_sequence, write_fasta_record,
+};
+use std::fs::File;
 use std::hint::black_box;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 fn benchmark_engine(c: &mut Criterion) {
     let mut group = c.benchmark_group("scan_sequence");
@@ -17,6 +27,7 @@ fn benchmark_engine(c: &mut Criterion) {
             let options = ScanOptions {
                 max_mismatches: k,
                 scan_reverse_complement: true,
+                ..ScanOptions::default()
             };
             group.bench_with_input(
                 BenchmarkId::new(format!("primers_{count}"), format!("k{k}")),
@@ -38,6 +49,382 @@ fn benchmark_engine(c: &mut Criterion) {
     group.finish();
 }
 
+fn bench_large_panel(c: &mut Criterion) {
+    let sequence_len = 5_000_000usize;
+    let primer_len = 20usize;
+    let primer_counts = [512usize, 1024usize];
+    let sequence = generate_sequence(sequence_len, 7);
+
+    // Criterion allows one throughput unit per group, so the same cases are reported twice:
+    // once as Mbp/sec and once as primers x Mbp/sec.
+    for (group_name, per_primer) in [("large_panel", false), ("large_panel_primer_bases", true)] {
+        let mut group = c.benchmark_group(group_name);
+        group.sample_size(10);
+        group.measurement_time(Duration::from_secs(30));
+
+        for &count in &primer_counts {
+            let primers = generate_primers_from_reference(&sequence, count, primer_len);
+            group.throughput(if per_primer {
+                Throughput::Elements((sequence_len * count) as u64)
+            } else {
+                Throughput::Bytes(sequence_len as u64)
+            });
+            for &k in &[0usize, 1usize, 2usize] {
+                let options = ScanOptions {
+                    max_mismatches: k,
+                    ..ScanOptions::default()
+                };
+                group.bench_with_input(
+                    BenchmarkId::new(format!("primers_{count}"), format!("k{k}")),
+                    &options,
+                    |b, opts| {
+                        b.iter(|| {
+                            let res = scan_sequence(&sequence, "synthetic_chr1", &primers, opts)
+                                .expect("scan");
+                            black_box(res.total_hits);
+                        });
+                    },
+                );
+            }
+        }
+        group.finish();
+    }
+}
+
+/// Exhaustive scan against the Bloom-filter seed prescreen on a CRISPR-library-sized panel.
+fn bench_bloom_prescreen(c: &mut Criterion) {
+    // Small enough that the exhaustive baseline finishes its samples in minutes.
+    let sequence_len = 100_000usize;
+    let primer_len = 24usize;
+    let sequence = generate_sequence(sequence_len, 7);
+    let primers = generate_primers_from_reference(&sequence, 10_000, primer_len);
+
+    let mut group = c.benchmark_group("bloom_prescreen");
+    group.sample_size(10);
+    group.measurement_time(Duration::from_secs(20));
+    group.throughput(Throughput::Bytes(sequence_len as u64));
+    for algorithm in [ScanAlgorithm::Exhaustive, ScanAlgorithm::Bloom] {
+        for &k in &[0usize, 1usize] {
+            let options = ScanOptions {
+                max_mismatches: k,
+                algorithm,
+                ..ScanOptions::default()
+            };
+            group.bench_with_input(
+                BenchmarkId::new(format!("{algorithm:?}").to_lowercase(), format!("k{k}")),
+                &options,
+                |b, opts| {
+                    b.iter(|| {
+                        let res = scan_sequence(&sequence, "synthetic_chr1", &primers, opts)
+                            .expect("scan");
+                        black_box(res.total_hits);
+                    });
+                },
+            );
+        }
+    }
+    group.finish();
+}
+
+/// Parsing a 50k-primer TSV panel against loading its `compile-panel` artifact.
+#[cfg(feature = "bincode")]
+fn bench_panel_load(c: &mut Criterion) {
+    use primer_scout::compiled_panel::{load_compiled_panel, save_compiled_panel};
+    use primer_scout::load_primers;
+
+    let sequence = generate_sequence(1_000_000, 7);
+    let primers = generate_primers_from_reference(&sequence, 50_000, 24);
+    let dir = std::env::temp_dir().join(format!("primer_scout_panel_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("create bench dir");
+    let tsv = dir.join("panel.tsv");
+    let mut out = BufWriter::new(File::create(&tsv).expect("create panel"));
+    writeln!(out, "name\tsequence").expect("write panel");
+    for primer in &primers {
+        writeln!(out, "{}\t{}", primer.name, primer.sequence).expect("write panel");
+    }
+    out.flush().expect("flush panel");
+    drop(out);
+    let compiled = dir.join("panel.psp");
+    save_compiled_panel(&compiled, &primers).expect("compile panel");
+
+    let mut group = c.benchmark_group("panel_load");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(primers.len() as u64));
+    group.bench_function("tsv", |b| {
+        b.iter(|| black_box(load_primers(&tsv).expect("load panel").len()));
+    });
+    group.bench_function("compiled", |b| {
+        b.iter(|| black_box(load_compiled_panel(&compiled).expect("load panel").len()));
+    });
+    group.finish();
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[cfg(not(feature = "bincode"))]
+fn bench_panel_load(_c: &mut Criterion) {}
+
+fn bench_multi_file(c: &mut Criterion) {
+    let total_len = 5_000_000usize;
+    let file_count = 10usize;
+    let sequence = generate_sequence(total_len, 7);
+    let primers = generate_primers_from_reference(&sequence, 128, 20);
+    let options = ScanOptions {
+        max_mismatches: 1,
+        ..ScanOptions::default()
+    };
+
+    let dir = std::env::temp_dir().join(format!("primer_scout_bench_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("create bench dir");
+    let single = vec![write_fasta(&dir.join("single.fa"), &sequence)];
+    let chunk_len = total_len / file_count;
+    let split = (0..file_count)
+        .map(|idx| {
+            let chunk = &sequence[idx * chunk_len..(idx + 1) * chunk_len];
+            write_fasta(&dir.join(format!("part_{idx:02}.fa")), chunk)
+        })
+        .collect::<Vec<_>>();
+
+    let mut group = c.benchmark_group("multi_file");
+    group.sample_size(10);
+    group.throughput(Throughput::Bytes(total_len as u64));
+    for (label, references) in [("1x5mbp", &single), ("10x500kbp", &split)] {
+        group.bench_with_input(BenchmarkId::from_parameter(label), references, |b, refs| {
+            b.iter(|| {
+                let res = scan_references(refs, &primers, &options).expect("scan");
+                black_box(res.total_hits);
+            });
+        });
+    }
+    group.finish();
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+/// Full `scan_references` over the same reference stored plain and gzipped, so the difference
+/// is the gzip decode cost. Throughput counts uncompressed sequence bytes in both cases.
+fn bench_reference_compression(c: &mut Criterion) {
+    let sequence_len = 5_000_000usize;
+    let sequence = generate_sequence(sequence_len, 7);
+    // A small exact-match panel keeps the scan cheap, so reading and decoding dominate.
+    let primers = generate_primers_from_reference(&sequence, 4, 20);
+    let options = ScanOptions {
+        max_mismatches: 0,
+        ..ScanOptions::default()
+    };
+
+    let dir = std::env::temp_dir().join(format!("primer_scout_gzip_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("create bench dir");
+    let plain = dir.join("reference.fa");
+    let gzipped = dir.join("reference.fa.gz");
+    let file = File::create(&plain).expect("create bench fasta");
+    write_reference(&mut BufWriter::new(file), &sequence);
+    let file = File::create(&gzipped).expect("create bench fasta");
+    let mut encoder = GzEncoder::new(file, Compression::default());
+    write_reference(&mut encoder, &sequence);
+    encoder.finish().expect("finish bench fasta");
+
+    let mut group = c.benchmark_group("reference_compression");
+    group.sample_size(10);
+    group.throughput(Throughput::Bytes(sequence_len as u64));
+    for (label, reference) in [("plain", plain), ("gzip", gzipped)] {
+        let references = vec![reference];
+        group.bench_with_input(
+            BenchmarkId::from_parameter(label),
+            &references,
+            |b, refs| {
+                b.iter(|| {
+                    let res = scan_references(refs, &primers, &options).expect("scan");
+                    black_box(res.total_hits);
+                });
+            },
+        );
+    }
+    group.finish();
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+fn bench_memory_profile(c: &mut Criterion) {
+    let sequence_len = 1_000_000usize;
+    let sequence = generate_sequence(sequence_len, 7);
+    let primers = generate_primers_from_reference(&sequence, 128, 20);
+
+    let mut group = c.benchmark_group("memory_profile");
+    group.throughput(Throughput::Bytes(sequence_len as u64));
+    for (label, profile) in [("fast", MemoryProfile::Fast), ("lean", MemoryProfile::Lean)] {
+        let options = ScanOptions {
+            max_mismatches: 1,
+            memory_profile: profile,
+            ..ScanOptions::default()
+        };
+        group.bench_with_input(BenchmarkId::new(label, "k1"), &options, |b, opts| {
+            b.iter(|| {
+                let res = scan_sequence(&sequence, "synthetic_chr1", &primers, opts).expect("scan");
+                black_box(res.total_hits);
+            });
+        });
+    }
+    group.finish();
+}
+
+fn bench_short_sequences(c: &mut Criterion) {
+    let read_len = 300usize;
+    let read_count = 10_000usize;
+    let reference = generate_sequence(read_len * read_count, 11);
+    let reads = reference
+        .as_bytes()
+        .chunks(read_len)
+        .map(|chunk| String::from_utf8(chunk.to_vec()).expect("ascii read"))
+        .collect::<Vec<_>>();
+    let primers = generate_primers_from_reference(&reference, 32, 20);
+    let options = ScanOptions {
+        max_mismatches: 1,
+        ..ScanOptions::default()
+    };
+    let scanner = Scanner::new(primers.clone(), options.clone()).expect("scanner");
+
+    let mut group = c.benchmark_group("short_sequences");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(read_count as u64));
+    group.bench_function("scan_sequence_per_read", |b| {
+        b.iter(|| {
+            for read in &reads {
+                let res = scan_sequence(read, "read", &primers, &options).expect("scan");
+                black_box(res.total_hits);
+            }
+        });
+    });
+    group.bench_function("scanner_per_read", |b| {
+        b.iter(|| {
+            for read in &reads {
+                let res = scanner.scan("read", read.as_bytes()).expect("scan");
+                black_box(res.total_hits);
+            }
+        });
+    });
+    group.finish();
+}
+
+fn bench_sort_output(c: &mut Criterion) {
+    let sequence_len = 2_000_000usize;
+    let sequence = generate_sequence(sequence_len, 7);
+    // Short primers at k=2 produce hundreds of thousands of hits, so the sort is measurable.
+    let primers = generate_primers_from_reference(&sequence, 32, 10);
+
+    let dir = std::env::temp_dir().join(format!("primer_scout_sort_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("create bench dir");
+    let references = vec![write_fasta(&dir.join("reference.fa"), &sequence)];
+
+    let mut group = c.benchmark_group("sort_output");
+    group.sample_size(10);
+    group.throughput(Throughput::Bytes(sequence_len as u64));
+    for (label, sort_output) in [("sorted", true), ("unsorted", false)] {
+        let options = ScanOptions {
+            max_mismatches: 2,
+            sort_output,
+            ..ScanOptions::default()
+        };
+        group.bench_with_input(BenchmarkId::from_parameter(label), &options, |b, opts| {
+            b.iter(|| {
+                let res = scan_references(&references, &primers, opts).expect("scan");
+                black_box(res.hits.len());
+            });
+        });
+    }
+    group.finish();
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+fn bench_per_primer_output(c: &mut Criterion) {
+    const PRIMERS: usize = 128;
+    const HITS: usize = 100_000;
+    let primer = Primer::from_name_and_sequence("p", "ACGTTGCAACGTAGGCTTAC").expect("primer");
+    let template = scan_sequence(
+        "ACGTTGCAACGTAGGCTTAC",
+        "synthetic_chr1",
+        &[primer],
+        &ScanOptions::default(),
+    )
+    .expect("scan")
+    .hits
+    .remove(0);
+    // Sorted like scan output: primer-major within the contig.
+    let hits = (0..HITS)
+        .map(|idx| Hit {
+            primer: format!("primer_{:03}", idx * PRIMERS / HITS),
+            start: idx * 10,
+            end: idx * 10 + 20,
+            ..template.clone()
+        })
+        .collect::<Vec<_>>();
+
+    let dir = std::env::temp_dir().join(format!("primer_scout_output_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("create bench dir");
+    let file_sink = |dir: &Path| {
+        PerPrimerSink::new(
+            dir.to_path_buf(),
+            "tsv",
+            Box::new(|out| Box::new(TsvSink::new(out, TsvColumns::default(), false))),
+        )
+    };
+    let write_all = |sink: &mut dyn HitSink| {
+        for hit in &hits {
+            sink.on_hit(hit).expect("write hit");
+        }
+        sink.finish().expect("finish");
+    };
+
+    let mut group = c.benchmark_group("per_primer_output");
+    group.sample_size(20);
+    group.throughput(Throughput::Elements(HITS as u64));
+    group.bench_function("single_file", |b| {
+        b.iter(|| {
+            let file = File::create(dir.join("all.tsv")).expect("create output");
+            let mut sink = TsvSink::new(BufWriter::new(file), TsvColumns::default(), false);
+            write_all(&mut sink);
+        });
+    });
+    group.bench_function("file_per_primer", |b| {
+        b.iter(|| write_all(&mut file_sink(&dir)));
+    });
+    #[cfg(feature = "async-output")]
+    group.bench_function("file_per_primer_async", |b| {
+        b.iter(|| {
+            let mut sink =
+                primer_scout::sink::ChannelSink::new(4, 4096, |_| Box::new(file_sink(&dir)));
+            write_all(&mut sink);
+        });
+    });
+    group.finish();
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+fn write_fasta(path: &Path, sequence: &str) -> PathBuf {
+    let mut out = BufWriter::new(File::create(path).expect("create bench fasta"));
+    writeln!(out, ">synthetic_chr1").expect("write header");
+    for line in sequence.as_bytes().chunks(80) {
+        out.write_all(line).expect("write sequence");
+        out.write_all(b"\n").expect("write newline");
+    }
+    out.flush().expect("flush bench fasta");
+    path.to_path_buf()
+}
+
+/// One `synthetic_chr1` record written like `gen-synthetic` does.
+fn write_reference<W: Write>(out: &mut W, sequence: &str) {
+    write_fasta_record(
+        out,
+        "synthetic_chr1",
+        sequence.as_bytes(),
+        DEFAULT_FASTA_LINE_WIDTH,
+    )
+    .expect("write bench fasta");
+    out.flush().expect("flush bench fasta");
+}
+
 fn generate_sequence(len: usize, seed: u64) -> String {
     const BASES: [u8; 4] = [b'A', b'C', b'G', b'T'];
     let mut rng = XorShift64::new(seed);
@@ -87,31 +474,17 @@ fn mutate_base(current: u8, rng: &mut XorShift64) -> u8 {
     b'A'
 }
 
-#[derive(Debug, Clone)]
-struct XorShift64 {
-    state: u64,
-}
-
-impl XorShift64 {
-    fn new(seed: u64) -> Self {
-        Self {
-            state: if seed == 0 {
-                0x9E37_79B9_7F4A_7C15
-            } else {
-                seed
-            },
-        }
-    }
-
-    fn next_u32(&mut self) -> u32 {
-        let mut x = self.state;
-        x ^= x << 13;
-        x ^= x >> 7;
-        x ^= x << 17;
-        self.state = x;
-        (x >> 32) as u32
-    }
-}
-
-criterion_group!(benches, benchmark_engine);
+criterion_group!(
+    benches,
+    benchmark_engine,
+    bench_large_panel,
+    bench_bloom_prescreen,
+    bench_panel_load,
+    bench_multi_file,
+    bench_reference_compression,
+    bench_memory_profile,
+    bench_short_sequences,
+    bench_sort_output,
+    bench_per_primer_output
+);
 criterion_main!(benches);
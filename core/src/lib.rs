@@ -0,0 +1,390 @@
+//! Allocation-only matching core of primer-scout: IUPAC base masks, primer normalization and
+//! mask construction, and per-window mismatch counting.
+//!
+//! The crate is `#![no_std]` and needs only `alloc`, so it builds for targets without a file
+//! system or threads (embedded devices, WASM). Reading references, parallel scanning, and
+//! output live in the `primer-scout` crate, which re-exports this one as `primer_scout::core`.
+
+#![no_std]
+
+extern crate alloc;
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// Mask of a base allowing all of A, C, G, and T (`N`).
+pub const ANY_BASE_MASK: u8 = 0b1111;
+
+/// Why a sequence could not be turned into a primer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SequenceError {
+    /// A character that is neither an IUPAC nucleotide code nor `U`.
+    UnsupportedBase(char),
+    /// Nothing but whitespace.
+    Empty,
+}
+
+impl fmt::Display for SequenceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnsupportedBase(base) => {
+                write!(f, "unsupported base '{base}' in primer sequence")
+            }
+            Self::Empty => f.write_str("primer sequence must not be empty"),
+        }
+    }
+}
+
+impl core::error::Error for SequenceError {}
+
+/// What [`normalize_sequence`] changed in the input to get the scanned sequence.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SequenceNormalization {
+    /// `U` bases read as `T` (RNA input).
+    pub uracils_converted: usize,
+    /// Lowercase bases uppercased.
+    pub lowercase_bases: usize,
+    /// Whitespace characters removed.
+    pub whitespace_removed: usize,
+}
+
+impl SequenceNormalization {
+    pub fn changed(&self) -> bool {
+        *self != Self::default()
+    }
+}
+
+/// A primer's normalized sequence with the masks the window loops test against.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrimerMasks {
+    pub sequence: String,
+    pub reverse_complement: String,
+    pub normalization: SequenceNormalization,
+    /// One IUPAC mask per base of `sequence`.
+    pub masks: Vec<u8>,
+    /// Forward mask in the low nibble and reverse-complement mask in the high nibble, so one
+    /// pass over the reference tests both strands.
+    pub combined_masks: Vec<u8>,
+    pub is_palindromic: bool,
+}
+
+impl PrimerMasks {
+    /// Normalizes `raw` (see [`normalize_sequence`]) and builds both strands' masks.
+    pub fn from_sequence(raw: &str) -> Result<Self, SequenceError> {
+        let (sequence, normalization) = normalize_sequence(raw)?;
+        if sequence.is_empty() {
+            return Err(SequenceError::Empty);
+        }
+        let reverse_complement = reverse_complement(&sequence)?;
+        let masks = to_masks(&sequence)?;
+        let combined_masks = combine_masks(&masks, &to_masks(&reverse_complement)?);
+        Ok(Self {
+            is_palindromic: sequence == reverse_complement,
+            sequence,
+            reverse_complement,
+            normalization,
+            masks,
+            combined_masks,
+        })
+    }
+}
+
+/// Uppercases `raw`, reads `U` as `T`, and drops whitespace; every remaining character must
+/// be an IUPAC nucleotide code.
+pub fn normalize_sequence(raw: &str) -> Result<(String, SequenceNormalization), SequenceError> {
+    let mut normalized = String::with_capacity(raw.len());
+    let mut changes = SequenceNormalization::default();
+    for ch in raw.chars() {
+        if ch.is_whitespace() {
+            changes.whitespace_removed += 1;
+            continue;
+        }
+        if !ch.is_ascii() || iupac_mask(ch as u8).is_none() {
+            return Err(SequenceError::UnsupportedBase(ch));
+        }
+        changes.uracils_converted += usize::from(matches!(ch, 'u' | 'U'));
+        changes.lowercase_bases += usize::from(ch.is_ascii_lowercase());
+        normalized.push(normalize_base(ch as u8) as char);
+    }
+    Ok((normalized, changes))
+}
+
+pub fn reverse_complement(sequence: &str) -> Result<String, SequenceError> {
+    sequence
+        .bytes()
+        .rev()
+        .map(|base| {
+            complement_base(base)
+                .map(char::from)
+                .ok_or(SequenceError::UnsupportedBase(char::from(base)))
+        })
+        .collect()
+}
+
+/// One IUPAC mask per base of `sequence`.
+pub fn to_masks(sequence: &str) -> Result<Vec<u8>, SequenceError> {
+    sequence
+        .bytes()
+        .map(|base| iupac_mask(base).ok_or(SequenceError::UnsupportedBase(char::from(base))))
+        .collect()
+}
+
+/// Packs forward and reverse-complement masks into one byte per position (forward low nibble).
+pub fn combine_masks(forward: &[u8], reverse: &[u8]) -> Vec<u8> {
+    forward
+        .iter()
+        .zip(reverse)
+        .map(|(&forward, &reverse)| forward | (reverse << 4))
+        .collect()
+}
+
+/// Uppercases `base` and reads `U` as `T`.
+pub fn normalize_base(base: u8) -> u8 {
+    match base {
+        b'u' | b'U' => b'T',
+        _ => base.to_ascii_uppercase(),
+    }
+}
+
+pub fn complement_base(base: u8) -> Option<u8> {
+    match normalize_base(base) {
+        b'A' => Some(b'T'),
+        b'C' => Some(b'G'),
+        b'G' => Some(b'C'),
+        b'T' => Some(b'A'),
+        b'R' => Some(b'Y'),
+        b'Y' => Some(b'R'),
+        b'S' => Some(b'S'),
+        b'W' => Some(b'W'),
+        b'K' => Some(b'M'),
+        b'M' => Some(b'K'),
+        b'B' => Some(b'V'),
+        b'D' => Some(b'H'),
+        b'H' => Some(b'D'),
+        b'V' => Some(b'B'),
+        b'N' => Some(b'N'),
+        _ => None,
+    }
+}
+
+/// The IUPAC code of a mask's low nibble; the inverse of [`iupac_mask`], with `N` for an
+/// empty mask.
+pub fn iupac_from_mask(mask: u8) -> u8 {
+    match mask & ANY_BASE_MASK {
+        0b0001 => b'A',
+        0b0010 => b'C',
+        0b0100 => b'G',
+        0b1000 => b'T',
+        0b0101 => b'R',
+        0b1010 => b'Y',
+        0b0110 => b'S',
+        0b1001 => b'W',
+        0b1100 => b'K',
+        0b0011 => b'M',
+        0b1110 => b'B',
+        0b1101 => b'D',
+        0b1011 => b'H',
+        0b0111 => b'V',
+        _ => b'N',
+    }
+}
+
+/// The IUPAC letter for a set of bases (A = 1, C = 2, G = 4, T = 8); the exact inverse of
+/// [`iupac_mask`]. `None` for the empty set and for masks with bits above the low nibble.
+pub fn mask_to_iupac(mask: u8) -> Option<char> {
+    match mask {
+        1..=ANY_BASE_MASK => Some(char::from(iupac_from_mask(mask))),
+        _ => None,
+    }
+}
+
+/// Bit set of the bases an IUPAC code allows: A = 1, C = 2, G = 4, T = 8.
+pub fn iupac_mask(base: u8) -> Option<u8> {
+    match normalize_base(base) {
+        b'A' => Some(0b0001),
+        b'C' => Some(0b0010),
+        b'G' => Some(0b0100),
+        b'T' => Some(0b1000),
+        b'R' => Some(0b0101),
+        b'Y' => Some(0b1010),
+        b'S' => Some(0b0110),
+        b'W' => Some(0b1001),
+        b'K' => Some(0b1100),
+        b'M' => Some(0b0011),
+        b'B' => Some(0b1110),
+        b'D' => Some(0b1101),
+        b'H' => Some(0b1011),
+        b'V' => Some(0b0111),
+        b'N' => Some(ANY_BASE_MASK),
+        _ => None,
+    }
+}
+
+/// Mismatches of the window at `start` against `masks` (one strand), exact up to
+/// `max_mismatches + 1`. `reference_mask(idx)` is the reference mask at `idx`.
+#[inline]
+pub fn forward_mismatches(
+    reference_mask: impl Fn(usize) -> u8,
+    start: usize,
+    masks: &[u8],
+    max_mismatches: usize,
+) -> usize {
+    let mut mismatches = 0usize;
+    for (offset, &query_mask) in masks.iter().enumerate() {
+        if query_mask & reference_mask(start + offset) == 0 {
+            mismatches += 1;
+            if mismatches > max_mismatches {
+                break;
+            }
+        }
+    }
+    mismatches
+}
+
+/// Forward and reverse mismatch counts of the window at `start`, each exact up to
+/// `max_mismatches + 1`. Both strands are tested together until one exceeds the budget; the
+/// other then finishes alone.
+#[inline]
+pub fn window_mismatches(
+    reference_mask: impl Fn(usize) -> u8,
+    start: usize,
+    combined_masks: &[u8],
+    max_mismatches: usize,
+) -> (usize, usize) {
+    let (mut forward, mut reverse) = (0usize, 0usize);
+    let mut offsets = combined_masks.iter().enumerate();
+    for (offset, &query_mask) in offsets.by_ref() {
+        // Replicate the reference mask into both nibbles and test both strands at once.
+        let shared = query_mask & (reference_mask(start + offset) * 0x11);
+        forward += usize::from(shared & 0x0f == 0);
+        reverse += usize::from(shared & 0xf0 == 0);
+        if forward > max_mismatches || reverse > max_mismatches {
+            break;
+        }
+    }
+    let (survivor, shift) = match (forward > max_mismatches, reverse > max_mismatches) {
+        (true, false) => (&mut reverse, 4),
+        (false, true) => (&mut forward, 0),
+        _ => return (forward, reverse),
+    };
+    for (offset, &query_mask) in offsets {
+        if (query_mask >> shift) & 0x0f & reference_mask(start + offset) == 0 {
+            *survivor += 1;
+            if *survivor > max_mismatches {
+                break;
+            }
+        }
+    }
+    (forward, reverse)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reference_masks(sequence: &str) -> Vec<u8> {
+        sequence
+            .bytes()
+            .map(|base| iupac_mask(base).unwrap_or(ANY_BASE_MASK))
+            .collect()
+    }
+
+    #[test]
+    fn primer_masks_normalize_and_cover_both_strands() {
+        let primer = PrimerMasks::from_sequence(" acgu Rn").expect("primer");
+        assert_eq!(primer.sequence, "ACGTRN");
+        assert_eq!(primer.reverse_complement, "NYACGT");
+        assert_eq!(
+            primer.masks,
+            [0b0001, 0b0010, 0b0100, 0b1000, 0b0101, 0b1111]
+        );
+        assert_eq!(primer.combined_masks[0], 0b0001 | (0b1111 << 4));
+        assert_eq!(
+            primer.normalization,
+            SequenceNormalization {
+                uracils_converted: 1,
+                lowercase_bases: 5,
+                whitespace_removed: 2,
+            }
+        );
+        assert!(!primer.is_palindromic);
+        assert!(
+            PrimerMasks::from_sequence("ACGT")
+                .expect("primer")
+                .is_palindromic
+        );
+
+        assert_eq!(
+            PrimerMasks::from_sequence("ACXT"),
+            Err(SequenceError::UnsupportedBase('X'))
+        );
+        assert_eq!(
+            PrimerMasks::from_sequence("AC\u{0141}T"),
+            Err(SequenceError::UnsupportedBase('\u{0141}'))
+        );
+        assert_eq!(PrimerMasks::from_sequence(" \t"), Err(SequenceError::Empty));
+    }
+
+    #[test]
+    fn iupac_codes_round_trip_through_masks() {
+        for &code in b"ACGTRYSWKMBDHVN" {
+            let mask = iupac_mask(code).expect("IUPAC code");
+            assert_eq!(iupac_from_mask(mask), code);
+            let complement = complement_base(code).expect("complement");
+            assert_eq!(complement_base(complement), Some(code));
+        }
+        assert_eq!(iupac_mask(b'u'), iupac_mask(b'T'));
+        assert_eq!(iupac_mask(b'-'), None);
+    }
+
+    #[test]
+    fn mask_to_iupac_inverts_iupac_mask_for_every_mask() {
+        for mask in 0..=ANY_BASE_MASK {
+            match mask_to_iupac(mask) {
+                Some(code) => assert_eq!(iupac_mask(code as u8), Some(mask), "{code}"),
+                None => assert_eq!(mask, 0),
+            }
+        }
+        for &code in b"ACGTRYSWKMBDHVN" {
+            assert_eq!(
+                iupac_mask(code).and_then(mask_to_iupac),
+                Some(char::from(code))
+            );
+        }
+        assert_eq!(mask_to_iupac(0b0001_0001), None);
+    }
+
+    #[test]
+    fn window_mismatches_match_per_strand_counts() {
+        let reference = reference_masks("TTGATTACATTTGTAATCAA");
+        let primer = PrimerMasks::from_sequence("GATTACA").expect("primer");
+        let mask_at = |idx: usize| reference[idx];
+        for start in 0..=reference.len() - primer.masks.len() {
+            for max_mismatches in 0..3 {
+                let reverse_masks = primer
+                    .combined_masks
+                    .iter()
+                    .map(|mask| mask >> 4)
+                    .collect::<Vec<_>>();
+                let expected = (
+                    forward_mismatches(mask_at, start, &primer.masks, max_mismatches),
+                    forward_mismatches(mask_at, start, &reverse_masks, max_mismatches),
+                );
+                let combined =
+                    window_mismatches(mask_at, start, &primer.combined_masks, max_mismatches);
+                assert_eq!(combined, expected, "start {start}, k {max_mismatches}");
+            }
+        }
+        // GATTACA at 2 on `+`, its reverse complement TGTAATC at 11 on `-`.
+        assert_eq!(
+            window_mismatches(mask_at, 2, &primer.combined_masks, 0).0,
+            0
+        );
+        assert_eq!(
+            window_mismatches(mask_at, 11, &primer.combined_masks, 0).1,
+            0
+        );
+    }
+}
@@ -0,0 +1,90 @@
+use anyhow::{Context, Result, bail};
+use std::collections::HashMap;
+use std::io::BufRead;
+use std::path::Path;
+
+use crate::open_reader;
+
+/// Source-to-canonical contig names (e.g. `1` and `NC_000001.11` to `chr1`), so hits from
+/// references with different naming conventions can be joined.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ContigAliases {
+    canonical: HashMap<String, String>,
+}
+
+impl ContigAliases {
+    /// Builds a table from `(source, canonical)` pairs; a later pair for the same source wins.
+    pub fn from_pairs<I, S>(pairs: I) -> Self
+    where
+        I: IntoIterator<Item = (S, S)>,
+        S: Into<String>,
+    {
+        Self {
+            canonical: pairs
+                .into_iter()
+                .map(|(source, canonical)| (source.into(), canonical.into()))
+                .collect(),
+        }
+    }
+
+    /// Canonical name of `contig`, or `None` when the table does not list it.
+    pub fn canonical(&self, contig: &str) -> Option<&str> {
+        self.canonical.get(contig).map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.canonical.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.canonical.is_empty()
+    }
+}
+
+/// Reads a `source<tab>canonical` TSV. An optional `source<tab>canonical` header, blank lines,
+/// and `#` comments are skipped; a source listed twice with different names is an error.
+pub fn load_contig_aliases(path: &Path) -> Result<ContigAliases> {
+    let reader = open_reader(path)?;
+    let mut canonical: HashMap<String, String> = HashMap::new();
+
+    for (line_idx, line) in reader.lines().enumerate() {
+        let line = line
+            .with_context(|| format!("failed reading contig aliases file '{}'", path.display()))?;
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let Some((source, name)) = trimmed.split_once('\t') else {
+            bail!(
+                "contig aliases line {} in '{}' must be source<tab>canonical",
+                line_idx + 1,
+                path.display()
+            );
+        };
+        let (source, name) = (source.trim(), name.trim());
+        if line_idx == 0
+            && source.eq_ignore_ascii_case("source")
+            && name.eq_ignore_ascii_case("canonical")
+        {
+            continue;
+        }
+        if source.is_empty() || name.is_empty() || name.contains('\t') {
+            bail!(
+                "contig aliases line {} in '{}' must have one non-empty source and canonical name",
+                line_idx + 1,
+                path.display()
+            );
+        }
+        if let Some(previous) = canonical.insert(source.to_string(), name.to_string())
+            && previous != name
+        {
+            bail!(
+                "contig aliases line {} in '{}' maps '{source}' to '{name}', but an earlier line \
+                 maps it to '{previous}'",
+                line_idx + 1,
+                path.display()
+            );
+        }
+    }
+    Ok(ContigAliases { canonical })
+}
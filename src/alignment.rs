@@ -0,0 +1,74 @@
+use crate::Hit;
+
+/// Alignment gap characters removed by [`crate::ScanOptions::degap`].
+pub(crate) fn is_gap(base: u8) -> bool {
+    matches!(base, b'-' | b'.')
+}
+
+/// Gap columns stripped from one aligned record, so ungapped positions can be mapped back to
+/// alignment columns.
+#[derive(Debug, Default)]
+pub(crate) struct GapMap {
+    /// `(ungapped position, gap columns before it)` at each position preceded by a gap run,
+    /// ascending by position.
+    shifts: Vec<(usize, usize)>,
+    gaps: usize,
+}
+
+impl GapMap {
+    /// Appends the non-gap bytes of `fragment` to `out`, recording every gap run relative to
+    /// `ungapped_len`, the number of bases kept before this fragment.
+    pub(crate) fn degap_into(&mut self, fragment: &[u8], ungapped_len: usize, out: &mut Vec<u8>) {
+        out.clear();
+        for &base in fragment {
+            if is_gap(base) {
+                self.gaps += 1;
+                let position = ungapped_len + out.len();
+                match self.shifts.last_mut() {
+                    Some((last, total)) if *last == position => *total = self.gaps,
+                    _ => self.shifts.push((position, self.gaps)),
+                }
+            } else {
+                out.push(base);
+            }
+        }
+    }
+
+    /// Alignment column of the base at ungapped `position`.
+    pub(crate) fn column(&self, position: usize) -> usize {
+        let idx = self.shifts.partition_point(|(shift, _)| *shift <= position);
+        position + idx.checked_sub(1).map_or(0, |idx| self.shifts[idx].1)
+    }
+
+    /// Rewrites a hit's half-open interval to alignment columns. The end follows the last
+    /// matched base, so gap columns inside the hit are covered and trailing ones are not.
+    pub(crate) fn translate_hit(&self, hit: &mut Hit) {
+        let end = self.column(hit.end - 1) + 1;
+        hit.start = self.column(hit.start);
+        hit.end = end;
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.shifts.clear();
+        self.gaps = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn columns_skip_leading_inner_and_split_gap_runs() {
+        let mut map = GapMap::default();
+        let mut out = Vec::new();
+        // Alignment columns: --AC-G | T..A (split across two fragments).
+        map.degap_into(b"--AC-G", 0, &mut out);
+        assert_eq!(out, b"ACG");
+        map.degap_into(b"T..A", 3, &mut out);
+        assert_eq!(out, b"TA");
+
+        let columns = (0..5).map(|pos| map.column(pos)).collect::<Vec<_>>();
+        assert_eq!(columns, vec![2, 3, 5, 6, 9]);
+    }
+}
@@ -0,0 +1,189 @@
+use anyhow::Result;
+use std::io::Write;
+use std::path::Path;
+
+use crate::Hit;
+use crate::pairing::product_pairs;
+use crate::regions::{NamedRegions, load_named_bed};
+
+/// A product predicted from a converging primer pair of a pair panel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Amplicon {
+    pub file: String,
+    pub contig: String,
+    /// Pair name without the forward/reverse suffix.
+    pub target: String,
+    /// 0-based, half-open span from the left primer's first base to the right primer's last.
+    pub start: usize,
+    pub end: usize,
+    /// Strand of the forward primer: `+` when it binds the left end, `-` when the reverse
+    /// primer does.
+    pub strand: char,
+    /// Length of the primer-binding site at each end.
+    pub left_primer_len: usize,
+    pub right_primer_len: usize,
+    /// Mismatches of both primer hits together.
+    pub mismatches: usize,
+    /// Names of the exclusion intervals the amplicon overlaps (see [`annotate_exclusions`]).
+    pub exclusions: Vec<String>,
+}
+
+impl Amplicon {
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// The insert between the two primer-binding sites; empty when they touch or overlap.
+    pub fn insert(&self) -> (usize, usize) {
+        let insert_start = self.start + self.left_primer_len;
+        let insert_end = self.end - self.right_primer_len;
+        if insert_start < insert_end {
+            (insert_start, insert_end)
+        } else {
+            (self.start, self.start)
+        }
+    }
+}
+
+/// Amplicons of a pair panel's hits (annotated with the same suffixes): each `+` hit paired
+/// with the nearest converging partner hit within `max_amplicon_size` bp, in either
+/// orientation. Sorted by file, contig, start, end, strand, then target.
+pub fn predicted_amplicons(
+    hits: &[Hit],
+    forward_suffix: &str,
+    reverse_suffix: &str,
+    max_amplicon_size: usize,
+) -> Vec<Amplicon> {
+    let mut amplicons = product_pairs(hits, forward_suffix, reverse_suffix, max_amplicon_size)
+        .into_iter()
+        .map(|pair| {
+            let (left, right) = (&hits[pair.left], &hits[pair.right]);
+            Amplicon {
+                file: left.file.clone(),
+                contig: left.contig.clone(),
+                target: pair.target,
+                start: left.start,
+                end: right.end,
+                strand: pair.strand,
+                left_primer_len: left.end - left.start,
+                right_primer_len: right.end - right.start,
+                mismatches: left.mismatches + right.mismatches,
+                exclusions: Vec::new(),
+            }
+        })
+        .collect::<Vec<_>>();
+    amplicons.sort_by(|a, b| {
+        (&a.file, &a.contig, a.start, a.end, a.strand, &a.target)
+            .cmp(&(&b.file, &b.contig, b.start, b.end, b.strand, &b.target))
+    });
+    amplicons
+}
+
+/// Reads exclusion intervals from a BED file; lines without a `name` column are named
+/// `contig:start-end`.
+pub fn load_exclusion_regions(path: &Path) -> Result<NamedRegions> {
+    load_named_bed(path, false)
+}
+
+/// Sets each amplicon's `exclusions` to the exclusion intervals its span overlaps. Returns
+/// how many amplicons overlap at least one.
+pub fn annotate_exclusions(amplicons: &mut [Amplicon], regions: &NamedRegions) -> usize {
+    for amplicon in amplicons.iter_mut() {
+        amplicon.exclusions = regions.overlapping(&amplicon.contig, amplicon.start, amplicon.end);
+    }
+    amplicons
+        .iter()
+        .filter(|amplicon| !amplicon.exclusions.is_empty())
+        .count()
+}
+
+/// Writes amplicons as BED12: the insert is the thick part, and the two primer-binding sites
+/// are the blocks, so genome browsers draw the primers as boxes joined by a line. Primers that
+/// touch or overlap give one block spanning the amplicon and an empty thick part. The score
+/// is `0`.
+pub fn write_amplicons_bed<W: Write>(out: &mut W, amplicons: &[Amplicon]) -> std::io::Result<()> {
+    for amplicon in amplicons {
+        let (thick_start, thick_end) = amplicon.insert();
+        write!(
+            out,
+            "{}\t{}\t{}\t{}\t0\t{}\t{thick_start}\t{thick_end}\t0",
+            amplicon.contig, amplicon.start, amplicon.end, amplicon.target, amplicon.strand
+        )?;
+        if thick_start < thick_end {
+            writeln!(
+                out,
+                "\t2\t{},{},\t0,{},",
+                amplicon.left_primer_len,
+                amplicon.right_primer_len,
+                amplicon.len() - amplicon.right_primer_len
+            )?;
+        } else {
+            writeln!(out, "\t1\t{},\t0,", amplicon.len())?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn amplicon(start: usize, end: usize) -> Amplicon {
+        Amplicon {
+            file: "ref.fa".to_string(),
+            contig: "chr1".to_string(),
+            target: "egfr".to_string(),
+            start,
+            end,
+            strand: '+',
+            left_primer_len: 10,
+            right_primer_len: 10,
+            mismatches: 0,
+            exclusions: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn exclusions_annotate_straddling_and_containing_products_only() {
+        let regions = NamedRegions::from_intervals([
+            ("chr1", 90, 120, "pseudo_a"),
+            ("chr1", 300, 310, "segdup"),
+            ("chr1", 500, 900, "pseudo_b"),
+            ("chr2", 0, 1000, "other_contig"),
+        ]);
+        let mut amplicons = vec![
+            // Straddles the end of pseudo_a.
+            amplicon(100, 200),
+            // Contains segdup.
+            amplicon(250, 400),
+            // Lies inside pseudo_b.
+            amplicon(600, 700),
+            // Touches pseudo_a and segdup without sharing a base.
+            amplicon(120, 300),
+        ];
+
+        assert_eq!(annotate_exclusions(&mut amplicons, &regions), 3);
+        let exclusions = amplicons
+            .iter()
+            .map(|amplicon| amplicon.exclusions.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            exclusions,
+            vec![
+                vec!["pseudo_a".to_string()],
+                vec!["segdup".to_string()],
+                vec!["pseudo_b".to_string()],
+                Vec::new(),
+            ]
+        );
+        assert_eq!(
+            regions.overlapping("chr1", 80, 305),
+            vec!["pseudo_a".to_string(), "segdup".to_string()]
+        );
+        assert!(regions.overlapping("chr3", 0, 1000).is_empty());
+    }
+}
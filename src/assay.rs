@@ -0,0 +1,218 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::{Hit, Primer};
+
+/// Overall call for one assay, in order of severity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AssayVerdict {
+    /// A component oligo has no hit (or is absent from the panel).
+    MissingComponent,
+    /// Every component binds, but no converging pair forms a product.
+    NoProduct,
+    /// More than one product is predicted.
+    OffTarget,
+    /// One product, but some component only binds with mismatches.
+    Mismatched,
+    /// One product from perfectly matching components.
+    Pass,
+}
+
+impl fmt::Display for AssayVerdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::MissingComponent => "missing_component",
+            Self::NoProduct => "no_product",
+            Self::OffTarget => "off_target",
+            Self::Mismatched => "mismatched",
+            Self::Pass => "pass",
+        })
+    }
+}
+
+/// One assay (a named primer pair) rolled up from its component hits.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AssayRow {
+    pub assay: String,
+    /// Expected component oligos: `<assay><forward_suffix>`, `<assay><reverse_suffix>`.
+    pub components: Vec<String>,
+    /// Components without a hit, including ones missing from the panel.
+    pub missing_components: Vec<String>,
+    pub all_found: bool,
+    /// Largest of the components' best (fewest) mismatch counts; `None` unless all are found.
+    pub worst_mismatches: Option<usize>,
+    /// Size of the predicted product with the fewest forward-primer mismatches (then the
+    /// shortest); `None` when no forward-orientation product is sized.
+    pub product_size: Option<usize>,
+    /// Converging primer pairs within the amplicon limit, in either orientation.
+    pub products: usize,
+    /// Products beyond the one intended product.
+    pub off_target_products: usize,
+    pub verdict: AssayVerdict,
+}
+
+/// Rolls per-oligo hits of a pair panel up to one row per assay, in panel order.
+///
+/// `hits` must carry the pairing annotations of `ScanOptions::pair_suffix_pattern` with the
+/// same suffixes: a product is a `+` hit flagged `amplifiable`, and its size is only known for
+/// forward-primer `+` hits (`amplicon_size`).
+pub fn assay_rollup(
+    primers: &[Primer],
+    hits: &[Hit],
+    forward_suffix: &str,
+    reverse_suffix: &str,
+) -> Vec<AssayRow> {
+    let mut assays: Vec<&str> = Vec::new();
+    for primer in primers {
+        let target = primer
+            .name
+            .strip_suffix(forward_suffix)
+            .or_else(|| primer.name.strip_suffix(reverse_suffix));
+        if let Some(target) = target
+            && !assays.contains(&target)
+        {
+            assays.push(target);
+        }
+    }
+
+    let mut by_primer: HashMap<&str, Vec<&Hit>> = HashMap::new();
+    for hit in hits {
+        by_primer.entry(hit.primer.as_str()).or_default().push(hit);
+    }
+    let in_panel = |name: &str| primers.iter().any(|primer| primer.name == name);
+
+    assays
+        .into_iter()
+        .map(|assay| {
+            let forward = format!("{assay}{forward_suffix}");
+            let reverse = format!("{assay}{reverse_suffix}");
+            let components = vec![forward.clone(), reverse.clone()];
+            let hits_of = |name: &str| by_primer.get(name).map_or(&[][..], Vec::as_slice);
+
+            let missing_components = components
+                .iter()
+                .filter(|name| !in_panel(name) || hits_of(name).is_empty())
+                .cloned()
+                .collect::<Vec<_>>();
+            let all_found = missing_components.is_empty();
+            let worst_mismatches = all_found
+                .then(|| {
+                    components
+                        .iter()
+                        .filter_map(|name| hits_of(name).iter().map(|hit| hit.mismatches).min())
+                        .max()
+                })
+                .flatten();
+
+            let products = components
+                .iter()
+                .flat_map(|name| hits_of(name))
+                .filter(|hit| hit.strand == '+' && hit.amplifiable)
+                .count();
+            let product_size = hits_of(&forward)
+                .iter()
+                .filter_map(|hit| hit.amplicon_size.map(|size| (hit.mismatches, size)))
+                .min()
+                .map(|(_, size)| size);
+            let off_target_products = products.saturating_sub(1);
+
+            let verdict = if !all_found {
+                AssayVerdict::MissingComponent
+            } else if products == 0 {
+                AssayVerdict::NoProduct
+            } else if off_target_products > 0 {
+                AssayVerdict::OffTarget
+            } else if worst_mismatches.is_some_and(|worst| worst > 0) {
+                AssayVerdict::Mismatched
+            } else {
+                AssayVerdict::Pass
+            };
+
+            AssayRow {
+                assay: assay.to_string(),
+                components,
+                missing_components,
+                all_found,
+                worst_mismatches,
+                product_size,
+                products,
+                off_target_products,
+                verdict,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ScanOptions, scan_sequence};
+
+    #[test]
+    fn rollup_flags_missing_components_and_products() {
+        // egfr: F at 0, R binding the minus strand downstream -> one 30 bp product.
+        // kras: F binds, R has no site. braf: only the R oligo is in the panel (and binds).
+        // tp53: F and R bind, but both on `+`, so nothing converges.
+        let egfr_f = "ACGTTGCAAC";
+        let egfr_r_site = "GGATCCTTAG";
+        let kras_f = "TTTTGGGGCC";
+        let tp53_f = "CATGCATGAA";
+        let tp53_r = "GTTCGTACTT";
+        let sequence =
+            format!("{egfr_f}CCCCCCCCCC{egfr_r_site}AAAAA{kras_f}AAAAA{tp53_f}AAAAA{tp53_r}AAAAA");
+        let primer = |name: &str, sequence: &str| {
+            Primer::from_name_and_sequence(name, sequence).expect("primer")
+        };
+        let egfr_r = primer("x", egfr_r_site).reverse_complement;
+        let panel = vec![
+            primer("egfr_F", egfr_f),
+            primer("egfr_R", &egfr_r),
+            primer("kras_F", kras_f),
+            primer("kras_R", "GAGAGAGAGA"),
+            primer("braf_R", "CCCCCCCCCC"),
+            primer("tp53_F", tp53_f),
+            primer("tp53_R", tp53_r),
+        ];
+        let options = ScanOptions {
+            pair_suffix_pattern: Some(("_F".to_string(), "_R".to_string())),
+            ..ScanOptions::default()
+        };
+        let result = scan_sequence(&sequence, "chr1", &panel, &options).expect("scan");
+
+        let rows = assay_rollup(&panel, &result.hits, "_F", "_R");
+        let summary = rows
+            .iter()
+            .map(|row| {
+                (
+                    row.assay.as_str(),
+                    row.missing_components.clone(),
+                    row.product_size,
+                    row.verdict,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![
+                ("egfr", vec![], Some(30), AssayVerdict::Pass),
+                (
+                    "kras",
+                    vec!["kras_R".to_string()],
+                    None,
+                    AssayVerdict::MissingComponent
+                ),
+                (
+                    "braf",
+                    vec!["braf_F".to_string()],
+                    None,
+                    AssayVerdict::MissingComponent
+                ),
+                ("tp53", vec![], None, AssayVerdict::NoProduct),
+            ]
+        );
+        assert_eq!(rows[0].worst_mismatches, Some(0));
+        assert_eq!(rows[1].worst_mismatches, None);
+    }
+}
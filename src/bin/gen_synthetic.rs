@@ -1,5 +1,6 @@
 use anyhow::{Context, Result, bail};
 use clap::Parser;
+use primer_scout::{DEFAULT_FASTA_LINE_WIDTH, DEFAULT_SEED, XorShift64, write_fasta_record};
 use std::fs::File;
 use std::io::{BufWriter, Write};
 use std::path::PathBuf;
@@ -16,9 +17,16 @@ fn main() -> Result<()> {
         bail!("--primer-count must be > 0");
     }
 
+    // One stream feeds the reference and then the panel, so a seed reproduces the files
+    // earlier releases generated for it.
     let mut rng = XorShift64::new(args.seed);
     let sequence = generate_sequence(args.bases, &mut rng);
-    write_fasta(&args.reference_out, "synthetic_chr1", &sequence)?;
+    write_fasta(
+        &args.reference_out,
+        "synthetic_chr1",
+        &sequence,
+        args.fasta_line_width,
+    )?;
     write_primers(
         &args.primers_out,
         &sequence,
@@ -51,8 +59,12 @@ struct Args {
     #[arg(long, default_value_t = 20)]
     primer_len: usize,
 
-    #[arg(long, default_value_t = 42)]
+    #[arg(long, default_value_t = DEFAULT_SEED)]
     seed: u64,
+
+    /// Wrap width of reference sequence lines (0 = no wrapping).
+    #[arg(long, default_value_t = DEFAULT_FASTA_LINE_WIDTH)]
+    fasta_line_width: usize,
 }
 
 fn generate_sequence(len: usize, rng: &mut XorShift64) -> Vec<u8> {
@@ -64,7 +76,12 @@ fn generate_sequence(len: usize, rng: &mut XorShift64) -> Vec<u8> {
     out
 }
 
-fn write_fasta(path: &PathBuf, contig_name: &str, sequence: &[u8]) -> Result<()> {
+fn write_fasta(
+    path: &PathBuf,
+    contig_name: &str,
+    sequence: &[u8],
+    line_width: usize,
+) -> Result<()> {
     if let Some(parent) = path.parent() {
         std::fs::create_dir_all(parent)
             .with_context(|| format!("failed to create directory '{}'", parent.display()))?;
@@ -73,10 +90,7 @@ fn write_fasta(path: &PathBuf, contig_name: &str, sequence: &[u8]) -> Result<()>
     let file =
         File::create(path).with_context(|| format!("failed to create '{}'", path.display()))?;
     let mut writer = BufWriter::new(file);
-    writeln!(writer, ">{contig_name}")?;
-    for chunk in sequence.chunks(80) {
-        writeln!(writer, "{}", String::from_utf8_lossy(chunk))?;
-    }
+    write_fasta_record(&mut writer, contig_name, sequence, line_width)?;
     writer.flush()?;
     Ok(())
 }
@@ -136,29 +150,3 @@ fn mutate_base(current: u8, rng: &mut XorShift64) -> u8 {
         _ => b'A',
     }
 }
-
-#[derive(Debug, Clone)]
-struct XorShift64 {
-    state: u64,
-}
-
-impl XorShift64 {
-    fn new(seed: u64) -> Self {
-        Self {
-            state: if seed == 0 {
-                0xA5A5_A5A5_A5A5_A5A5
-            } else {
-                seed
-            },
-        }
-    }
-
-    fn next_u32(&mut self) -> u32 {
-        let mut x = self.state;
-        x ^= x << 13;
-        x ^= x >> 7;
-        x ^= x << 17;
-        self.state = x;
-        (x >> 32) as u32
-    }
-}
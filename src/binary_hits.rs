@@ -0,0 +1,142 @@
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use crate::Hit;
+use crate::transcripts::TranscriptRegion;
+
+/// Leading bytes of every binary hit file.
+const MAGIC: &[u8; 8] = b"PSHITS\0\x01";
+
+/// Every [`Hit`] field, always encoded. bincode is not self-describing, so the
+/// `skip_serializing_if` attributes that keep NDJSON rows short cannot be used here.
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "Hit")]
+struct HitRecord {
+    file: String,
+    contig: String,
+    primer: String,
+    primer_len: usize,
+    start: usize,
+    end: usize,
+    strand: char,
+    mismatches: usize,
+    matched: String,
+    complexity: f64,
+    matched_fraction: f64,
+    amplicon_size: Option<usize>,
+    nearest_partner_distance: Option<i64>,
+    amplifiable: bool,
+    primer_sequence: Option<String>,
+    primer_reverse_complement: Option<String>,
+    fwd_mismatches: Option<usize>,
+    rev_mismatches: Option<usize>,
+    gc_skew: Option<f64>,
+    transcript: Option<String>,
+    transcript_pos: Option<usize>,
+    transcript_region: Option<TranscriptRegion>,
+    contig_relative_position: Option<f64>,
+    masked_sequence: Option<String>,
+    overhang: usize,
+    repeat_class: Option<String>,
+    ambiguous_positions: usize,
+    matches_via_ambiguity: usize,
+    extendable: Option<bool>,
+    original_contig: Option<String>,
+    tag: Option<String>,
+}
+
+#[derive(Serialize)]
+struct HitRef<'a>(#[serde(with = "HitRecord")] &'a Hit);
+
+#[derive(Deserialize)]
+struct OwnedHit(#[serde(with = "HitRecord")] Hit);
+
+/// Writes `hits` as a bincode hit file that [`load_hits`] reads back.
+///
+/// The layout follows the `Hit` struct of this exact primer-scout version and is not a stable
+/// interchange format: use it between pipeline stages of one installation, and NDJSON for
+/// anything that is archived or shared.
+pub fn write_hits<W: Write>(out: &mut W, hits: &[Hit]) -> Result<()> {
+    out.write_all(MAGIC)?;
+    bincode::serialize_into(&mut *out, env!("CARGO_PKG_VERSION"))?;
+    let records = hits.iter().map(HitRef).collect::<Vec<_>>();
+    bincode::serialize_into(&mut *out, &records)?;
+    Ok(())
+}
+
+/// Reads a hit file written by [`write_hits`] (`--format bincode`). Files from another
+/// primer-scout version are rejected rather than misread.
+pub fn load_hits(path: &Path) -> Result<Vec<Hit>> {
+    let file = File::open(path).with_context(|| format!("failed to open '{}'", path.display()))?;
+    let mut reader = BufReader::new(file);
+    let mut magic = [0u8; MAGIC.len()];
+    if reader.read_exact(&mut magic).is_err() || &magic != MAGIC {
+        bail!("'{}' is not a primer-scout hit file", path.display());
+    }
+    let version: String = bincode::deserialize_from(&mut reader)
+        .with_context(|| format!("failed reading hit file '{}'", path.display()))?;
+    if version != env!("CARGO_PKG_VERSION") {
+        bail!(
+            "hit file '{}' was written by primer-scout {version}, this is {}; re-run the scan",
+            path.display(),
+            env!("CARGO_PKG_VERSION")
+        );
+    }
+    let records: Vec<OwnedHit> = bincode::deserialize_from(&mut reader)
+        .with_context(|| format!("failed reading hit file '{}'", path.display()))?;
+    Ok(records.into_iter().map(|OwnedHit(hit)| hit).collect())
+}
+
+/// [`write_hits`] to a new file at `path`.
+pub fn save_hits(path: &Path, hits: &[Hit]) -> Result<()> {
+    let file =
+        File::create(path).with_context(|| format!("failed to create '{}'", path.display()))?;
+    let mut out = BufWriter::new(file);
+    write_hits(&mut out, hits)?;
+    out.flush()
+        .with_context(|| format!("failed writing hit file '{}'", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::tmp_path;
+    use crate::{Primer, ScanOptions, scan_sequence};
+
+    #[test]
+    fn hits_round_trip_through_a_binary_file() {
+        let path = tmp_path("hits.bin");
+        let primers = vec![
+            Primer::from_name_and_sequence("amp_F", "ACGTTGCA").expect("primer"),
+            Primer::from_name_and_sequence("amp_R", "GGTTCCAA").expect("primer"),
+        ];
+        let options = ScanOptions {
+            max_mismatches: 1,
+            pair_suffix_pattern: Some(("_F".to_string(), "_R".to_string())),
+            include_gc_skew: true,
+            ..ScanOptions::default()
+        };
+        let result = scan_sequence("ACGTTGCACCCCTTGGAACCACGTAGCA", "chr1", &primers, &options)
+            .expect("scan");
+        assert!(result.hits.iter().any(|hit| hit.amplicon_size.is_some()));
+
+        save_hits(&path, &result.hits).expect("save hits");
+        let loaded = load_hits(&path).expect("load hits");
+        assert_eq!(
+            serde_json::to_string(&loaded).expect("json"),
+            serde_json::to_string(&result.hits).expect("json")
+        );
+
+        std::fs::write(&path, "{\"primer\":\"p1\"}\n").expect("write text");
+        let err = load_hits(&path).expect_err("not a hit file");
+        assert!(
+            err.to_string().contains("not a primer-scout hit file"),
+            "{err}"
+        );
+
+        std::fs::remove_file(path).expect("remove hit file");
+    }
+}
@@ -1,18 +1,79 @@
-use anyhow::{Context, Result};
-use clap::Parser;
+use anyhow::{Context, Result, bail};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use crossterm::style::Color;
+use flate2::Compression;
+use flate2::write::GzEncoder;
 use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::ffi::OsString;
-use std::io::{self, BufWriter, Write};
+use std::fs::File;
+use std::io::{self, BufWriter, IsTerminal, Write};
 use std::num::NonZeroUsize;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
 
-use crate::{PrimerSummary, ScanOptions, load_primers, scan_references};
+use crate::aliases::load_contig_aliases;
+use crate::amplicons::{
+    annotate_exclusions, load_exclusion_regions, predicted_amplicons, write_amplicons_bed,
+};
+use crate::assay::{AssayRow, assay_rollup};
+use crate::demo::{DEMO_DIR, write_demo};
+use crate::explain::{ExplainQuery, explain_window, write_explanation};
+use crate::extract::{extract_panel, load_contigs_where};
+use crate::groups::{
+    GroupSummary, GroupedSummary, grouped_summary, length_class, load_primer_groups,
+};
+use crate::haplotypes::{HaplotypeCall, haplotype_calls};
+use crate::known_sites::load_known_sites;
+use crate::panel::{IdtDefaults, PanelFormat, write_panel};
+use crate::profile::{
+    position_profiles, write_position_profiles_json, write_position_profiles_tsv,
+};
+use crate::regions::{
+    ContigSummary, DEFAULT_HOTSPOT_MIN_PRIMERS, DEFAULT_HOTSPOT_WINDOW, Hotspot, contig_summary,
+    coverage_gaps, find_hotspots, load_bed, write_gaps_bed, write_hotspots_bed,
+};
+use crate::sink::{
+    BED_SORTED_EXTRA_FIELDS, BedSink, FileSinkFactory, GenbankFeatureSink, Gff3Sink, HitFastaSink,
+    HitSink, JsonSink, OutputFormat, PerPrimerSink, SortedBedSink, TsvColumns, TsvSink,
+    bed_autosql, output_format,
+};
+use crate::transcripts::load_transcripts;
+use crate::warnings::{DEFAULT_WARNING_LIMIT, WarningLog};
+use crate::{
+    CancelToken, DEFAULT_FASTA_LINE_WIDTH, DEFAULT_MAX_AMPLICON_SIZE, DEFAULT_MAX_DEGENERACY,
+    DEFAULT_MIN_PRIMER_COMPLEXITY, DEFAULT_PARTNER_WINDOW, DEFAULT_SEED, FastaStrictness,
+    FileCompletion, Hit, MemoryProfile, OverhangScoring, PathStyle, Primer, PrimerFormat,
+    PrimerSummary, RnaPolicy, ScanAlgorithm, ScanOptions, ScanResult, check_degeneracy,
+    check_rna_policy, consensus_primer, exclude_primers_by_name_regex,
+    filter_primers_by_name_regex, hash_hits, load_primers, load_primers_from_reader,
+    low_complexity_primers, mismatch_mean_and_std, partition_exact_primers,
+    poisson_confidence_interval, scan_references, strand_bias,
+};
 
 const MAX_THREAD_MULTIPLIER: usize = 4;
+#[cfg(feature = "async-output")]
+const OUTPUT_WRITER_THREADS: usize = 4;
+/// Records buffered per writer thread before the scan's output loop blocks.
+#[cfg(feature = "async-output")]
+const OUTPUT_CHANNEL_CAPACITY: usize = 4096;
+// Fraction of each contig scanned by --dry-run-estimate.
+const DRY_RUN_SAMPLE_RATE: f64 = 0.01;
+// Conventional exit status for a process stopped by SIGINT (128 + 2).
+const INTERRUPTED_EXIT_CODE: i32 = 130;
+// Exit status when --verify-hash does not match the scan's hit hash.
+const HASH_MISMATCH_EXIT_CODE: i32 = 2;
+// Name suffix of the pseudo-primer carrying reverse-strand hits under --split-orientations.
+const REVERSE_ORIENTATION_SUFFIX: &str = "_rc";
+// Peak bytes per base for the fast profile: raw contig buffer + normalized bases + masks.
+const FAST_PROFILE_BYTES_PER_BASE: u64 = 3;
+// Conservative expansion ratio used to size gzip-compressed references.
+const GZIP_EXPANSION_ESTIMATE: u64 = 4;
 
 pub fn run() -> Result<()> {
-    let cli = Cli::parse();
-    execute(cli)
+    run_from_args(std::env::args_os())
 }
 
 pub fn run_from_args<I, T>(args: I) -> Result<()>
@@ -20,78 +81,1560 @@ where
     I: IntoIterator<Item = T>,
     T: Into<OsString> + Clone,
 {
-    let cli = Cli::parse_from(args);
-    execute(cli)
+    let args = args.into_iter().map(Into::into).collect::<Vec<OsString>>();
+    let cli = Cli::parse_from(&args);
+    execute(cli, &args)
 }
 
-fn execute(cli: Cli) -> Result<()> {
-    let primers = load_primers(&cli.primers)
-        .with_context(|| format!("failed loading primers from '{}'", cli.primers.display()))?;
+fn execute(mut cli: Cli, args: &[OsString]) -> Result<()> {
+    if let Some(command) = cli.command.take() {
+        return match command {
+            Command::ExportPanel(args) => export_panel(&args),
+            Command::ExtractPanel(args) => run_extract_panel(&args),
+            Command::Consensus(args) => run_consensus(&args),
+            Command::Demo(args) => run_demo(&args),
+            #[cfg(feature = "bincode")]
+            Command::Compile(args) => compile_panel(&args),
+        };
+    }
 
-    let options = ScanOptions {
-        max_mismatches: cli.max_mismatches,
-        scan_reverse_complement: !cli.no_revcomp,
+    let primers_path = cli.primers.as_deref().context("--primers is required")?;
+    if is_stdin(primers_path) && cli.references.iter().any(|path| is_stdin(path)) {
+        bail!("--primers and --reference cannot both read from stdin ('-')");
+    }
+    let mut primers = load_panel(primers_path, cli.primers_stdin_format.map(Into::into))
+        .with_context(|| format!("failed loading primers from '{}'", primers_path.display()))?;
+    if let Some(pattern) = &cli.primer_name_regex {
+        primers = if cli.primer_name_regex_invert {
+            exclude_primers_by_name_regex(primers, pattern)?
+        } else {
+            filter_primers_by_name_regex(primers, pattern)?
+        };
+    }
+    let rna_policy = if cli.reject_rna {
+        RnaPolicy::Reject
+    } else if cli.expect_rna {
+        RnaPolicy::Expect
+    } else {
+        RnaPolicy::Accept
     };
+    check_rna_policy(&primers, rna_policy)
+        .with_context(|| format!("failed loading primers from '{}'", primers_path.display()))?;
+    check_degeneracy(&primers, cli.max_degeneracy)
+        .with_context(|| format!("failed loading primers from '{}'", primers_path.display()))?;
+    if cli.exact_only {
+        let (exact, deferred) = partition_exact_primers(primers);
+        if exact.is_empty() {
+            bail!(
+                "--exact-only left no primers: every primer in '{}' is degenerate",
+                primers_path.display()
+            );
+        }
+        if !deferred.is_empty() {
+            let names = deferred
+                .iter()
+                .map(|primer| primer.name.as_str())
+                .collect::<Vec<_>>();
+            eprintln!(
+                "info: --exact-only deferred {} degenerate primer(s): {}",
+                deferred.len(),
+                names.join(", ")
+            );
+        }
+        primers = exact;
+    }
+    let warnings = Arc::new(WarningLog::new(cli.max_warnings));
+    if cli.warn_low_complexity_primers || cli.skip_low_complexity {
+        let flagged = low_complexity_primers(&primers, cli.min_primer_complexity);
+        for &(idx, complexity) in &flagged {
+            let primer = &primers[idx];
+            warnings.warn(
+                "low_complexity_primer",
+                format_args!(
+                    "primer '{}' ('{}') has complexity {complexity:.2}, below {}{}",
+                    primer.name,
+                    primer.sequence,
+                    cli.min_primer_complexity,
+                    if cli.skip_low_complexity {
+                        "; skipped"
+                    } else {
+                        ""
+                    }
+                ),
+            );
+        }
+        if cli.skip_low_complexity && !flagged.is_empty() {
+            if flagged.len() == primers.len() {
+                bail!(
+                    "every primer in '{}' is below complexity {}",
+                    primers_path.display(),
+                    cli.min_primer_complexity
+                );
+            }
+            primers = primers
+                .into_iter()
+                .enumerate()
+                .filter(|(idx, _)| !flagged.iter().any(|(flagged_idx, _)| flagged_idx == idx))
+                .map(|(_, primer)| primer)
+                .collect();
+        }
+    }
+
+    let regions = cli
+        .regions_bed
+        .as_deref()
+        .map(|path| {
+            load_bed(path)
+                .with_context(|| format!("failed loading regions from '{}'", path.display()))
+        })
+        .transpose()?;
+
+    let transcripts = cli
+        .transcripts
+        .as_deref()
+        .map(|path| {
+            load_transcripts(path)
+                .with_context(|| format!("failed loading transcripts from '{}'", path.display()))
+        })
+        .transpose()?;
+
+    let known_sites = cli
+        .known_sites
+        .as_deref()
+        .map(|path| {
+            load_known_sites(path)
+                .with_context(|| format!("failed loading known sites from '{}'", path.display()))
+        })
+        .transpose()?;
+
+    let contig_aliases = cli
+        .contig_aliases
+        .as_deref()
+        .map(|path| {
+            load_contig_aliases(path)
+                .with_context(|| format!("failed loading contig aliases from '{}'", path.display()))
+        })
+        .transpose()?;
+
+    let groups = cli
+        .groups
+        .as_deref()
+        .map(|path| {
+            load_primer_groups(path)
+                .with_context(|| format!("failed loading groups from '{}'", path.display()))
+        })
+        .transpose()?
+        .unwrap_or_default();
 
     let max_threads = available_threads()
         .saturating_mul(MAX_THREAD_MULTIPLIER)
         .max(1);
     let threads = cli.threads.max(1).min(max_threads);
-    let pool = rayon::ThreadPoolBuilder::new()
-        .num_threads(threads)
-        .build()
-        .context("failed to create rayon thread pool")?;
+    let options = ScanOptions {
+        max_mismatches: cli.max_mismatches,
+        scan_reverse_complement: !cli.no_revcomp,
+        pair_suffix_pattern: cli.pair_suffixes.clone(),
+        max_amplicon_size: cli.max_amplicon,
+        partner_window: cli.partner_window,
+        only_amplifiable: cli.only_amplifiable,
+        seed: cli.seed,
+        memory_profile: resolve_memory_profile(&cli),
+        lowercase_mismatches: cli.lowercase_mismatches,
+        algorithm: cli.algorithm.into(),
+        min_hit_complexity: cli.min_complexity,
+        max_ambiguous_positions: cli.max_ambiguous_positions,
+        include_primer_sequence: cli.with_primer_seq,
+        include_gc_skew: cli.gc_skew,
+        fasta_strictness: if cli.fasta_strict {
+            FastaStrictness::Strict
+        } else {
+            FastaStrictness::Lenient
+        },
+        degap: cli.degap,
+        keep_alignment_coords: cli.keep_alignment_coords,
+        min_base_quality: cli.min_base_quality,
+        deduplicate_iupac_equivalent_hits: cli.dedup_iupac_hits,
+        compute_genome_coverage: cli.genome_coverage,
+        include_relative_position: cli.relative_position,
+        include_masked_sequence: cli.masked_sequence,
+        known_sites,
+        time_limit_per_primer: cli.time_limit_per_primer.map(Duration::from_millis),
+        sample_rate: cli.dry_run_estimate.then_some(DRY_RUN_SAMPLE_RATE),
+        overhang: cli.allow_overhang.then_some(cli.overhang_scoring.into()),
+        repeat_library_bed: cli.repeat_library.clone(),
+        dedup_hits: cli.dedup_hits,
+        sanitize_contig_names: cli.sanitize_contig_names,
+        contig_aliases,
+        path_style: cli.path_style.into(),
+        collapse_strand_duplicates: cli.collapse_strand_duplicates,
+        sort_output: !cli.no_sort,
+        warnings,
+        regions,
+        transcripts,
+        cancel: CancelToken::new(),
+        groups,
+        auto_tune_threads: cli.auto_tune_threads,
+        threads: Some(threads),
+        tag: cli.tag.clone(),
+        hash_ignores_tag: cli.ignore_tag,
+        predict_extension: cli.predict_extension,
+        extendable_only: cli.extendable_only,
+        spill_threshold: cli.spill_threshold,
+    };
+    if let Some(terms) = &cli.explain {
+        return run_explain(&cli.references, terms, &primers, &options);
+    }
+    install_interrupt_handler(&options.cancel)?;
+
+    if let Some(path) = &cli.emit_manifest {
+        write_manifest(path, &run_manifest(&cli, args, threads)?)?;
+    }
+
+    if let Some(path) = &cli.autosql {
+        if explicit_output_format(&cli) != Some(OutputFormat::BedSorted) {
+            bail!("--autosql describes --output-format bed-sorted columns; pass that format too");
+        }
+        std::fs::write(path, bed_autosql(BED_SORTED_EXTRA_FIELDS))
+            .with_context(|| format!("failed writing autoSql '{}'", path.display()))?;
+    }
+
+    let mut scan = scan_references(&cli.references, &primers, &options)?;
+    if let Some(threads) = scan.auto_tuned_threads {
+        eprintln!("auto-tuned threads: {threads}");
+    }
+    if let Some(path) = &cli.emit_hit_fasta {
+        let mut sink = HitFastaSink::new(create_output(path)?, cli.fasta_line_width);
+        for hit in &scan.hits {
+            sink.on_hit(hit)?;
+        }
+        sink.finish()
+            .with_context(|| format!("failed writing hit FASTA '{}'", path.display()))?;
+    }
+    options.warnings.finish();
+    if cli.dry_run_estimate {
+        let estimate = (scan.total_hits as f64 / DRY_RUN_SAMPLE_RATE).round() as u64;
+        let (low, high) = poisson_confidence_interval(scan.total_hits, DRY_RUN_SAMPLE_RATE, 0.95);
+        println!(
+            "Estimated total hits: {estimate} (from {}% sample)",
+            DRY_RUN_SAMPLE_RATE * 100.0
+        );
+        println!("95% confidence interval: {low}-{high}");
+        return Ok(());
+    }
+
+    if let Some(path) = &cli.gaps {
+        let gaps = coverage_gaps(
+            &scan.contigs,
+            &scan.hits,
+            cli.pair_suffixes.is_some(),
+            cli.min_gap,
+        );
+        let file =
+            File::create(path).with_context(|| format!("failed to create '{}'", path.display()))?;
+        let mut out = BufWriter::new(file);
+        write_gaps_bed(&mut out, &gaps)?;
+        out.flush()
+            .with_context(|| format!("failed writing gaps BED '{}'", path.display()))?;
+    }
+    if let Some(path) = &cli.position_profile {
+        let genomes = scan
+            .files
+            .iter()
+            .map(|status| status.file.clone())
+            .collect::<Vec<_>>();
+        let profiles = position_profiles(&primers, &scan.hits, &genomes);
+        let file =
+            File::create(path).with_context(|| format!("failed to create '{}'", path.display()))?;
+        let mut out = BufWriter::new(file);
+        if path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("json"))
+        {
+            write_position_profiles_json(&mut out, &genomes, &profiles)?;
+        } else {
+            write_position_profiles_tsv(&mut out, &profiles)?;
+        }
+        out.flush()
+            .with_context(|| format!("failed writing position profile '{}'", path.display()))?;
+    }
+
+    let mut excluded_amplicons = 0;
+    if cli.amplicons_bed.is_some() || cli.amplicon_exclude_bed.is_some() {
+        let (forward_suffix, reverse_suffix) = cli
+            .pair_suffixes
+            .as_ref()
+            .expect("clap requires pair_suffixes");
+        let mut amplicons =
+            predicted_amplicons(&scan.hits, forward_suffix, reverse_suffix, cli.max_amplicon);
+        if let Some(path) = &cli.amplicon_exclude_bed {
+            let regions = load_exclusion_regions(path)
+                .with_context(|| format!("failed loading exclusion BED '{}'", path.display()))?;
+            excluded_amplicons = annotate_exclusions(&mut amplicons, &regions);
+            for amplicon in amplicons.iter().filter(|a| !a.exclusions.is_empty()) {
+                eprintln!(
+                    "excluded amplicon: {} {}:{}-{} overlaps {}",
+                    amplicon.target,
+                    amplicon.contig,
+                    amplicon.start,
+                    amplicon.end,
+                    amplicon.exclusions.join(",")
+                );
+            }
+        }
+        if let Some(path) = &cli.amplicons_bed {
+            let file = File::create(path)
+                .with_context(|| format!("failed to create '{}'", path.display()))?;
+            let mut out = BufWriter::new(file);
+            write_amplicons_bed(&mut out, &amplicons)?;
+            out.flush()
+                .with_context(|| format!("failed writing amplicons BED '{}'", path.display()))?;
+        }
+    }
+
+    if cli.count_only {
+        match cli.count_by {
+            Some(CountBy::Mismatches) => emit_count_by_mismatches(
+                &count_by_mismatches(&scan.hits, cli.max_mismatches),
+                cli.json,
+            )?,
+            None => emit_count(scan.total_hits, cli.json)?,
+        }
+    } else if let Some(pattern) = &cli.haplotype_regex {
+        emit_haplotype_calls(
+            &cli,
+            &haplotype_calls(&primers, &scan.contigs, &scan.hits, pattern)?,
+        )?;
+    } else if cli.contig_summary {
+        emit_contig_summary(&cli, &contig_summary(&scan.hits))?;
+    } else if cli.assay_report {
+        let (forward_suffix, reverse_suffix) = cli
+            .pair_suffixes
+            .as_ref()
+            .expect("clap requires pair_suffixes");
+        emit_assay_report(
+            &assay_rollup(&primers, &scan.hits, forward_suffix, reverse_suffix),
+            cli.json,
+        )?;
+    } else if let Some(params) = &cli.hotspots {
+        let window = params.first().copied().unwrap_or(DEFAULT_HOTSPOT_WINDOW);
+        let min_primers = params
+            .get(1)
+            .copied()
+            .unwrap_or(DEFAULT_HOTSPOT_MIN_PRIMERS);
+        let hotspots = find_hotspots(&scan.hits, window, min_primers);
+        if let Some(path) = &cli.hotspots_bed {
+            let file = File::create(path)
+                .with_context(|| format!("failed to create '{}'", path.display()))?;
+            let mut out = BufWriter::new(file);
+            write_hotspots_bed(&mut out, &hotspots)?;
+            out.flush()
+                .with_context(|| format!("failed writing hotspots BED '{}'", path.display()))?;
+        }
+        emit_hotspots(&hotspots, cli.json)?;
+    } else if let (Some(hits_path), Some(summary_path)) = (&cli.hits_out, &cli.summary_out) {
+        let explicit = explicit_output_format(&cli);
+        if cli.split_orientations {
+            scan.summary = split_summary_orientations(&scan.summary, &scan.hits);
+            label_reverse_orientation_hits(&mut scan, cli.ignore_tag);
+        }
+        let mut sink = file_sink(&cli, hits_path, explicit)?;
+        for hit in scan.hit_stream()? {
+            sink.on_hit(&hit?)?;
+        }
+        sink.finish()?;
+        let mut sink = file_sink(&cli, summary_path, explicit)?;
+        for row in &scan.summary {
+            sink.on_summary(row)?;
+        }
+        sink.finish()?;
+    } else if cli.summary && cli.groups.is_some() {
+        emit_group_summary(&scan.group_summaries, cli.json)?;
+    } else if cli.summary
+        && let Some(group_by) = cli.group_by
+    {
+        if cli.split_orientations {
+            scan.summary = split_summary_orientations(&scan.summary, &scan.hits);
+        }
+        let groups = match group_by {
+            GroupBy::Pool => {
+                let pools = primers
+                    .iter()
+                    .filter_map(|primer| Some((primer.name.as_str(), primer.pool.as_deref()?)))
+                    .collect::<HashMap<_, _>>();
+                if pools.is_empty() {
+                    bail!(
+                        "--group-by pool needs a `pool` column in the panel header (or a JSONL \
+                         `pool` field); '{}' assigns no primer to a pool",
+                        primers_path.display()
+                    );
+                }
+                grouped_summary(&scan.summary, |row| {
+                    let primer = row
+                        .primer
+                        .strip_suffix(REVERSE_ORIENTATION_SUFFIX)
+                        .filter(|_| cli.split_orientations)
+                        .unwrap_or(&row.primer);
+                    pools.get(primer).unwrap_or(&".").to_string()
+                })
+            }
+            GroupBy::Length => grouped_summary(&scan.summary, |row| length_class(row.primer_len)),
+            GroupBy::Panel => {
+                let panel = primers_path.file_name().map_or_else(
+                    || primers_path.display().to_string(),
+                    |name| name.to_string_lossy().into_owned(),
+                );
+                grouped_summary(&scan.summary, |_| panel.clone())
+            }
+        };
+        emit_grouped_summary(&cli, &groups)?;
+    } else if cli.summary {
+        if cli.split_orientations {
+            scan.summary = split_summary_orientations(&scan.summary, &scan.hits);
+        }
+        let mut sink = output_sink(&cli)?;
+        for row in &scan.summary {
+            sink.on_summary(row)?;
+        }
+        sink.finish()?;
+    } else {
+        if cli.split_orientations {
+            label_reverse_orientation_hits(&mut scan, cli.ignore_tag);
+        }
+        if !write_binary_hits(&cli, &mut scan)? {
+            let mut sink = output_sink(&cli)?;
+            for hit in scan.hit_stream()? {
+                sink.on_hit(&hit?)?;
+            }
+            sink.finish()?;
+        }
+    }
+
+    if scan.partial {
+        eprintln!("interrupted: output covers only the completed part of the scan");
+        for status in &scan.files {
+            let completion = match status.completion {
+                FileCompletion::Complete => "complete",
+                FileCompletion::Partial => "partial",
+                FileCompletion::NotStarted => "not started",
+            };
+            eprintln!("  {}: {completion}", status.file);
+        }
+        std::process::exit(INTERRUPTED_EXIT_CODE);
+    }
+
+    if let Some(fraction) = scan.genome_coverage_fraction {
+        eprintln!("genome coverage: {fraction:.6}");
+    }
+    if cli.print_hash {
+        eprintln!("hit hash: {}", scan.hit_hash);
+    }
+    if let Some(expected) = &cli.verify_hash {
+        if !expected.trim().eq_ignore_ascii_case(&scan.hit_hash) {
+            eprintln!(
+                "hit hash mismatch: expected {}, scan produced {}",
+                expected.trim(),
+                scan.hit_hash
+            );
+            std::process::exit(HASH_MISMATCH_EXIT_CODE);
+        }
+        eprintln!("hit hash verified: {}", scan.hit_hash);
+    }
+    if cli.fail_on_excluded_amplicon && excluded_amplicons > 0 {
+        bail!(
+            "{excluded_amplicons} predicted amplicon(s) overlap --amplicon-exclude-bed intervals"
+        );
+    }
+
+    Ok(())
+}
+
+/// The first Ctrl+C cancels the scan so the completed part is still written; a second one
+/// exits immediately.
+fn install_interrupt_handler(cancel: &CancelToken) -> Result<()> {
+    use signal_hook::consts::SIGINT;
+
+    signal_hook::flag::register_conditional_shutdown(
+        SIGINT,
+        INTERRUPTED_EXIT_CODE,
+        Arc::clone(cancel.flag()),
+    )
+    .context("failed to install Ctrl+C handler")?;
+    signal_hook::flag::register(SIGINT, Arc::clone(cancel.flag()))
+        .context("failed to install Ctrl+C handler")?;
+    Ok(())
+}
+
+#[derive(Debug, Parser)]
+#[command(
+    version,
+    about = "Fast Rust primer off-target scanner for FASTA references",
+    subcommand_negates_reqs = true,
+    args_conflicts_with_subcommands = true
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Primer panel file (.tsv, .csv or FASTA), or `-` for stdin. Format: name<tab>sequence.
+    #[arg(long, short = 'p', required = true)]
+    primers: Option<PathBuf>,
+
+    /// Scan only primers whose name matches this regular expression (e.g. `^EGFR_`).
+    #[arg(long, value_name = "PATTERN")]
+    primer_name_regex: Option<String>,
+
+    /// With --primer-name-regex, scan the primers that do not match instead.
+    #[arg(long, requires = "primer_name_regex")]
+    primer_name_regex_invert: bool,
+
+    /// Fail when a primer contains U: the panel is expected to be DNA.
+    #[arg(long, conflicts_with = "expect_rna")]
+    reject_rna: bool,
+
+    /// Fail when a primer contains T: the panel is expected to be RNA (U is read as T).
+    #[arg(long)]
+    expect_rna: bool,
+
+    /// Fail when a primer stands for more than this many concrete sequences (the product of
+    /// the bases each IUPAC code allows; 20 `N`s are 4^20), which is almost always a typo.
+    #[arg(long, value_name = "N", default_value_t = DEFAULT_MAX_DEGENERACY)]
+    max_degeneracy: u64,
+
+    /// Scan only primers without IUPAC ambiguity codes, listing the deferred degenerate ones on
+    /// stderr: a quick exact-sequence first pass.
+    #[arg(long)]
+    exact_only: bool,
+
+    /// Warn about primers whose base composition complexity (see --min-complexity) is below
+    /// --min-primer-complexity, such as homopolymers and dinucleotide repeats.
+    #[arg(long)]
+    warn_low_complexity_primers: bool,
+
+    /// Like --warn-low-complexity-primers, but also drop those primers from the scan.
+    #[arg(long)]
+    skip_low_complexity: bool,
+
+    /// Complexity threshold (0..1) for --warn-low-complexity-primers and --skip-low-complexity.
+    #[arg(
+        long,
+        value_name = "F",
+        default_value_t = DEFAULT_MIN_PRIMER_COMPLEXITY,
+        value_parser = parse_unit_fraction
+    )]
+    min_primer_complexity: f64,
+
+    /// Reject reference FASTA with non-IUPAC sequence characters or nameless headers,
+    /// reporting file, line, and column.
+    #[arg(long, conflicts_with = "fasta_lenient")]
+    fasta_strict: bool,
+
+    /// Read non-IUPAC reference characters as N and warn with a per-contig count (default).
+    #[arg(long)]
+    fasta_lenient: bool,
+
+    /// Strip alignment gap characters (`-`, `.`) from reference sequences; coordinates are
+    /// then ungapped.
+    #[arg(long)]
+    degap: bool,
+
+    /// With --degap, report hit coordinates as columns of the original alignment.
+    #[arg(
+        long,
+        requires = "degap",
+        conflicts_with_all = [
+            "gaps",
+            "regions_bed",
+            "pair_suffixes",
+            "amplicons_bed",
+            "amplicon_exclude_bed",
+            "genome_coverage",
+        ]
+    )]
+    keep_alignment_coords: bool,
+
+    /// For FASTQ references, treat bases below this Phred quality as N (never a mismatch).
+    #[arg(long, value_name = "PHRED")]
+    min_base_quality: Option<u8>,
+
+    /// Layout of a panel read from stdin with `--primers -` (default: detect tsv/csv/fasta).
+    #[arg(long, value_enum, value_name = "FORMAT")]
+    primers_stdin_format: Option<PrimerFormatArg>,
+
+    /// Reference FASTA file(s), plain text or .gz.
+    #[arg(long = "reference", short = 'r', value_name = "FASTA", required = true)]
+    references: Vec<PathBuf>,
+
+    /// Allowed substitutions per hit.
+    #[arg(long = "max-mismatches", short = 'k', default_value_t = 1)]
+    max_mismatches: usize,
+
+    /// BED of expected off-target sites with the primer name in column 4; overlapping hits of
+    /// that primer are dropped and counted as `known_suppressed` in --summary rows.
+    #[arg(long, value_name = "BED")]
+    known_sites: Option<PathBuf>,
+
+    /// Stop scanning a primer on a contig after this many milliseconds, keeping its hits so
+    /// far; such primers are marked `timed_out` in --summary rows and have incomplete counts.
+    #[arg(long, value_name = "MS")]
+    time_limit_per_primer: Option<u64>,
+
+    /// Scan only the intervals of this BED file (overlaps are merged; coordinates stay absolute).
+    #[arg(long, value_name = "BED")]
+    regions_bed: Option<PathBuf>,
+
+    /// Disable reverse-complement scanning.
+    #[arg(long)]
+    no_revcomp: bool,
+
+    /// Count soft-masked (lowercase) reference bases as mismatches.
+    #[arg(long)]
+    lowercase_mismatches: bool,
+
+    /// Drop hits whose matched-sequence entropy (0..1) falls below this value.
+    #[arg(long, value_name = "F", value_parser = parse_unit_fraction)]
+    min_complexity: Option<f64>,
+
+    /// Also write every hit's matched reference bases as FASTA to this path.
+    #[arg(long, value_name = "PATH")]
+    emit_hit_fasta: Option<PathBuf>,
+
+    /// Append the primer sequence (and its reverse complement on `-` hits) to each hit.
+    #[arg(long)]
+    with_primer_seq: bool,
+
+    /// Append the GC skew `(G-C)/(G+C)` of each hit's matched bases.
+    #[arg(long)]
+    gc_skew: bool,
+
+    /// Append the fraction of the primer length each hit covers (1.0 for full-length hits).
+    #[arg(long)]
+    matched_fraction: bool,
+
+    /// Append each hit's start as a fraction of its contig length.
+    #[arg(long)]
+    relative_position: bool,
+
+    /// Append the matched bases with those at the primer's IUPAC-ambiguous positions lowercased.
+    #[arg(long)]
+    masked_sequence: bool,
+
+    /// Also report primers binding with part of their length hanging off a contig end; hits
+    /// cover the on-contig bases and an `overhang` column gives the off-contig length.
+    #[arg(long)]
+    allow_overhang: bool,
+
+    /// How --allow-overhang scores off-contig bases: as mismatches, or not at all (then at
+    /// most half of the primer may hang off).
+    #[arg(long, value_enum, default_value_t, requires = "allow_overhang")]
+    overhang_scoring: OverhangScoringArg,
+
+    /// Append `ambiguous_positions` (reference bases in the hit that are not a concrete
+    /// A/C/G/T) and `matches_via_ambiguity` (those that match only through the ambiguity).
+    #[arg(long)]
+    ambiguity_columns: bool,
+
+    /// Drop hits covering more than N ambiguous (IUPAC or N) reference bases.
+    #[arg(long, value_name = "N")]
+    max_ambiguous_positions: Option<usize>,
+
+    /// Repeat annotation BED (chrom, start, end, name); appends the name of the repeat each
+    /// hit overlaps as `repeat_class` (`.` outside repeats).
+    #[arg(long, value_name = "BED")]
+    repeat_library: Option<PathBuf>,
+
+    /// BED12 or GFF3/GTF exon structures; appends transcript, spliced position, and
+    /// exonic/intronic/junction_spanning columns to each hit.
+    #[arg(long, value_name = "FILE")]
+    transcripts: Option<PathBuf>,
+
+    /// Drop a primer's hit when a more degenerate primer that covers it (e.g. RAATGG over
+    /// AAATGG) hits the same site and strand.
+    #[arg(long)]
+    dedup_iupac_hits: bool,
+
+    /// Merge `+`/`-` hits of one primer at identical coordinates into one `=` row.
+    #[arg(long)]
+    collapse_strand_duplicates: bool,
+
+    /// Wrap width of FASTA sequence lines written by --emit-hit-fasta (0 = no wrapping).
+    #[arg(long, value_name = "N", default_value_t = DEFAULT_FASTA_LINE_WIDTH)]
+    fasta_line_width: usize,
+
+    /// Write predicted amplicons as BED12 (strand of the forward primer, insert as the thick
+    /// part, primer-binding sites as blocks).
+    #[arg(long, value_name = "BED", requires = "pair_suffixes")]
+    amplicons_bed: Option<PathBuf>,
+
+    /// Report predicted amplicons overlapping these BED intervals (named by the BED `name`
+    /// column) on stderr.
+    #[arg(long, value_name = "BED", requires = "pair_suffixes")]
+    amplicon_exclude_bed: Option<PathBuf>,
+
+    /// Exit with an error when any predicted amplicon overlaps an --amplicon-exclude-bed interval.
+    #[arg(long, requires = "amplicon_exclude_bed")]
+    fail_on_excluded_amplicon: bool,
+
+    /// Write reference stretches covered by no hit (by no predicted amplicon with --pair-suffixes) as BED.
+    #[arg(long, value_name = "BED")]
+    gaps: Option<PathBuf>,
+
+    /// Smallest gap length (bp) written by --gaps.
+    #[arg(long, value_name = "N", default_value_t = 1, requires = "gaps")]
+    min_gap: usize,
+
+    /// Write a primer x position matrix of mismatch frequencies over each reference's best hit
+    /// per primer (JSON when PATH ends in .json, TSV otherwise).
+    #[arg(long, value_name = "PATH")]
+    position_profile: Option<PathBuf>,
+
+    /// Instead of hit rows, print windows (default 100000 bp) where at least MIN_PRIMERS
+    /// (default 3) distinct primers hit, ranked by distinct primer count.
+    #[arg(
+        long,
+        num_args = 0..=2,
+        value_names = ["WINDOW", "MIN_PRIMERS"],
+        conflicts_with_all = ["count_only", "summary"]
+    )]
+    hotspots: Option<Vec<usize>>,
+
+    /// Also write --hotspots windows as BED.
+    #[arg(long, value_name = "BED", requires = "hotspots")]
+    hotspots_bed: Option<PathBuf>,
+
+    /// Skip the final hit sort and emit hits in discovery order (order is not guaranteed).
+    #[arg(long)]
+    no_sort: bool,
+
+    /// After sorting, drop hit records identical in every field to another one (a guard for
+    /// overlapping scans; reported as a warning when any are found).
+    #[arg(long, conflicts_with = "no_sort")]
+    dedup_hits: bool,
+
+    /// Replace characters outside `[A-Za-z0-9._:-]` in contig names with `_` in every output
+    /// (NDJSON hits keep the name as read in `original_contig`).
+    #[arg(long)]
+    sanitize_contig_names: bool,
+
+    /// TSV of `source<tab>canonical` contig names; reference contigs are renamed as they are
+    /// read, so every output (and --regions matching) uses the canonical names. Contigs not
+    /// listed keep their name with a warning.
+    #[arg(long, value_name = "TSV")]
+    contig_aliases: Option<PathBuf>,
+
+    /// Reference paths in hit rows and --emit-manifest: `native` as given, or `forward` with
+    /// `/` separators on every platform so outputs match across systems.
+    #[arg(long, value_enum, default_value_t)]
+    path_style: PathStyleArg,
+
+    /// Exit with status 2 unless the scan's hit hash (SHA-256 of the sorted canonical hit rows)
+    /// equals this hex string.
+    #[arg(long, value_name = "SHA256")]
+    verify_hash: Option<String>,
+
+    /// Print the scan's hit hash to stderr (the value to pass to --verify-hash later).
+    #[arg(long)]
+    print_hash: bool,
+
+    /// Stamp every hit and summary row with this label (a trailing `tag` column, a `tag`
+    /// field in NDJSON), e.g. the pass of a multi-pass scan. Tagged hits hash differently
+    /// from untagged ones.
+    #[arg(long, value_name = "LABEL")]
+    tag: Option<String>,
+
+    /// Leave --tag out of the hit hash, so --print-hash / --verify-hash match untagged runs.
+    #[arg(long, requires = "tag")]
+    ignore_tag: bool,
+
+    /// Write a JSON manifest of the run (version, threads, arguments, every option value and
+    /// SHA-256 digests of the primer and reference files) to this path before scanning.
+    #[arg(long, value_name = "PATH")]
+    emit_manifest: Option<PathBuf>,
+
+    /// Instead of scanning, explain why one primer does or does not hit one window: the
+    /// per-base comparison on each strand, the filter that rejected it, and the nearest hits.
+    /// `pos` is the 0-based window start, as in hit rows.
+    #[arg(
+        long,
+        num_args = 3,
+        value_names = ["primer=NAME", "contig=CHR", "pos=START"]
+    )]
+    explain: Option<Vec<String>>,
+
+    /// Print the fraction of scanned reference bases inside at least one hit to stderr.
+    #[arg(long)]
+    genome_coverage: bool,
+
+    /// Spill sorted hit runs to temp files once more than N hits are held in memory, then
+    /// merge them on output. Keeps genome-wide scans of promiscuous primers within modest RAM.
+    /// Also sizes the runs of `--output-format bed-sorted`.
+    #[arg(
+        long,
+        value_name = "N",
+        conflicts_with_all = [
+            "no_sort",
+            "gaps",
+            "amplicons_bed",
+            "amplicon_exclude_bed",
+            "repeat_library",
+            "dedup_hits",
+            "sanitize_contig_names",
+            "groups",
+            "split_orientations",
+            "emit_hit_fasta",
+            "count_by",
+            "hotspots",
+            "assay_report",
+            "position_profile",
+            "haplotype_regex",
+            "contig_summary",
+        ]
+    )]
+    spill_threshold: Option<usize>,
+
+    /// Print each warning category at most this many times, then only count it.
+    #[arg(long, value_name = "N", default_value_t = DEFAULT_WARNING_LIMIT)]
+    max_warnings: usize,
+
+    /// Emit one JSON object per line instead of TSV.
+    #[arg(long)]
+    json: bool,
+
+    /// Write hit rows to --output in a binary format instead of text on stdout.
+    #[cfg(feature = "bincode")]
+    #[arg(
+        long,
+        value_enum,
+        requires = "output",
+        conflicts_with_all = [
+            "json",
+            "count_only",
+            "summary",
+            "hotspots",
+            "assay_report",
+            "output_per_primer",
+            "output_format",
+            "hits_out",
+        ]
+    )]
+    format: Option<HitFormat>,
+
+    /// Write hit (or --summary) rows to this file instead of stdout, in the format its
+    /// extension names (.tsv, .csv, .json/.ndjson, .bed, .gff3; `.gz` is compressed). Unknown
+    /// extensions get TSV.
+    #[arg(
+        long,
+        short = 'o',
+        value_name = "PATH",
+        conflicts_with_all = ["count_only", "hotspots", "assay_report", "output_per_primer"]
+    )]
+    output: Option<PathBuf>,
+
+    /// Layout of hit (or --summary) rows; overrides the --output extension.
+    #[arg(long, value_enum, value_name = "FORMAT", conflicts_with = "json")]
+    output_format: Option<OutputFormatArg>,
+
+    /// Write the autoSql (.as) file describing --output-format bed-sorted columns, for
+    /// `bedToBigBed -type=bed6+2 -as=PATH`.
+    #[arg(long, value_name = "PATH")]
+    autosql: Option<PathBuf>,
+
+    /// Write --output in --output-format even when its extension names another format.
+    #[arg(long, requires_all = ["output", "output_format"])]
+    force_format: bool,
+
+    /// Write each primer's hit rows to `<DIR>/<primer>.tsv` (`.ndjson` with --json) instead of
+    /// stdout.
+    #[arg(
+        long,
+        value_name = "DIR",
+        conflicts_with_all = ["count_only", "summary", "hotspots", "assay_report"]
+    )]
+    output_per_primer: Option<PathBuf>,
+
+    /// Write hit rows to this file and summary rows to --summary-out from the same scan. Each
+    /// file's format follows its extension as for --output (`.gz` is compressed).
+    #[arg(
+        long,
+        value_name = "PATH",
+        requires = "summary_out",
+        conflicts_with_all = [
+            "output",
+            "output_per_primer",
+            "count_only",
+            "summary",
+            "hotspots",
+            "assay_report",
+        ]
+    )]
+    hits_out: Option<PathBuf>,
+
+    /// Per-primer summary rows for --hits-out.
+    #[arg(long, value_name = "PATH", requires = "hits_out")]
+    summary_out: Option<PathBuf>,
+
+    /// Color TSV hit rows per primer (`auto` honors NO_COLOR and non-terminal stdout).
+    #[arg(long, value_enum, default_value_t = ColorChoice::Auto)]
+    color: ColorChoice,
+
+    /// Output per-primer summary rows.
+    ///
+    /// Palindromic primers (equal to their reverse complement) are scanned once: each site is a
+    /// single `+` hit row, counted once in total_hits and in both forward_hits and reverse_hits.
+    #[arg(long)]
+    summary: bool,
+
+    /// Report reverse-strand hits under their own `<primer>_rc` name, with separate summary rows.
+    #[arg(long)]
+    split_orientations: bool,
+
+    /// Group --summary rows by the panel's `pool` column, 5 bp primer length class, or the whole
+    /// panel, closing each group with a `__subtotal__` row (nested groups with --json).
+    #[arg(
+        long,
+        value_name = "KEY",
+        requires = "summary",
+        conflicts_with = "groups"
+    )]
+    group_by: Option<GroupBy>,
+
+    /// primer<tab>group TSV; --summary then reports one row per group instead of per primer.
+    #[arg(long, value_name = "TSV")]
+    groups: Option<PathBuf>,
+
+    /// Add the first and last hit position (file, contig, start), the expected random hit
+    /// count, and the mean and standard deviation of mismatches per hit to --summary rows.
+    #[arg(long, requires = "summary")]
+    extended_summary: bool,
+
+    /// Add hits_k0 .. hits_kK columns to --summary rows: hits with exactly 0, 1, ... -k
+    /// mismatches.
+    #[arg(long, requires = "summary")]
+    mismatch_breakdown: bool,
+
+    /// Add a strand_bias column to --summary rows: (forward - reverse) / (forward + reverse)
+    /// hits, 0 without hits. NDJSON summary rows always carry it.
+    #[arg(long, requires = "summary")]
+    strand_bias: bool,
+
+    /// Output only total number of hits.
+    #[arg(long)]
+    count_only: bool,
+
+    /// Scan a random 1% of the reference and print the estimated total hit count with a 95%
+    /// confidence interval instead of any hits.
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "count_only",
+            "summary",
+            "hotspots",
+            "assay_report",
+            "output",
+            "output_per_primer",
+            "verify_hash",
+        ]
+    )]
+    dry_run_estimate: bool,
+
+    /// Break the count-only total down by a hit attribute.
+    #[arg(long, value_enum, requires = "count_only")]
+    count_by: Option<CountBy>,
+
+    /// Name suffixes marking forward/reverse primer pairs, e.g. `_F,_R`.
+    #[arg(long, value_name = "FWD,REV", value_parser = parse_pair_suffixes)]
+    pair_suffixes: Option<(String, String)>,
+
+    /// Largest amplicon (bp) reported for paired hits.
+    #[arg(long, default_value_t = DEFAULT_MAX_AMPLICON_SIZE)]
+    max_amplicon: usize,
+
+    /// Largest distance (bp) reported for the nearest partner-primer hit.
+    #[arg(long, default_value_t = DEFAULT_PARTNER_WINDOW)]
+    partner_window: usize,
+
+    /// Keep only hits with a converging partner hit within --max-amplicon.
+    #[arg(long, requires = "pair_suffixes")]
+    only_amplifiable: bool,
+
+    /// Predict whether each hit's primer would extend: the 3'-terminal base must pair, at most
+    /// one of the five 3'-most bases may mismatch, and one paired base there must be G or C.
+    /// Adds an `extendable` column (field in NDJSON).
+    #[arg(long)]
+    predict_extension: bool,
+
+    /// Keep only hits --predict-extension calls extendable.
+    #[arg(long, requires = "predict_extension")]
+    extendable_only: bool,
+
+    /// Instead of hit rows, print one row per primer pair (assay): components found, worst
+    /// mismatch, predicted product size, off-target product count, and a verdict.
+    #[arg(
+        long,
+        requires = "pair_suffixes",
+        conflicts_with_all = ["count_only", "summary", "hotspots", "only_amplifiable", "split_orientations"]
+    )]
+    assay_report: bool,
+
+    /// Instead of hit rows, write one row per primer and sample to --output or stdout: whether
+    /// the primer hits `both` haplotypes, `hap1_only`, `hap2_only`, or `none`. Contigs are
+    /// grouped into samples by the regex's `sample` capture (e.g. '(?P<sample>.+)_hap[12]'); an
+    /// optional `hap` capture names the haplotype, otherwise the contig name does.
+    #[arg(
+        long,
+        value_name = "REGEX",
+        conflicts_with_all = ["count_only", "summary", "hotspots", "assay_report", "hits_out"]
+    )]
+    haplotype_regex: Option<String>,
+
+    /// Instead of hit rows, write one row per contig with hits to --output or stdout, most-hit
+    /// first: contig, total_hits, distinct_primers, perfect_hits. Spots repeat-rich or decoy contigs that
+    /// attract a disproportionate share of hits.
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "count_only",
+            "summary",
+            "hotspots",
+            "assay_report",
+            "hits_out",
+            "haplotype_regex",
+        ]
+    )]
+    contig_summary: bool,
+
+    /// Seed for sampling/randomized steps; same seed + same inputs gives identical output.
+    #[arg(long, default_value_t = DEFAULT_SEED)]
+    seed: u64,
+
+    /// Contig memory layout: `fast` (~2 bytes/base) or `lean` (~0.5 bytes/base, slower).
+    #[arg(long, value_enum)]
+    memory_profile: Option<MemoryProfileArg>,
+
+    /// Memory budget (e.g. `4G`, `512M`); picks `lean` when the largest reference may not fit.
+    #[arg(long, value_name = "BYTES", value_parser = parse_byte_size)]
+    max_memory: Option<u64>,
+
+    /// Window search: `exhaustive` tests every window; `bloom` tests only windows holding a
+    /// primer's 12-mer seed (faster for panels of thousands of primers, same hits).
+    #[arg(long, value_enum, default_value_t)]
+    algorithm: ScanAlgorithmArg,
+
+    /// Number of worker threads.
+    #[arg(long, default_value_t = default_threads())]
+    threads: usize,
+
+    /// Time a 100 kbp calibration scan at 1, 2, 4, ... threads and use the fastest count.
+    #[arg(long, conflicts_with = "threads")]
+    auto_tune_threads: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum CountBy {
+    /// One row per mismatch level (0..=max-mismatches).
+    Mismatches,
+}
+
+#[cfg(feature = "bincode")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum HitFormat {
+    /// bincode-encoded hits for `primer_scout::binary_hits::load_hits`; tied to this
+    /// primer-scout version.
+    Bincode,
+}
+
+/// How `--group-by` assigns summary rows to groups.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum GroupBy {
+    /// The panel's `pool` column.
+    Pool,
+    /// 5 bp primer length classes, e.g. `20-24`.
+    Length,
+    /// One group for the whole panel.
+    Panel,
+}
+
+// Command-line spellings of library enums; the library types stay free of clap.
+
+/// `--primers-stdin-format` values; see [`PrimerFormat`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum PrimerFormatArg {
+    /// `name<tab>sequence` rows, optional header.
+    Tsv,
+    /// `name,sequence` rows, optional header.
+    Csv,
+    /// `>name` records.
+    Fasta,
+    /// One `{"name": ..., "sequence": ...}` object per line; `name` and `pool` are optional.
+    Jsonl,
+}
+
+impl From<PrimerFormatArg> for PrimerFormat {
+    fn from(format: PrimerFormatArg) -> Self {
+        match format {
+            PrimerFormatArg::Tsv => Self::Tsv,
+            PrimerFormatArg::Csv => Self::Csv,
+            PrimerFormatArg::Fasta => Self::Fasta,
+            PrimerFormatArg::Jsonl => Self::Jsonl,
+        }
+    }
+}
+
+/// `--overhang-scoring` values; see [`OverhangScoring`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+enum OverhangScoringArg {
+    /// Each off-contig base counts as a mismatch, so at most `max_mismatches` bases hang off.
+    #[default]
+    Mismatch,
+    /// Off-contig bases are not scored; at least half of the primer must lie on the contig.
+    Exclude,
+}
+
+impl From<OverhangScoringArg> for OverhangScoring {
+    fn from(scoring: OverhangScoringArg) -> Self {
+        match scoring {
+            OverhangScoringArg::Mismatch => Self::Mismatch,
+            OverhangScoringArg::Exclude => Self::Exclude,
+        }
+    }
+}
+
+/// `--path-style` values; see [`PathStyle`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+enum PathStyleArg {
+    /// As given, with this platform's separators (`\` on Windows).
+    #[default]
+    Native,
+    /// `/` separators on every platform, so outputs match across systems.
+    Forward,
+}
+
+impl From<PathStyleArg> for PathStyle {
+    fn from(style: PathStyleArg) -> Self {
+        match style {
+            PathStyleArg::Native => Self::Native,
+            PathStyleArg::Forward => Self::Forward,
+        }
+    }
+}
+
+/// `--output-format` values; see [`OutputFormat`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OutputFormatArg {
+    /// Tab-separated rows.
+    Tsv,
+    /// Comma-separated rows.
+    Csv,
+    /// JSON objects, one per line.
+    #[value(alias = "json")]
+    Ndjson,
+    /// BED hit intervals.
+    Bed,
+    /// Sorted BED intervals with extra columns, ready for `bedToBigBed`.
+    BedSorted,
+    /// GFF3 primer-binding-site features.
+    Gff3,
+    /// `primer_bind` entries of a GenBank feature table.
+    GenbankFeatures,
+    /// Recognized so `.sam` paths are not silently written as TSV; not implemented yet.
+    Sam,
+}
+
+impl From<OutputFormatArg> for OutputFormat {
+    fn from(format: OutputFormatArg) -> Self {
+        match format {
+            OutputFormatArg::Tsv => Self::Tsv,
+            OutputFormatArg::Csv => Self::Csv,
+            OutputFormatArg::Ndjson => Self::Ndjson,
+            OutputFormatArg::Bed => Self::Bed,
+            OutputFormatArg::BedSorted => Self::BedSorted,
+            OutputFormatArg::Gff3 => Self::Gff3,
+            OutputFormatArg::GenbankFeatures => Self::GenbankFeatures,
+            OutputFormatArg::Sam => Self::Sam,
+        }
+    }
+}
+
+/// `--memory-profile` values; see [`MemoryProfile`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum MemoryProfileArg {
+    /// Normalized bases plus one mask byte per base (~2 bytes/base).
+    Fast,
+    /// 4-bit masks packed two per byte (~0.5 bytes/base); slower inner loop.
+    Lean,
+}
+
+impl From<MemoryProfileArg> for MemoryProfile {
+    fn from(profile: MemoryProfileArg) -> Self {
+        match profile {
+            MemoryProfileArg::Fast => Self::Fast,
+            MemoryProfileArg::Lean => Self::Lean,
+        }
+    }
+}
+
+/// `--algorithm` values; see [`ScanAlgorithm`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+enum ScanAlgorithmArg {
+    /// Test every window of every primer.
+    #[default]
+    Exhaustive,
+    /// Test only windows holding a primer's 12-mer seed; same hits.
+    Bloom,
+}
+
+impl From<ScanAlgorithmArg> for ScanAlgorithm {
+    fn from(algorithm: ScanAlgorithmArg) -> Self {
+        match algorithm {
+            ScanAlgorithmArg::Exhaustive => Self::Exhaustive,
+            ScanAlgorithmArg::Bloom => Self::Bloom,
+        }
+    }
+}
+
+/// `export-panel --format` values; see [`PanelFormat`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum PanelFormatArg {
+    /// `name<tab>sequence` with a header row.
+    Tsv,
+    /// One `>name` record per primer.
+    Fasta,
+    /// IDT bulk upload CSV: Name, Sequence, Scale, Purification.
+    Idt,
+}
+
+impl From<PanelFormatArg> for PanelFormat {
+    fn from(format: PanelFormatArg) -> Self {
+        match format {
+            PanelFormatArg::Tsv => Self::Tsv,
+            PanelFormatArg::Fasta => Self::Fasta,
+            PanelFormatArg::Idt => Self::Idt,
+        }
+    }
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Write the normalized primer panel (U->T, uppercase) for ordering or re-import.
+    ExportPanel(ExportPanelArgs),
+    /// Build a panel TSV from BED primer locations by extracting their reference sequence.
+    ExtractPanel(ExtractPanelArgs),
+    /// Print the degenerate (IUPAC) primer covering every sequence of an aligned panel.
+    Consensus(ConsensusArgs),
+    /// Write the built-in demo panel and reference used by the quickstart commands.
+    Demo(DemoArgs),
+    /// Save a validated panel as a binary `.psp` file that `--primers` loads without parsing.
+    #[cfg(feature = "bincode")]
+    #[command(name = "compile-panel")]
+    Compile(CompilePanelArgs),
+}
+
+#[cfg(feature = "bincode")]
+#[derive(Debug, Args)]
+struct CompilePanelArgs {
+    /// Primer panel file (.tsv, .csv or FASTA), or `-` for stdin.
+    #[arg(long, short = 'p')]
+    primers: PathBuf,
+
+    /// Compiled panel to write; tied to this primer-scout version.
+    #[arg(long, short = 'o', value_name = "PANEL.psp")]
+    out: PathBuf,
+}
+
+#[derive(Debug, Args)]
+struct ConsensusArgs {
+    /// Aligned, equal-length sequences (FASTA, .tsv or .csv), or `-` for stdin.
+    #[arg(long, short = 'p')]
+    primers: PathBuf,
+}
+
+fn run_consensus(args: &ConsensusArgs) -> Result<()> {
+    let primers = load_panel(&args.primers, None)
+        .with_context(|| format!("failed loading sequences from '{}'", args.primers.display()))?;
+    let sequences = primers
+        .iter()
+        .map(|primer| primer.sequence.as_str())
+        .collect::<Vec<_>>();
+    let consensus = consensus_primer(&sequences)
+        .with_context(|| format!("no consensus for '{}'", args.primers.display()))?;
+    println!("{consensus}");
+    Ok(())
+}
+
+#[derive(Debug, Args)]
+struct DemoArgs {
+    /// Directory to write demo_primers.tsv and demo.fa into.
+    #[arg(long, short = 'o', value_name = "DIR", default_value = DEMO_DIR)]
+    out_dir: PathBuf,
+}
+
+fn run_demo(args: &DemoArgs) -> Result<()> {
+    let (primers, reference) = write_demo(&args.out_dir)?;
+    println!("wrote {} and {}", primers.display(), reference.display());
+    println!(
+        "try: primer-scout --primers {} --reference {} --summary",
+        primers.display(),
+        reference.display()
+    );
+    Ok(())
+}
+
+#[derive(Debug, Args)]
+struct ExportPanelArgs {
+    /// Primer panel file (.tsv, .csv or FASTA), or `-` for stdin.
+    #[arg(long, short = 'p')]
+    primers: PathBuf,
+
+    /// Output layout.
+    #[arg(long, value_enum, default_value_t = PanelFormatArg::Tsv)]
+    format: PanelFormatArg,
+
+    /// Output file (default: stdout).
+    #[arg(long, short = 'o')]
+    output: Option<PathBuf>,
+
+    /// Synthesis scale column value for `--format idt`.
+    #[arg(long, default_value_t = IdtDefaults::default().scale)]
+    idt_scale: String,
+
+    /// Purification column value for `--format idt`.
+    #[arg(long, default_value_t = IdtDefaults::default().purification)]
+    idt_purification: String,
+
+    /// Wrap width of FASTA sequence lines for `--format fasta` (0 = no wrapping).
+    #[arg(long, value_name = "N", default_value_t = DEFAULT_FASTA_LINE_WIDTH)]
+    fasta_line_width: usize,
+}
+
+fn is_stdin(path: &Path) -> bool {
+    path == Path::new("-")
+}
+
+/// Loads a panel from a file, or from stdin when `path` is `-`.
+fn load_panel(path: &Path, stdin_format: Option<PrimerFormat>) -> Result<Vec<Primer>> {
+    if is_stdin(path) {
+        return load_primers_from_reader(
+            io::stdin().lock(),
+            stdin_format.unwrap_or_default(),
+            "<stdin>",
+        );
+    }
+    if stdin_format.is_some() {
+        bail!("--primers-stdin-format only applies to --primers -");
+    }
+    if path
+        .extension()
+        .is_some_and(|extension| extension == COMPILED_PANEL_EXTENSION)
+    {
+        return load_compiled_panel(path);
+    }
+    load_primers(path)
+}
+
+/// Extension of `compile-panel` output, which `--primers` loads without parsing.
+const COMPILED_PANEL_EXTENSION: &str = "psp";
+
+#[cfg(feature = "bincode")]
+fn load_compiled_panel(path: &Path) -> Result<Vec<Primer>> {
+    crate::compiled_panel::load_compiled_panel(path)
+}
+
+#[cfg(not(feature = "bincode"))]
+fn load_compiled_panel(path: &Path) -> Result<Vec<Primer>> {
+    bail!(
+        "'{}' is a compiled panel, but this build lacks the bincode feature; pass the source \
+         panel instead",
+        path.display()
+    )
+}
+
+fn export_panel(args: &ExportPanelArgs) -> Result<()> {
+    let primers = load_panel(&args.primers, None)
+        .with_context(|| format!("failed loading primers from '{}'", args.primers.display()))?;
+    let idt = IdtDefaults {
+        scale: args.idt_scale.clone(),
+        purification: args.idt_purification.clone(),
+    };
+
+    match &args.output {
+        Some(path) => {
+            let file = File::create(path)
+                .with_context(|| format!("failed to create '{}'", path.display()))?;
+            let mut out = BufWriter::new(file);
+            write_panel(
+                &mut out,
+                &primers,
+                args.format.into(),
+                &idt,
+                args.fasta_line_width,
+            )?;
+            out.flush()?;
+        }
+        None => {
+            let mut out = BufWriter::new(io::stdout().lock());
+            write_panel(
+                &mut out,
+                &primers,
+                args.format.into(),
+                &idt,
+                args.fasta_line_width,
+            )?;
+            out.flush()?;
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug, Args)]
+struct ExtractPanelArgs {
+    /// BED of primer locations: chrom, start, end, name, and optional score and strand.
+    #[arg(long)]
+    bed: PathBuf,
 
-    let scan = pool.install(|| scan_references(&cli.references, &primers, &options))?;
+    /// Reference FASTA (.fa/.fasta, optionally .gz) the BED coordinates refer to.
+    #[arg(long, short = 'r')]
+    reference: PathBuf,
 
-    if cli.count_only {
-        emit_count(scan.total_hits, cli.json)?;
-    } else if cli.summary {
-        emit_summary(&scan.summary, cli.json)?;
-    } else {
-        emit_hits(&scan.hits, cli.json)?;
-    }
+    /// Output panel TSV (default: stdout).
+    #[arg(long, short = 'o')]
+    out: Option<PathBuf>,
+}
 
+/// `--explain`: finds the primer and the first reference holding the contig, then prints the
+/// window's explanation.
+fn run_explain(
+    references: &[PathBuf],
+    terms: &[String],
+    primers: &[Primer],
+    options: &ScanOptions,
+) -> Result<()> {
+    let query = ExplainQuery::parse(terms)?;
+    let primer = primers
+        .iter()
+        .find(|primer| primer.name == query.primer)
+        .with_context(|| format!("primer '{}' is not in the panel", query.primer))?;
+    // `contig=` names the contig as hits report it, after aliasing and sanitizing.
+    let mut found = None;
+    for reference in references {
+        let contigs = load_contigs_where(reference, |header| {
+            options.reported_contig_name(header) == query.contig
+        })
+        .with_context(|| format!("failed reading reference '{}'", reference.display()))?;
+        if let Some(contig) = contigs.into_iter().next() {
+            found = Some(contig);
+            break;
+        }
+    }
+    let Some((header_name, sequence)) = found else {
+        bail!("contig '{}' is not in any --reference file", query.contig);
+    };
+    // Regions, known sites, and transcripts are keyed by the aliased name.
+    let scanned = ExplainQuery {
+        contig: options.aliased_contig_name(&header_name).to_string(),
+        ..query.clone()
+    };
+    let mut explanation = explain_window(
+        &String::from_utf8_lossy(&sequence),
+        &scanned,
+        primer,
+        options,
+    )?;
+    if options.sanitize_contig_names {
+        explanation.contig = query.contig.clone();
+        for hit in &mut explanation.nearest_hits {
+            hit.original_contig = Some(std::mem::replace(&mut hit.contig, query.contig.clone()));
+        }
+    }
+    let mut out = BufWriter::new(io::stdout().lock());
+    write_explanation(&mut out, &explanation)?;
+    out.flush()?;
     Ok(())
 }
 
-#[derive(Debug, Parser)]
-#[command(
-    version,
-    about = "Fast Rust primer off-target scanner for FASTA references"
-)]
-struct Cli {
-    /// Primer panel file (.tsv or .csv). Format: name<tab>sequence.
-    #[arg(long, short = 'p')]
-    primers: PathBuf,
-
-    /// Reference FASTA file(s), plain text or .gz.
-    #[arg(long = "reference", short = 'r', value_name = "FASTA", required = true)]
-    references: Vec<PathBuf>,
+#[cfg(feature = "bincode")]
+fn compile_panel(args: &CompilePanelArgs) -> Result<()> {
+    if args
+        .out
+        .extension()
+        .is_none_or(|extension| extension != COMPILED_PANEL_EXTENSION)
+    {
+        bail!(
+            "compiled panel '{}' must end in .{COMPILED_PANEL_EXTENSION} for --primers to \
+             recognize it",
+            args.out.display()
+        );
+    }
+    let primers = load_panel(&args.primers, None)
+        .with_context(|| format!("failed loading primers from '{}'", args.primers.display()))?;
+    crate::compiled_panel::save_compiled_panel(&args.out, &primers)?;
+    eprintln!(
+        "compiled {} primers into '{}'",
+        primers.len(),
+        args.out.display()
+    );
+    Ok(())
+}
 
-    /// Allowed substitutions per hit.
-    #[arg(long = "max-mismatches", short = 'k', default_value_t = 1)]
-    max_mismatches: usize,
+fn run_extract_panel(args: &ExtractPanelArgs) -> Result<()> {
+    let primers = extract_panel(&args.bed, &args.reference)
+        .with_context(|| format!("failed extracting primers from '{}'", args.bed.display()))?;
+    let idt = IdtDefaults::default();
 
-    /// Disable reverse-complement scanning.
-    #[arg(long)]
-    no_revcomp: bool,
+    match &args.out {
+        Some(path) => {
+            let file = File::create(path)
+                .with_context(|| format!("failed to create '{}'", path.display()))?;
+            let mut out = BufWriter::new(file);
+            write_panel(&mut out, &primers, PanelFormat::Tsv, &idt, 0)?;
+            out.flush()?;
+        }
+        None => {
+            let mut out = BufWriter::new(io::stdout().lock());
+            write_panel(&mut out, &primers, PanelFormat::Tsv, &idt, 0)?;
+            out.flush()?;
+        }
+    }
+    Ok(())
+}
 
-    /// Emit one JSON object per line instead of TSV.
-    #[arg(long)]
-    json: bool,
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
 
-    /// Output per-primer summary rows.
-    #[arg(long)]
-    summary: bool,
+impl ColorChoice {
+    fn enabled(self) -> bool {
+        match self {
+            Self::Always => true,
+            Self::Never => false,
+            Self::Auto => {
+                std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+                    && io::stdout().is_terminal()
+            }
+        }
+    }
+}
 
-    /// Output only total number of hits.
-    #[arg(long)]
-    count_only: bool,
+const PRIMER_PALETTE: [Color; 10] = [
+    Color::Cyan,
+    Color::Green,
+    Color::Yellow,
+    Color::Magenta,
+    Color::Blue,
+    Color::Red,
+    Color::DarkCyan,
+    Color::DarkGreen,
+    Color::DarkYellow,
+    Color::DarkMagenta,
+];
 
-    /// Number of worker threads.
-    #[arg(long, default_value_t = default_threads())]
-    threads: usize,
+/// Stable color for a primer name (FNV-1a hash into a fixed palette), identical across runs.
+pub fn primer_color(name: &str) -> Color {
+    let hash = name.bytes().fold(0xcbf2_9ce4_8422_2325u64, |acc, byte| {
+        (acc ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    });
+    PRIMER_PALETTE[(hash % PRIMER_PALETTE.len() as u64) as usize]
 }
 
 fn default_threads() -> usize {
@@ -104,24 +1647,470 @@ fn available_threads() -> usize {
         .unwrap_or(1)
 }
 
-fn emit_hits(hits: &[crate::Hit], as_json: bool) -> Result<()> {
-    let mut out = BufWriter::new(io::stdout().lock());
+fn resolve_memory_profile(cli: &Cli) -> MemoryProfile {
+    if let Some(profile) = cli.memory_profile {
+        return profile.into();
+    }
+    let Some(budget) = cli.max_memory else {
+        return MemoryProfile::Fast;
+    };
+
+    let largest_reference_bases = cli
+        .references
+        .iter()
+        .filter_map(|path| {
+            let size = std::fs::metadata(path).ok()?.len();
+            let is_gz = path
+                .extension()
+                .and_then(|x| x.to_str())
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("gz"));
+            Some(if is_gz {
+                size.saturating_mul(GZIP_EXPANSION_ESTIMATE)
+            } else {
+                size
+            })
+        })
+        .max()
+        .unwrap_or(0);
+
+    if largest_reference_bases.saturating_mul(FAST_PROFILE_BYTES_PER_BASE) > budget {
+        MemoryProfile::Lean
+    } else {
+        MemoryProfile::Fast
+    }
+}
+
+fn parse_unit_fraction(raw: &str) -> std::result::Result<f64, String> {
+    let value = raw
+        .trim()
+        .parse::<f64>()
+        .map_err(|_| format!("invalid number '{raw}'"))?;
+    if (0.0..=1.0).contains(&value) {
+        Ok(value)
+    } else {
+        Err(format!("value must be within [0, 1], got {value}"))
+    }
+}
+
+fn parse_byte_size(raw: &str) -> std::result::Result<u64, String> {
+    let trimmed = raw.trim();
+    let (digits, multiplier) = match trimmed.char_indices().last() {
+        Some((idx, unit)) if unit.is_ascii_alphabetic() => {
+            let multiplier = match unit.to_ascii_uppercase() {
+                'K' => 1u64 << 10,
+                'M' => 1u64 << 20,
+                'G' => 1u64 << 30,
+                'T' => 1u64 << 40,
+                _ => return Err(format!("unknown size unit '{unit}' (use K, M, G or T)")),
+            };
+            (&trimmed[..idx], multiplier)
+        }
+        _ => (trimmed, 1),
+    };
+    let value = digits
+        .trim()
+        .parse::<u64>()
+        .map_err(|_| format!("invalid byte size '{raw}'"))?;
+    value
+        .checked_mul(multiplier)
+        .filter(|bytes| *bytes > 0)
+        .ok_or_else(|| format!("byte size '{raw}' must be > 0 and fit in 64 bits"))
+}
+
+fn parse_pair_suffixes(raw: &str) -> std::result::Result<(String, String), String> {
+    let (forward, reverse) = raw
+        .split_once(',')
+        .ok_or_else(|| "expected two comma-separated suffixes, e.g. _F,_R".to_string())?;
+    let (forward, reverse) = (forward.trim(), reverse.trim());
+    if forward.is_empty() || reverse.is_empty() {
+        return Err("pair suffixes must not be empty".to_string());
+    }
+    if forward == reverse {
+        return Err("forward and reverse suffixes must differ".to_string());
+    }
+    Ok((forward.to_string(), reverse.to_string()))
+}
+
+/// The built-in [`HitSink`] selected by the output flags, writing to `--output` or stdout.
+fn output_sink(cli: &Cli) -> Result<Box<dyn HitSink>> {
+    if let Some(dir) = &cli.output_per_primer {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("failed to create '{}'", dir.display()))?;
+        return Ok(per_primer_sink(cli, dir));
+    }
+    let explicit = explicit_output_format(cli);
+    let Some(path) = &cli.output else {
+        let out = BufWriter::new(io::stdout().lock());
+        let format = explicit.unwrap_or(OutputFormat::Tsv);
+        return text_sink(cli, format, out, cli.color.enabled());
+    };
+    file_sink(cli, path, explicit)
+}
+
+/// The text format named by `--output-format` or `--json`, if any.
+fn explicit_output_format(cli: &Cli) -> Option<OutputFormat> {
+    cli.output_format
+        .map(Into::into)
+        .or(cli.json.then_some(OutputFormat::Ndjson))
+}
+
+/// A text sink writing `path` in `explicit` or the format its extension names, gzip-compressed
+/// for `.gz`.
+fn file_sink(cli: &Cli, path: &Path, explicit: Option<OutputFormat>) -> Result<Box<dyn HitSink>> {
+    if explicit.is_none() && OutputFormat::from_path(path).is_none() {
+        eprintln!(
+            "info: no output format is known for the extension of '{}'; writing TSV",
+            path.display()
+        );
+    }
+    let format = output_format(path, explicit, cli.force_format)?;
+    text_sink(cli, format, create_output(path)?, false)
+}
+
+/// A new file at `path`, gzip-compressed for `.gz`.
+fn create_output(path: &Path) -> Result<BufWriter<Box<dyn Write>>> {
+    let file =
+        File::create(path).with_context(|| format!("failed to create '{}'", path.display()))?;
+    let out: Box<dyn Write> = if path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("gz"))
+    {
+        Box::new(GzEncoder::new(file, Compression::default()))
+    } else {
+        Box::new(file)
+    };
+    Ok(BufWriter::new(out))
+}
+
+fn text_sink<W: Write + 'static>(
+    cli: &Cli,
+    format: OutputFormat,
+    out: W,
+    color: bool,
+) -> Result<Box<dyn HitSink>> {
+    let columns = TsvColumns {
+        color,
+        ..tsv_columns(cli)
+    };
+    Ok(match format {
+        OutputFormat::Tsv => Box::new(TsvSink::new(out, columns, cli.extended_summary)),
+        OutputFormat::Csv => Box::new(TsvSink::csv(out, columns, cli.extended_summary)),
+        OutputFormat::Ndjson => Box::new(JsonSink::new(out, cli.extended_summary)),
+        OutputFormat::Bed => Box::new(BedSink::new(out)),
+        OutputFormat::BedSorted => {
+            let sink = SortedBedSink::new(out);
+            match cli.spill_threshold {
+                Some(hits) => Box::new(sink.with_run_hits(hits)),
+                None => Box::new(sink),
+            }
+        }
+        OutputFormat::Gff3 => Box::new(Gff3Sink::new(out)),
+        OutputFormat::GenbankFeatures => Box::new(GenbankFeatureSink::new(out)),
+        OutputFormat::Sam => bail!("SAM output is not supported; use bed or gff3"),
+    })
+}
+
+fn tsv_columns(cli: &Cli) -> TsvColumns {
+    TsvColumns {
+        pairing: cli.pair_suffixes.is_some(),
+        color: false,
+        primer_sequence: cli.with_primer_seq,
+        gc_skew: cli.gc_skew,
+        matched_fraction: cli.matched_fraction,
+        transcripts: cli.transcripts.is_some(),
+        relative_position: cli.relative_position,
+        masked_sequence: cli.masked_sequence,
+        overhang: cli.allow_overhang,
+        repeat_class: cli.repeat_library.is_some(),
+        ambiguity: cli.ambiguity_columns,
+        extendable: cli.predict_extension,
+        mismatch_breakdown: cli.mismatch_breakdown,
+        strand_bias: cli.strand_bias,
+        tag: cli.tag.is_some(),
+        known_suppressed: cli.known_sites.is_some(),
+        timed_out: cli.time_limit_per_primer.is_some(),
+    }
+}
+
+/// One uncolored TSV (or NDJSON) file per primer in `dir`.
+fn per_primer_file_sink(cli: &Cli, dir: &Path) -> PerPrimerSink {
+    let (json, columns) = (cli.json, tsv_columns(cli));
+    let (extension, make_sink): (_, FileSinkFactory) = if json {
+        (
+            "ndjson",
+            Box::new(|out| Box::new(JsonSink::new(out, false))),
+        )
+    } else {
+        (
+            "tsv",
+            Box::new(move |out| Box::new(TsvSink::new(out, columns, false))),
+        )
+    };
+    PerPrimerSink::new(dir.to_path_buf(), extension, make_sink)
+}
+
+#[cfg(not(feature = "async-output"))]
+fn per_primer_sink(cli: &Cli, dir: &Path) -> Box<dyn HitSink> {
+    Box::new(per_primer_file_sink(cli, dir))
+}
+
+/// Spreads the per-primer files over background writer threads.
+#[cfg(feature = "async-output")]
+fn per_primer_sink(cli: &Cli, dir: &Path) -> Box<dyn HitSink> {
+    Box::new(crate::sink::ChannelSink::new(
+        OUTPUT_WRITER_THREADS,
+        OUTPUT_CHANNEL_CAPACITY,
+        |_| Box::new(per_primer_file_sink(cli, dir)),
+    ))
+}
+
+/// Writes the hits to `--output` when `--format` asks for a binary file; `false` when the
+/// hits still go to stdout as text.
+#[cfg(feature = "bincode")]
+fn write_binary_hits(cli: &Cli, scan: &mut ScanResult) -> Result<bool> {
+    let (Some(HitFormat::Bincode), Some(path)) = (cli.format, &cli.output) else {
+        return Ok(false);
+    };
+    let hits = scan.hit_stream()?.collect::<Result<Vec<_>>>()?;
+    crate::binary_hits::save_hits(path, &hits)?;
+    Ok(true)
+}
+
+#[cfg(not(feature = "bincode"))]
+fn write_binary_hits(_cli: &Cli, _scan: &mut ScanResult) -> Result<bool> {
+    Ok(false)
+}
+
+/// Appends `_rc` to the primer of every `-` hit and rehashes them, so the hit hash
+/// describes the rows written.
+fn label_reverse_orientation_hits(scan: &mut ScanResult, ignore_tag: bool) {
+    for hit in scan.hits.iter_mut().filter(|hit| hit.strand == '-') {
+        hit.primer.push_str(REVERSE_ORIENTATION_SUFFIX);
+    }
+    scan.hit_hash = hash_hits(&scan.hits, ignore_tag);
+}
+
+/// Splits each summary row into a forward row (original name) and a `<primer>_rc` row,
+/// recounted from the hits. Palindromic `+` hits and collapsed `=` hits count toward both.
+fn split_summary_orientations(summary: &[PrimerSummary], hits: &[Hit]) -> Vec<PrimerSummary> {
+    let mut rows = BTreeMap::new();
+    let mut contigs: HashMap<String, HashSet<(&str, &str)>> = HashMap::new();
+    let mut mismatch_sums: HashMap<String, (u64, u64)> = HashMap::new();
+    for row in summary {
+        // Suppressed known-site hits are gone, so their orientation is unknown; the forward
+        // row keeps the primer's count. Palindromic `+` hits count toward both rows, and so
+        // does their expectation.
+        let (forward_expected, reverse_expected) = if row.is_palindromic {
+            (row.expected_random_hits, row.expected_random_hits)
+        } else {
+            let reverse = row.expected_random_reverse_hits;
+            (row.expected_random_hits - reverse, reverse)
+        };
+        for (name, known_suppressed, expected) in [
+            (row.primer.clone(), row.known_suppressed, forward_expected),
+            (
+                format!("{}{REVERSE_ORIENTATION_SUFFIX}", row.primer),
+                0,
+                reverse_expected,
+            ),
+        ] {
+            rows.insert(
+                name.clone(),
+                PrimerSummary {
+                    primer: name,
+                    primer_len: row.primer_len,
+                    total_hits: 0,
+                    perfect_hits: 0,
+                    forward_hits: 0,
+                    reverse_hits: 0,
+                    contigs_with_hits: 0,
+                    is_palindromic: row.is_palindromic,
+                    first_hit_position: None,
+                    last_hit_position: None,
+                    longer_than_every_contig: row.longer_than_every_contig,
+                    original_sequence: row.original_sequence.clone(),
+                    normalization: row.normalization,
+                    known_suppressed,
+                    timed_out: row.timed_out,
+                    expected_random_hits: expected,
+                    expected_random_reverse_hits: 0.0,
+                    mean_mismatch: 0.0,
+                    std_mismatch: 0.0,
+                    strand_bias: 0.0,
+                    per_mismatch_counts: vec![0; row.per_mismatch_counts.len()],
+                    tag: row.tag.clone(),
+                },
+            );
+        }
+    }
+
     for hit in hits {
+        let reverse_name = format!("{}{REVERSE_ORIENTATION_SUFFIX}", hit.primer);
+        let palindromic = rows.get(&hit.primer).is_some_and(|row| row.is_palindromic);
+        let targets = match hit.strand {
+            '+' if palindromic => vec![(hit.primer.clone(), true), (reverse_name, false)],
+            '+' => vec![(hit.primer.clone(), true)],
+            '-' => vec![(reverse_name, false)],
+            _ => vec![(hit.primer.clone(), true), (reverse_name, false)],
+        };
+        for (name, forward) in targets {
+            let Some(row) = rows.get_mut(&name) else {
+                continue;
+            };
+            row.total_hits += 1;
+            row.perfect_hits += u64::from(hit.mismatches == 0);
+            if let Some(count) = row.per_mismatch_counts.get_mut(hit.mismatches) {
+                *count += 1;
+            }
+            let mismatches = hit.mismatches as u64;
+            let sums = mismatch_sums.entry(name.clone()).or_default();
+            sums.0 += mismatches;
+            sums.1 += mismatches * mismatches;
+            if forward {
+                row.forward_hits += 1;
+            } else {
+                row.reverse_hits += 1;
+            }
+            let position = (hit.file.clone(), hit.contig.clone(), hit.start);
+            if row
+                .first_hit_position
+                .as_ref()
+                .is_none_or(|first| hit.start < first.2)
+            {
+                row.first_hit_position = Some(position.clone());
+            }
+            if row
+                .last_hit_position
+                .as_ref()
+                .is_none_or(|last| hit.start > last.2)
+            {
+                row.last_hit_position = Some(position);
+            }
+            contigs
+                .entry(name)
+                .or_default()
+                .insert((hit.file.as_str(), hit.contig.as_str()));
+        }
+    }
+
+    rows.into_values()
+        .map(|mut row| {
+            row.contigs_with_hits = contigs.get(&row.primer).map_or(0, |set| set.len() as u64);
+            let (total, total_squared) =
+                mismatch_sums.get(&row.primer).copied().unwrap_or_default();
+            (row.mean_mismatch, row.std_mismatch) =
+                mismatch_mean_and_std(row.total_hits, total, total_squared);
+            row.strand_bias = strand_bias(row.forward_hits, row.reverse_hits);
+            row
+        })
+        .collect()
+}
+
+fn emit_group_summary(groups: &[GroupSummary], as_json: bool) -> Result<()> {
+    let mut out = BufWriter::new(io::stdout().lock());
+    for row in groups {
+        if as_json {
+            writeln!(out, "{}", serde_json::to_string(row)?)?;
+        } else {
+            writeln!(
+                out,
+                "{}\t{}\t{}\t{}",
+                row.group, row.total_hits, row.primers_with_hits, row.contigs_with_hits
+            )?;
+        }
+    }
+    out.flush()?;
+    Ok(())
+}
+
+/// `--group-by` summary to `--output` or stdout: with NDJSON one object per group with its
+/// rows nested, otherwise the `--summary` rows (TSV or CSV, with their optional columns)
+/// prefixed by the group, each group closed by a `__subtotal__` row.
+fn emit_grouped_summary(cli: &Cli, groups: &[GroupedSummary]) -> Result<()> {
+    let (format, mut out) = report_output(cli)?;
+    let mut sink = match format {
+        OutputFormat::Ndjson => {
+            for grouped in groups {
+                writeln!(out, "{}", serde_json::to_string(grouped)?)?;
+            }
+            out.flush()?;
+            return Ok(());
+        }
+        OutputFormat::Tsv => TsvSink::new(out, tsv_columns(cli), cli.extended_summary),
+        OutputFormat::Csv => TsvSink::csv(out, tsv_columns(cli), cli.extended_summary),
+        _ => bail!("--group-by summaries are written as tsv, csv, or ndjson"),
+    };
+    for grouped in groups {
+        sink.set_row_prefix(Some(grouped.group.clone()));
+        for row in &grouped.rows {
+            sink.on_summary(row)?;
+        }
+        sink.on_subtotal(&grouped.subtotal)?;
+    }
+    sink.finish()
+}
+
+/// `--output` (or stdout) for a report other than hit rows, with the format its extension or
+/// `--output-format`/`--json` names, TSV by default.
+fn report_output(cli: &Cli) -> Result<(OutputFormat, BufWriter<Box<dyn Write>>)> {
+    let explicit = explicit_output_format(cli);
+    Ok(match &cli.output {
+        Some(path) => (
+            output_format(path, explicit, cli.force_format)?,
+            create_output(path)?,
+        ),
+        None => (
+            explicit.unwrap_or(OutputFormat::Tsv),
+            BufWriter::new(Box::new(io::stdout().lock()) as Box<dyn Write>),
+        ),
+    })
+}
+
+/// [`report_output`] for a report with only TSV and NDJSON layouts; true for NDJSON.
+fn tsv_or_ndjson_output(cli: &Cli, report: &str) -> Result<(bool, BufWriter<Box<dyn Write>>)> {
+    let (format, out) = report_output(cli)?;
+    let as_json = match format {
+        OutputFormat::Ndjson => true,
+        OutputFormat::Tsv => false,
+        _ => bail!("{report} rows are written as tsv or ndjson"),
+    };
+    Ok((as_json, out))
+}
+
+fn emit_hotspots(hotspots: &[Hotspot], as_json: bool) -> Result<()> {
+    let mut out = BufWriter::new(io::stdout().lock());
+    for (rank, hotspot) in hotspots.iter().enumerate() {
+        if as_json {
+            writeln!(out, "{}", serde_json::to_string(hotspot)?)?;
+        } else {
+            writeln!(
+                out,
+                "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+                rank + 1,
+                hotspot.file,
+                hotspot.contig,
+                hotspot.start,
+                hotspot.end,
+                hotspot.distinct_primers,
+                hotspot.hits,
+                hotspot.primers.join(",")
+            )?;
+        }
+    }
+    out.flush()?;
+    Ok(())
+}
+
+fn emit_contig_summary(cli: &Cli, rows: &[ContigSummary]) -> Result<()> {
+    let (as_json, mut out) = tsv_or_ndjson_output(cli, "--contig-summary")?;
+    for row in rows {
         if as_json {
-            writeln!(out, "{}", serde_json::to_string(hit)?)?;
+            writeln!(out, "{}", serde_json::to_string(row)?)?;
         } else {
             writeln!(
                 out,
-                "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
-                hit.file,
-                hit.contig,
-                hit.primer,
-                hit.primer_len,
-                hit.start,
-                hit.end,
-                hit.strand,
-                hit.mismatches,
-                hit.matched
+                "{}\t{}\t{}\t{}",
+                row.contig, row.total_hits, row.distinct_primers, row.perfect_hits
             )?;
         }
     }
@@ -129,22 +2118,36 @@ fn emit_hits(hits: &[crate::Hit], as_json: bool) -> Result<()> {
     Ok(())
 }
 
-fn emit_summary(summary: &[PrimerSummary], as_json: bool) -> Result<()> {
+fn emit_haplotype_calls(cli: &Cli, calls: &[HaplotypeCall]) -> Result<()> {
+    let (as_json, mut out) = tsv_or_ndjson_output(cli, "--haplotype-regex")?;
+    for call in calls {
+        if as_json {
+            writeln!(out, "{}", serde_json::to_string(call)?)?;
+        } else {
+            writeln!(out, "{}\t{}\t{}", call.primer, call.sample, call.phase)?;
+        }
+    }
+    out.flush()?;
+    Ok(())
+}
+
+fn emit_assay_report(rows: &[AssayRow], as_json: bool) -> Result<()> {
     let mut out = BufWriter::new(io::stdout().lock());
-    for row in summary {
+    for row in rows {
         if as_json {
             writeln!(out, "{}", serde_json::to_string(row)?)?;
         } else {
+            let optional = |value: Option<usize>| value.map_or(".".to_string(), |v| v.to_string());
             writeln!(
                 out,
                 "{}\t{}\t{}\t{}\t{}\t{}\t{}",
-                row.primer,
-                row.primer_len,
-                row.total_hits,
-                row.perfect_hits,
-                row.forward_hits,
-                row.reverse_hits,
-                row.contigs_with_hits
+                row.assay,
+                row.components.join(","),
+                row.all_found,
+                optional(row.worst_mismatches),
+                optional(row.product_size),
+                row.off_target_products,
+                row.verdict
             )?;
         }
     }
@@ -171,3 +2174,677 @@ fn emit_count(total: u64, as_json: bool) -> Result<()> {
     out.flush()?;
     Ok(())
 }
+
+fn count_by_mismatches(hits: &[crate::Hit], max_mismatches: usize) -> BTreeMap<usize, u64> {
+    let mut counts = (0..=max_mismatches)
+        .map(|level| (level, 0u64))
+        .collect::<BTreeMap<_, _>>();
+    for hit in hits {
+        *counts.entry(hit.mismatches).or_default() += 1;
+    }
+    counts
+}
+
+fn emit_count_by_mismatches(counts: &BTreeMap<usize, u64>, as_json: bool) -> Result<()> {
+    let mut out = BufWriter::new(io::stdout().lock());
+    if as_json {
+        writeln!(out, "{}", serde_json::to_string(counts)?)?;
+    } else {
+        for (level, count) in counts {
+            writeln!(out, "{level}\t{count}")?;
+        }
+    }
+    out.flush()?;
+    Ok(())
+}
+
+/// What `--emit-manifest` records: enough to rerun a scan and check its inputs are unchanged.
+#[derive(Debug, Serialize)]
+struct RunManifest {
+    version: &'static str,
+    threads: usize,
+    /// Command-line arguments after the program name; rerunning with them repeats the scan.
+    arguments: Vec<String>,
+    /// Value of every option by argument id, defaults included.
+    options: BTreeMap<String, Vec<String>>,
+    primers: ManifestFile,
+    references: Vec<ManifestFile>,
+}
+
+#[derive(Debug, Serialize)]
+struct ManifestFile {
+    /// Written in the run's --path-style.
+    path: String,
+    /// Digest of the file's bytes as stored; absent for stdin.
+    sha256: Option<String>,
+}
+
+impl ManifestFile {
+    fn new(path: &Path, style: PathStyle) -> Result<Self> {
+        let sha256 = if is_stdin(path) {
+            None
+        } else {
+            Some(file_sha256(path)?)
+        };
+        Ok(Self {
+            path: style.render(path),
+            sha256,
+        })
+    }
+}
+
+fn run_manifest(cli: &Cli, args: &[OsString], threads: usize) -> Result<RunManifest> {
+    let command = <Cli as clap::CommandFactory>::command();
+    let matches = command.clone().get_matches_from(args);
+    let options = command
+        .get_arguments()
+        .filter_map(|arg| {
+            let id = arg.get_id().as_str();
+            let values = matches.get_raw(id)?;
+            Some((
+                id.to_string(),
+                values
+                    .map(|value| value.to_string_lossy().into_owned())
+                    .collect(),
+            ))
+        })
+        .collect();
+    let primers = cli.primers.as_deref().context("--primers is required")?;
+    Ok(RunManifest {
+        version: env!("CARGO_PKG_VERSION"),
+        threads,
+        arguments: args
+            .iter()
+            .skip(1)
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect(),
+        options,
+        primers: ManifestFile::new(primers, cli.path_style.into())?,
+        references: cli
+            .references
+            .iter()
+            .map(|path| ManifestFile::new(path, cli.path_style.into()))
+            .collect::<Result<_>>()?,
+    })
+}
+
+fn file_sha256(path: &Path) -> Result<String> {
+    let mut file =
+        File::open(path).with_context(|| format!("failed to open '{}'", path.display()))?;
+    let mut hasher = Sha256::new();
+    io::copy(&mut file, &mut hasher)
+        .with_context(|| format!("failed to read '{}'", path.display()))?;
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect())
+}
+
+fn write_manifest(path: &Path, manifest: &RunManifest) -> Result<()> {
+    let file = File::create(path)
+        .with_context(|| format!("failed to create manifest '{}'", path.display()))?;
+    let mut out = BufWriter::new(file);
+    serde_json::to_writer_pretty(&mut out, manifest)?;
+    writeln!(out)?;
+    out.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{run_cli, tmp_path};
+    use crate::{Primer, ScanOptions, scan_sequence};
+
+    #[test]
+    fn count_by_mismatches_buckets_every_level() {
+        let primer = Primer::from_name_and_sequence("p1", "ACGTAC").expect("primer");
+        let options = ScanOptions {
+            max_mismatches: 2,
+            scan_reverse_complement: false,
+            ..ScanOptions::default()
+        };
+        // Perfect site, one 1-mismatch site, one 2-mismatch site.
+        let scan = scan_sequence(
+            "ACGTACTTTTACGAACTTTTTCGAACTTTT",
+            "chr1",
+            &[primer],
+            &options,
+        )
+        .expect("scan");
+
+        let counts = count_by_mismatches(&scan.hits, options.max_mismatches);
+        assert_eq!(counts.values().sum::<u64>(), scan.total_hits);
+        assert_eq!(counts, BTreeMap::from([(0, 1), (1, 1), (2, 1)]));
+
+        let counts = count_by_mismatches(&[], 3);
+        assert_eq!(counts, BTreeMap::from([(0, 0), (1, 0), (2, 0), (3, 0)]));
+    }
+
+    #[test]
+    fn split_orientations_divide_counts_between_pseudo_primers() {
+        let primers = vec![
+            Primer::from_name_and_sequence("p1", "ACGTTG").expect("primer"),
+            Primer::from_name_and_sequence("pal", "GAATTC").expect("palindrome"),
+        ];
+        let options = ScanOptions {
+            max_mismatches: 0,
+            ..ScanOptions::default()
+        };
+        // p1: two `+` sites and one `-` site (CAACGT); pal: one site reported on `+`.
+        let mut scan = scan_sequence("ACGTTGCCACGTTGCCCAACGTCCGAATTC", "chr1", &primers, &options)
+            .expect("scan");
+
+        let split = split_summary_orientations(&scan.summary, &scan.hits);
+        let counts = split
+            .iter()
+            .map(|row| {
+                (
+                    row.primer.as_str(),
+                    row.total_hits,
+                    row.forward_hits,
+                    row.reverse_hits,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            counts,
+            vec![
+                ("p1", 2, 2, 0),
+                ("p1_rc", 1, 0, 1),
+                ("pal", 1, 1, 0),
+                ("pal_rc", 1, 0, 1),
+            ]
+        );
+        assert_eq!(split[1].first_hit_position.as_ref().map(|p| p.2), Some(16));
+
+        let unlabelled_hash = scan.hit_hash.clone();
+        label_reverse_orientation_hits(&mut scan, false);
+        let mut names = scan
+            .hits
+            .iter()
+            .map(|hit| hit.primer.as_str())
+            .collect::<Vec<_>>();
+        names.sort_unstable();
+        assert_eq!(names, vec!["p1", "p1", "p1_rc", "pal"]);
+        assert_ne!(scan.hit_hash, unlabelled_hash);
+        assert_eq!(scan.hit_hash, crate::compute_hit_hash(&scan.hits));
+    }
+
+    #[test]
+    fn explain_finds_contigs_by_their_reported_name() {
+        let reference = tmp_path("explain.fa");
+        std::fs::write(&reference, ">1\nTTACGTACTT\n>chr2|alt\nACGTACGGGG\n")
+            .expect("write reference");
+        let primers = vec![Primer::from_name_and_sequence("p1", "ACGTAC").expect("primer")];
+        let options = ScanOptions {
+            contig_aliases: Some(crate::aliases::ContigAliases::from_pairs([("1", "chr1")])),
+            sanitize_contig_names: true,
+            ..ScanOptions::default()
+        };
+        let explain = |terms: &[&str]| {
+            let terms = terms
+                .iter()
+                .map(|term| term.to_string())
+                .collect::<Vec<_>>();
+            run_explain(std::slice::from_ref(&reference), &terms, &primers, &options)
+        };
+
+        explain(&["primer=p1", "contig=chr1", "pos=2"]).expect("aliased name");
+        explain(&["primer=p1", "contig=chr2_alt", "pos=0"]).expect("sanitized name");
+        // Header names are not what hits report.
+        assert!(explain(&["primer=p1", "contig=1", "pos=2"]).is_err());
+        assert!(explain(&["primer=p1", "contig=chr2|alt", "pos=0"]).is_err());
+        let past_the_end = format!("pos={}", usize::MAX);
+        assert!(explain(&["primer=p1", "contig=chr1", &past_the_end]).is_err());
+    }
+
+    #[test]
+    fn contig_summary_and_haplotype_calls_honor_output() {
+        let primers = tmp_path("report_primers.tsv");
+        let reference = tmp_path("report_ref.fa");
+        let contigs = tmp_path("report_contigs.json");
+        let calls = tmp_path("report_calls.tsv");
+        let bed = tmp_path("report_calls.bed");
+        std::fs::write(&primers, "p1\tACGTAC\n").expect("write primers");
+        std::fs::write(&reference, ">s1_hap1\nTTACGTACTT\n>s1_hap2\nTTTTTTTTTT\n")
+            .expect("write reference");
+        let run = |report: &[&str], output: &Path| {
+            let mut args = vec![
+                "--primers".into(),
+                primers.as_os_str().to_owned(),
+                "--reference".into(),
+                reference.as_os_str().to_owned(),
+                "--max-mismatches".into(),
+                "0".into(),
+                "--output".into(),
+                output.as_os_str().to_owned(),
+            ];
+            args.extend(report.iter().map(Into::into));
+            run_cli(args)
+        };
+
+        run(&["--contig-summary"], &contigs).expect("contig summary");
+        let row: serde_json::Value = serde_json::from_str(
+            std::fs::read_to_string(&contigs)
+                .expect("read contig summary")
+                .trim(),
+        )
+        .expect("one ndjson row");
+        assert_eq!(row["contig"], "s1_hap1");
+        assert_eq!(row["total_hits"], 1);
+
+        let haplotypes = ["--haplotype-regex", "(?P<sample>.+)_hap[12]"];
+        run(&haplotypes, &calls).expect("haplotype calls");
+        assert_eq!(
+            std::fs::read_to_string(&calls).expect("read calls"),
+            "p1\ts1\thap1_only\n"
+        );
+        assert!(run(&haplotypes, &bed).is_err());
+    }
+
+    #[test]
+    fn emit_hit_fasta_writes_one_record_per_hit_beside_any_output() {
+        let primers = tmp_path("hit_fasta_primers.tsv");
+        let reference = tmp_path("hit_fasta_ref.fa");
+        let hit_fasta = tmp_path("hit_fasta.fa");
+        std::fs::write(&primers, "p1\tATGC\n").expect("write primers");
+        std::fs::write(&reference, ">chr1\nTTTATGCCCGGCATTT\n>chr2\nATGA\n")
+            .expect("write reference");
+
+        run_cli([
+            "--primers".into(),
+            primers.as_os_str().to_owned(),
+            "--reference".into(),
+            reference.as_os_str().to_owned(),
+            "--max-mismatches".into(),
+            "1".into(),
+            "--count-only".into(),
+            "--emit-hit-fasta".into(),
+            hit_fasta.as_os_str().to_owned(),
+        ])
+        .expect("scan");
+
+        let options = ScanOptions {
+            max_mismatches: 1,
+            ..ScanOptions::default()
+        };
+        let primer = Primer::from_name_and_sequence("p1", "ATGC").expect("primer");
+        let hits = crate::scan_references(std::slice::from_ref(&reference), &[primer], &options)
+            .expect("scan")
+            .hits;
+        let expected = hits
+            .iter()
+            .map(|hit| {
+                let header = format!("p1_{}_{}_{}", hit.contig, hit.start, hit.strand);
+                format!(">{header}\n{}\n", hit.matched)
+            })
+            .collect::<String>();
+        let fasta = std::fs::read_to_string(&hit_fasta).expect("read hit fasta");
+        assert_eq!(fasta, expected);
+        assert!(fasta.starts_with(">p1_chr1_3_+\nATGC\n"));
+    }
+
+    #[test]
+    fn palindromic_primers_report_consistently_in_every_output_mode() {
+        let primers = tmp_path("palindrome_primers.tsv");
+        let reference = tmp_path("palindrome_ref.fa");
+        std::fs::write(&primers, "ecori\tGAATTC\nplain\tGAATTA\n").expect("write primers");
+        std::fs::write(&reference, ">chr1\nCCGAATTCCCGAATTCCC\n").expect("write reference");
+        let run = |name: &str, extra: &[&str]| {
+            let output = tmp_path(name);
+            let mut args = vec![
+                "--primers".into(),
+                primers.as_os_str().to_owned(),
+                "--reference".into(),
+                reference.as_os_str().to_owned(),
+                "--max-mismatches".into(),
+                "0".into(),
+                "--output".into(),
+                output.as_os_str().to_owned(),
+            ];
+            args.extend(extra.iter().map(Into::into));
+            run_cli(args).expect("scan");
+            let rows = std::fs::read_to_string(&output).expect("read output");
+            let _ = std::fs::remove_file(output);
+            rows
+        };
+        let fields = |row: &str| row.split('\t').map(str::to_string).collect::<Vec<_>>();
+
+        // Each site is one `+` row in hit TSV and BED.
+        let hits = run("palindrome_hits.tsv", &[]);
+        let strands = hits
+            .lines()
+            .map(|row| fields(row)[6].clone())
+            .collect::<Vec<_>>();
+        assert_eq!(strands, ["+", "+"], "{hits}");
+        let bed = run("palindrome_hits.bed", &[]);
+        let strands = bed
+            .lines()
+            .map(|row| fields(row)[5].clone())
+            .collect::<Vec<_>>();
+        assert_eq!(strands, ["+", "+"], "{bed}");
+
+        // primer primer_len total perfect forward reverse contigs is_palindromic
+        let summary = run("palindrome_summary.tsv", &["--summary"]);
+        let rows = summary.lines().map(fields).collect::<Vec<_>>();
+        assert_eq!(rows[0], ["ecori", "6", "2", "2", "2", "2", "1", "true"]);
+        assert_eq!(rows[1], ["plain", "6", "0", "0", "0", "0", "0", "false"]);
+
+        let json = run("palindrome_summary.ndjson", &["--summary"]);
+        let row: serde_json::Value =
+            serde_json::from_str(json.lines().next().expect("ecori row")).expect("json row");
+        assert_eq!(row["primer"], "ecori");
+        assert_eq!(row["is_palindromic"], true);
+        assert_eq!(
+            (
+                &row["total_hits"],
+                &row["forward_hits"],
+                &row["reverse_hits"]
+            ),
+            (
+                &serde_json::json!(2),
+                &serde_json::json!(2),
+                &serde_json::json!(2)
+            )
+        );
+
+        let forward_only = run("palindrome_forward.tsv", &["--summary", "--no-revcomp"]);
+        let ecori = fields(forward_only.lines().next().expect("ecori row"));
+        assert_eq!(&ecori[2..], ["2", "2", "2", "0", "1", "true"]);
+
+        // Both orientation rows see every palindromic site.
+        let split = run(
+            "palindrome_split.tsv",
+            &["--summary", "--split-orientations"],
+        );
+        let totals = split
+            .lines()
+            .map(fields)
+            .filter(|row| row[0].starts_with("ecori"))
+            .map(|row| (row[0].clone(), row[2].clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            totals,
+            [
+                ("ecori".to_string(), "2".to_string()),
+                ("ecori_rc".to_string(), "2".to_string())
+            ]
+        );
+
+        for path in [primers, reference] {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+
+    #[test]
+    fn primer_color_is_stable_per_name() {
+        assert_eq!(primer_color("BRCA1_F"), primer_color("BRCA1_F"));
+        assert!(PRIMER_PALETTE.contains(&primer_color("")));
+        let distinct = (0..32)
+            .map(|idx| format!("p{idx:04}"))
+            .map(|name| primer_color(&name))
+            .collect::<Vec<_>>();
+        assert!(distinct.iter().any(|color| *color != distinct[0]));
+    }
+
+    #[test]
+    fn hits_out_and_summary_out_come_from_one_scan() {
+        use std::io::Read;
+
+        let primers = tmp_path("both_primers.tsv");
+        let reference = tmp_path("both_ref.fa");
+        let hits = tmp_path("both_hits.tsv.gz");
+        let summary = tmp_path("both_summary.tsv");
+        std::fs::write(&primers, "p1\tACGTAC\np2\tGGATCC\n").expect("write primers");
+        std::fs::write(&reference, ">chr1\nTTACGTACTTGGATCCAAGTACGTAA\n").expect("write reference");
+
+        run_cli([
+            "--primers".into(),
+            primers.as_os_str().to_owned(),
+            "--reference".into(),
+            reference.as_os_str().to_owned(),
+            "--max-mismatches".into(),
+            "1".into(),
+            "--hits-out".into(),
+            hits.as_os_str().to_owned(),
+            "--summary-out".into(),
+            summary.as_os_str().to_owned(),
+        ])
+        .expect("scan");
+
+        let mut hit_rows = String::new();
+        flate2::read::GzDecoder::new(File::open(&hits).expect("open hits"))
+            .read_to_string(&mut hit_rows)
+            .expect("gunzip hits");
+        let summary_rows = std::fs::read_to_string(&summary).expect("read summary");
+        let totals = summary_rows
+            .lines()
+            .map(|row| {
+                let fields = row.split('\t').collect::<Vec<_>>();
+                (fields[0], fields[2].parse::<usize>().expect("total_hits"))
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(totals.len(), 2);
+        for (primer, total) in totals {
+            let rows = hit_rows
+                .lines()
+                .filter(|row| row.split('\t').nth(2) == Some(primer))
+                .count();
+            assert!(total > 0);
+            assert_eq!(rows, total, "{primer}");
+        }
+
+        for path in [primers, reference, hits, summary] {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+
+    #[test]
+    fn group_by_pool_subtotals_sum_member_rows() {
+        let primers = tmp_path("pool_primers.tsv");
+        let reference = tmp_path("pool_ref.fa");
+        let summary = tmp_path("pool_summary.tsv");
+        std::fs::write(
+            &primers,
+            "name\tsequence\tpool\np1\tACGTAC\t1\np2\tGGATCC\t2\np3\tAAGTAC\t1\n",
+        )
+        .expect("write primers");
+        std::fs::write(&reference, ">chr1\nTTACGTACTTGGATCCAAGTACGTAA\n").expect("write reference");
+        let args = |group_by: &str| {
+            [
+                "--primers".into(),
+                primers.as_os_str().to_owned(),
+                "--reference".into(),
+                reference.as_os_str().to_owned(),
+                "--max-mismatches".into(),
+                "1".into(),
+                "--summary".into(),
+                "--group-by".into(),
+                group_by.into(),
+                "--output".into(),
+                summary.as_os_str().to_owned(),
+            ]
+        };
+
+        run_cli(args("pool")).expect("scan");
+        let rows = std::fs::read_to_string(&summary).expect("read summary");
+        let rows = rows
+            .lines()
+            .map(|row| row.split('\t').collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let order = rows
+            .iter()
+            .map(|fields| (fields[0], fields[1]))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            order,
+            [
+                ("1", "p1"),
+                ("1", "p3"),
+                ("1", "__subtotal__"),
+                ("2", "p2"),
+                ("2", "__subtotal__")
+            ]
+        );
+        for pool in ["1", "2"] {
+            let members = rows
+                .iter()
+                .filter(|fields| fields[0] == pool && fields[1] != "__subtotal__")
+                .collect::<Vec<_>>();
+            let subtotal = rows
+                .iter()
+                .find(|fields| fields[0] == pool && fields[1] == "__subtotal__")
+                .expect("subtotal row");
+            assert_eq!(subtotal[2], members.len().to_string());
+            for column in 3..=6 {
+                let sum = members
+                    .iter()
+                    .map(|fields| fields[column].parse::<u64>().expect("count"))
+                    .sum::<u64>();
+                assert_eq!(
+                    subtotal[column],
+                    sum.to_string(),
+                    "pool {pool} column {column}"
+                );
+            }
+        }
+        assert_ne!(rows[2][3], "0");
+
+        run_cli(args("length")).expect("scan");
+        let rows = std::fs::read_to_string(&summary).expect("read summary");
+        assert!(rows.lines().all(|row| row.starts_with("5-9\t")), "{rows}");
+
+        // Grouped rows are ordinary summary rows: CSV, optional columns, and the tag apply.
+        let csv = summary.with_extension("csv");
+        let mut tagged = args("pool").to_vec();
+        tagged.truncate(tagged.len() - 1);
+        tagged.extend([
+            csv.as_os_str().to_owned(),
+            "--strand-bias".into(),
+            "--tag".into(),
+            "run1".into(),
+        ]);
+        run_cli(tagged).expect("scan");
+        let rows = std::fs::read_to_string(&csv).expect("read summary");
+        let member = rows
+            .lines()
+            .find(|row| row.starts_with("1,p1,"))
+            .expect("p1 row");
+        let fields = member.split(',').collect::<Vec<_>>();
+        assert_eq!(fields.len(), 11, "{member}");
+        assert_eq!(fields[10], "run1");
+        assert!(
+            rows.lines().any(|row| row.starts_with("2,__subtotal__,1,")),
+            "{rows}"
+        );
+
+        for path in [primers, reference, summary, csv] {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+
+    #[test]
+    fn manifest_round_trips_the_options() {
+        let primers = tmp_path("manifest_primers.tsv");
+        let reference = tmp_path("manifest_ref.fa");
+        let manifest = tmp_path("manifest.json");
+        std::fs::write(&primers, "p1\tACGTAC\n").expect("write primers");
+        std::fs::write(&reference, ">chr1\nTTACGTACTT\n").expect("write reference");
+
+        let args = [
+            "--primers".into(),
+            primers.as_os_str().to_owned(),
+            "--reference".into(),
+            reference.as_os_str().to_owned(),
+            "--max-mismatches".into(),
+            "2".into(),
+            "--threads".into(),
+            "1".into(),
+            "--count-only".into(),
+            "--emit-manifest".into(),
+            manifest.as_os_str().to_owned(),
+        ];
+        run_cli(args.clone()).expect("scan");
+        let written: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&manifest).expect("read manifest"))
+                .expect("manifest json");
+
+        let arguments = written["arguments"]
+            .as_array()
+            .expect("arguments")
+            .iter()
+            .map(|arg| arg.as_str().expect("argument").to_string());
+        let reparsed =
+            Cli::parse_from(std::iter::once("primer-scout".to_string()).chain(arguments));
+        assert_eq!(
+            format!("{reparsed:?}"),
+            format!(
+                "{:?}",
+                Cli::parse_from(std::iter::once("primer-scout".into()).chain(args))
+            )
+        );
+        assert_eq!(
+            written["options"]["max_mismatches"],
+            serde_json::json!(["2"])
+        );
+        assert_eq!(
+            written["options"]["partner_window"]
+                .as_array()
+                .map(Vec::len),
+            Some(1)
+        );
+        assert_eq!(written["threads"], 1);
+        assert_eq!(written["version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(
+            written["primers"]["sha256"].as_str(),
+            Some(file_sha256(&primers).expect("hash").as_str())
+        );
+        assert_eq!(
+            written["references"][0]["sha256"].as_str().map(str::len),
+            Some(64)
+        );
+
+        for path in [primers, reference, manifest] {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+
+    #[test]
+    fn tag_is_the_last_column_of_hit_and_summary_rows() {
+        let primers = tmp_path("tag_primers.tsv");
+        let reference = tmp_path("tag_ref.fa");
+        let output = tmp_path("tag_out.tsv");
+        std::fs::write(&primers, "p1\tGATTACA\n").expect("write primers");
+        std::fs::write(&reference, ">chr1\nTTGATTACATT\n").expect("write reference");
+        let last_columns = |summary: bool| {
+            let mut args: Vec<std::ffi::OsString> = vec![
+                "--primers".into(),
+                primers.as_os_str().to_owned(),
+                "--reference".into(),
+                reference.as_os_str().to_owned(),
+                "--tag".into(),
+                "strict".into(),
+                "--output".into(),
+                output.as_os_str().to_owned(),
+            ];
+            if summary {
+                args.push("--summary".into());
+            }
+            run_cli(args).expect("scan");
+            std::fs::read_to_string(&output)
+                .expect("read output")
+                .lines()
+                .map(|row| row.rsplit('\t').next().unwrap_or_default().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(last_columns(false), ["strict"]);
+        assert_eq!(last_columns(true), ["strict"]);
+
+        for path in [primers, reference, output] {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
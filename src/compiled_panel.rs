@@ -0,0 +1,170 @@
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use crate::Primer;
+use crate::core::SequenceNormalization;
+
+/// Leading bytes of every compiled panel.
+const MAGIC: &[u8; 8] = b"PSPANEL\x01";
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "SequenceNormalization")]
+struct NormalizationRecord {
+    uracils_converted: usize,
+    lowercase_bases: usize,
+    whitespace_removed: usize,
+}
+
+/// Every [`Primer`] field, including the masks, so loading skips normalization and mask
+/// construction.
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "Primer")]
+struct PrimerRecord {
+    name: String,
+    sequence: String,
+    reverse_complement: String,
+    original_sequence: Option<String>,
+    #[serde(with = "NormalizationRecord")]
+    normalization: SequenceNormalization,
+    pool: Option<String>,
+    masks: Vec<u8>,
+    combined_masks: Vec<u8>,
+    is_palindromic: bool,
+}
+
+#[derive(Serialize)]
+struct PrimerRef<'a>(#[serde(with = "PrimerRecord")] &'a Primer);
+
+#[derive(Deserialize)]
+struct OwnedPrimer(#[serde(with = "PrimerRecord")] Primer);
+
+/// Writes `primers` as a compiled panel that [`load_compiled_panel`] reads back: the magic
+/// bytes, the primer-scout version, the SHA-256 of the encoded primers, then the primers.
+///
+/// Like hit files, the layout follows the `Primer` struct of this exact primer-scout version;
+/// keep the source panel and recompile after upgrading.
+pub fn write_compiled_panel<W: Write>(out: &mut W, primers: &[Primer]) -> Result<()> {
+    let payload = bincode::serialize(&primers.iter().map(PrimerRef).collect::<Vec<_>>())?;
+    out.write_all(MAGIC)?;
+    bincode::serialize_into(&mut *out, env!("CARGO_PKG_VERSION"))?;
+    out.write_all(&Sha256::digest(&payload))?;
+    out.write_all(&payload)?;
+    Ok(())
+}
+
+/// Reads a panel written by [`write_compiled_panel`] (`compile-panel`). Files from another
+/// primer-scout version or whose primers do not match the stored checksum are rejected.
+pub fn load_compiled_panel(path: &Path) -> Result<Vec<Primer>> {
+    let file = File::open(path).with_context(|| format!("failed to open '{}'", path.display()))?;
+    let mut reader = BufReader::new(file);
+    let mut magic = [0u8; MAGIC.len()];
+    if reader.read_exact(&mut magic).is_err() || &magic != MAGIC {
+        bail!("'{}' is not a compiled primer-scout panel", path.display());
+    }
+    let version: String = bincode::deserialize_from(&mut reader)
+        .with_context(|| format!("failed reading compiled panel '{}'", path.display()))?;
+    if version != env!("CARGO_PKG_VERSION") {
+        bail!(
+            "compiled panel '{}' was built by primer-scout {version}, this is {}; recompile it \
+             with compile-panel",
+            path.display(),
+            env!("CARGO_PKG_VERSION")
+        );
+    }
+    let mut checksum = [0u8; 32];
+    let mut payload = Vec::new();
+    reader
+        .read_exact(&mut checksum)
+        .and_then(|()| reader.read_to_end(&mut payload))
+        .with_context(|| format!("failed reading compiled panel '{}'", path.display()))?;
+    if Sha256::digest(&payload).as_slice() != checksum {
+        bail!(
+            "compiled panel '{}' is corrupt (checksum mismatch); recompile it with compile-panel",
+            path.display()
+        );
+    }
+    let records: Vec<OwnedPrimer> = bincode::deserialize(&payload)
+        .with_context(|| format!("failed reading compiled panel '{}'", path.display()))?;
+    Ok(records
+        .into_iter()
+        .map(|OwnedPrimer(primer)| primer)
+        .collect())
+}
+
+/// [`write_compiled_panel`] to a new file at `path`.
+pub fn save_compiled_panel(path: &Path, primers: &[Primer]) -> Result<()> {
+    let file =
+        File::create(path).with_context(|| format!("failed to create '{}'", path.display()))?;
+    let mut out = BufWriter::new(file);
+    write_compiled_panel(&mut out, primers)?;
+    out.flush()
+        .with_context(|| format!("failed writing compiled panel '{}'", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::tmp_path;
+    use crate::{ScanOptions, scan_sequence};
+
+    #[test]
+    fn compiled_panel_round_trips_and_rejects_damage() {
+        let path = tmp_path("panel.psp");
+        let primers = vec![
+            Primer::from_name_and_sequence("rna", "acgu uGCA").expect("primer"),
+            Primer::from_name_and_sequence("palindrome", "GAATTC").expect("primer"),
+            Primer::from_name_and_sequence("degenerate", "GGRTTCNA").expect("primer"),
+        ];
+
+        save_compiled_panel(&path, &primers).expect("save panel");
+        let loaded = load_compiled_panel(&path).expect("load panel");
+        let fields = |primers: &[Primer]| {
+            primers
+                .iter()
+                .map(|primer| {
+                    (
+                        primer.name.clone(),
+                        primer.reverse_complement.clone(),
+                        primer.original_sequence.clone(),
+                        primer.normalization,
+                        primer.combined_masks.clone(),
+                        primer.is_palindromic,
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(fields(&loaded), fields(&primers));
+        let reference = "TTACGTTGCAGGAATTCCGGATTCAAT";
+        let options = ScanOptions::default();
+        assert_eq!(
+            scan_sequence(reference, "chr1", &loaded, &options)
+                .expect("scan")
+                .hit_hash,
+            scan_sequence(reference, "chr1", &primers, &options)
+                .expect("scan")
+                .hit_hash
+        );
+
+        let mut bytes = std::fs::read(&path).expect("read panel");
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xff;
+        std::fs::write(&path, &bytes).expect("write panel");
+        let err = load_compiled_panel(&path).expect_err("corrupt panel");
+        assert!(err.to_string().contains("checksum mismatch"), "{err}");
+
+        let mut stale = MAGIC.to_vec();
+        bincode::serialize_into(&mut stale, "0.0.1").expect("encode version");
+        std::fs::write(&path, &stale).expect("write panel");
+        let err = load_compiled_panel(&path).expect_err("old panel");
+        assert!(err.to_string().contains("recompile it"), "{err}");
+
+        std::fs::write(&path, "name\tsequence\n").expect("write text");
+        let err = load_compiled_panel(&path).expect_err("text panel");
+        assert!(err.to_string().contains("not a compiled"), "{err}");
+        std::fs::remove_file(path).expect("remove panel");
+    }
+}
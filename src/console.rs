@@ -1,3 +1,5 @@
+use crate::CancelToken;
+use crate::demo::{DEMO_DIR, demo_present, write_demo};
 use crate::update::UpdateInfo;
 use crossterm::cursor::MoveTo;
 use crossterm::event::{self, Event, KeyCode, KeyModifiers};
@@ -10,23 +12,38 @@ use crossterm::{execute, queue};
 use serde::{Deserialize, Serialize};
 use std::cmp::min;
 use std::env;
+use std::ffi::OsString;
 use std::fs::{self, OpenOptions};
 use std::io::{self, Write};
 use std::path::{Component, Path, PathBuf};
-use std::process::Command;
-use std::time::Duration;
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 const MAX_HISTORY_ITEMS: usize = 300;
+const DEFAULT_MAX_HISTORY_AGE_DAYS: u64 = 30;
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
 const MAX_RENDERED_ITEMS: usize = 120;
 const HISTORY_DIR_NAME: &str = ".primer-scout";
+/// History directory under the Windows per-user application data folder.
+const WINDOWS_HISTORY_DIR_NAME: &str = "primer-scout";
 const HISTORY_FILE_NAME: &str = "console_history.ndjson";
+/// Background scans run one at a time; later ones wait in the queue.
+const MAX_RUNNING_JOBS: usize = 1;
+/// How often a job's worker checks whether its scan exited or was cancelled.
+const JOB_POLL_INTERVAL: Duration = Duration::from_millis(50);
 const UPGRADE_COMMAND: &str =
     "cargo install --git https://github.com/yash27-lab/primer-scout --branch main --force";
 const CONSOLE_COMMANDS: &[(&str, &str)] = &[
     ("/help", "show all commands"),
     ("/basics", "beginner quickstart"),
     ("/examples", "more examples"),
+    ("/demo", "write the quickstart demo files"),
     ("/scan", "run scan engine"),
+    ("/explain", "why a primer does or does not hit a position"),
+    ("/jobs", "list background scans"),
+    ("/cancel", "cancel a background scan"),
     ("/upgrade", "print upgrade command"),
     ("/version", "show installed version"),
     ("/history", "show session history path"),
@@ -45,24 +62,79 @@ enum Role {
 struct Entry {
     role: Role,
     text: String,
+    /// Unix seconds when the entry was created. Entries from history files written before
+    /// timestamps existed read as `0`, so an age limit drops them.
+    #[serde(default)]
+    timestamp: u64,
+}
+
+impl Entry {
+    fn new(role: Role, text: impl Into<String>) -> Self {
+        Self {
+            role,
+            text: text.into(),
+            timestamp: unix_now(),
+        }
+    }
+
+    fn user(text: impl Into<String>) -> Self {
+        Self::new(Role::User, text)
+    }
+
+    fn assistant(text: impl Into<String>) -> Self {
+        Self::new(Role::Assistant, text)
+    }
+
+    fn system(text: impl Into<String>) -> Self {
+        Self::new(Role::System, text)
+    }
+}
+
+/// Session history retention settings.
+#[derive(Debug, Clone)]
+struct ConsoleConfig {
+    /// Entries saved longer ago than this are dropped on load; `0` keeps them forever.
+    max_history_age_days: u64,
+}
+
+impl Default for ConsoleConfig {
+    fn default() -> Self {
+        Self {
+            max_history_age_days: DEFAULT_MAX_HISTORY_AGE_DAYS,
+        }
+    }
+}
+
+impl ConsoleConfig {
+    fn from_env() -> Self {
+        let mut config = Self::default();
+        if let Some(days) = env::var("PRIMER_SCOUT_HISTORY_MAX_DAYS")
+            .ok()
+            .and_then(|raw| raw.trim().parse::<u64>().ok())
+        {
+            config.max_history_age_days = days;
+        }
+        config
+    }
 }
 
 pub fn run(command_name: &str, update_info: Option<&UpdateInfo>) -> io::Result<()> {
     let history_path = resolve_history_path();
-    let mut entries = load_entries(&history_path).unwrap_or_default();
+    let config = ConsoleConfig::from_env();
+    let mut entries = load_entries(&history_path, &config).unwrap_or_default();
 
     if entries.is_empty() {
         push_beginner_banner(&mut entries);
     } else {
-        entries.push(Entry {
-            role: Role::System,
-            text: "Previous session restored.".to_string(),
-        });
+        entries.push(Entry::system("Previous session restored."));
     }
 
     let _guard = TerminalGuard::enter()?;
     let mut stdout = io::stdout();
     let mut input = String::new();
+    let mut jobs = JobRegistry::default();
+    let mut workers = Vec::new();
+    let (job_events, finished_jobs) = mpsc::channel();
     let update_line = update_info.map(|u| {
         format!(
             "Update available: v{} | Run: {}",
@@ -71,12 +143,23 @@ pub fn run(command_name: &str, update_info: Option<&UpdateInfo>) -> io::Result<(
     });
 
     loop {
+        if pump_jobs(
+            &mut jobs,
+            &job_events,
+            &finished_jobs,
+            &mut workers,
+            &mut entries,
+        ) {
+            trim_entries(&mut entries, MAX_HISTORY_ITEMS);
+            save_entries(&history_path, &entries)?;
+        }
         draw(
             &mut stdout,
             command_name,
             &entries,
             &input,
             update_line.as_deref(),
+            jobs.status_strip().as_deref(),
         )?;
 
         if !event::poll(Duration::from_millis(150))? {
@@ -88,10 +171,7 @@ pub fn run(command_name: &str, update_info: Option<&UpdateInfo>) -> io::Result<(
         };
 
         if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
-            entries.push(Entry {
-                role: Role::System,
-                text: "Session saved. Bye.".to_string(),
-            });
+            entries.push(Entry::system("Session saved. Bye."));
             save_entries(&history_path, &entries)?;
             break;
         }
@@ -112,15 +192,12 @@ pub fn run(command_name: &str, update_info: Option<&UpdateInfo>) -> io::Result<(
                 }
 
                 if submitted == "x" || submitted.eq_ignore_ascii_case("/exit") {
-                    entries.push(Entry {
-                        role: Role::System,
-                        text: "Session saved. Bye.".to_string(),
-                    });
+                    entries.push(Entry::system("Session saved. Bye."));
                     save_entries(&history_path, &entries)?;
                     break;
                 }
 
-                handle_message(submitted, &mut entries);
+                handle_message(submitted, &mut entries, &mut jobs);
                 trim_entries(&mut entries, MAX_HISTORY_ITEMS);
                 save_entries(&history_path, &entries)?;
             }
@@ -128,14 +205,16 @@ pub fn run(command_name: &str, update_info: Option<&UpdateInfo>) -> io::Result<(
         }
     }
 
+    // Scans still running would outlive the console; stop them before leaving.
+    jobs.cancel_unfinished(Instant::now());
+    for worker in workers {
+        let _ = worker.join();
+    }
     Ok(())
 }
 
-fn handle_message(message: String, entries: &mut Vec<Entry>) {
-    entries.push(Entry {
-        role: Role::User,
-        text: message.clone(),
-    });
+fn handle_message(message: String, entries: &mut Vec<Entry>, jobs: &mut JobRegistry) {
+    entries.push(Entry::user(message.clone()));
 
     if message == "/help" {
         push_help(entries);
@@ -152,115 +231,130 @@ fn handle_message(message: String, entries: &mut Vec<Entry>) {
         return;
     }
 
+    if message == "/demo" {
+        let text = match write_demo(Path::new(DEMO_DIR)) {
+            Ok((primers, reference)) => format!(
+                "Wrote {} and {}. The quickstart scans are ready: /basics",
+                primers.display(),
+                reference.display()
+            ),
+            Err(err) => format!("Could not write the demo files: {err:#}"),
+        };
+        entries.push(Entry::assistant(text));
+        return;
+    }
+
     if message == "/upgrade" {
-        entries.push(Entry {
-            role: Role::Assistant,
-            text: format!("Run this command in shell:\n{UPGRADE_COMMAND}"),
-        });
+        entries.push(Entry::assistant(format!(
+            "Run this command in shell:\n{UPGRADE_COMMAND}"
+        )));
         return;
     }
 
     if message == "/version" {
-        entries.push(Entry {
-            role: Role::Assistant,
-            text: format!("primer-scout version: {}", env!("CARGO_PKG_VERSION")),
-        });
+        entries.push(Entry::assistant(format!(
+            "primer-scout version: {}",
+            env!("CARGO_PKG_VERSION")
+        )));
         return;
     }
 
     if message == "/history" {
-        entries.push(Entry {
-            role: Role::Assistant,
-            text: format!("History file: {}", resolve_history_path().display()),
-        });
+        entries.push(Entry::assistant(format!(
+            "History file: {}",
+            resolve_history_path().display()
+        )));
         return;
     }
 
     if message == "/clear" {
         entries.clear();
-        entries.push(Entry {
-            role: Role::Assistant,
-            text: "Console cleared. Session continues.".to_string(),
-        });
+        entries.push(Entry::assistant("Console cleared. Session continues."));
         return;
     }
 
     if message == "primer" || message == "primer --splash" {
-        entries.push(Entry {
-            role: Role::Assistant,
-            text: "You are already inside primer console. Use /scan <args> or /help.".to_string(),
-        });
+        entries.push(Entry::assistant(
+            "You are already inside primer console. Use /scan <args> or /help.",
+        ));
         return;
     }
 
     if let Some(scan_args) = message.strip_prefix("/scan") {
         let arg_str = scan_args.trim();
         if arg_str.is_empty() {
-            entries.push(Entry {
-                role: Role::Assistant,
-                text: "Usage: /scan --primers <file.tsv> --reference <ref.fa> [flags]".to_string(),
-            });
+            entries.push(Entry::assistant(
+                "Usage: /scan --primers <file.tsv> --reference <ref.fa> [flags]",
+            ));
             return;
         }
 
-        run_scan_with_args(parse_cli_args(arg_str), entries);
+        queue_scan(parse_cli_args(arg_str), entries, jobs);
+        return;
+    }
+
+    if let Some(explain_args) = message.strip_prefix("/explain") {
+        match explain_scan_args(explain_args) {
+            Some(args) => queue_scan(args, entries, jobs),
+            None => entries.push(Entry::assistant("Usage: /explain primer=NAME contig=CHR pos=START --primers <file.tsv> --reference <ref.fa> [flags]")),
+        }
+        return;
+    }
+
+    if message == "/jobs" {
+        entries.push(Entry::assistant(jobs.listing(Instant::now())));
+        return;
+    }
+
+    if let Some(id) = message.strip_prefix("/cancel") {
+        let text = match id.trim().trim_start_matches('#').parse::<usize>() {
+            Ok(id) => jobs
+                .cancel(id, Instant::now())
+                .unwrap_or_else(|message| message),
+            Err(_) => "Usage: /cancel <job id> (see /jobs)".to_string(),
+        };
+        entries.push(Entry::assistant(text));
         return;
     }
 
     if let Some(args) = parse_direct_scan_args(&message) {
-        run_scan_with_args(args, entries);
+        queue_scan(args, entries, jobs);
         return;
     }
 
-    entries.push(Entry {
-        role: Role::Assistant,
-        text: "Unknown command. Use /help to see available commands.".to_string(),
-    });
+    entries.push(Entry::assistant(
+        "Unknown command. Use /help to see available commands.",
+    ));
 }
 
 fn push_beginner_banner(entries: &mut Vec<Entry>) {
-    entries.push(Entry {
-        role: Role::Assistant,
-        text: "Welcome to primer console.".to_string(),
-    });
-    entries.push(Entry {
-        role: Role::Assistant,
-        text: "Type /basics for beginner quickstart or /help for full command list.".to_string(),
-    });
+    entries.push(Entry::assistant("Welcome to primer console."));
+    entries.push(Entry::assistant(
+        "Type /basics for beginner quickstart or /help for full command list.",
+    ));
     push_basics(entries);
 }
 
 fn push_help(entries: &mut Vec<Entry>) {
-    entries.push(Entry {
-        role: Role::Assistant,
-        text: "Commands:\n/help\n/basics\n/examples\n/scan <args>\n/upgrade\n/version\n/history\n/clear\nx or /exit"
-            .to_string(),
-    });
-    entries.push(Entry {
-        role: Role::Assistant,
-        text: "You can use /scan ... OR direct command style: `primer-scout --help` or `--primers ... --reference ...`"
-            .to_string(),
-    });
+    entries.push(Entry::assistant("Commands:\n/help\n/basics\n/examples\n/demo\n/scan <args>\n/explain primer=NAME contig=CHR pos=START <args>\n/jobs\n/cancel <id>\n/upgrade\n/version\n/history\n/clear\nx or /exit"));
+    entries.push(Entry::assistant("You can use /scan ... OR direct command style: `primer-scout --help` or `--primers ... --reference ...`"));
 }
 
 fn push_basics(entries: &mut Vec<Entry>) {
-    entries.push(Entry {
-        role: Role::Assistant,
-        text: "Beginner quickstart:\n1) /scan --primers data/demo_primers.tsv --reference data/demo.fa --count-only\n2) /scan --primers data/demo_primers.tsv --reference data/demo.fa --summary\n3) /scan --primers data/demo_primers.tsv --reference data/demo.fa --max-mismatches 1"
-            .to_string(),
-    });
-    entries.push(Entry {
-        role: Role::Assistant,
-        text: "Need more? run /examples. Exit with Ctrl+C or x.".to_string(),
-    });
+    if !demo_present(Path::new(DEMO_DIR)) {
+        entries.push(Entry::assistant(format!(
+            "The demo files are not in ./{DEMO_DIR} (installed with cargo?). Type /demo to \
+                 write them there first; the scans below read them."
+        )));
+    }
+    entries.push(Entry::assistant("Beginner quickstart:\n1) /scan --primers data/demo_primers.tsv --reference data/demo.fa --count-only\n2) /scan --primers data/demo_primers.tsv --reference data/demo.fa --summary\n3) /scan --primers data/demo_primers.tsv --reference data/demo.fa --max-mismatches 1"));
+    entries.push(Entry::assistant(
+        "Need more? run /examples. Exit with Ctrl+C or x.",
+    ));
 }
 
 fn push_examples(entries: &mut Vec<Entry>) {
-    entries.push(Entry {
-        role: Role::Assistant,
-        text: "Examples:\n/scan --primers data/demo_primers.tsv --reference data/demo.fa --json\n/scan --primers data/demo_primers.tsv --reference data/demo.fa --no-revcomp\n/scan --primers data/demo_primers.tsv --reference data/demo.fa --max-mismatches 2 --summary"
-            .to_string(),
-    });
+    entries.push(Entry::assistant("Examples:\n/scan --primers data/demo_primers.tsv --reference data/demo.fa --json\n/scan --primers data/demo_primers.tsv --reference data/demo.fa --no-revcomp\n/scan --primers data/demo_primers.tsv --reference data/demo.fa --max-mismatches 2 --summary"));
 }
 
 fn parse_cli_args(arg_str: &str) -> Vec<String> {
@@ -270,6 +364,23 @@ fn parse_cli_args(arg_str: &str) -> Vec<String> {
         .collect::<Vec<_>>()
 }
 
+/// `/explain` arguments as scan arguments: the `primer=`, `contig=`, and `pos=` terms move
+/// behind `--explain`, everything else is passed through. `None` unless all three are given.
+fn explain_scan_args(arg_str: &str) -> Option<Vec<String>> {
+    let (terms, mut args): (Vec<String>, Vec<String>) =
+        parse_cli_args(arg_str).into_iter().partition(|arg| {
+            ["primer=", "contig=", "pos="]
+                .iter()
+                .any(|key| arg.starts_with(key))
+        });
+    if terms.len() != 3 {
+        return None;
+    }
+    args.push("--explain".to_string());
+    args.extend(terms);
+    Some(args)
+}
+
 fn parse_direct_scan_args(message: &str) -> Option<Vec<String>> {
     let trimmed = message.trim();
     if trimmed.is_empty() {
@@ -298,33 +409,307 @@ fn parse_direct_scan_args(message: &str) -> Option<Vec<String>> {
     None
 }
 
-fn run_scan_with_args(args: Vec<String>, entries: &mut Vec<Entry>) {
-    match Command::new("primer-scout").args(&args).output() {
-        Ok(output) => {
-            if output.status.success() {
-                let stdout = String::from_utf8_lossy(&output.stdout);
-                let text = summarize_output(stdout.trim(), "Scan completed.");
-                entries.push(Entry {
-                    role: Role::Assistant,
-                    text,
-                });
-            } else {
-                let stderr = String::from_utf8_lossy(&output.stderr);
-                let text = summarize_output(stderr.trim(), "Scan failed.");
-                entries.push(Entry {
-                    role: Role::Assistant,
-                    text: format!("Scan error: {text}"),
-                });
+fn queue_scan(args: Vec<String>, entries: &mut Vec<Entry>, jobs: &mut JobRegistry) {
+    let id = jobs.enqueue(args, Instant::now());
+    entries.push(Entry::assistant(format!(
+        "Scan queued as job #{id}. /jobs lists jobs, /cancel {id} stops it."
+    )));
+}
+
+/// Lifecycle of a background scan: Queued → Running → Done, Failed, or Cancelled. A queued
+/// job can also be cancelled before it starts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum JobState {
+    Queued,
+    Running,
+    Done,
+    Failed,
+    Cancelled,
+}
+
+impl JobState {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Queued => "queued",
+            Self::Running => "running",
+            Self::Done => "done",
+            Self::Failed => "failed",
+            Self::Cancelled => "cancelled",
+        }
+    }
+
+    fn is_finished(self) -> bool {
+        matches!(self, Self::Done | Self::Failed | Self::Cancelled)
+    }
+
+    fn can_become(self, next: Self) -> bool {
+        matches!(
+            (self, next),
+            (Self::Queued, Self::Running | Self::Cancelled)
+                | (Self::Running, Self::Done | Self::Failed | Self::Cancelled)
+        )
+    }
+}
+
+#[derive(Debug)]
+struct Job {
+    id: usize,
+    args: Vec<String>,
+    state: JobState,
+    queued_at: Instant,
+    started_at: Option<Instant>,
+    finished_at: Option<Instant>,
+    /// First line of the scan's output (or error), once finished.
+    summary: Option<String>,
+    /// Signalled by `/cancel`; the job's worker kills the scan when it sees it.
+    cancel: CancelToken,
+}
+
+impl Job {
+    /// Run time once started, otherwise time spent waiting in the queue.
+    fn elapsed(&self, now: Instant) -> Duration {
+        let since = self.started_at.unwrap_or(self.queued_at);
+        self.finished_at
+            .unwrap_or(now)
+            .saturating_duration_since(since)
+    }
+}
+
+/// Background scans of this console session, in submission order.
+#[derive(Debug, Default)]
+struct JobRegistry {
+    jobs: Vec<Job>,
+    next_id: usize,
+}
+
+impl JobRegistry {
+    /// Adds a queued job and returns its id (ids start at 1).
+    fn enqueue(&mut self, args: Vec<String>, now: Instant) -> usize {
+        self.next_id += 1;
+        self.jobs.push(Job {
+            id: self.next_id,
+            args,
+            state: JobState::Queued,
+            queued_at: now,
+            started_at: None,
+            finished_at: None,
+            summary: None,
+            cancel: CancelToken::new(),
+        });
+        self.next_id
+    }
+
+    fn job_mut(&mut self, id: usize) -> Result<&mut Job, String> {
+        self.jobs
+            .iter_mut()
+            .find(|job| job.id == id)
+            .ok_or_else(|| format!("No job #{id}. /jobs lists jobs."))
+    }
+
+    fn transition(&mut self, id: usize, next: JobState, now: Instant) -> Result<&mut Job, String> {
+        let job = self.job_mut(id)?;
+        if !job.state.can_become(next) {
+            return Err(format!(
+                "Job #{id} is {} and cannot become {}.",
+                job.state.label(),
+                next.label()
+            ));
+        }
+        job.state = next;
+        if next == JobState::Running {
+            job.started_at = Some(now);
+        } else if next.is_finished() {
+            job.finished_at = Some(now);
+        }
+        Ok(job)
+    }
+
+    /// Marks the oldest queued job running, if fewer than [`MAX_RUNNING_JOBS`] are, and
+    /// returns what its worker needs.
+    fn start_next(&mut self, now: Instant) -> Option<(usize, Vec<String>, CancelToken)> {
+        if self.count(JobState::Running) >= MAX_RUNNING_JOBS {
+            return None;
+        }
+        let id = self
+            .jobs
+            .iter()
+            .find(|job| job.state == JobState::Queued)?
+            .id;
+        let job = self.transition(id, JobState::Running, now).ok()?;
+        Some((id, job.args.clone(), job.cancel.clone()))
+    }
+
+    /// Records a running job's outcome as reported by its worker.
+    fn finish(&mut self, event: JobEvent, now: Instant) -> Result<(), String> {
+        let job = self.transition(event.id, event.state, now)?;
+        job.summary = Some(event.output.lines().next().unwrap_or_default().to_string());
+        Ok(())
+    }
+
+    /// `/cancel`: a queued job is cancelled at once; a running one has its token signalled
+    /// and becomes cancelled when its worker reports back.
+    fn cancel(&mut self, id: usize, now: Instant) -> Result<String, String> {
+        match self.job_mut(id)?.state {
+            JobState::Queued => {
+                self.transition(id, JobState::Cancelled, now)?;
+                Ok(format!("Job #{id} cancelled before it started."))
+            }
+            JobState::Running => {
+                self.job_mut(id)?.cancel.cancel();
+                Ok(format!("Cancelling job #{id}..."))
             }
+            state => Err(format!("Job #{id} already {}.", state.label())),
         }
-        Err(_) => {
-            entries.push(Entry {
-                role: Role::Assistant,
-                text: "Could not run `primer-scout` from console. Install binary in PATH first."
-                    .to_string(),
-            });
+    }
+
+    /// Cancels every queued job and signals every running one.
+    fn cancel_unfinished(&mut self, now: Instant) {
+        let ids = self
+            .jobs
+            .iter()
+            .filter(|job| !job.state.is_finished())
+            .map(|job| job.id)
+            .collect::<Vec<_>>();
+        for id in ids {
+            let _ = self.cancel(id, now);
         }
     }
+
+    fn count(&self, state: JobState) -> usize {
+        self.jobs.iter().filter(|job| job.state == state).count()
+    }
+
+    /// `/jobs` output: one line per job with id, state, elapsed time, arguments, and the
+    /// first output line of finished jobs.
+    fn listing(&self, now: Instant) -> String {
+        if self.jobs.is_empty() {
+            return "No jobs yet. Start one with /scan <args>.".to_string();
+        }
+        let mut lines = vec!["Jobs:".to_string()];
+        for job in &self.jobs {
+            let mut line = format!(
+                "#{} {:<9} {:>6.1}s  {}",
+                job.id,
+                job.state.label(),
+                job.elapsed(now).as_secs_f64(),
+                job.args.join(" ")
+            );
+            if let Some(summary) = job.summary.as_deref().filter(|text| !text.is_empty()) {
+                line.push_str(&format!(" -> {summary}"));
+            }
+            lines.push(line);
+        }
+        lines.join("\n")
+    }
+
+    /// Compact strip for the console frame, e.g. `jobs: 1 running, 2 queued`; `None` when no
+    /// job is waiting or running.
+    fn status_strip(&self) -> Option<String> {
+        let (running, queued) = (self.count(JobState::Running), self.count(JobState::Queued));
+        if running + queued == 0 {
+            return None;
+        }
+        Some(format!("jobs: {running} running, {queued} queued"))
+    }
+}
+
+/// A worker's report that its scan exited.
+#[derive(Debug)]
+struct JobEvent {
+    id: usize,
+    state: JobState,
+    /// The scan's stdout on success, otherwise its stderr or the launch error.
+    output: String,
+}
+
+/// Folds finished-job reports into the registry and console entries, then starts queued jobs.
+/// Returns `true` when entries were added.
+fn pump_jobs(
+    jobs: &mut JobRegistry,
+    events: &Sender<JobEvent>,
+    finished: &Receiver<JobEvent>,
+    workers: &mut Vec<JoinHandle<()>>,
+    entries: &mut Vec<Entry>,
+) -> bool {
+    let mut added = false;
+    while let Ok(event) = finished.try_recv() {
+        let now = Instant::now();
+        let text = match event.state {
+            JobState::Done => format!(
+                "Job #{} done:\n{}",
+                event.id,
+                summarize_output(event.output.trim(), "Scan completed.")
+            ),
+            JobState::Cancelled => format!("Job #{} cancelled.", event.id),
+            _ => format!(
+                "Job #{} failed: {}",
+                event.id,
+                summarize_output(event.output.trim(), "Scan failed.")
+            ),
+        };
+        if jobs.finish(event, now).is_ok() {
+            entries.push(Entry::assistant(text));
+            added = true;
+        }
+    }
+
+    workers.retain(|worker| !worker.is_finished());
+    while let Some((id, args, cancel)) = jobs.start_next(Instant::now()) {
+        let events = events.clone();
+        workers.push(thread::spawn(move || {
+            let _ = events.send(run_scan_job(id, &args, &cancel));
+        }));
+    }
+    added
+}
+
+/// Runs one `primer-scout` scan as a child process, killing it if `cancel` is signalled.
+fn run_scan_job(id: usize, args: &[String], cancel: &CancelToken) -> JobEvent {
+    let event = |state, output: String| JobEvent { id, state, output };
+    let spawned = Command::new("primer-scout")
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn();
+    let Ok(mut child) = spawned else {
+        return event(
+            JobState::Failed,
+            "Could not run `primer-scout` from console. Install binary in PATH first.".to_string(),
+        );
+    };
+
+    // Drain both pipes on their own threads so a chatty scan never blocks on a full pipe.
+    let drain = |pipe: Option<Box<dyn io::Read + Send>>| {
+        thread::spawn(move || {
+            let mut text = Vec::new();
+            if let Some(mut pipe) = pipe {
+                let _ = pipe.read_to_end(&mut text);
+            }
+            String::from_utf8_lossy(&text).into_owned()
+        })
+    };
+    let stdout = drain(child.stdout.take().map(|pipe| Box::new(pipe) as _));
+    let stderr = drain(child.stderr.take().map(|pipe| Box::new(pipe) as _));
+
+    let status = loop {
+        if cancel.is_cancelled() {
+            let _ = child.kill();
+        }
+        match child.try_wait() {
+            Ok(Some(status)) => break Some(status),
+            Ok(None) => thread::sleep(JOB_POLL_INTERVAL),
+            Err(_) => break None,
+        }
+    };
+    let stdout = stdout.join().unwrap_or_default();
+    let stderr = stderr.join().unwrap_or_default();
+
+    match status {
+        _ if cancel.is_cancelled() => event(JobState::Cancelled, String::new()),
+        Some(status) if status.success() => event(JobState::Done, stdout),
+        _ => event(JobState::Failed, stderr),
+    }
 }
 
 fn summarize_output(raw: &str, fallback: &str) -> String {
@@ -352,6 +737,7 @@ fn draw(
     entries: &[Entry],
     input: &str,
     update_line: Option<&str>,
+    job_status: Option<&str>,
 ) -> io::Result<()> {
     let (cols, rows) = terminal::size()?;
     let cols_usize = cols as usize;
@@ -402,6 +788,18 @@ fn draw(
         Print("─".repeat(cols_usize)),
         ResetColor
     )?;
+    if let Some(status) = job_status {
+        queue!(
+            out,
+            MoveTo(2, input_row.saturating_sub(1)),
+            SetForegroundColor(Color::Yellow),
+            Print(clip_to_width(
+                &format!(" {status} "),
+                cols_usize.saturating_sub(4)
+            )),
+            ResetColor
+        )?;
+    }
 
     let message_top = separator_row.saturating_add(1);
     let suggestion_lines = build_suggestion_lines(input, cols_usize.saturating_sub(1));
@@ -564,8 +962,29 @@ fn resolve_history_path() -> PathBuf {
 }
 
 fn default_history_dir() -> PathBuf {
-    let home = env::var("HOME").unwrap_or_else(|_| ".".to_string());
-    PathBuf::from(home).join(HISTORY_DIR_NAME)
+    history_dir(cfg!(windows), |name| env::var_os(name))
+}
+
+/// `%LOCALAPPDATA%\primer-scout` on Windows (else `%APPDATA%`, then
+/// `%USERPROFILE%\.primer-scout`), `$HOME/.primer-scout` elsewhere, and `./.primer-scout` when
+/// none of them is set.
+fn history_dir(windows: bool, var: impl Fn(&str) -> Option<OsString>) -> PathBuf {
+    let dir = |name: &str| {
+        var(name)
+            .filter(|value| !value.is_empty())
+            .map(PathBuf::from)
+    };
+    if windows {
+        if let Some(app_data) = dir("LOCALAPPDATA").or_else(|| dir("APPDATA")) {
+            return app_data.join(WINDOWS_HISTORY_DIR_NAME);
+        }
+        if let Some(profile) = dir("USERPROFILE") {
+            return profile.join(HISTORY_DIR_NAME);
+        }
+    } else if let Some(home) = dir("HOME") {
+        return home.join(HISTORY_DIR_NAME);
+    }
+    PathBuf::from(".").join(HISTORY_DIR_NAME)
 }
 
 fn sanitize_history_override(base_dir: &Path, raw: &str) -> Option<PathBuf> {
@@ -590,7 +1009,7 @@ fn sanitize_history_override(base_dir: &Path, raw: &str) -> Option<PathBuf> {
     Some(base_dir.join(candidate))
 }
 
-fn load_entries(path: &Path) -> io::Result<Vec<Entry>> {
+fn load_entries(path: &Path, config: &ConsoleConfig) -> io::Result<Vec<Entry>> {
     reject_symlink(path)?;
     let content = fs::read_to_string(path)?;
     let mut entries = Vec::new();
@@ -602,6 +1021,7 @@ fn load_entries(path: &Path) -> io::Result<Vec<Entry>> {
             entries.push(entry);
         }
     }
+    trim_expired_entries(&mut entries, config.max_history_age_days, unix_now());
     trim_entries(&mut entries, MAX_HISTORY_ITEMS);
     Ok(entries)
 }
@@ -686,6 +1106,23 @@ fn secure_file_permissions(path: &Path) -> io::Result<()> {
     Ok(())
 }
 
+/// Drops entries created more than `max_age_days` before `now`, including entries from
+/// history files written before timestamps existed (`timestamp == 0`).
+fn trim_expired_entries(entries: &mut Vec<Entry>, max_age_days: u64, now: u64) {
+    if max_age_days == 0 {
+        return;
+    }
+    let cutoff = now.saturating_sub(max_age_days.saturating_mul(SECONDS_PER_DAY));
+    entries.retain(|entry| entry.timestamp >= cutoff);
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0)
+}
+
 fn trim_entries(entries: &mut Vec<Entry>, max_items: usize) {
     if entries.len() > max_items {
         let drop_count = entries.len() - max_items;
@@ -721,6 +1158,43 @@ mod tests {
         assert_eq!(path, base.join("alt.ndjson"));
     }
 
+    #[test]
+    fn history_dir_follows_platform_conventions() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(key, _)| *key == name)
+                    .map(|(_, value)| OsString::from(value))
+            }
+        };
+        const LOCAL: &str = r"C:\Users\ana\AppData\Local";
+        const WINDOWS: [(&str, &str); 4] = [
+            ("LOCALAPPDATA", LOCAL),
+            ("APPDATA", r"C:\Users\ana\AppData\Roaming"),
+            ("USERPROFILE", r"C:\Users\ana"),
+            ("HOME", "/home/ana"),
+        ];
+        assert_eq!(
+            history_dir(true, env(&WINDOWS)),
+            PathBuf::from(LOCAL).join("primer-scout")
+        );
+        assert_eq!(
+            history_dir(
+                true,
+                env(&[("USERPROFILE", r"C:\Users\ana"), ("LOCALAPPDATA", "")])
+            ),
+            PathBuf::from(r"C:\Users\ana").join(".primer-scout")
+        );
+        assert_eq!(
+            history_dir(false, env(&WINDOWS)),
+            PathBuf::from("/home/ana/.primer-scout")
+        );
+        assert_eq!(
+            history_dir(false, env(&[])),
+            PathBuf::from("./.primer-scout")
+        );
+    }
+
     #[test]
     fn session_override_rejects_parent_traversal() {
         let base = PathBuf::from("/tmp/user/.primer-scout");
@@ -728,10 +1202,164 @@ mod tests {
         assert!(path.is_none());
     }
 
+    #[test]
+    fn entries_older_than_max_age_are_dropped_on_load() {
+        let path = std::env::temp_dir().join(format!(
+            "primer_scout_{}_{}_history.ndjson",
+            std::process::id(),
+            unix_now()
+        ));
+        let now = unix_now();
+        let line = |text: &str, timestamp: u64| {
+            format!(r#"{{"role":"User","text":"{text}","timestamp":{timestamp}}}"#)
+        };
+        let content = [
+            line("stale", now - 31 * SECONDS_PER_DAY),
+            line("recent", now - 29 * SECONDS_PER_DAY),
+            r#"{"role":"User","text":"legacy"}"#.to_string(),
+        ]
+        .join("\n");
+        fs::write(&path, content).expect("write history");
+
+        let kept = load_entries(&path, &ConsoleConfig::default()).expect("load history");
+        let texts: Vec<_> = kept.iter().map(|entry| entry.text.as_str()).collect();
+        assert_eq!(texts, vec!["recent"]);
+
+        let unlimited = ConsoleConfig {
+            max_history_age_days: 0,
+        };
+        assert_eq!(load_entries(&path, &unlimited).expect("load").len(), 3);
+
+        fs::remove_file(path).expect("remove history");
+    }
+
     #[test]
     fn session_override_rejects_absolute_outside_base_dir() {
         let base = PathBuf::from("/tmp/user/.primer-scout");
         let path = sanitize_history_override(&base, "/tmp/user/notes.txt");
         assert!(path.is_none());
     }
+
+    fn args(text: &str) -> Vec<String> {
+        parse_cli_args(text)
+    }
+
+    #[test]
+    fn jobs_move_through_queued_running_and_finished_states() {
+        let t0 = Instant::now();
+        let mut jobs = JobRegistry::default();
+        assert_eq!(jobs.status_strip(), None);
+        let first = jobs.enqueue(args("--primers a.tsv --reference a.fa"), t0);
+        let second = jobs.enqueue(args("--primers b.tsv --reference b.fa"), t0);
+        assert_eq!((first, second), (1, 2));
+
+        // One job runs at a time; the second waits.
+        let (id, started_args, _) = jobs.start_next(t0).expect("first job starts");
+        assert_eq!((id, started_args.len()), (first, 4));
+        assert!(jobs.start_next(t0).is_none());
+        assert_eq!(
+            jobs.status_strip().as_deref(),
+            Some("jobs: 1 running, 1 queued")
+        );
+
+        let event = JobEvent {
+            id: first,
+            state: JobState::Done,
+            output: "hits: 12\nmore".to_string(),
+        };
+        jobs.finish(event, t0 + Duration::from_millis(1500))
+            .expect("running job finishes");
+        let listing = jobs.listing(t0 + Duration::from_secs(9));
+        assert!(
+            listing.contains("#1 done         1.5s  --primers a.tsv --reference a.fa -> hits: 12"),
+            "{listing}"
+        );
+        assert!(listing.contains("#2 queued"), "{listing}");
+
+        // A finished job cannot finish again or be restarted.
+        let repeat = JobEvent {
+            id: first,
+            state: JobState::Failed,
+            output: String::new(),
+        };
+        assert!(jobs.finish(repeat, t0).is_err());
+        assert_eq!(jobs.start_next(t0).map(|(id, _, _)| id), Some(second));
+    }
+
+    #[test]
+    fn cancel_stops_queued_jobs_and_signals_running_ones() {
+        let t0 = Instant::now();
+        let mut jobs = JobRegistry::default();
+        let running = jobs.enqueue(args("--count-only"), t0);
+        let queued = jobs.enqueue(args("--summary"), t0);
+        let (_, _, token) = jobs.start_next(t0).expect("job starts");
+
+        jobs.cancel(queued, t0).expect("queued job cancels");
+        assert_eq!(jobs.count(JobState::Cancelled), 1);
+        assert!(jobs.start_next(t0).is_none());
+
+        jobs.cancel(running, t0).expect("running job is signalled");
+        assert!(token.is_cancelled());
+        // Still running until the worker reports back.
+        assert_eq!(jobs.count(JobState::Running), 1);
+        let event = JobEvent {
+            id: running,
+            state: JobState::Cancelled,
+            output: String::new(),
+        };
+        jobs.finish(event, t0).expect("worker reports cancellation");
+        assert_eq!(jobs.count(JobState::Cancelled), 2);
+        assert_eq!(jobs.status_strip(), None);
+
+        assert!(jobs.cancel(running, t0).is_err());
+        assert!(jobs.cancel(99, t0).is_err());
+    }
+
+    #[test]
+    fn explain_command_moves_the_window_terms_behind_the_flag() {
+        assert_eq!(
+            explain_scan_args(
+                " pos=120 --primers p.tsv primer=egfr_F --reference r.fa contig=chr7"
+            ),
+            Some(
+                [
+                    "--primers",
+                    "p.tsv",
+                    "--reference",
+                    "r.fa",
+                    "--explain",
+                    "pos=120",
+                    "primer=egfr_F",
+                    "contig=chr7",
+                ]
+                .map(String::from)
+                .to_vec()
+            )
+        );
+        assert_eq!(explain_scan_args("primer=egfr_F --primers p.tsv"), None);
+    }
+
+    #[test]
+    fn jobs_and_cancel_commands_answer_in_the_console() {
+        let mut entries = Vec::new();
+        let mut jobs = JobRegistry::default();
+        handle_message("/jobs".to_string(), &mut entries, &mut jobs);
+        handle_message("/scan --count-only".to_string(), &mut entries, &mut jobs);
+        handle_message("/cancel 1".to_string(), &mut entries, &mut jobs);
+        handle_message("/cancel x".to_string(), &mut entries, &mut jobs);
+        let replies = entries
+            .iter()
+            .filter(|entry| matches!(entry.role, Role::Assistant))
+            .map(|entry| entry.text.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            replies,
+            vec![
+                "No jobs yet. Start one with /scan <args>.",
+                "Scan queued as job #1. /jobs lists jobs, /cancel 1 stops it.",
+                "Job #1 cancelled before it started.",
+                "Usage: /cancel <job id> (see /jobs)",
+            ]
+        );
+    }
 }
@@ -0,0 +1,193 @@
+use crate::{MemoryProfile, ScanOptions, iupac_from_mask, normalize_base, reference_mask};
+
+/// Read-only view of a contig as consumed by the matching loop.
+pub(crate) trait ContigMasks: Sync {
+    fn len(&self) -> usize;
+
+    fn mask(&self, idx: usize) -> u8;
+
+    /// Reference bases covering `[start, end)` as reported in `Hit::matched`.
+    fn matched(&self, start: usize, end: usize) -> String;
+}
+
+impl<M: ContigMasks> ContigMasks for &M {
+    fn len(&self) -> usize {
+        (**self).len()
+    }
+
+    #[inline]
+    fn mask(&self, idx: usize) -> u8 {
+        (**self).mask(idx)
+    }
+
+    fn matched(&self, start: usize, end: usize) -> String {
+        (**self).matched(start, end)
+    }
+}
+
+/// Fast layout: normalized bases plus one mask byte per base (~2 bytes/base).
+#[derive(Debug)]
+pub(crate) struct DenseContig {
+    bases: Vec<u8>,
+    masks: Vec<u8>,
+}
+
+impl DenseContig {
+    pub(crate) fn from_raw(sequence: &[u8], lowercase_mismatches: bool) -> Self {
+        let masks = sequence
+            .iter()
+            .map(|&base| reference_mask(base, lowercase_mismatches))
+            .collect();
+        let bases = sequence.iter().copied().map(normalize_base).collect();
+        Self { bases, masks }
+    }
+}
+
+impl ContigMasks for DenseContig {
+    fn len(&self) -> usize {
+        self.masks.len()
+    }
+
+    #[inline]
+    fn mask(&self, idx: usize) -> u8 {
+        self.masks[idx]
+    }
+
+    fn matched(&self, start: usize, end: usize) -> String {
+        String::from_utf8_lossy(&self.bases[start..end]).to_string()
+    }
+}
+
+/// Borrowed layout: masks computed from the caller's bases on each lookup (no copy).
+#[derive(Clone, Copy)]
+pub(crate) struct RawContig<'a> {
+    sequence: &'a [u8],
+    lowercase_mismatches: bool,
+}
+
+impl<'a> RawContig<'a> {
+    pub(crate) fn new(sequence: &'a [u8], lowercase_mismatches: bool) -> Self {
+        Self {
+            sequence,
+            lowercase_mismatches,
+        }
+    }
+}
+
+impl ContigMasks for RawContig<'_> {
+    fn len(&self) -> usize {
+        self.sequence.len()
+    }
+
+    #[inline]
+    fn mask(&self, idx: usize) -> u8 {
+        reference_mask(self.sequence[idx], self.lowercase_mismatches)
+    }
+
+    fn matched(&self, start: usize, end: usize) -> String {
+        let bases = self.sequence[start..end]
+            .iter()
+            .copied()
+            .map(normalize_base)
+            .collect::<Vec<_>>();
+        String::from_utf8_lossy(&bases).into_owned()
+    }
+}
+
+/// Lean layout: 4-bit masks packed two bases per byte (~0.5 bytes/base).
+///
+/// Bases outside the IUPAC alphabet (and soft-masked bases under `lowercase_mismatches`)
+/// keep no letter, so `matched` renders them as `N`.
+#[derive(Debug, Default)]
+pub(crate) struct PackedContig {
+    packed: Vec<u8>,
+    len: usize,
+    lowercase_mismatches: bool,
+}
+
+impl PackedContig {
+    pub(crate) fn new(lowercase_mismatches: bool) -> Self {
+        Self {
+            lowercase_mismatches,
+            ..Self::default()
+        }
+    }
+
+    pub(crate) fn from_raw(sequence: &[u8], lowercase_mismatches: bool) -> Self {
+        let mut contig = Self::new(lowercase_mismatches);
+        contig.extend(sequence);
+        contig
+    }
+
+    pub(crate) fn extend(&mut self, sequence: &[u8]) {
+        self.packed.reserve(sequence.len().div_ceil(2));
+        for &base in sequence {
+            let mask = reference_mask(base, self.lowercase_mismatches);
+            if self.len & 1 == 0 {
+                self.packed.push(mask);
+            } else if let Some(last) = self.packed.last_mut() {
+                *last |= mask << 4;
+            }
+            self.len += 1;
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.packed.clear();
+        self.len = 0;
+    }
+}
+
+impl ContigMasks for PackedContig {
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    fn mask(&self, idx: usize) -> u8 {
+        (self.packed[idx >> 1] >> ((idx & 1) * 4)) & 0b1111
+    }
+
+    fn matched(&self, start: usize, end: usize) -> String {
+        (start..end)
+            .map(|idx| iupac_from_mask(self.mask(idx)) as char)
+            .collect()
+    }
+}
+
+/// Per-contig sequence accumulator used while streaming a FASTA file.
+#[derive(Debug)]
+pub(crate) enum ContigBuffer {
+    Raw(Vec<u8>),
+    Packed(PackedContig),
+}
+
+impl ContigBuffer {
+    pub(crate) fn new(options: &ScanOptions) -> Self {
+        match options.memory_profile {
+            MemoryProfile::Fast => Self::Raw(Vec::new()),
+            MemoryProfile::Lean => Self::Packed(PackedContig::new(options.lowercase_mismatches)),
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        match self {
+            Self::Raw(bytes) => bytes.len(),
+            Self::Packed(packed) => packed.len,
+        }
+    }
+
+    pub(crate) fn extend(&mut self, fragment: &[u8]) {
+        match self {
+            Self::Raw(bytes) => bytes.extend_from_slice(fragment),
+            Self::Packed(packed) => packed.extend(fragment),
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        match self {
+            Self::Raw(bytes) => bytes.clear(),
+            Self::Packed(packed) => packed.clear(),
+        }
+    }
+}
@@ -0,0 +1,33 @@
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+
+/// Directory the quickstart commands read the demo files from, relative to the working
+/// directory.
+pub const DEMO_DIR: &str = "data";
+pub const DEMO_PRIMERS_FILE: &str = "demo_primers.tsv";
+pub const DEMO_REFERENCE_FILE: &str = "demo.fa";
+
+/// The repository's `data/demo_primers.tsv`: exact, reverse-strand, and degenerate (`TGYA`)
+/// primers.
+pub const DEMO_PRIMERS: &[u8] = include_bytes!("../data/demo_primers.tsv");
+/// The repository's `data/demo.fa`: two short contigs that also hold one-mismatch sites.
+pub const DEMO_REFERENCE: &[u8] = include_bytes!("../data/demo.fa");
+
+/// Whether both demo files exist in `dir`.
+pub fn demo_present(dir: &Path) -> bool {
+    dir.join(DEMO_PRIMERS_FILE).is_file() && dir.join(DEMO_REFERENCE_FILE).is_file()
+}
+
+/// Writes the embedded demo panel and reference into `dir` (created if needed), replacing
+/// earlier copies. Returns the primer and reference paths.
+pub fn write_demo(dir: &Path) -> Result<(PathBuf, PathBuf)> {
+    std::fs::create_dir_all(dir)
+        .with_context(|| format!("failed to create '{}'", dir.display()))?;
+    let primers = dir.join(DEMO_PRIMERS_FILE);
+    let reference = dir.join(DEMO_REFERENCE_FILE);
+    for (path, bytes) in [(&primers, DEMO_PRIMERS), (&reference, DEMO_REFERENCE)] {
+        std::fs::write(path, bytes)
+            .with_context(|| format!("failed writing '{}'", path.display()))?;
+    }
+    Ok((primers, reference))
+}
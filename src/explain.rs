@@ -0,0 +1,498 @@
+use anyhow::{Context, Result, bail};
+use std::io::Write;
+
+use crate::{
+    Hit, Primer, ScanAlgorithm, ScanOptions, iupac_from_mask, reference_mask, scan_sequence,
+};
+
+/// Hits on each side of the window listed in an explanation.
+const NEAREST_HITS: usize = 3;
+const UNMODELED_REJECTION: &str = "not reported for a reason explain does not model (sampling, a time limit, or IUPAC deduplication)";
+
+/// One window to explain: `primer=NAME contig=CHR pos=START`, with `pos` the 0-based window
+/// start as in hit rows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExplainQuery {
+    pub primer: String,
+    pub contig: String,
+    pub start: usize,
+}
+
+impl ExplainQuery {
+    /// Parses `key=value` terms in any order; every key is required exactly once.
+    pub fn parse<S: AsRef<str>>(terms: &[S]) -> Result<Self> {
+        let (mut primer, mut contig, mut start) = (None, None, None);
+        for term in terms {
+            let term = term.as_ref();
+            let Some((key, value)) = term.split_once('=') else {
+                bail!("explain term '{term}' must be key=value (primer=, contig=, pos=)");
+            };
+            let slot = match key {
+                "primer" => &mut primer,
+                "contig" => &mut contig,
+                "pos" => &mut start,
+                _ => bail!("unknown explain key '{key}'; expected primer, contig, or pos"),
+            };
+            if slot.replace(value.to_string()).is_some() {
+                bail!("explain key '{key}' given twice");
+            }
+        }
+        let (Some(primer), Some(contig), Some(start)) = (primer, contig, start) else {
+            bail!("--explain needs primer=NAME contig=CHR pos=START");
+        };
+        let start = start
+            .parse()
+            .with_context(|| format!("explain pos '{start}' is not a 0-based position"))?;
+        Ok(Self {
+            primer,
+            contig,
+            start,
+        })
+    }
+}
+
+/// One primer base against the reference base it is aligned to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PositionCheck {
+    /// Offset in the window, 0-based from its left (`+` strand) end.
+    pub offset: usize,
+    pub reference_base: char,
+    pub primer_base: char,
+    /// Bases each side allows, e.g. `AG` for `R`; empty for a reference base read as a
+    /// mismatch (soft-masked with [`ScanOptions::lowercase_mismatches`]).
+    pub reference_bases: String,
+    pub primer_bases: String,
+    pub matches: bool,
+}
+
+impl PositionCheck {
+    /// A match that needed an ambiguity code on either side.
+    pub fn is_ambiguous_match(&self) -> bool {
+        self.matches && (self.reference_bases.len() > 1 || self.primer_bases.len() > 1)
+    }
+}
+
+/// The window tested against one orientation of the primer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StrandCheck {
+    pub strand: char,
+    /// The primer as aligned to the `+` strand: its sequence for `+`, its reverse complement
+    /// for `-`.
+    pub aligned_primer: String,
+    /// False when the scan skips this strand ([`ScanOptions::scan_reverse_complement`]).
+    pub scanned: bool,
+    pub positions: Vec<PositionCheck>,
+    pub mismatches: usize,
+}
+
+/// Why one primer does or does not hit one window, and where it does hit nearby.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WindowExplanation {
+    pub primer: String,
+    pub contig: String,
+    pub start: usize,
+    pub end: usize,
+    /// The reference window as read; empty when it does not fit on the contig.
+    pub window: String,
+    pub strands: Vec<StrandCheck>,
+    /// The step that rejected the window, or `None` when the scan reports a hit here.
+    pub rejection: Option<String>,
+    /// Closest reported hits of the primer on the contig, nearest first.
+    pub nearest_hits: Vec<Hit>,
+}
+
+/// Re-evaluates one window of `contig_sequence` for `primer` under `options`, outside the
+/// scan loop: per-position mask checks on each strand, then the hit filters in scan order.
+/// The verdict agrees with a full scan of the contig, which also supplies the nearest hits.
+pub fn explain_window(
+    contig_sequence: &str,
+    query: &ExplainQuery,
+    primer: &Primer,
+    options: &ScanOptions,
+) -> Result<WindowExplanation> {
+    let start = query.start;
+    let end = start
+        .checked_add(primer.len())
+        .with_context(|| format!("explain pos {start} is past the end of any contig"))?;
+    // Only the hits matter, so file outputs and renaming are switched off.
+    let full_options = ScanOptions {
+        spill_threshold: None,
+        sanitize_contig_names: false,
+        ..options.clone()
+    };
+    let mut hits = scan_sequence(
+        contig_sequence,
+        &query.contig,
+        std::slice::from_ref(primer),
+        &full_options,
+    )?
+    .hits;
+    let reported = hits.iter().any(|hit| hit.start == start && hit.end == end);
+    hits.retain(|hit| hit.start != start);
+    hits.sort_by_key(|hit| (hit.start.abs_diff(start), hit.start));
+    hits.truncate(2 * NEAREST_HITS);
+
+    let mut explanation = WindowExplanation {
+        primer: primer.name.clone(),
+        contig: query.contig.clone(),
+        start,
+        end,
+        window: String::new(),
+        strands: Vec::new(),
+        rejection: None,
+        nearest_hits: hits,
+    };
+    let Some(window) = contig_sequence.as_bytes().get(start..end) else {
+        explanation.rejection = Some(format!(
+            "contig end: the {} bp window runs past the end of the {} bp contig",
+            primer.len(),
+            contig_sequence.len()
+        ));
+        return Ok(explanation);
+    };
+    explanation.window = String::from_utf8_lossy(window).into_owned();
+
+    let reverse_masks = primer
+        .combined_masks
+        .iter()
+        .map(|mask| mask >> 4)
+        .collect::<Vec<_>>();
+    let mut orientations = vec![('+', primer.sequence.as_str(), primer.masks.as_slice(), true)];
+    if !primer.is_palindromic {
+        orientations.push((
+            '-',
+            primer.reverse_complement.as_str(),
+            reverse_masks.as_slice(),
+            options.scan_reverse_complement,
+        ));
+    }
+    explanation.strands = orientations
+        .into_iter()
+        .map(|(strand, aligned, masks, scanned)| {
+            strand_check(window, strand, aligned, masks, scanned, options)
+        })
+        .collect();
+
+    if !reported {
+        explanation.rejection = Some(rejection(&explanation, contig_sequence, primer, options)?);
+    }
+    Ok(explanation)
+}
+
+fn strand_check(
+    window: &[u8],
+    strand: char,
+    aligned_primer: &str,
+    masks: &[u8],
+    scanned: bool,
+    options: &ScanOptions,
+) -> StrandCheck {
+    let positions = window
+        .iter()
+        .zip(aligned_primer.bytes())
+        .zip(masks)
+        .enumerate()
+        .map(|(offset, ((&base, primer_base), &primer_mask))| {
+            let mask = reference_mask(base, options.lowercase_mismatches);
+            PositionCheck {
+                offset,
+                reference_base: base as char,
+                primer_base: primer_base as char,
+                reference_bases: mask_bases(mask),
+                primer_bases: mask_bases(primer_mask),
+                matches: mask & primer_mask != 0,
+            }
+        })
+        .collect::<Vec<_>>();
+    StrandCheck {
+        strand,
+        aligned_primer: aligned_primer.to_string(),
+        scanned,
+        mismatches: positions.iter().filter(|check| !check.matches).count(),
+        positions,
+    }
+}
+
+fn mask_bases(mask: u8) -> String {
+    (0..4)
+        .filter(|bit| mask & (1 << bit) != 0)
+        .map(|bit| iupac_from_mask(1 << bit) as char)
+        .collect()
+}
+
+/// The first step, in scan order, that drops the window.
+fn rejection(
+    explanation: &WindowExplanation,
+    contig_sequence: &str,
+    primer: &Primer,
+    options: &ScanOptions,
+) -> Result<String> {
+    let (start, end) = (explanation.start, explanation.end);
+    let k = options.max_mismatches;
+    let best_scanned = explanation
+        .strands
+        .iter()
+        .filter(|check| check.scanned)
+        .map(|check| check.mismatches)
+        .min()
+        .unwrap_or(usize::MAX);
+    if best_scanned > k {
+        if let Some(skipped) = explanation
+            .strands
+            .iter()
+            .find(|check| !check.scanned && check.mismatches <= k)
+        {
+            return Ok(format!(
+                "strand filter: the {} strand matches with {} mismatch(es), but reverse-complement \
+                 scanning is off",
+                skipped.strand, skipped.mismatches
+            ));
+        }
+        return Ok(format!(
+            "mismatch budget: {best_scanned} mismatch(es) on the best strand, above \
+             --max-mismatches {k}"
+        ));
+    }
+    if let Some(regions) = &options.regions
+        && !regions
+            .intervals(&explanation.contig)
+            .iter()
+            .any(|range| range.start <= start && end <= range.end)
+    {
+        return Ok("regions: the window is not inside one --regions-bed interval".to_string());
+    }
+
+    // Score the window alone, with no filters, to get the hit the filters would judge.
+    let bare = ScanOptions {
+        max_mismatches: k,
+        scan_reverse_complement: options.scan_reverse_complement,
+        lowercase_mismatches: options.lowercase_mismatches,
+        algorithm: ScanAlgorithm::Exhaustive,
+        ..ScanOptions::default()
+    };
+    let candidates = scan_sequence(
+        &contig_sequence[start..end],
+        &explanation.contig,
+        std::slice::from_ref(primer),
+        &bare,
+    )?
+    .hits;
+    let Some(mut hit) = candidates.into_iter().next() else {
+        return Ok(UNMODELED_REJECTION.to_string());
+    };
+    hit.start += start;
+    hit.end += start;
+    if let Some(min) = options.min_hit_complexity
+        && hit.complexity < min
+    {
+        return Ok(format!(
+            "complexity: hit complexity {:.3} is below --min-hit-complexity {min}",
+            // Entropy of a single-base window comes out as -0.0.
+            hit.complexity.abs()
+        ));
+    }
+    if let Some(max) = options.max_ambiguous_positions
+        && hit.ambiguous_positions > max
+    {
+        return Ok(format!(
+            "ambiguous bases: {} ambiguous reference position(s), above \
+             --max-ambiguous-positions {max}",
+            hit.ambiguous_positions
+        ));
+    }
+    if options
+        .known_sites
+        .as_ref()
+        .is_some_and(|known| known.contains(&hit))
+    {
+        return Ok(
+            "exclusion BED: the hit overlaps a --known-sites interval of this primer".to_string(),
+        );
+    }
+    Ok(UNMODELED_REJECTION.to_string())
+}
+
+/// Writes `explanation` as text: the verdict and nearest hits first, then the window against
+/// each strand with a `|` (match), `:` (match through an ambiguity code), or `x` (mismatch)
+/// marker line, then every mismatch and ambiguous match with the bases each side allows.
+pub fn write_explanation<W: Write>(
+    out: &mut W,
+    explanation: &WindowExplanation,
+) -> std::io::Result<()> {
+    let location = format!(
+        "{}:{}-{}",
+        explanation.contig, explanation.start, explanation.end
+    );
+    match &explanation.rejection {
+        None => writeln!(out, "verdict: hit ({} at {location})", explanation.primer)?,
+        Some(reason) => writeln!(
+            out,
+            "verdict: no hit ({} at {location}) - {reason}",
+            explanation.primer
+        )?,
+    }
+    let nearest = explanation
+        .nearest_hits
+        .iter()
+        .map(|hit| format!("{}{} ({} mm)", hit.start, hit.strand, hit.mismatches))
+        .collect::<Vec<_>>();
+    if nearest.is_empty() {
+        writeln!(out, "nearest hits: none on {}", explanation.contig)?;
+    } else {
+        writeln!(out, "nearest hits: {}", nearest.join(", "))?;
+    }
+    if explanation.window.is_empty() {
+        return Ok(());
+    }
+    writeln!(out, "reference  {}", explanation.window)?;
+    for check in &explanation.strands {
+        let markers = check
+            .positions
+            .iter()
+            .map(|position| match position {
+                position if !position.matches => 'x',
+                position if position.is_ambiguous_match() => ':',
+                _ => '|',
+            })
+            .collect::<String>();
+        let skipped = if check.scanned { "" } else { ", not scanned" };
+        writeln!(out, "           {markers}")?;
+        writeln!(
+            out,
+            "{} primer   {}  {} mismatch(es){skipped}",
+            check.strand, check.aligned_primer, check.mismatches
+        )?;
+    }
+    for check in &explanation.strands {
+        for position in check
+            .positions
+            .iter()
+            .filter(|position| !position.matches || position.is_ambiguous_match())
+        {
+            let kind = if position.matches {
+                "ambiguous match"
+            } else {
+                "mismatch"
+            };
+            writeln!(
+                out,
+                "{} offset {}: {kind}, reference {} {{{}}} vs primer {} {{{}}}",
+                check.strand,
+                position.offset,
+                position.reference_base,
+                position.reference_bases,
+                position.primer_base,
+                position.primer_bases
+            )?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::known_sites::KnownSites;
+
+    fn explain(
+        sequence: &str,
+        primer: &str,
+        start: usize,
+        options: &ScanOptions,
+    ) -> WindowExplanation {
+        let primer = Primer::from_name_and_sequence("p1", primer).expect("primer");
+        let query = ExplainQuery::parse(&["primer=p1", "contig=chr1", &format!("pos={start}")])
+            .expect("query");
+        explain_window(sequence, &query, &primer, options).expect("explain")
+    }
+
+    #[test]
+    fn explanations_name_the_step_that_rejects_the_window() {
+        // GATTACA at 2, a two-mismatch copy (GAGTACT) at 11, its reverse complement at 20, and
+        // a poly-A stretch at 30.
+        let sequence = "CCGATTACACCGAGTACTCCTGTAATCCCCAAAAAAAACC";
+        let defaults = ScanOptions::default();
+        let reason = |primer: &str, start: usize, options: &ScanOptions| {
+            explain(sequence, primer, start, options)
+                .rejection
+                .unwrap_or_default()
+        };
+
+        let hit = explain(sequence, "GATTACA", 2, &defaults);
+        assert_eq!(hit.rejection, None);
+        assert_eq!(
+            hit.nearest_hits
+                .iter()
+                .map(|hit| (hit.start, hit.strand))
+                .collect::<Vec<_>>(),
+            vec![(20, '-')]
+        );
+        assert_eq!(
+            reason("GATTACA", 11, &defaults),
+            "mismatch budget: 2 mismatch(es) on the best strand, above --max-mismatches 0"
+        );
+        let forward_only = ScanOptions {
+            scan_reverse_complement: false,
+            ..ScanOptions::default()
+        };
+        assert_eq!(
+            reason("GATTACA", 20, &forward_only),
+            "strand filter: the - strand matches with 0 mismatch(es), but reverse-complement \
+             scanning is off"
+        );
+        let complex = ScanOptions {
+            min_hit_complexity: Some(0.5),
+            ..ScanOptions::default()
+        };
+        assert!(
+            reason("AAAAAAAA", 30, &complex).starts_with("complexity: hit complexity 0.000"),
+            "{}",
+            reason("AAAAAAAA", 30, &complex)
+        );
+        let known = ScanOptions {
+            known_sites: Some(KnownSites::from_sites([("chr1", 0, 10, "p1")])),
+            ..ScanOptions::default()
+        };
+        assert_eq!(
+            reason("GATTACA", 2, &known),
+            "exclusion BED: the hit overlaps a --known-sites interval of this primer"
+        );
+        assert_eq!(
+            reason("GATTACA", 36, &defaults),
+            "contig end: the 7 bp window runs past the end of the 40 bp contig"
+        );
+    }
+
+    #[test]
+    fn explanation_text_shows_each_base_and_the_iupac_sets() {
+        let options = ScanOptions {
+            max_mismatches: 1,
+            ..ScanOptions::default()
+        };
+        let explanation = explain("CCGANTCCACC", "GRTTACA", 2, &options);
+        let mut text = Vec::new();
+        write_explanation(&mut text, &explanation).expect("write");
+        let text = String::from_utf8(text).expect("utf8");
+        let lines = text.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], "verdict: hit (p1 at chr1:2-9)");
+        assert_eq!(lines[1], "nearest hits: none on chr1");
+        assert_eq!(lines[2], "reference  GANTCCA");
+        assert_eq!(lines[3], "           |::|x||");
+        assert_eq!(lines[4], "+ primer   GRTTACA  1 mismatch(es)");
+        assert!(
+            text.contains("+ offset 1: ambiguous match, reference A {A} vs primer R {AG}"),
+            "{text}"
+        );
+        assert!(
+            text.contains("+ offset 2: ambiguous match, reference N {ACGT} vs primer T {T}"),
+            "{text}"
+        );
+        assert!(
+            text.contains("+ offset 4: mismatch, reference C {C} vs primer A {A}"),
+            "{text}"
+        );
+
+        let err = ExplainQuery::parse(&["primer=p1", "pos=3"]).expect_err("missing contig");
+        assert!(err.to_string().contains("contig=CHR"), "{err}");
+    }
+}
@@ -0,0 +1,115 @@
+use crate::profile::mismatch_positions;
+use crate::{Hit, Primer};
+
+/// 3'-most primer bases whose pairing decides [`is_extendable`].
+pub const EXTENSION_WINDOW: usize = 5;
+
+/// Mismatches [`is_extendable`] tolerates inside the [`EXTENSION_WINDOW`], never at the
+/// 3'-terminal base.
+pub const MAX_EXTENSION_WINDOW_MISMATCHES: usize = 1;
+
+/// Whether a polymerase would likely extend `primer` bound at `hit`: its 3'-terminal base
+/// pairs, at most [`MAX_EXTENSION_WINDOW_MISMATCHES`] of its [`EXTENSION_WINDOW`] 3'-most bases
+/// mismatch, and at least one paired base there is a G or C, so the 3' end holds on long enough
+/// to prime. Bases hanging off the contig count as mismatches (see [`mismatch_positions`]).
+///
+/// A heuristic: it ignores which bases mismatch, proofreading polymerases, and the stability of
+/// the rest of the duplex.
+pub fn is_extendable(hit: &Hit, primer: &Primer) -> bool {
+    let len = primer.len();
+    let window = len.saturating_sub(EXTENSION_WINDOW)..len;
+    let mismatched = mismatch_positions(hit, primer)
+        .into_iter()
+        .filter(|position| window.contains(position))
+        .collect::<Vec<_>>();
+    if mismatched.contains(&(len - 1)) || mismatched.len() > MAX_EXTENSION_WINDOW_MISMATCHES {
+        return false;
+    }
+    primer
+        .sequence
+        .bytes()
+        .enumerate()
+        .skip(window.start)
+        .any(|(position, base)| {
+            matches!(base, b'G' | b'C' | b'S') && !mismatched.contains(&position)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ScanOptions, scan_sequence};
+
+    #[test]
+    fn three_prime_terminal_mismatch_blocks_extension() {
+        let primer = Primer::from_name_and_sequence("p1", "ATTAGACGTC").expect("primer");
+        let options = ScanOptions {
+            max_mismatches: 2,
+            scan_reverse_complement: false,
+            predict_extension: true,
+            ..ScanOptions::default()
+        };
+        // Perfect site; 3'-terminal mismatch; two mismatches in the 3' window; one mismatch
+        // near the 5' end.
+        let sites = ["ATTAGACGTC", "ATTAGACGTA", "ATTAGAGCTC", "TTTAGACGTC"];
+        let extendable = sites
+            .iter()
+            .map(|site| {
+                let scan = scan_sequence(site, "chr1", std::slice::from_ref(&primer), &options)
+                    .expect("scan");
+                assert_eq!(scan.hits.len(), 1, "{site}");
+                scan.hits[0].extendable
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            extendable,
+            [Some(true), Some(false), Some(false), Some(true)]
+        );
+
+        // An A/T-only 3' end pairs perfectly but is too weak to prime.
+        let weak = Primer::from_name_and_sequence("weak", "GCGCGATTAT").expect("primer");
+        let scan = scan_sequence("GCGCGATTAT", "chr1", std::slice::from_ref(&weak), &options)
+            .expect("scan");
+        assert_eq!(scan.hits[0].extendable, Some(false));
+
+        let scan = scan_sequence(
+            sites[0],
+            "chr1",
+            std::slice::from_ref(&primer),
+            &ScanOptions::default(),
+        )
+        .expect("scan");
+        assert_eq!(scan.hits[0].extendable, None);
+    }
+
+    #[test]
+    fn extendable_only_drops_hits_from_summaries_and_hash() {
+        let primer = Primer::from_name_and_sequence("p1", "ATTAGACGTC").expect("primer");
+        let options = ScanOptions {
+            max_mismatches: 1,
+            scan_reverse_complement: false,
+            predict_extension: true,
+            extendable_only: true,
+            ..ScanOptions::default()
+        };
+        // An extendable site, then one with a 3'-terminal mismatch.
+        let scan = scan_sequence(
+            "ATTAGACGTCGGGGGATTAGACGTA",
+            "chr1",
+            std::slice::from_ref(&primer),
+            &options,
+        )
+        .expect("scan");
+        assert_eq!(scan.hits.len(), 1);
+        assert_eq!(scan.hits[0].start, 0);
+        assert_eq!(scan.total_hits, 1);
+        assert_eq!(scan.summary[0].total_hits, 1);
+        assert_eq!(scan.hit_hash, crate::compute_hit_hash(&scan.hits));
+
+        let unpredicted = ScanOptions {
+            predict_extension: false,
+            ..options
+        };
+        assert!(scan_sequence("ATTAGACGTC", "chr1", &[primer], &unpredicted).is_err());
+    }
+}
@@ -0,0 +1,231 @@
+use anyhow::{Context, Result, bail};
+use std::collections::{HashMap, HashSet};
+use std::io::BufRead;
+use std::path::Path;
+
+use crate::{
+    DEFAULT_MAX_CONTIG_BASES, Primer, open_reader, parse_contig_name, read_limit_from_env,
+};
+
+/// One primer location from a BED file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct BedPrimer {
+    line_number: usize,
+    contig: String,
+    start: usize,
+    end: usize,
+    name: String,
+    reverse: bool,
+}
+
+/// Builds a primer panel from BED primer locations: each interval's reference sequence,
+/// reverse-complemented for `-` strand entries, named after the BED name column.
+pub fn extract_panel(bed: &Path, reference: &Path) -> Result<Vec<Primer>> {
+    let entries = load_primer_bed(bed)?;
+    let wanted = entries
+        .iter()
+        .map(|entry| entry.contig.as_str())
+        .collect::<HashSet<_>>();
+    let contigs = load_contigs(reference, &wanted)?;
+
+    entries
+        .iter()
+        .map(|entry| {
+            let Some(sequence) = contigs.get(&entry.contig) else {
+                bail!(
+                    "BED line {} in '{}' names contig '{}' which is not in '{}'",
+                    entry.line_number,
+                    bed.display(),
+                    entry.contig,
+                    reference.display()
+                );
+            };
+            if entry.end > sequence.len() {
+                bail!(
+                    "BED line {} in '{}' ends at {} past the end of '{}' ({} bp)",
+                    entry.line_number,
+                    bed.display(),
+                    entry.end,
+                    entry.contig,
+                    sequence.len()
+                );
+            }
+            let bases = String::from_utf8_lossy(&sequence[entry.start..entry.end]);
+            let context = || {
+                format!(
+                    "invalid primer '{}' on BED line {} in '{}'",
+                    entry.name,
+                    entry.line_number,
+                    bed.display()
+                )
+            };
+            let forward =
+                Primer::from_name_and_sequence(entry.name.clone(), &bases).with_context(context)?;
+            if entry.reverse {
+                Primer::from_name_and_sequence(entry.name.clone(), &forward.reverse_complement)
+                    .with_context(context)
+            } else {
+                Ok(forward)
+            }
+        })
+        .collect()
+}
+
+/// Reads BED6-style primer rows: `chrom`, `start`, `end`, `name`, and optionally `score` and
+/// `strand` (`.` or missing means `+`). Blank, `#`, `track`, and `browser` lines are skipped.
+fn load_primer_bed(path: &Path) -> Result<Vec<BedPrimer>> {
+    let reader = open_reader(path)?;
+    let mut entries = Vec::new();
+
+    for (line_idx, line) in reader.lines().enumerate() {
+        let line_number = line_idx + 1;
+        let line = line.with_context(|| format!("failed reading BED file '{}'", path.display()))?;
+        let trimmed = line.trim();
+        if trimmed.is_empty()
+            || trimmed.starts_with('#')
+            || trimmed.starts_with("track")
+            || trimmed.starts_with("browser")
+        {
+            continue;
+        }
+
+        let fields = trimmed.split('\t').map(str::trim).collect::<Vec<_>>();
+        if fields.len() < 4 || fields[3].is_empty() {
+            bail!(
+                "BED line {} in '{}' needs chrom, start, end, and name columns",
+                line_number,
+                path.display()
+            );
+        }
+        let parse = |value: &str| {
+            value.parse::<usize>().with_context(|| {
+                format!(
+                    "invalid coordinate '{}' on BED line {} in '{}'",
+                    value,
+                    line_number,
+                    path.display()
+                )
+            })
+        };
+        let (start, end) = (parse(fields[1])?, parse(fields[2])?);
+        if start >= end {
+            bail!(
+                "BED line {} in '{}' has an empty interval {}..{}",
+                line_number,
+                path.display(),
+                start,
+                end
+            );
+        }
+        let reverse = match fields.get(5).copied().unwrap_or(".") {
+            "+" | "." => false,
+            "-" => true,
+            other => bail!(
+                "BED line {} in '{}' has invalid strand '{}'",
+                line_number,
+                path.display(),
+                other
+            ),
+        };
+        entries.push(BedPrimer {
+            line_number,
+            contig: fields[0].to_string(),
+            start,
+            end,
+            name: fields[3].to_string(),
+            reverse,
+        });
+    }
+    Ok(entries)
+}
+
+/// Loads the sequence of every contig in `wanted`; other contigs are skipped without buffering.
+pub(crate) fn load_contigs(
+    path: &Path,
+    wanted: &HashSet<&str>,
+) -> Result<HashMap<String, Vec<u8>>> {
+    load_contigs_where(path, |name| wanted.contains(name))
+}
+
+/// Loads the sequence of every contig whose header name passes `wanted`, keyed by that name.
+pub(crate) fn load_contigs_where(
+    path: &Path,
+    wanted: impl Fn(&str) -> bool,
+) -> Result<HashMap<String, Vec<u8>>> {
+    let reader = open_reader(path)?;
+    let max_contig_bases =
+        read_limit_from_env("PRIMER_SCOUT_MAX_CONTIG_BASES", DEFAULT_MAX_CONTIG_BASES);
+    let mut contigs: HashMap<String, Vec<u8>> = HashMap::new();
+    let mut current: Option<String> = None;
+
+    for line in reader.lines() {
+        let line =
+            line.with_context(|| format!("failed reading reference '{}'", path.display()))?;
+        let trimmed = line.trim();
+        if let Some(header) = trimmed.strip_prefix('>') {
+            let name = parse_contig_name(header);
+            current = wanted(&name).then(|| {
+                contigs.entry(name.clone()).or_default();
+                name
+            });
+            continue;
+        }
+        let Some(name) = &current else {
+            continue;
+        };
+        let sequence = contigs.get_mut(name).expect("current contig is registered");
+        if sequence.len().saturating_add(trimmed.len()) > max_contig_bases {
+            bail!(
+                "contig '{}' in '{}' exceeds safety limit of {} bases (override with PRIMER_SCOUT_MAX_CONTIG_BASES)",
+                name,
+                path.display(),
+                max_contig_bases
+            );
+        }
+        sequence.extend_from_slice(trimmed.as_bytes());
+    }
+    Ok(contigs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::tmp_path;
+
+    #[test]
+    fn extracts_both_strands_and_reports_bad_lines() {
+        let reference = tmp_path("extract_ref.fa");
+        let bed = tmp_path("extract_panel.bed");
+        std::fs::write(
+            &reference,
+            ">chr1 test\nACGTACCGGT\nTTAGGCATGC\n>chr2\nGGGG\n",
+        )
+        .expect("write reference");
+        std::fs::write(
+            &bed,
+            "# primers\nchr1\t2\t8\tfwd\t0\t+\nchr1\t10\t16\trev\t0\t-\nchr2\t0\t4\tplain\n",
+        )
+        .expect("write bed");
+
+        let primers = extract_panel(&bed, &reference).expect("extract");
+        let extracted = primers
+            .iter()
+            .map(|primer| (primer.name.as_str(), primer.sequence.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            extracted,
+            vec![("fwd", "GTACCG"), ("rev", "GCCTAA"), ("plain", "GGGG")]
+        );
+
+        std::fs::write(&bed, "chr1\t0\t6\tok\nchr1\t15\t25\toff_end\t0\t+\n").expect("write bed");
+        let err = format!("{:#}", extract_panel(&bed, &reference).unwrap_err());
+        assert!(err.contains("BED line 2"), "{err}");
+
+        std::fs::write(&bed, "chr9\t0\t6\tmissing\n").expect("write bed");
+        let err = format!("{:#}", extract_panel(&bed, &reference).unwrap_err());
+        assert!(err.contains("BED line 1") && err.contains("chr9"), "{err}");
+
+        std::fs::remove_file(reference).expect("remove reference");
+        std::fs::remove_file(bed).expect("remove bed");
+    }
+}
@@ -0,0 +1,242 @@
+use anyhow::{Context, Result, bail};
+use std::io::{BufRead, Read};
+
+use crate::spill::HitCollector;
+use crate::{
+    ContigLength, DEFAULT_MAX_FASTA_LINE_BYTES, FastaStrictness, FileScanResult, Primer,
+    ScanOptions, SummaryAccumulator, iupac_mask, parse_contig_name, postprocess_hits,
+    read_limit_from_env, regions, report_coerced_bases, scan_contig,
+};
+
+/// Offset of Sanger / Illumina 1.8+ quality characters.
+const PHRED_OFFSET: u8 = 33;
+
+/// True when the first non-blank byte of the stream starts a FASTQ record (`@`).
+pub(crate) fn looks_like_fastq(reader: &mut dyn BufRead) -> Result<bool> {
+    loop {
+        let buffer = reader.fill_buf()?;
+        let Some(&first) = buffer.first() else {
+            return Ok(false);
+        };
+        if first.is_ascii_whitespace() {
+            reader.consume(1);
+            continue;
+        }
+        return Ok(first == b'@');
+    }
+}
+
+/// Scans every read of a FASTQ file as its own contig, named after the read.
+pub(crate) fn scan_fastq_file(
+    reader: Box<dyn BufRead + Send>,
+    file_name: &str,
+    primers: &[Primer],
+    options: &ScanOptions,
+    collector: &mut HitCollector,
+) -> Result<FileScanResult> {
+    let mut lines = FastqLines {
+        reader,
+        file_name,
+        line_number: 0,
+        max_line_bytes: read_limit_from_env(
+            "PRIMER_SCOUT_MAX_FASTA_LINE_BYTES",
+            DEFAULT_MAX_FASTA_LINE_BYTES,
+        ),
+    };
+    let mut summary_acc = vec![SummaryAccumulator::default(); primers.len()];
+    let mut contigs = Vec::new();
+    let mut total_hits = 0u64;
+    let mut covered_bases = 0u64;
+    let (mut header, mut sequence, mut separator, mut quality) =
+        (Vec::new(), Vec::new(), Vec::new(), Vec::new());
+    let mut masked = Vec::new();
+
+    loop {
+        if options.cancel.is_cancelled() {
+            break;
+        }
+        if !lines.next(&mut header)? {
+            break;
+        }
+        if header.is_empty() {
+            continue;
+        }
+        let record_line = lines.line_number;
+        let Some(name) = header.strip_prefix(b"@") else {
+            bail!("FASTQ record on line {record_line} of '{file_name}' does not start with '@'");
+        };
+        let name = parse_contig_name(&String::from_utf8_lossy(name));
+        let complete = lines.next(&mut sequence)?
+            && lines.next(&mut separator)?
+            && separator.starts_with(b"+")
+            && lines.next(&mut quality)?;
+        if !complete {
+            bail!("FASTQ record '{name}' on line {record_line} of '{file_name}' is truncated");
+        }
+        if quality.len() != sequence.len() {
+            bail!(
+                "FASTQ record '{name}' on line {record_line} of '{file_name}' has {} quality values for {} bases",
+                quality.len(),
+                sequence.len()
+            );
+        }
+
+        let coerced_bases = count_invalid_bases(&sequence, options, file_name, &name, record_line)?;
+        report_coerced_bases(file_name, &name, coerced_bases, options);
+        contigs.push(ContigLength {
+            file: file_name.to_string(),
+            contig: name.clone(),
+            length: sequence.len(),
+            coerced_bases,
+        });
+        let bases = mask_low_quality_bases(&sequence, &quality, options, &mut masked);
+        let mut result = scan_contig(file_name, &name, bases, primers, options)?;
+        total_hits += result.total_hits;
+        postprocess_hits(&mut result.hits, options);
+        if options.compute_genome_coverage {
+            covered_bases += regions::covered_bases(&result.hits);
+        }
+        collector.push(result.hits)?;
+        for (acc, delta) in summary_acc.iter_mut().zip(result.summary) {
+            acc.merge(delta);
+        }
+    }
+
+    Ok(FileScanResult {
+        summary: summary_acc,
+        total_hits,
+        contigs,
+        covered_bases,
+        completed: !options.cancel.is_cancelled(),
+    })
+}
+
+/// The read's bases with every base below [`ScanOptions::min_base_quality`] (Phred+33)
+/// replaced by `N`, which matches any primer base and so never counts as a mismatch.
+/// `masked` is reused across reads; without a threshold `sequence` is returned as is.
+fn mask_low_quality_bases<'a>(
+    sequence: &'a [u8],
+    quality: &[u8],
+    options: &ScanOptions,
+    masked: &'a mut Vec<u8>,
+) -> &'a [u8] {
+    let Some(min_quality) = options.min_base_quality else {
+        return sequence;
+    };
+    masked.clear();
+    masked.extend(sequence.iter().zip(quality).map(|(&base, &score)| {
+        if score.saturating_sub(PHRED_OFFSET) < min_quality {
+            b'N'
+        } else {
+            base
+        }
+    }));
+    masked
+}
+
+struct FastqLines<'a> {
+    reader: Box<dyn BufRead + Send>,
+    file_name: &'a str,
+    /// 1-based number of the line last read.
+    line_number: usize,
+    max_line_bytes: usize,
+}
+
+impl FastqLines<'_> {
+    /// Reads one line without its line ending into `buf`; `false` at end of input.
+    fn next(&mut self, buf: &mut Vec<u8>) -> Result<bool> {
+        self.line_number += 1;
+        buf.clear();
+        let read = Read::take(&mut self.reader, self.max_line_bytes as u64 + 1)
+            .read_until(b'\n', buf)
+            .with_context(|| format!("failed reading FASTQ '{}'", self.file_name))?;
+        if buf.len() > self.max_line_bytes {
+            bail!(
+                "FASTQ line {} in '{}' exceeds safety limit of {} bytes (override with PRIMER_SCOUT_MAX_FASTA_LINE_BYTES)",
+                self.line_number,
+                self.file_name,
+                self.max_line_bytes
+            );
+        }
+        buf.truncate(buf.trim_ascii_end().len());
+        Ok(read > 0)
+    }
+}
+
+fn count_invalid_bases(
+    sequence: &[u8],
+    options: &ScanOptions,
+    file_name: &str,
+    read_name: &str,
+    record_line: usize,
+) -> Result<usize> {
+    let invalid = sequence
+        .iter()
+        .filter(|&&base| iupac_mask(base).is_none())
+        .count();
+    if invalid > 0 && options.fasta_strictness == FastaStrictness::Strict {
+        let idx = sequence
+            .iter()
+            .position(|&base| iupac_mask(base).is_none())
+            .unwrap_or_default();
+        bail!(
+            "invalid sequence character '{}' in read '{}' at {}:{}:{} (not an IUPAC base)",
+            sequence[idx].escape_ascii(),
+            read_name,
+            file_name,
+            record_line + 1,
+            idx + 1
+        );
+    }
+    Ok(invalid)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::tmp_path;
+    use crate::{Primer, ScanOptions, scan_references};
+
+    #[test]
+    fn low_quality_mismatches_do_not_count() {
+        let reads = tmp_path("reads.fq");
+        // read1 mismatches the primer at its 3rd base (T vs G), which has quality 2 ('#').
+        std::fs::write(
+            &reads,
+            "@read1 lane=1\nAAACTTTACC\n+\nIIIII#IIII\n@read2\nAAACTGTACC\n+read2\nIIIIIIIIII\n",
+        )
+        .expect("write reads");
+        let primers = vec![Primer::from_name_and_sequence("p1", "CTGTA").expect("primer")];
+        let scan = |min_base_quality| {
+            let options = ScanOptions {
+                min_base_quality,
+                scan_reverse_complement: false,
+                ..ScanOptions::default()
+            };
+            scan_references(std::slice::from_ref(&reads), &primers, &options).expect("scan")
+        };
+        let hits = |result: crate::ScanResult| {
+            result
+                .hits
+                .into_iter()
+                .map(|hit| (hit.contig, hit.start, hit.mismatches))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(hits(scan(None)), vec![("read2".to_string(), 3, 0)]);
+        assert_eq!(
+            hits(scan(Some(20))),
+            vec![("read1".to_string(), 3, 0), ("read2".to_string(), 3, 0)]
+        );
+
+        std::fs::write(&reads, "@read1\nACGT\n+\nIII\n").expect("write reads");
+        let err = scan_references(
+            std::slice::from_ref(&reads),
+            &primers,
+            &ScanOptions::default(),
+        )
+        .expect_err("quality length mismatch");
+        assert!(err.to_string().contains("3 quality values"), "{err}");
+
+        std::fs::remove_file(reads).expect("remove reads");
+    }
+}
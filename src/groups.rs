@@ -0,0 +1,163 @@
+use anyhow::{Context, Result, bail};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::io::BufRead;
+use std::path::Path;
+
+use crate::{Hit, PrimerSummary, open_reader};
+
+/// Named set of primers (e.g. every primer targeting one gene) reported together.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrimerGroup {
+    pub name: String,
+    pub primers: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GroupSummary {
+    pub group: String,
+    pub total_hits: u64,
+    pub primers_with_hits: u64,
+    /// Distinct (file, contig) pairs hit by any primer of the group.
+    pub contigs_with_hits: u64,
+}
+
+/// Summary rows of one `--group-by` group, in summary order, and their subtotal.
+#[derive(Debug, Clone, Serialize)]
+pub struct GroupedSummary {
+    pub group: String,
+    pub rows: Vec<PrimerSummary>,
+    pub subtotal: SummarySubtotal,
+}
+
+/// The additive [`PrimerSummary`] counts summed over a group's rows.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct SummarySubtotal {
+    pub primers: u64,
+    pub total_hits: u64,
+    pub perfect_hits: u64,
+    pub forward_hits: u64,
+    pub reverse_hits: u64,
+    /// `total_hits / primers`.
+    pub hits_per_primer: f64,
+}
+
+/// Splits `summary` into groups named by `group_of`, keeping the order of first appearance.
+pub fn grouped_summary(
+    summary: &[PrimerSummary],
+    group_of: impl Fn(&PrimerSummary) -> String,
+) -> Vec<GroupedSummary> {
+    let mut groups: Vec<GroupedSummary> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+    for row in summary {
+        let group = group_of(row);
+        let slot = *index.entry(group.clone()).or_insert_with(|| {
+            groups.push(GroupedSummary {
+                group,
+                rows: Vec::new(),
+                subtotal: SummarySubtotal::default(),
+            });
+            groups.len() - 1
+        });
+        let grouped = &mut groups[slot];
+        grouped.subtotal.primers += 1;
+        grouped.subtotal.total_hits += row.total_hits;
+        grouped.subtotal.perfect_hits += row.perfect_hits;
+        grouped.subtotal.forward_hits += row.forward_hits;
+        grouped.subtotal.reverse_hits += row.reverse_hits;
+        grouped.rows.push(row.clone());
+    }
+    for grouped in &mut groups {
+        grouped.subtotal.hits_per_primer =
+            grouped.subtotal.total_hits as f64 / grouped.subtotal.primers as f64;
+    }
+    groups
+}
+
+/// 5 bp length class of a primer, e.g. `20-24` for a 22-mer.
+pub fn length_class(len: usize) -> String {
+    let low = len / 5 * 5;
+    format!("{low}-{}", low + 4)
+}
+
+/// Reads a `primer<tab>group` TSV. An optional `primer<tab>group` header, blank lines, and `#`
+/// comments are skipped. Groups keep the order of their first appearance.
+pub fn load_primer_groups(path: &Path) -> Result<Vec<PrimerGroup>> {
+    let reader = open_reader(path)?;
+    let mut groups: Vec<PrimerGroup> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+
+    for (line_idx, line) in reader.lines().enumerate() {
+        let line =
+            line.with_context(|| format!("failed reading groups file '{}'", path.display()))?;
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let Some((primer, group)) = trimmed.split_once('\t') else {
+            bail!(
+                "groups line {} in '{}' must be primer<tab>group",
+                line_idx + 1,
+                path.display()
+            );
+        };
+        let (primer, group) = (primer.trim(), group.trim());
+        if line_idx == 0
+            && primer.eq_ignore_ascii_case("primer")
+            && group.eq_ignore_ascii_case("group")
+        {
+            continue;
+        }
+        if primer.is_empty() || group.is_empty() {
+            bail!(
+                "groups line {} in '{}' has an empty primer or group name",
+                line_idx + 1,
+                path.display()
+            );
+        }
+
+        let slot = *index.entry(group.to_string()).or_insert_with(|| {
+            groups.push(PrimerGroup {
+                name: group.to_string(),
+                primers: Vec::new(),
+            });
+            groups.len() - 1
+        });
+        groups[slot].primers.push(primer.to_string());
+    }
+    Ok(groups)
+}
+
+/// Group-level totals derived from the per-primer summary and the hit rows.
+pub(crate) fn summarize_groups(
+    groups: &[PrimerGroup],
+    summary: &[PrimerSummary],
+    hits: &[Hit],
+) -> Vec<GroupSummary> {
+    let by_primer: HashMap<&str, &PrimerSummary> = summary
+        .iter()
+        .map(|row| (row.primer.as_str(), row))
+        .collect();
+
+    groups
+        .iter()
+        .map(|group| {
+            let members: HashSet<&str> = group.primers.iter().map(String::as_str).collect();
+            let rows = members
+                .iter()
+                .filter_map(|primer| by_primer.get(primer))
+                .collect::<Vec<_>>();
+            let contigs = hits
+                .iter()
+                .filter(|hit| members.contains(hit.primer.as_str()))
+                .map(|hit| (hit.file.as_str(), hit.contig.as_str()))
+                .collect::<HashSet<_>>();
+            GroupSummary {
+                group: group.name.clone(),
+                total_hits: rows.iter().map(|row| row.total_hits).sum(),
+                primers_with_hits: rows.iter().filter(|row| row.total_hits > 0).count() as u64,
+                contigs_with_hits: contigs.len() as u64,
+            }
+        })
+        .collect()
+}
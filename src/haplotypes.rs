@@ -0,0 +1,219 @@
+use anyhow::{Context, Result, bail};
+use regex::Regex;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashSet};
+use std::fmt;
+
+use crate::{ContigLength, Hit, Primer};
+
+/// Which of a sample's two haplotypes a primer binds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Phase {
+    Both,
+    Hap1Only,
+    Hap2Only,
+    None,
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Both => "both",
+            Self::Hap1Only => "hap1_only",
+            Self::Hap2Only => "hap2_only",
+            Self::None => "none",
+        })
+    }
+}
+
+/// One primer in one sample.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HaplotypeCall {
+    pub primer: String,
+    pub sample: String,
+    pub phase: Phase,
+}
+
+/// Contigs of one sample, split into its two haplotypes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Sample {
+    name: String,
+    /// `(file, contig)` of haplotype 1 and haplotype 2.
+    haplotypes: [HashSet<(String, String)>; 2],
+}
+
+/// Groups `contigs` into samples by the `sample` named capture of `pattern`. Within a sample,
+/// the `hap` capture (the whole contig name when the pattern has none) names the haplotype,
+/// and the two haplotype names in sorted order are haplotype 1 and 2, so `sample1_hap1` and
+/// `sample1_hap2` come out as expected. Contigs the pattern does not match are ignored; a
+/// matched sample without exactly two haplotypes is an error.
+fn group_samples(contigs: &[ContigLength], pattern: &str) -> Result<Vec<Sample>> {
+    let regex =
+        Regex::new(pattern).with_context(|| format!("invalid haplotype regex '{pattern}'"))?;
+    if !regex.capture_names().any(|name| name == Some("sample")) {
+        bail!("haplotype regex '{pattern}' has no (?P<sample>...) capture");
+    }
+
+    let mut order: Vec<String> = Vec::new();
+    let mut members: Vec<Vec<(String, &ContigLength)>> = Vec::new();
+    for contig in contigs {
+        let Some(captures) = regex.captures(&contig.contig) else {
+            continue;
+        };
+        let sample = captures["sample"].to_string();
+        let haplotype = captures
+            .name("hap")
+            .map_or(contig.contig.as_str(), |hap| hap.as_str())
+            .to_string();
+        let slot = match order.iter().position(|name| *name == sample) {
+            Some(slot) => slot,
+            None => {
+                order.push(sample);
+                members.push(Vec::new());
+                order.len() - 1
+            }
+        };
+        members[slot].push((haplotype, contig));
+    }
+
+    order
+        .into_iter()
+        .zip(members)
+        .map(|(name, members)| {
+            let labels = members
+                .iter()
+                .map(|(haplotype, _)| haplotype.as_str())
+                .collect::<BTreeSet<_>>();
+            let labels = labels.into_iter().collect::<Vec<_>>();
+            if labels.len() != 2 {
+                bail!(
+                    "sample '{name}' has {} haplotype(s) ({}); --haplotype-regex expects two \
+                     per sample",
+                    labels.len(),
+                    labels.join(", ")
+                );
+            }
+            let mut haplotypes = [HashSet::new(), HashSet::new()];
+            for (haplotype, contig) in &members {
+                let idx = usize::from(haplotype.as_str() == labels[1]);
+                haplotypes[idx].insert((contig.file.clone(), contig.contig.clone()));
+            }
+            Ok(Sample { name, haplotypes })
+        })
+        .collect()
+}
+
+/// Per primer (panel order) and sample (order of first contig), whether the primer has any
+/// hit on each of the sample's haplotypes; see `group_samples` for how `pattern` forms them.
+pub fn haplotype_calls(
+    primers: &[Primer],
+    contigs: &[ContigLength],
+    hits: &[Hit],
+    pattern: &str,
+) -> Result<Vec<HaplotypeCall>> {
+    let samples = group_samples(contigs, pattern)?;
+    let hit_contigs = hits
+        .iter()
+        .map(|hit| (hit.primer.as_str(), hit.file.as_str(), hit.contig.as_str()))
+        .collect::<HashSet<_>>();
+    Ok(primers
+        .iter()
+        .flat_map(|primer| {
+            let hit_contigs = &hit_contigs;
+            samples.iter().map(move |sample| {
+                let [hap1, hap2] = sample.haplotypes.each_ref().map(|haplotype| {
+                    haplotype.iter().any(|(file, contig)| {
+                        hit_contigs.contains(&(
+                            primer.name.as_str(),
+                            file.as_str(),
+                            contig.as_str(),
+                        ))
+                    })
+                });
+                HaplotypeCall {
+                    primer: primer.name.clone(),
+                    sample: sample.name.clone(),
+                    phase: match (hap1, hap2) {
+                        (true, true) => Phase::Both,
+                        (true, false) => Phase::Hap1Only,
+                        (false, true) => Phase::Hap2Only,
+                        (false, false) => Phase::None,
+                    },
+                }
+            })
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::tmp_path;
+    use crate::{ScanOptions, scan_references};
+
+    #[test]
+    fn haplotype_calls_roll_up_contig_hits_per_sample() {
+        let path = tmp_path("haplotypes.fa");
+        let flank = "T".repeat(10);
+        let site = "ACGTTGCAAC";
+        // sample1 is heterozygous at the primer's 3rd base; sample2 has the site on both
+        // haplotypes, sample3 on neither; unmatched contigs are ignored.
+        let het = "ACCTTGCAAC";
+        std::fs::write(
+            &path,
+            format!(
+                ">sample1_hap1\n{flank}{site}{flank}\n>sample1_hap2\n{flank}{het}{flank}\n\
+                 >sample2_hap1\n{flank}{site}{flank}\n>sample2_hap2\n{flank}{site}{flank}\n\
+                 >sample3_hap2\n{flank}{flank}\n>sample3_hap1\n{flank}{flank}\n\
+                 >chrUn\n{flank}{site}{flank}\n"
+            ),
+        )
+        .expect("write reference");
+        let primers = vec![Primer::from_name_and_sequence("p1", site).expect("primer")];
+        let pattern = "(?P<sample>.+)_hap[12]";
+        let phases = |max_mismatches| {
+            let options = ScanOptions {
+                max_mismatches,
+                ..ScanOptions::default()
+            };
+            let result =
+                scan_references(std::slice::from_ref(&path), &primers, &options).expect("scan");
+            haplotype_calls(&primers, &result.contigs, &result.hits, pattern)
+                .expect("calls")
+                .into_iter()
+                .map(|call| (call.sample, call.phase))
+                .collect::<Vec<_>>()
+        };
+
+        let sample = |name: &str, phase| (name.to_string(), phase);
+        assert_eq!(
+            phases(0),
+            [
+                sample("sample1", Phase::Hap1Only),
+                sample("sample2", Phase::Both),
+                sample("sample3", Phase::None),
+            ]
+        );
+        assert_eq!(phases(1)[0], sample("sample1", Phase::Both));
+
+        let result = scan_references(
+            std::slice::from_ref(&path),
+            &primers,
+            &ScanOptions::default(),
+        )
+        .expect("scan");
+        let err = haplotype_calls(
+            &primers,
+            &result.contigs,
+            &result.hits,
+            "(?P<sample>sample2)_hap1",
+        )
+        .expect_err("one haplotype per sample");
+        assert!(err.to_string().contains("expects two"), "{err}");
+        let err = haplotype_calls(&primers, &result.contigs, &result.hits, ".+_hap[12]")
+            .expect_err("no sample capture");
+        assert!(err.to_string().contains("(?P<sample>"), "{err}");
+        std::fs::remove_file(path).expect("remove reference");
+    }
+}
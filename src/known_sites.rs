@@ -0,0 +1,100 @@
+use anyhow::Result;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::Path;
+
+use crate::Hit;
+use crate::regions::{merge_intervals, read_bed_records};
+
+/// Expected off-target sites (e.g. known pseudogene hits) per contig and primer, whose hits
+/// are suppressed from the output.
+#[derive(Debug, Clone, Default)]
+pub struct KnownSites {
+    /// Merged site intervals by contig, then primer name, sorted by start.
+    by_contig: HashMap<String, HashMap<String, Vec<Range<usize>>>>,
+}
+
+impl KnownSites {
+    /// Builds a set from `(contig, start, end, primer)` sites; overlapping ones are merged.
+    pub fn from_sites<I, S>(sites: I) -> Self
+    where
+        I: IntoIterator<Item = (S, usize, usize, S)>,
+        S: Into<String>,
+    {
+        let mut by_contig: HashMap<String, HashMap<String, Vec<Range<usize>>>> = HashMap::new();
+        for (contig, start, end, primer) in sites {
+            by_contig
+                .entry(contig.into())
+                .or_default()
+                .entry(primer.into())
+                .or_default()
+                .push(start..end);
+        }
+        for ranges in by_contig.values_mut().flat_map(HashMap::values_mut) {
+            *ranges = merge_intervals(std::mem::take(ranges));
+        }
+        Self { by_contig }
+    }
+
+    /// True when `hit` overlaps a known site listed for its contig and primer.
+    pub fn contains(&self, hit: &Hit) -> bool {
+        let Some(ranges) = self
+            .by_contig
+            .get(&hit.contig)
+            .and_then(|by_primer| by_primer.get(&hit.primer))
+        else {
+            return false;
+        };
+        let idx = ranges.partition_point(|range| range.end <= hit.start);
+        ranges.get(idx).is_some_and(|range| range.start < hit.end)
+    }
+}
+
+/// Reads known sites from a BED file whose `name` column is the primer name.
+pub fn load_known_sites(path: &Path) -> Result<KnownSites> {
+    let records = read_bed_records(path, true)?;
+    Ok(KnownSites::from_sites(records.into_iter().map(|record| {
+        (
+            record.contig,
+            record.start,
+            record.end,
+            record.name.unwrap_or_default(),
+        )
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Primer, ScanOptions, scan_sequence};
+
+    #[test]
+    fn known_site_is_suppressed_and_novel_site_remains() {
+        // p1 binds at 5 (known pseudogene site) and at 30 (novel); p2 binds at 49.
+        let sequence = "AAAAAGATTACAAAAAAAAAAAAAAAAAAAGATTACAAAAAAAAAAAAACCGGTTAAAAA";
+        let primers = vec![
+            Primer::from_name_and_sequence("p1", "GATTACA").expect("primer"),
+            Primer::from_name_and_sequence("p2", "CCGGTT").expect("primer"),
+        ];
+        // The p2 interval covers p1's novel site but lists another primer.
+        let known = KnownSites::from_sites([("chr1", 8, 9, "p1"), ("chr1", 25, 40, "p2")]);
+        let options = ScanOptions {
+            scan_reverse_complement: false,
+            known_sites: Some(known),
+            ..ScanOptions::default()
+        };
+
+        let result = scan_sequence(sequence, "chr1", &primers, &options).expect("scan");
+        let hits = result
+            .hits
+            .iter()
+            .map(|hit| (hit.primer.as_str(), hit.start))
+            .collect::<Vec<_>>();
+        assert_eq!(hits, vec![("p1", 30), ("p2", 49)]);
+        assert_eq!(result.total_hits, 2);
+        let p1 = &result.summary[0];
+        assert_eq!((p1.total_hits, p1.known_suppressed), (1, 1));
+        assert_eq!(p1.first_hit_position.as_ref().map(|pos| pos.2), Some(30));
+        assert_eq!(result.summary[1].known_suppressed, 0);
+    }
+}
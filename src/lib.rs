@@ -1,29 +1,97 @@
 use anyhow::{Context, Result, bail};
 use flate2::read::MultiGzDecoder;
 use rayon::prelude::*;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::env;
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::{BufRead, BufReader, Read, Write};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
 
+use aliases::ContigAliases;
+use alignment::GapMap;
+use contig::{ContigBuffer, ContigMasks, DenseContig, PackedContig, RawContig};
+use groups::{GroupSummary, PrimerGroup};
+use known_sites::KnownSites;
+pub use primer_scout_core as core;
+pub use primer_scout_core::SequenceNormalization;
+pub use primer_scout_core::mask_to_iupac;
+use primer_scout_core::{
+    PrimerMasks, forward_mismatches, normalize_sequence, to_masks, window_mismatches,
+};
+pub(crate) use primer_scout_core::{iupac_from_mask, iupac_mask, normalize_base};
+use regions::{NamedRegions, RegionSet, load_named_bed};
+use spill::{HitCollector, HitStream, SpilledHits};
+use transcripts::{TranscriptRegion, TranscriptSet};
+use warnings::WarningLog;
+
+pub mod aliases;
+mod alignment;
+pub mod amplicons;
+pub mod assay;
+#[cfg(feature = "bincode")]
+pub mod binary_hits;
 pub mod cli;
+#[cfg(feature = "bincode")]
+pub mod compiled_panel;
 pub mod console;
+mod contig;
+pub mod demo;
+pub mod explain;
+pub mod extension;
+pub mod extract;
+mod fastq;
+pub mod groups;
+pub mod haplotypes;
+pub mod known_sites;
+mod pairing;
+pub mod panel;
+mod prescreen;
+pub mod profile;
+pub mod regions;
+pub mod sink;
+pub mod spill;
 pub mod splash;
+#[cfg(test)]
+mod test_support;
+pub mod transcripts;
+mod tuning;
 pub mod update;
+pub mod warnings;
 
 const DEFAULT_MAX_PRIMER_FILE_BYTES: usize = 16 * 1024 * 1024;
 const DEFAULT_MAX_PRIMER_LINE_BYTES: usize = 32 * 1024;
 const DEFAULT_MAX_FASTA_LINE_BYTES: usize = 8 * 1024 * 1024;
 const DEFAULT_MAX_CONTIG_BASES: usize = 250_000_000;
+const FASTA_READ_CHUNK_BYTES: usize = 64 * 1024;
+// Window starts scanned between checks of `ScanOptions::cancel` and the per-primer deadline.
+const CANCEL_CHECK_INTERVAL: usize = 4096;
+// Window starts per block kept or skipped together by `ScanOptions::sample_rate`.
+const SAMPLE_BLOCK_WINDOWS: usize = 1024;
+pub const DEFAULT_MAX_AMPLICON_SIZE: usize = 2000;
+pub const DEFAULT_PARTNER_WINDOW: usize = 5000;
+pub const DEFAULT_SEED: u64 = 42;
+pub const DEFAULT_FASTA_LINE_WIDTH: usize = 80;
 
 #[derive(Debug, Clone)]
 pub struct Primer {
     pub name: String,
     pub sequence: String,
     pub reverse_complement: String,
+    /// The sequence as given, when normalization changed it (see `normalization`).
+    pub original_sequence: Option<String>,
+    pub normalization: SequenceNormalization,
+    /// Pool from the panel's `pool` column (delimited panels whose header names one) or
+    /// JSONL `pool` field; `None` when the panel has none or the value is empty.
+    pub pool: Option<String>,
     masks: Vec<u8>,
-    reverse_masks: Vec<u8>,
+    /// Forward mask in the low nibble and reverse-complement mask in the high nibble, so one
+    /// pass over the reference tests both strands.
+    combined_masks: Vec<u8>,
     is_palindromic: bool,
 }
 
@@ -37,30 +105,345 @@ impl Primer {
     }
 
     pub fn from_name_and_sequence(name: impl Into<String>, sequence: &str) -> Result<Self> {
-        let normalized = normalize_query(sequence)?;
-        if normalized.is_empty() {
-            bail!("primer sequence must not be empty");
-        }
-
-        let reverse_complement = reverse_complement(&normalized)?;
-        let masks = to_masks(&normalized)?;
-        let reverse_masks = to_masks(&reverse_complement)?;
-
+        let core = PrimerMasks::from_sequence(sequence)?;
         Ok(Self {
             name: name.into(),
-            sequence: normalized.clone(),
-            reverse_complement: reverse_complement.clone(),
-            masks,
-            reverse_masks,
-            is_palindromic: normalized == reverse_complement,
+            sequence: core.sequence,
+            reverse_complement: core.reverse_complement,
+            original_sequence: core.normalization.changed().then(|| sequence.to_string()),
+            normalization: core.normalization,
+            pool: None,
+            masks: core.masks,
+            combined_masks: core.combined_masks,
+            is_palindromic: core.is_palindromic,
+        })
+    }
+
+    /// Every position allows exactly one base (no IUPAC ambiguity codes).
+    pub fn is_exact(&self) -> bool {
+        self.masks.iter().all(|mask| mask.count_ones() == 1)
+    }
+
+    /// Number of concrete sequences the primer stands for: the product of the bases each
+    /// IUPAC code allows (`N` = 4, `R` = 2, ...), saturating at `u64::MAX`.
+    pub fn degeneracy(&self) -> u64 {
+        self.masks.iter().fold(1u64, |product, mask| {
+            product.saturating_mul(u64::from(mask.count_ones()))
         })
     }
 }
 
+/// Default `--max-degeneracy`: generous for real degenerate primers, but catches runs of `N`.
+pub const DEFAULT_MAX_DEGENERACY: u64 = 4096;
+
+/// Default `--min-primer-complexity`: below a trinucleotide repeat (`0.79`), above a
+/// dinucleotide repeat (`0.5`).
+pub const DEFAULT_MIN_PRIMER_COMPLEXITY: f64 = 0.6;
+
+/// Panel-wide expectation about RNA (`U`) versus DNA (`T`) primer input.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RnaPolicy {
+    /// Read `U` as `T` without complaint.
+    #[default]
+    Accept,
+    /// Reject primers containing `U`.
+    Reject,
+    /// Reject primers containing `T`, i.e. DNA pasted into an RNA panel.
+    Expect,
+}
+
+/// Errors on the first primer whose input violates `policy`.
+pub fn check_rna_policy(primers: &[Primer], policy: RnaPolicy) -> Result<()> {
+    for primer in primers {
+        let input = primer
+            .original_sequence
+            .as_deref()
+            .unwrap_or(&primer.sequence);
+        match policy {
+            RnaPolicy::Accept => {}
+            RnaPolicy::Reject if primer.normalization.uracils_converted > 0 => {
+                bail!(
+                    "primer '{}' contains U (RNA input '{input}'); expected DNA oligos",
+                    primer.name
+                );
+            }
+            RnaPolicy::Expect if input.contains(['T', 't']) => {
+                bail!(
+                    "primer '{}' contains T (DNA input '{input}'); expected RNA oligos",
+                    primer.name
+                );
+            }
+            RnaPolicy::Reject | RnaPolicy::Expect => {}
+        }
+    }
+    Ok(())
+}
+
+/// Errors on the first primer whose [`Primer::degeneracy`] exceeds `max_degeneracy`; such
+/// primers match nearly everywhere and are almost always data-entry errors.
+pub fn check_degeneracy(primers: &[Primer], max_degeneracy: u64) -> Result<()> {
+    for (idx, primer) in primers.iter().enumerate() {
+        let degeneracy = primer.degeneracy();
+        if degeneracy > max_degeneracy {
+            bail!(
+                "primer '{}' (primer {} of the panel, '{}') has degeneracy {degeneracy}, above \
+                 the limit of {max_degeneracy}",
+                primer.name,
+                idx + 1,
+                primer.sequence
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Index and [`sequence_complexity`] of every primer scoring below `min_complexity`.
+/// Homopolymer and dinucleotide-repeat primers bind almost everywhere and are design errors.
+pub fn low_complexity_primers(primers: &[Primer], min_complexity: f64) -> Vec<(usize, f64)> {
+    primers
+        .iter()
+        .enumerate()
+        .map(|(idx, primer)| (idx, sequence_complexity(&primer.sequence)))
+        .filter(|&(_, complexity)| complexity < min_complexity)
+        .collect()
+}
+
+/// Splits the panel into exact primers ([`Primer::is_exact`]) and degenerate ones, each in
+/// panel order.
+pub fn partition_exact_primers(primers: Vec<Primer>) -> (Vec<Primer>, Vec<Primer>) {
+    primers.into_iter().partition(Primer::is_exact)
+}
+
+/// Degenerate primer covering every one of `seqs`, which must be aligned to equal length:
+/// each column becomes the IUPAC code for the bases observed in it, so a column of `A` and
+/// `G` gives `R`. Inputs are normalized like primers, and an IUPAC code in an input adds every
+/// base it allows.
+pub fn consensus_primer(seqs: &[&str]) -> Result<String> {
+    let mut columns: Option<Vec<u8>> = None;
+    for (idx, seq) in seqs.iter().enumerate() {
+        let (normalized, _) = normalize_sequence(seq)
+            .with_context(|| format!("invalid sequence {} of the alignment", idx + 1))?;
+        let masks = to_masks(&normalized)
+            .with_context(|| format!("invalid sequence {} of the alignment", idx + 1))?;
+        match &mut columns {
+            None => columns = Some(masks),
+            Some(columns) if columns.len() != masks.len() => bail!(
+                "sequence {} of the alignment has {} bases, but sequence 1 has {}; consensus \
+                 needs aligned sequences of equal length",
+                idx + 1,
+                masks.len(),
+                columns.len()
+            ),
+            Some(columns) => {
+                for (column, mask) in columns.iter_mut().zip(masks) {
+                    *column |= mask;
+                }
+            }
+        }
+    }
+    let columns = columns.context("consensus needs at least one sequence")?;
+    Ok(columns
+        .into_iter()
+        .map(|mask| char::from(iupac_from_mask(mask)))
+        .collect())
+}
+
+/// How reference FASTA deviations from the IUPAC alphabet are handled.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FastaStrictness {
+    /// Read non-IUPAC sequence characters as `N`, counting them per contig
+    /// ([`ContigLength::coerced_bases`]) and warning; nameless headers become `unknown_contig`.
+    #[default]
+    Lenient,
+    /// Reject non-IUPAC sequence characters (with file, line, and column) and nameless headers.
+    Strict,
+}
+
+/// How [`ScanOptions::overhang`] scores primer bases that hang off a contig end.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OverhangScoring {
+    /// Each off-contig base counts as a mismatch, so at most `max_mismatches` bases hang off.
+    #[default]
+    Mismatch,
+    /// Off-contig bases are not scored; at least half of the primer must lie on the contig.
+    Exclude,
+}
+
+/// How each contig is searched for primer windows.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ScanAlgorithm {
+    /// Test every window of every primer.
+    #[default]
+    Exhaustive,
+    /// Look up the contig's 12-mers in a Bloom filter of primer seeds first and test only the
+    /// windows a seed points at. Pays off for panels of thousands of primers; primers too
+    /// short or degenerate to seed (fewer than 12 concrete bases per `max_mismatches + 1`
+    /// segment) fall back to the exhaustive scan. Same hits either way.
+    Bloom,
+}
+
+/// How contig sequence is held in memory while scanning.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MemoryProfile {
+    /// Normalized bases plus one mask byte per base (~2 bytes/base).
+    #[default]
+    Fast,
+    /// 4-bit masks packed two per byte (~0.5 bytes/base); slower inner loop.
+    Lean,
+}
+
+/// How reference paths are written in hits, file statuses, and run manifests.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PathStyle {
+    /// As given, with this platform's separators (`\` on Windows).
+    #[default]
+    Native,
+    /// `/` separators on every platform, so outputs match across systems.
+    Forward,
+}
+
+impl PathStyle {
+    /// `path` as this style writes it.
+    pub fn render(self, path: &Path) -> String {
+        self.render_with_separator(&path.display().to_string(), std::path::MAIN_SEPARATOR)
+    }
+
+    fn render_with_separator(self, rendered: &str, separator: char) -> String {
+        match self {
+            Self::Forward if separator != '/' => rendered.replace(separator, "/"),
+            _ => rendered.to_string(),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ScanOptions {
     pub max_mismatches: usize,
     pub scan_reverse_complement: bool,
+    /// Forward/reverse name suffixes (e.g. `("_F", "_R")`) identifying primer pairs.
+    pub pair_suffix_pattern: Option<(String, String)>,
+    /// Largest amplicon, in bp, reported when pairing forward and reverse hits.
+    pub max_amplicon_size: usize,
+    /// Largest distance, in bp, reported for the nearest partner-primer hit.
+    pub partner_window: usize,
+    /// Drop hits without a converging partner hit ([`Hit::amplifiable`]), taking them back
+    /// out of the summaries. Needs `pair_suffix_pattern`.
+    pub only_amplifiable: bool,
+    /// Seed for every sampling or randomized step; same seed + same inputs gives identical output.
+    pub seed: u64,
+    /// Contig layout while scanning: [`MemoryProfile::Lean`] trades scan speed for a quarter
+    /// of the memory. Hits are the same, except lean reports non-IUPAC reference bytes as `N`
+    /// in [`Hit::matched`].
+    pub memory_profile: MemoryProfile,
+    pub algorithm: ScanAlgorithm,
+    /// Count soft-masked (lowercase) reference bases as guaranteed mismatches.
+    pub lowercase_mismatches: bool,
+    /// Drop hits whose matched sequence falls below this [`sequence_complexity`].
+    pub min_hit_complexity: Option<f64>,
+    /// Drop hits with more reference positions than this that are not a concrete A/C/G/T
+    /// (see [`Hit::ambiguous_positions`]).
+    pub max_ambiguous_positions: Option<usize>,
+    /// Copy the primer sequence (and reverse complement for `-` hits) into each hit.
+    pub include_primer_sequence: bool,
+    /// Merge `+`/`-` hits of one primer at identical coordinates into a single `=` hit.
+    pub collapse_strand_duplicates: bool,
+    /// Sort hits by (file, contig, primer, start, strand, mismatches) before returning.
+    /// When `false`, hits keep discovery order: implementation-defined, but typically
+    /// position-ordered per contig and primer.
+    pub sort_output: bool,
+    /// Shared, rate-limited sink for scan warnings.
+    pub warnings: Arc<WarningLog>,
+    /// Restrict scanning to these intervals; hits must lie entirely inside one of them.
+    /// Contigs without intervals are skipped. Coordinates stay absolute.
+    pub regions: Option<RegionSet>,
+    /// Stops the scan early when cancelled; see [`CancelToken`].
+    pub cancel: CancelToken,
+    /// Primer groups (e.g. per target gene) summarized in [`ScanResult::group_summaries`].
+    pub groups: Vec<PrimerGroup>,
+    /// Before [`scan_references`], time a short calibration scan at 1, 2, 4, ... threads and
+    /// run the real scan with the fastest count (reported in [`ScanResult::auto_tuned_threads`]).
+    pub auto_tune_threads: bool,
+    /// Run every scan on a dedicated pool of this many rayon workers (`0` picks rayon's
+    /// default count), whatever pool the caller is in; the caller's thread blocks until the
+    /// scan is done. [`Scanner::new`] builds the pool once and every scan of that scanner
+    /// shares it. `None` runs on the caller's pool: the one entered with
+    /// `ThreadPool::install`, otherwise rayon's global pool. Ignored when `auto_tune_threads`
+    /// is set, which picks its own count.
+    pub threads: Option<usize>,
+    /// Label stamped on every hit and summary row ([`Hit::tag`], [`PrimerSummary::tag`]), so
+    /// the passes of a multi-pass scan stay apart after [`ScanResult::merge`]. Part of the
+    /// [`ScanResult::hit_hash`] identity unless `hash_ignores_tag` is set.
+    pub tag: Option<String>,
+    /// Leave [`Hit::tag`] out of [`ScanResult::hit_hash`], so tagged and untagged runs of the
+    /// same scan hash alike.
+    pub hash_ignores_tag: bool,
+    /// Predict whether each hit's primer would extend from its 3' end ([`Hit::extendable`]).
+    pub predict_extension: bool,
+    /// Drop hits not predicted extendable, taking them back out of the summaries. Needs
+    /// `predict_extension`.
+    pub extendable_only: bool,
+    /// When sorting, spill a sorted run to a temp file whenever more than this many hits are
+    /// held in memory; read the merged result back with [`ScanResult::hit_stream`].
+    /// `None` keeps every hit in memory.
+    pub spill_threshold: Option<usize>,
+    /// Compute [`Hit::gc_skew`] for every hit.
+    pub include_gc_skew: bool,
+    pub fasta_strictness: FastaStrictness,
+    /// Remove alignment gap characters (`-`, `.`) while loading contigs, so hits, regions,
+    /// and contig lengths use ungapped coordinates.
+    pub degap: bool,
+    /// With `degap`, report hit coordinates (and first/last hit positions) as columns of the
+    /// original alignment. Regions and contig lengths stay ungapped, so it cannot be combined
+    /// with the options that relate hits to them or to each other (`pair_suffix_pattern`,
+    /// `compute_genome_coverage`).
+    pub keep_alignment_coords: bool,
+    /// For FASTQ references, read bases with a Phred quality below this as `N`, so they never
+    /// count as mismatches.
+    pub min_base_quality: Option<u8>,
+    /// Exon structures used to add transcript coordinates to each hit.
+    pub transcripts: Option<TranscriptSet>,
+    /// When two primers hit the same site and one is a strict IUPAC instance of the other
+    /// (every sequence in its [`iupac_expand`] is in the other's, e.g. `AAATGG` of `RAATGG`),
+    /// drop the specific primer's hit and keep the degenerate one. Summaries count only the
+    /// kept hits.
+    pub deduplicate_iupac_equivalent_hits: bool,
+    /// Fill [`ScanResult::genome_coverage_fraction`].
+    pub compute_genome_coverage: bool,
+    /// Compute [`Hit::contig_relative_position`] for every hit.
+    pub include_relative_position: bool,
+    /// Fill [`Hit::masked_sequence`] for every hit.
+    pub include_masked_sequence: bool,
+    /// Expected off-target sites: overlapping hits of the listed primer are dropped and
+    /// counted in [`PrimerSummary::known_suppressed`] instead.
+    pub known_sites: Option<KnownSites>,
+    /// Wall-clock budget of each primer on each contig. A primer that exceeds it stops
+    /// scanning that contig, keeps the hits found so far, and is marked
+    /// [`PrimerSummary::timed_out`]; its counts are then incomplete.
+    pub time_limit_per_primer: Option<Duration>,
+    /// Scan only a random subset of each contig: blocks of 1,024 window starts are each kept
+    /// with this probability, drawn per contig from [`ScanOptions::seed`] so every primer
+    /// sees the same blocks. Dividing hit counts by the rate estimates the full scan's; see
+    /// [`poisson_confidence_interval`].
+    pub sample_rate: Option<f64>,
+    /// Also test windows that hang off either contig end (primers binding across an assembly
+    /// break), scoring the off-contig bases as given. Such hits cover only the on-contig
+    /// bases and report the rest in [`Hit::overhang`]. Not combined with `sample_rate`.
+    pub overhang: Option<OverhangScoring>,
+    /// Repeat library BED (`chrom start end name`, e.g. SINE/LINE/satellite annotations) read
+    /// by [`Scanner::new`]; hits overlapping a repeat get its name in [`Hit::repeat_class`].
+    pub repeat_library_bed: Option<PathBuf>,
+    /// After sorting, drop hit records equal in every field to another one, taking them back
+    /// out of the counts. A guard for overlapping scans; a correct scan has none.
+    pub dedup_hits: bool,
+    /// Rewrite contig names in hits, contig lengths, and summaries with
+    /// [`sanitize_contig_name`], keeping the original in [`Hit::original_contig`].
+    pub sanitize_contig_names: bool,
+    /// Rename contigs of reference FASTA files to their canonical names as headers are read,
+    /// so hits, regions, and summaries all use them. Contigs the table does not list keep
+    /// their name and raise an `unaliased_contig` warning.
+    pub contig_aliases: Option<ContigAliases>,
+    /// How reference paths are written in [`Hit::file`] and [`FileStatus::file`].
+    pub path_style: PathStyle,
 }
 
 impl Default for ScanOptions {
@@ -68,21 +451,312 @@ impl Default for ScanOptions {
         Self {
             max_mismatches: 0,
             scan_reverse_complement: true,
+            pair_suffix_pattern: None,
+            max_amplicon_size: DEFAULT_MAX_AMPLICON_SIZE,
+            partner_window: DEFAULT_PARTNER_WINDOW,
+            only_amplifiable: false,
+            seed: DEFAULT_SEED,
+            memory_profile: MemoryProfile::Fast,
+            algorithm: ScanAlgorithm::Exhaustive,
+            lowercase_mismatches: false,
+            min_hit_complexity: None,
+            max_ambiguous_positions: None,
+            include_primer_sequence: false,
+            collapse_strand_duplicates: false,
+            sort_output: true,
+            warnings: Arc::new(WarningLog::default()),
+            regions: None,
+            cancel: CancelToken::new(),
+            groups: Vec::new(),
+            auto_tune_threads: false,
+            threads: None,
+            tag: None,
+            hash_ignores_tag: false,
+            predict_extension: false,
+            extendable_only: false,
+            spill_threshold: None,
+            include_gc_skew: false,
+            fasta_strictness: FastaStrictness::Lenient,
+            degap: false,
+            keep_alignment_coords: false,
+            min_base_quality: None,
+            transcripts: None,
+            deduplicate_iupac_equivalent_hits: false,
+            compute_genome_coverage: false,
+            include_relative_position: false,
+            include_masked_sequence: false,
+            known_sites: None,
+            time_limit_per_primer: None,
+            sample_rate: None,
+            overhang: None,
+            repeat_library_bed: None,
+            dedup_hits: false,
+            sanitize_contig_names: false,
+            contig_aliases: None,
+            path_style: PathStyle::Native,
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize)]
+impl ScanOptions {
+    /// The name scans give a contig whose FASTA header names it `header_name`: its
+    /// [`ScanOptions::contig_aliases`] entry, if any.
+    pub fn aliased_contig_name<'a>(&'a self, header_name: &'a str) -> &'a str {
+        self.contig_aliases
+            .as_ref()
+            .and_then(|aliases| aliases.canonical(header_name))
+            .unwrap_or(header_name)
+    }
+
+    /// The name hits report for a contig whose FASTA header names it `header_name`:
+    /// [`ScanOptions::aliased_contig_name`], then [`sanitize_contig_name`] with
+    /// [`ScanOptions::sanitize_contig_names`].
+    pub fn reported_contig_name(&self, header_name: &str) -> String {
+        let aliased = self.aliased_contig_name(header_name);
+        if self.sanitize_contig_names {
+            sanitize_contig_name(aliased)
+        } else {
+            aliased.to_string()
+        }
+    }
+
+    /// The run's single source of randomness, seeded from [`ScanOptions::seed`].
+    pub fn rng(&self) -> XorShift64 {
+        XorShift64::new(self.seed)
+    }
+
+    /// Independent stream for one sampling feature, see [`XorShift64::from_seed_and_label`].
+    pub fn rng_for(&self, label: &str) -> XorShift64 {
+        XorShift64::from_seed_and_label(self.seed, label)
+    }
+
+    /// Checks the invariants between fields (and against `primers`) that the struct cannot
+    /// express, failing on the first one violated. [`Scanner::new`], and with it every scan
+    /// entry point, calls this before scanning.
+    pub fn validate(&self, primers: &[Primer]) -> Result<()> {
+        if self.spill_threshold.is_some()
+            && self.sort_output
+            && (!self.groups.is_empty()
+                || self.repeat_library_bed.is_some()
+                || self.dedup_hits
+                || self.sanitize_contig_names)
+        {
+            bail!(
+                "spill_threshold cannot be combined with groups, repeat_library_bed, dedup_hits, \
+                 or sanitize_contig_names"
+            );
+        }
+        if let Some(tag) = &self.tag
+            && (tag.is_empty() || tag.contains(['\t', '\n', '\r']))
+        {
+            bail!("tag must be non-empty and free of tabs and line breaks, got {tag:?}");
+        }
+        if self.spill_threshold == Some(0) {
+            bail!("spill_threshold must be at least 1");
+        }
+        if self.dedup_hits && !self.sort_output {
+            bail!("dedup_hits requires sort_output");
+        }
+        if self.only_amplifiable && self.pair_suffix_pattern.is_none() {
+            bail!("only_amplifiable requires pair_suffix_pattern");
+        }
+        if self.extendable_only && !self.predict_extension {
+            bail!("extendable_only requires predict_extension");
+        }
+        if self.keep_alignment_coords && !self.degap {
+            bail!("keep_alignment_coords requires degap");
+        }
+        if self.keep_alignment_coords
+            && (self.pair_suffix_pattern.is_some() || self.compute_genome_coverage)
+        {
+            bail!(
+                "keep_alignment_coords cannot be combined with pair_suffix_pattern or \
+                 compute_genome_coverage, which work in ungapped coordinates"
+            );
+        }
+        if let Some(min_complexity) = self.min_hit_complexity
+            && !(0.0..=1.0).contains(&min_complexity)
+        {
+            bail!("min_hit_complexity must be within [0, 1], got {min_complexity}");
+        }
+        if let Some(rate) = self.sample_rate
+            && !(rate > 0.0 && rate <= 1.0)
+        {
+            bail!("sample_rate must be within (0, 1], got {rate}");
+        }
+        if let Some((forward_suffix, reverse_suffix)) = &self.pair_suffix_pattern
+            && (forward_suffix.is_empty() || forward_suffix == reverse_suffix)
+        {
+            bail!(
+                "pair_suffix_pattern needs a non-empty forward suffix distinct from the reverse \
+                 one, got ('{forward_suffix}', '{reverse_suffix}')"
+            );
+        }
+        for group in &self.groups {
+            if let Some(member) = group
+                .primers
+                .iter()
+                .find(|member| !primers.iter().any(|primer| &primer.name == *member))
+            {
+                bail!(
+                    "primer '{member}' in group '{}' is not in the primer panel",
+                    group.name
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Cooperative cancellation flag, checked between contigs and periodically inside the scan loop.
+/// A cancelled scan returns the hits found so far with [`ScanResult::partial`] set.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// The shared flag, for registration with signal handlers.
+    pub fn flag(&self) -> &Arc<AtomicBool> {
+        &self.0
+    }
+}
+
+/// Small deterministic PRNG shared by sampling features, benchmarks, and data generators.
+#[derive(Debug, Clone)]
+pub struct XorShift64 {
+    state: u64,
+}
+
+impl XorShift64 {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            state: if seed == 0 {
+                0xA5A5_A5A5_A5A5_A5A5
+            } else {
+                seed
+            },
+        }
+    }
+
+    /// Derives a per-feature stream from the run seed and a stable label, so adding draws to
+    /// one feature never shifts the numbers another feature sees.
+    pub fn from_seed_and_label(seed: u64, label: &str) -> Self {
+        let label_hash = label.bytes().fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+        });
+        // SplitMix64 finalizer spreads nearby seeds/labels across the state space.
+        let mut z = (seed ^ label_hash).wrapping_add(0x9e37_79b9_7f4a_7c15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        Self::new(z ^ (z >> 31))
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.state = x;
+        x
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Hit {
     pub file: String,
     pub contig: String,
     pub primer: String,
     pub primer_len: usize,
     pub start: usize,
+    /// Exclusive end of the reference span. `end - start` is `primer_len` only for hits fully
+    /// inside the contig, so coordinate consumers (BED, amplicon sizes, gaps) use the span.
     pub end: usize,
     pub strand: char,
     pub mismatches: usize,
     pub matched: String,
+    /// Normalized Shannon entropy of `matched`, see [`sequence_complexity`].
+    pub complexity: f64,
+    /// Fraction of the primer's length covered by the hit: `1.0` except for hits hanging off
+    /// a contig end (see `overhang`).
+    pub matched_fraction: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amplicon_size: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nearest_partner_distance: Option<i64>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub amplifiable: bool,
+    /// Primer sequence as loaded (5'->3'), set when `ScanOptions::include_primer_sequence` is on.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub primer_sequence: Option<String>,
+    /// Primer reverse complement, aligned base-for-base with `matched` on `-` strand hits.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub primer_reverse_complement: Option<String>,
+    /// Per-strand mismatch counts of a collapsed `=` hit.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fwd_mismatches: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rev_mismatches: Option<usize>,
+    /// [`gc_skew`] of `matched`, set when `ScanOptions::include_gc_skew` is on.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gc_skew: Option<f64>,
+    /// Transcript the hit falls in, set when `ScanOptions::transcripts` is given.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transcript: Option<String>,
+    /// 0-based spliced-transcript position of the hit's 5'-most base in transcript
+    /// orientation; exonic hits only.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transcript_pos: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transcript_region: Option<TranscriptRegion>,
+    /// `start / contig length`, set when `ScanOptions::include_relative_position` is on.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contig_relative_position: Option<f64>,
+    /// `matched` with the bases at the primer's ambiguous (IUPAC) positions lowercased, e.g.
+    /// `ATgC` for `ATNC` on `ATGC`; set when `ScanOptions::include_masked_sequence` is on.
+    /// Positions where the primer names one base stay uppercase, mismatched or not.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub masked_sequence: Option<String>,
+    /// Primer bases hanging off the contig (see [`ScanOptions::overhang`]): off the start
+    /// when `start` is 0, otherwise off the end. `start..end` and `matched` cover only the
+    /// on-contig bases, so `end - start + overhang == primer_len`.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub overhang: usize,
+    /// Name of the [`ScanOptions::repeat_library_bed`] repeat(s) the hit overlaps.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repeat_class: Option<String>,
+    /// Reference positions in the hit that are not a concrete A/C/G/T (IUPAC codes, `N`).
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub ambiguous_positions: usize,
+    /// Positions that count as matches only because the reference base is ambiguous.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub matches_via_ambiguity: usize,
+    /// [`extension::is_extendable`], set when [`ScanOptions::predict_extension`] is on. A
+    /// collapsed `=` hit extends when either strand does.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extendable: Option<bool>,
+    /// The contig name as read from the reference, set when
+    /// [`ScanOptions::sanitize_contig_names`] replaced `contig`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub original_contig: Option<String>,
+    /// [`ScanOptions::tag`] of the scan that found the hit; merged results
+    /// ([`ScanResult::merge`]) keep each hit's own.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -94,6 +768,82 @@ pub struct PrimerSummary {
     pub forward_hits: u64,
     pub reverse_hits: u64,
     pub contigs_with_hits: u64,
+    /// Palindromic primers are scanned once; each hit counts toward both `forward_hits`
+    /// and `reverse_hits` (when reverse-complement scanning is on) but only once in `total_hits`.
+    pub is_palindromic: bool,
+    /// `(file, contig, start)` of the hit with the smallest / largest start across all contigs.
+    /// Serialized only by the CLI's `--extended-summary` rows.
+    #[serde(skip)]
+    pub first_hit_position: Option<(String, String, usize)>,
+    #[serde(skip)]
+    pub last_hit_position: Option<(String, String, usize)>,
+    /// No scanned contig (or region) was long enough to hold the primer, so zero hits here
+    /// means "could never match" rather than "no off-targets".
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub longer_than_every_contig: bool,
+    /// [`Primer::original_sequence`]: the input, when normalization changed it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub original_sequence: Option<String>,
+    /// [`Primer::normalization`], when it changed the input.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub normalization: Option<SequenceNormalization>,
+    /// Hits dropped because they overlap one of this primer's [`ScanOptions::known_sites`];
+    /// not part of the other counts.
+    #[serde(skip_serializing_if = "is_zero")]
+    pub known_suppressed: u64,
+    /// [`ScanOptions::time_limit_per_primer`] cut this primer's scan of at least one contig
+    /// short, so its hits and counts are incomplete.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub timed_out: bool,
+    /// Hits expected by chance: every scanned window times the probability that a random
+    /// window matches within the mismatch budget, with bases drawn independently from the
+    /// contig's A/C/G/T frequencies (see [`random_window_match_probability`]). Reverse-strand
+    /// windows are included when reverse-complement scanning is on, except for palindromes.
+    pub expected_random_hits: f64,
+    /// The `-` strand share of `expected_random_hits`.
+    #[serde(skip)]
+    pub expected_random_reverse_hits: f64,
+    /// Mean mismatches per hit, `0.0` without hits. Serialized only by the CLI's
+    /// `--extended-summary` rows, like `std_mismatch`.
+    #[serde(skip)]
+    pub mean_mismatch: f64,
+    /// Population standard deviation of the mismatches per hit, `0.0` without hits.
+    #[serde(skip)]
+    pub std_mismatch: f64,
+    /// [`strand_bias`] of `forward_hits` and `reverse_hits`.
+    pub strand_bias: f64,
+    /// Hits with exactly `i` mismatches at index `i`, for `i` up to
+    /// [`ScanOptions::max_mismatches`]; sums to `total_hits`.
+    pub per_mismatch_counts: Vec<u64>,
+    /// [`ScanOptions::tag`] of the scan the row summarizes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+}
+
+fn is_zero<T: Default + PartialEq>(value: &T) -> bool {
+    *value == T::default()
+}
+
+/// `(forward - reverse) / (forward + reverse)`: `1.0` when every hit is on `+`, `-1.0` when
+/// every hit is on `-`, and `0.0` when balanced or without hits.
+pub fn strand_bias(forward: u64, reverse: u64) -> f64 {
+    let total = forward + reverse;
+    if total == 0 {
+        return 0.0;
+    }
+    (forward as f64 - reverse as f64) / total as f64
+}
+
+/// Mean and population standard deviation of `hits` mismatch counts from their sum and sum of
+/// squares; `(0.0, 0.0)` without hits.
+pub(crate) fn mismatch_mean_and_std(hits: u64, total: u64, total_squared: u64) -> (f64, f64) {
+    if hits == 0 {
+        return (0.0, 0.0);
+    }
+    let count = hits as f64;
+    let mean = total as f64 / count;
+    let variance = (total_squared as f64 / count - mean * mean).max(0.0);
+    (mean, variance.sqrt())
 }
 
 #[derive(Debug, Clone)]
@@ -101,13 +851,300 @@ pub struct ScanResult {
     pub hits: Vec<Hit>,
     pub summary: Vec<PrimerSummary>,
     pub total_hits: u64,
+    /// Warnings recorded on `ScanOptions::warnings` per category, including suppressed ones.
+    pub warnings: BTreeMap<String, u64>,
+    /// Every scanned contig with its length, in input order.
+    pub contigs: Vec<ContigLength>,
+    /// The run seed ([`ScanOptions::seed`]) every randomized step derived from.
+    pub seed: u64,
+    /// `true` when [`ScanOptions::cancel`] stopped the scan before every reference was read.
+    pub partial: bool,
+    /// How far each reference file got, in input order.
+    pub files: Vec<FileStatus>,
+    /// One row per [`ScanOptions::groups`] entry, in the same order.
+    pub group_summaries: Vec<GroupSummary>,
+    /// Worker count chosen by [`ScanOptions::auto_tune_threads`], if tuning ran.
+    pub auto_tuned_threads: Option<usize>,
+    /// Sorted runs spilled under [`ScanOptions::spill_threshold`]. When set, `hits` holds only
+    /// the last in-memory run; [`ScanResult::hit_stream`] yields the complete sorted hit list.
+    pub spilled_hits: Option<Arc<SpilledHits>>,
+    /// [`compute_hit_hash`] of every hit, for verifying shipped results.
+    pub hit_hash: String,
+    /// Fraction of scanned bases (contig lengths, or region lengths with
+    /// [`ScanOptions::regions`]) inside at least one hit; set by
+    /// [`ScanOptions::compute_genome_coverage`].
+    pub genome_coverage_fraction: Option<f64>,
+}
+
+impl ScanResult {
+    /// Takes every hit in sorted order, merging spilled runs with the in-memory hits.
+    /// Leaves `hits` empty.
+    pub fn hit_stream(&mut self) -> Result<HitStream> {
+        HitStream::new(std::mem::take(&mut self.hits), self.spilled_hits.take())
+    }
+
+    /// Folds in `other`, e.g. a second pass with other options and its own
+    /// [`ScanOptions::tag`], keeping every hit and summary row with its tag: hits are
+    /// re-sorted, summary rows stay grouped by primer (this result's first), warning counts
+    /// add up, and contigs and files are joined. `hit_hash` is recomputed over the merged hits,
+    /// tags included; `genome_coverage_fraction` is cleared, as the passes may overlap.
+    /// Neither result may hold spilled hits; collect them with [`ScanResult::hit_stream`].
+    pub fn merge(&mut self, other: ScanResult) -> Result<()> {
+        if self.spilled_hits.is_some() || other.spilled_hits.is_some() {
+            bail!("cannot merge scan results with spilled hits; collect them with hit_stream");
+        }
+        self.hits.extend(other.hits);
+        self.hits.sort_by(compare_hits);
+        self.summary.extend(other.summary);
+        self.summary.sort_by(|a, b| a.primer.cmp(&b.primer));
+        self.total_hits += other.total_hits;
+        for (category, count) in other.warnings {
+            *self.warnings.entry(category).or_default() += count;
+        }
+        for contig in other.contigs {
+            if !self
+                .contigs
+                .iter()
+                .any(|seen| (&seen.file, &seen.contig) == (&contig.file, &contig.contig))
+            {
+                self.contigs.push(contig);
+            }
+        }
+        for file in other.files {
+            if !self.files.iter().any(|seen| seen.file == file.file) {
+                self.files.push(file);
+            }
+        }
+        self.partial |= other.partial;
+        self.group_summaries.extend(other.group_summaries);
+        self.hit_hash = compute_hit_hash(&self.hits);
+        self.genome_coverage_fraction = None;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileCompletion {
+    Complete,
+    Partial,
+    NotStarted,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FileStatus {
+    pub file: String,
+    pub completion: FileCompletion,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ContigLength {
+    pub file: String,
+    pub contig: String,
+    pub length: usize,
+    /// Non-IUPAC sequence characters (digits, `*`, gaps, ...) read as `N` in lenient mode.
+    pub coerced_bases: usize,
+}
+
+/// Writes one FASTA record, wrapping the sequence every `line_width` bases (`0` = no wrapping).
+pub fn write_fasta_record<W: Write>(
+    out: &mut W,
+    header: &str,
+    sequence: &[u8],
+    line_width: usize,
+) -> std::io::Result<()> {
+    writeln!(out, ">{header}")?;
+    if line_width == 0 || sequence.is_empty() {
+        out.write_all(sequence)?;
+        return out.write_all(b"\n");
+    }
+    for line in sequence.chunks(line_width) {
+        out.write_all(line)?;
+        out.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+/// `name` with every character outside `[A-Za-z0-9._:-]` replaced by `_`, so the name
+/// survives TSV, BED, and GFF parsers.
+pub fn sanitize_contig_name(name: &str) -> String {
+    name.chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || matches!(ch, '.' | '_' | ':' | '-') {
+                ch
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// GC skew `(G - C) / (G + C)` of `sequence`, from `-1.0` (all C) to `+1.0` (all G).
+///
+/// Sequences without G or C score `0.0`.
+pub fn gc_skew(sequence: &str) -> f64 {
+    let (mut g, mut c) = (0u32, 0u32);
+    for base in sequence.bytes() {
+        match normalize_base(base) {
+            b'G' => g += 1,
+            b'C' => c += 1,
+            _ => {}
+        }
+    }
+    if g + c == 0 {
+        return 0.0;
+    }
+    (f64::from(g) - f64::from(c)) / f64::from(g + c)
+}
+
+/// Every A/C/G/T sequence an IUPAC `sequence` stands for, in lexicographic order
+/// (`RAATGG` gives `AAATGG`, `GAATGG`). The result grows exponentially with the number of
+/// degenerate bases, so `N`-rich sequences should not be expanded.
+pub fn iupac_expand(sequence: &str) -> Result<Vec<String>> {
+    let mut expansions = vec![String::with_capacity(sequence.len())];
+    for base in sequence.bytes() {
+        let mask = iupac_mask(base)
+            .with_context(|| format!("unsupported IUPAC base '{}'", base as char))?;
+        let choices = [b'A', b'C', b'G', b'T']
+            .into_iter()
+            .enumerate()
+            .filter(|(bit, _)| mask & (1 << bit) != 0)
+            .map(|(_, base)| base as char)
+            .collect::<Vec<_>>();
+        expansions = expansions
+            .into_iter()
+            .flat_map(|prefix| {
+                choices.iter().map(move |&choice| {
+                    let mut next = prefix.clone();
+                    next.push(choice);
+                    next
+                })
+            })
+            .collect();
+    }
+    Ok(expansions)
+}
+
+/// Shannon entropy of the A/C/G/T composition of `sequence`, normalized to `[0, 1]`.
+///
+/// Homopolymers score `0.0`; equal use of all four bases scores `1.0`.
+/// Ambiguous and unknown bases are ignored.
+pub fn sequence_complexity(sequence: &str) -> f64 {
+    let mut counts = [0u32; 4];
+    for base in sequence.bytes() {
+        match normalize_base(base) {
+            b'A' => counts[0] += 1,
+            b'C' => counts[1] += 1,
+            b'G' => counts[2] += 1,
+            b'T' => counts[3] += 1,
+            _ => {}
+        }
+    }
+
+    let total: u32 = counts.iter().sum();
+    if total == 0 {
+        return 0.0;
+    }
+    let entropy: f64 = counts
+        .iter()
+        .filter(|&&count| count > 0)
+        .map(|&count| {
+            let p = f64::from(count) / f64::from(total);
+            -p * p.log2()
+        })
+        .sum::<f64>()
+        // A single base sums to -0.0.
+        .abs();
+    entropy / 2.0
+}
+
+/// Layout of a primer panel stream.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PrimerFormat {
+    /// Detect FASTA from a leading `>`, otherwise split on tab (or comma) per the first row.
+    #[default]
+    Auto,
+    /// `name<tab>sequence` rows, optional header.
+    Tsv,
+    /// `name,sequence` rows, optional header.
+    Csv,
+    /// `>name` records.
+    Fasta,
+    /// One `{"name": ..., "sequence": ...}` object per line; `name` and `pool` are optional.
+    Jsonl,
+}
+
+#[derive(Deserialize)]
+struct JsonPrimer {
+    #[serde(default)]
+    name: String,
+    sequence: String,
+    #[serde(default)]
+    pool: Option<String>,
 }
 
 pub fn load_primers(path: &Path) -> Result<Vec<Primer>> {
-    let mut reader = open_reader(path)?;
+    let reader = open_reader(path)?;
+    load_primers_from_reader(reader, PrimerFormat::Auto, &path.display().to_string())
+}
+
+/// Keeps primers whose name matches `pattern`. Fails when the pattern does not compile or
+/// no primer matches.
+pub fn filter_primers_by_name_regex(primers: Vec<Primer>, pattern: &str) -> Result<Vec<Primer>> {
+    select_primers_by_name(primers, pattern, false)
+}
+
+/// Keeps primers whose name does not match `pattern`; the inverse of
+/// [`filter_primers_by_name_regex`].
+pub fn exclude_primers_by_name_regex(primers: Vec<Primer>, pattern: &str) -> Result<Vec<Primer>> {
+    select_primers_by_name(primers, pattern, true)
+}
+
+fn select_primers_by_name(
+    primers: Vec<Primer>,
+    pattern: &str,
+    invert: bool,
+) -> Result<Vec<Primer>> {
+    let regex = regex::Regex::new(pattern)
+        .with_context(|| format!("invalid primer name regex '{pattern}'"))?;
+    let kept = primers
+        .into_iter()
+        .filter(|primer| regex.is_match(&primer.name) != invert)
+        .collect::<Vec<_>>();
+    if kept.is_empty() {
+        bail!(
+            "no primer names {} '{pattern}'",
+            if invert {
+                "are left after excluding"
+            } else {
+                "match"
+            }
+        );
+    }
+    Ok(kept)
+}
+
+/// Reads a primer panel from any buffered stream (e.g. stdin). `source` names the stream in
+/// error messages.
+pub fn load_primers_from_reader<R: BufRead>(
+    mut reader: R,
+    format: PrimerFormat,
+    source: &str,
+) -> Result<Vec<Primer>> {
     let mut line = String::new();
     let mut primers = Vec::new();
-    let mut delimiter: Option<char> = None;
+    let mut delimiter: Option<char> = match format {
+        PrimerFormat::Tsv => Some('\t'),
+        PrimerFormat::Csv => Some(','),
+        _ => None,
+    };
+    let mut is_fasta: Option<bool> = match format {
+        PrimerFormat::Auto => None,
+        explicit => Some(explicit == PrimerFormat::Fasta),
+    };
+    let mut fasta_record: Option<(String, String)> = None;
+    // Column of `pool` in a delimited panel's header.
+    let mut pool_column: Option<usize> = None;
     let mut row_index = 0usize;
     let max_file_bytes = read_limit_from_env(
         "PRIMER_SCOUT_MAX_PRIMER_FILE_BYTES",
@@ -123,7 +1160,7 @@ pub fn load_primers(path: &Path) -> Result<Vec<Primer>> {
         line.clear();
         let read_bytes = reader
             .read_line(&mut line)
-            .with_context(|| format!("failed reading primer file '{}'", path.display()))?;
+            .with_context(|| format!("failed reading primer file '{source}'"))?;
         if read_bytes == 0 {
             break;
         }
@@ -131,14 +1168,14 @@ pub fn load_primers(path: &Path) -> Result<Vec<Primer>> {
         if total_bytes > max_file_bytes {
             bail!(
                 "primer file '{}' exceeds safety limit of {} bytes (override with PRIMER_SCOUT_MAX_PRIMER_FILE_BYTES)",
-                path.display(),
+                source,
                 max_file_bytes
             );
         }
         if read_bytes > max_line_bytes {
             bail!(
                 "primer line in '{}' exceeds safety limit of {} bytes (override with PRIMER_SCOUT_MAX_PRIMER_LINE_BYTES)",
-                path.display(),
+                source,
                 max_line_bytes
             );
         }
@@ -148,6 +1185,43 @@ pub fn load_primers(path: &Path) -> Result<Vec<Primer>> {
             continue;
         }
 
+        if format == PrimerFormat::Jsonl {
+            row_index += 1;
+            let record: JsonPrimer = serde_json::from_str(trimmed)
+                .with_context(|| format!("invalid JSON primer at row {row_index} in '{source}'"))?;
+            let mut primer = build_primer(
+                &record.name,
+                &record.sequence,
+                row_index,
+                source,
+                primers.len(),
+            )?;
+            primer.pool = record.pool.filter(|pool| !pool.is_empty());
+            primers.push(primer);
+            continue;
+        }
+
+        if *is_fasta.get_or_insert_with(|| trimmed.starts_with('>')) {
+            if let Some(header) = trimmed.strip_prefix('>') {
+                if let Some((name, sequence)) = fasta_record.take() {
+                    primers.push(build_primer(
+                        &name,
+                        &sequence,
+                        row_index,
+                        source,
+                        primers.len(),
+                    )?);
+                }
+                row_index += 1;
+                fasta_record = Some((header.trim().to_string(), String::new()));
+            } else if let Some((_, sequence)) = fasta_record.as_mut() {
+                sequence.push_str(trimmed);
+            } else {
+                bail!("invalid FASTA panel '{source}': found sequence before header");
+            }
+            continue;
+        }
+
         let del = delimiter.unwrap_or_else(|| infer_delimiter(trimmed));
         delimiter = Some(del);
         let parts: Vec<&str> = trimmed.split(del).map(str::trim).collect();
@@ -160,102 +1234,416 @@ pub fn load_primers(path: &Path) -> Result<Vec<Primer>> {
         };
 
         if row_index == 1 && is_header(name_raw, seq_raw) {
+            pool_column = parts
+                .iter()
+                .position(|column| column.eq_ignore_ascii_case("pool"));
             continue;
         }
 
-        let name = if name_raw.is_empty() {
-            format!("primer_{:04}", primers.len() + 1)
-        } else {
-            name_raw.to_string()
-        };
-        let primer = Primer::from_name_and_sequence(name, seq_raw).with_context(|| {
-            format!(
-                "invalid primer sequence at row {} in '{}'",
-                row_index,
-                path.display()
-            )
-        })?;
+        let mut primer = build_primer(name_raw, seq_raw, row_index, source, primers.len())?;
+        primer.pool = pool_column
+            .and_then(|column| parts.get(column))
+            .filter(|pool| !pool.is_empty())
+            .map(|pool| pool.to_string());
         primers.push(primer);
     }
 
+    if let Some((name, sequence)) = fasta_record.take() {
+        primers.push(build_primer(
+            &name,
+            &sequence,
+            row_index,
+            source,
+            primers.len(),
+        )?);
+    }
+
     if primers.is_empty() {
-        bail!("no primers found in '{}'", path.display());
+        bail!("no primers found in '{source}'");
     }
 
     Ok(primers)
 }
 
-pub fn scan_references(
-    references: &[PathBuf],
-    primers: &[Primer],
-    options: &ScanOptions,
-) -> Result<ScanResult> {
-    if references.is_empty() {
-        bail!("no reference files supplied");
-    }
-    if primers.is_empty() {
-        bail!("no primers supplied");
-    }
+fn build_primer(
+    name_raw: &str,
+    seq_raw: &str,
+    row_index: usize,
+    source: &str,
+    loaded: usize,
+) -> Result<Primer> {
+    let name = if name_raw.is_empty() {
+        format!("primer_{:04}", loaded + 1)
+    } else {
+        name_raw.to_string()
+    };
+    Primer::from_name_and_sequence(name, seq_raw)
+        .with_context(|| format!("invalid primer sequence at row {row_index} in '{source}'"))
+}
 
-    let mut merged_hits = Vec::new();
-    let mut summary_acc = vec![SummaryAccumulator::default(); primers.len()];
-    let mut total_hits = 0u64;
+/// A primer panel and scan options prepared once and reused across many scans.
+///
+/// Construction validates the panel and options and reads the environment limits, so each
+/// [`Scanner::scan`] only does the matching itself. `Scanner` is `Sync`: a web handler or a
+/// rayon loop over many short sequences can share one instance.
+#[derive(Debug, Clone)]
+pub struct Scanner {
+    primers: Vec<Primer>,
+    options: ScanOptions,
+    max_contig_bases: usize,
+    /// [`ScanOptions::repeat_library_bed`], loaded once.
+    repeats: Option<Arc<NamedRegions>>,
+    /// The [`ScanOptions::threads`] pool every scan runs on, built once.
+    pool: Option<Arc<rayon::ThreadPool>>,
+}
 
-    for reference in references {
-        let file_result = scan_reference_file(reference, primers, options)?;
-        total_hits += file_result.total_hits;
-        merged_hits.extend(file_result.hits);
+/// Post-processed hits of one [`Scanner::scan`] call, in discovery order.
+#[derive(Debug, Clone, Default)]
+pub struct ContigHits {
+    pub hits: Vec<Hit>,
+    pub total_hits: u64,
+}
 
-        for (acc, delta) in summary_acc.iter_mut().zip(file_result.summary.into_iter()) {
-            acc.total_hits += delta.total_hits;
-            acc.perfect_hits += delta.perfect_hits;
-            acc.forward_hits += delta.forward_hits;
-            acc.reverse_hits += delta.reverse_hits;
-            acc.contigs_with_hits += delta.contigs_with_hits;
+impl Scanner {
+    pub fn new(primers: Vec<Primer>, options: ScanOptions) -> Result<Self> {
+        if primers.is_empty() {
+            bail!("no primers supplied");
         }
+        options.validate(&primers)?;
+        let repeats = match &options.repeat_library_bed {
+            Some(path) => Some(Arc::new(load_named_bed(path, true).with_context(|| {
+                format!("failed loading repeat library '{}'", path.display())
+            })?)),
+            None => None,
+        };
+        let pool = match options.threads {
+            Some(threads) if !options.auto_tune_threads => Some(scan_pool(threads)?),
+            _ => None,
+        };
+        Ok(Self {
+            primers,
+            options,
+            max_contig_bases: read_limit_from_env(
+                "PRIMER_SCOUT_MAX_CONTIG_BASES",
+                DEFAULT_MAX_CONTIG_BASES,
+            ),
+            repeats,
+            pool,
+        })
     }
 
-    merged_hits.sort_by(|a, b| {
-        (
-            &a.file,
-            &a.contig,
-            &a.primer,
-            a.start,
-            a.strand,
-            a.mismatches,
-        )
-            .cmp(&(
-                &b.file,
-                &b.contig,
-                &b.primer,
-                b.start,
-                b.strand,
-                b.mismatches,
-            ))
-    });
+    /// Runs `op` on this scanner's pool, or on the caller's pool without one; see
+    /// [`ScanOptions::threads`].
+    fn install<T: Send>(&self, op: impl FnOnce() -> T + Send) -> T {
+        match &self.pool {
+            Some(pool) => pool.install(op),
+            None => op(),
+        }
+    }
 
-    let mut summary = primers
-        .iter()
-        .zip(summary_acc)
-        .map(|(primer, acc)| PrimerSummary {
-            primer: primer.name.clone(),
-            primer_len: primer.len(),
-            total_hits: acc.total_hits,
-            perfect_hits: acc.perfect_hits,
-            forward_hits: acc.forward_hits,
-            reverse_hits: acc.reverse_hits,
-            contigs_with_hits: acc.contigs_with_hits,
-        })
-        .collect::<Vec<_>>();
+    pub fn primers(&self) -> &[Primer] {
+        &self.primers
+    }
 
-    summary.sort_by(|a, b| a.primer.cmp(&b.primer));
+    fn annotate_repeats(&self, hits: &mut [Hit]) {
+        if let Some(repeats) = &self.repeats {
+            regions::annotate_repeats(hits, repeats);
+        }
+    }
 
-    Ok(ScanResult {
-        hits: merged_hits,
-        summary,
-        total_hits,
-    })
-}
+    pub fn options(&self) -> &ScanOptions {
+        &self.options
+    }
+
+    /// Scans one in-memory sequence; no summary, sorting, or warnings aggregation.
+    pub fn scan(&self, contig_name: &str, sequence: &[u8]) -> Result<ContigHits> {
+        self.install(|| self.scan_in_pool(contig_name, sequence))
+    }
+
+    fn scan_in_pool(&self, contig_name: &str, sequence: &[u8]) -> Result<ContigHits> {
+        self.check_contig_len(contig_name, sequence.len())?;
+        let mut contig = scan_contig(
+            "in-memory",
+            contig_name,
+            sequence,
+            &self.primers,
+            &self.options,
+        )?;
+        postprocess_hits(&mut contig.hits, &self.options);
+        self.annotate_repeats(&mut contig.hits);
+        if self.options.sanitize_contig_names {
+            sanitize_hit_contigs(&mut contig.hits);
+        }
+        Ok(ContigHits {
+            hits: contig.hits,
+            total_hits: contig.total_hits,
+        })
+    }
+
+    /// Scans `references` like [`Scanner::scan_references`], then streams every hit (sorted,
+    /// spilled runs included) and every primer summary row into `sink` and finishes it.
+    /// The returned result's `hits` is left empty.
+    pub fn scan_into(
+        &self,
+        references: &[PathBuf],
+        sink: &mut dyn sink::HitSink,
+    ) -> Result<ScanResult> {
+        let mut result = self.scan_references(references)?;
+        for hit in result.hit_stream()? {
+            sink.on_hit(&hit?)?;
+        }
+        for row in &result.summary {
+            sink.on_summary(row)?;
+        }
+        sink.finish()?;
+        Ok(result)
+    }
+
+    pub fn scan_references(&self, references: &[PathBuf]) -> Result<ScanResult> {
+        let (primers, options) = (self.primers.as_slice(), &self.options);
+        if references.is_empty() {
+            bail!("no reference files supplied");
+        }
+        if options.auto_tune_threads {
+            let threads = tuning::tune_threads(&references[0], primers, options)?;
+            let tuned = Self {
+                options: ScanOptions {
+                    auto_tune_threads: false,
+                    threads: Some(threads),
+                    ..options.clone()
+                },
+                pool: Some(scan_pool(threads)?),
+                ..self.clone()
+            };
+            let mut result = tuned.scan_references(references)?;
+            result.auto_tuned_threads = Some(threads);
+            return Ok(result);
+        }
+        self.install(|| self.scan_references_in_pool(references))
+    }
+
+    fn scan_references_in_pool(&self, references: &[PathBuf]) -> Result<ScanResult> {
+        let (primers, options) = (self.primers.as_slice(), &self.options);
+
+        let spill_threshold = options.spill_threshold.filter(|_| options.sort_output);
+        let mut collector = HitCollector::new(spill_threshold);
+        let mut summary_acc = vec![SummaryAccumulator::default(); primers.len()];
+        let mut total_hits = 0u64;
+        let mut covered_bases = 0u64;
+        let mut contigs = Vec::new();
+        let mut seen_references = HashSet::new();
+        let mut files = Vec::new();
+
+        for reference in references {
+            // The same file listed twice would count every hit twice.
+            let identity = reference
+                .canonicalize()
+                .unwrap_or_else(|_| reference.clone());
+            if !seen_references.insert(identity) {
+                options.warnings.warn(
+                    "duplicate_reference",
+                    format_args!(
+                        "reference '{}' was given more than once; scanning it once",
+                        reference.display()
+                    ),
+                );
+                continue;
+            }
+            if options.cancel.is_cancelled() {
+                files.push(FileStatus {
+                    file: options.path_style.render(reference),
+                    completion: FileCompletion::NotStarted,
+                });
+                continue;
+            }
+            let file_result = scan_reference_file(reference, primers, options, &mut collector)?;
+            files.push(FileStatus {
+                file: options.path_style.render(reference),
+                completion: if file_result.completed {
+                    FileCompletion::Complete
+                } else {
+                    FileCompletion::Partial
+                },
+            });
+            total_hits += file_result.total_hits;
+            covered_bases += file_result.covered_bases;
+            contigs.extend(file_result.contigs);
+
+            for (acc, delta) in summary_acc.iter_mut().zip(file_result.summary) {
+                acc.merge(delta);
+            }
+        }
+
+        let (mut merged_hits, spilled_hits) = collector.finish();
+        if options.sort_output {
+            merged_hits.sort_by(compare_hits);
+        }
+        if options.dedup_hits && options.sort_output {
+            let duplicates = dedup_hits(&mut merged_hits);
+            if !duplicates.is_empty() {
+                let primer_index = primers
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, primer)| (primer.name.as_str(), idx))
+                    .collect::<HashMap<_, _>>();
+                for hit in &duplicates {
+                    let idx = primer_index[hit.primer.as_str()];
+                    let both_strands =
+                        options.scan_reverse_complement && primers[idx].is_palindromic;
+                    summary_acc[idx].remove_hit(hit, both_strands);
+                }
+                total_hits -= duplicates.len() as u64;
+                options.warnings.warn(
+                    "duplicate_hits",
+                    format_args!("removed {} duplicate hit record(s)", duplicates.len()),
+                );
+            }
+        }
+        self.annotate_repeats(&mut merged_hits);
+        if options.sanitize_contig_names {
+            sanitize_hit_contigs(&mut merged_hits);
+            // Sanitizing can change the relative order of contig names.
+            if options.sort_output {
+                merged_hits.sort_by(compare_hits);
+            }
+            for contig in &mut contigs {
+                contig.contig = sanitize_contig_name(&contig.contig);
+            }
+        }
+        let hit_hash = match &spilled_hits {
+            Some(spilled) => {
+                let mut hasher = HitHasher::new(options.hash_ignores_tag);
+                for hit in HitStream::new(merged_hits.clone(), Some(Arc::clone(spilled)))? {
+                    hasher.update(&hit?);
+                }
+                hasher.finish()
+            }
+            None => hash_hits(&merged_hits, options.hash_ignores_tag),
+        };
+
+        let mut summary = build_summary(primers, summary_acc, options);
+        if options.sanitize_contig_names {
+            sanitize_hit_position_contigs(&mut summary);
+        }
+        warn_unmatchable_primers(&summary, options);
+        let group_summaries = groups::summarize_groups(&options.groups, &summary, &merged_hits);
+        let genome_coverage_fraction = options
+            .compute_genome_coverage
+            .then(|| regions::coverage_fraction(covered_bases, &contigs, options.regions.as_ref()));
+
+        Ok(ScanResult {
+            hits: merged_hits,
+            summary,
+            total_hits,
+            warnings: options.warnings.counts(),
+            contigs,
+            seed: options.seed,
+            partial: files
+                .iter()
+                .any(|status| status.completion != FileCompletion::Complete),
+            files,
+            group_summaries,
+            auto_tuned_threads: None,
+            spilled_hits,
+            hit_hash,
+            genome_coverage_fraction,
+        })
+    }
+
+    pub fn scan_sequence(&self, sequence: &str, contig_name: &str) -> Result<ScanResult> {
+        self.install(|| self.scan_sequence_in_pool(sequence, contig_name))
+    }
+
+    fn scan_sequence_in_pool(&self, sequence: &str, contig_name: &str) -> Result<ScanResult> {
+        let (primers, options) = (self.primers.as_slice(), &self.options);
+        self.check_contig_len(contig_name, sequence.len())?;
+
+        let mut contig = scan_contig(
+            "in-memory",
+            contig_name,
+            sequence.as_bytes(),
+            primers,
+            options,
+        )?;
+        postprocess_hits(&mut contig.hits, options);
+        self.annotate_repeats(&mut contig.hits);
+        let contig_name = if options.sanitize_contig_names {
+            sanitize_hit_contigs(&mut contig.hits);
+            sanitize_contig_name(contig_name)
+        } else {
+            contig_name.to_string()
+        };
+
+        let mut summary = build_summary(primers, contig.summary, options);
+        if options.sanitize_contig_names {
+            sanitize_hit_position_contigs(&mut summary);
+        }
+        warn_unmatchable_primers(&summary, options);
+        let group_summaries = groups::summarize_groups(&options.groups, &summary, &contig.hits);
+        let hit_hash = hash_hits(&contig.hits, options.hash_ignores_tag);
+        let contigs = vec![ContigLength {
+            file: "in-memory".to_string(),
+            contig: contig_name,
+            length: sequence.len(),
+            coerced_bases: sequence
+                .bytes()
+                .filter(|&base| iupac_mask(base).is_none())
+                .count(),
+        }];
+        let genome_coverage_fraction = options.compute_genome_coverage.then(|| {
+            regions::coverage_fraction(
+                regions::covered_bases(&contig.hits),
+                &contigs,
+                options.regions.as_ref(),
+            )
+        });
+
+        Ok(ScanResult {
+            hits: contig.hits,
+            summary,
+            total_hits: contig.total_hits,
+            warnings: options.warnings.counts(),
+            contigs,
+            seed: options.seed,
+            partial: options.cancel.is_cancelled(),
+            files: Vec::new(),
+            group_summaries,
+            auto_tuned_threads: None,
+            spilled_hits: None,
+            hit_hash,
+            genome_coverage_fraction,
+        })
+    }
+
+    fn check_contig_len(&self, contig_name: &str, len: usize) -> Result<()> {
+        if len > self.max_contig_bases {
+            bail!(
+                "input sequence '{}' exceeds safety limit of {} bases (override with PRIMER_SCOUT_MAX_CONTIG_BASES)",
+                contig_name,
+                self.max_contig_bases
+            );
+        }
+        Ok(())
+    }
+}
+
+/// A pool of `threads` rayon workers (`0` for rayon's default count) for
+/// [`ScanOptions::threads`].
+fn scan_pool(threads: usize) -> Result<Arc<rayon::ThreadPool>> {
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build()
+        .context("failed to create rayon thread pool")?;
+    Ok(Arc::new(pool))
+}
+
+pub fn scan_references(
+    references: &[PathBuf],
+    primers: &[Primer],
+    options: &ScanOptions,
+) -> Result<ScanResult> {
+    Scanner::new(primers.to_vec(), options.clone())?.scan_references(references)
+}
 
 pub fn scan_sequence(
     sequence: &str,
@@ -263,56 +1651,422 @@ pub fn scan_sequence(
     primers: &[Primer],
     options: &ScanOptions,
 ) -> Result<ScanResult> {
-    if primers.is_empty() {
-        bail!("no primers supplied");
+    Scanner::new(primers.to_vec(), options.clone())?.scan_sequence(sequence, contig_name)
+}
+
+/// Lazily scans `sequence` for one primer on the current thread, yielding hits by start (a
+/// window's `+` hit before its `-` hit) as the windows are tested; no reference copy is made.
+///
+/// Per-window options apply: mismatches, strands, complexity and ambiguity limits, and the
+/// optional hit fields. Contig-level ones (regions, sampling, overhangs, known sites, IUPAC
+/// deduplication, time limits) do not, and the hits' `file` and `contig` are empty. Fails up
+/// front when [`ScanOptions::validate`] does, or when `max_mismatches` reaches the primer
+/// length.
+///
+/// ```
+/// use primer_scout::{Primer, ScanOptions, scan_one};
+///
+/// let primer = Primer::from_name_and_sequence("p1", "ACGTAC").unwrap();
+/// let options = ScanOptions {
+///     max_mismatches: 1,
+///     ..ScanOptions::default()
+/// };
+/// let reference = b"TTACGTTCGGACGTACTT";
+/// let first_perfect = scan_one(&primer, reference, &options)
+///     .unwrap()
+///     .find(|hit| hit.mismatches == 0)
+///     .unwrap();
+/// assert_eq!((first_perfect.start, first_perfect.strand), (10, '+'));
+/// ```
+pub fn scan_one<'a>(
+    primer: &'a Primer,
+    sequence: &'a [u8],
+    opts: &'a ScanOptions,
+) -> Result<impl Iterator<Item = Hit> + 'a> {
+    opts.validate(std::slice::from_ref(primer))?;
+    if primer.is_empty() {
+        bail!("primer '{}' has zero length", primer.name);
     }
-    let max_contig_bases =
-        read_limit_from_env("PRIMER_SCOUT_MAX_CONTIG_BASES", DEFAULT_MAX_CONTIG_BASES);
-    if sequence.len() > max_contig_bases {
+    if opts.max_mismatches >= primer.len() {
         bail!(
-            "input sequence '{}' exceeds safety limit of {} bases (override with PRIMER_SCOUT_MAX_CONTIG_BASES)",
-            contig_name,
-            max_contig_bases
+            "max_mismatches ({}) must be below the length of primer '{}' ({}), or every window \
+             matches",
+            opts.max_mismatches,
+            primer.name,
+            primer.len()
         );
     }
+    let contig = RawContig::new(sequence, opts.lowercase_mismatches);
+    let builder = HitBuilder {
+        file_name: "",
+        contig_name: "",
+        primer,
+        options: opts,
+        deadline: None,
+    };
+    let windows = (sequence.len() + 1).saturating_sub(primer.len());
+    let both_strands = opts.scan_reverse_complement && !primer.is_palindromic;
+    let mut summary = SummaryAccumulator::default();
+    let matches = window_matches(
+        contig,
+        0..windows,
+        primer,
+        both_strands,
+        opts.max_mismatches,
+    );
+    Ok(matches.filter_map(move |(start, strand, mismatches)| {
+        let mut hits = Vec::new();
+        let span = start..start + primer.len();
+        builder.push(&contig, span, strand, mismatches, &mut summary, &mut hits);
+        hits.pop()
+    }))
+}
 
-    let contig = scan_contig("in-memory", contig_name, sequence, primers, options)?;
-
+fn build_summary(
+    primers: &[Primer],
+    accumulators: Vec<SummaryAccumulator>,
+    options: &ScanOptions,
+) -> Vec<PrimerSummary> {
     let mut summary = primers
         .iter()
-        .zip(contig.summary)
-        .map(|(primer, acc)| PrimerSummary {
-            primer: primer.name.clone(),
-            primer_len: primer.len(),
-            total_hits: acc.total_hits,
-            perfect_hits: acc.perfect_hits,
-            forward_hits: acc.forward_hits,
-            reverse_hits: acc.reverse_hits,
-            contigs_with_hits: acc.contigs_with_hits,
+        .zip(accumulators)
+        .map(|(primer, mut acc)| {
+            acc.counts_by_mismatch.resize(options.max_mismatches + 1, 0);
+            let (mean_mismatch, std_mismatch) = mismatch_mean_and_std(
+                acc.total_hits,
+                acc.total_mismatches,
+                acc.total_mismatches_squared,
+            );
+            PrimerSummary {
+                primer: primer.name.clone(),
+                primer_len: primer.len(),
+                total_hits: acc.total_hits,
+                perfect_hits: acc.perfect_hits,
+                forward_hits: acc.forward_hits,
+                reverse_hits: acc.reverse_hits,
+                contigs_with_hits: acc.contigs_with_hits,
+                is_palindromic: primer.is_palindromic,
+                first_hit_position: acc.first_hit,
+                last_hit_position: acc.last_hit,
+                longer_than_every_contig: !acc.had_window,
+                original_sequence: primer.original_sequence.clone(),
+                normalization: primer
+                    .normalization
+                    .changed()
+                    .then_some(primer.normalization),
+                known_suppressed: acc.known_suppressed,
+                timed_out: acc.timed_out,
+                expected_random_hits: acc.expected_forward + acc.expected_reverse,
+                expected_random_reverse_hits: acc.expected_reverse,
+                mean_mismatch,
+                std_mismatch,
+                strand_bias: strand_bias(acc.forward_hits, acc.reverse_hits),
+                per_mismatch_counts: acc.counts_by_mismatch,
+                tag: options.tag.clone(),
+            }
         })
         .collect::<Vec<_>>();
     summary.sort_by(|a, b| a.primer.cmp(&b.primer));
+    summary
+}
 
-    Ok(ScanResult {
-        hits: contig.hits,
-        summary,
-        total_hits: contig.total_hits,
-    })
+/// One aggregated warning naming every primer that no scanned contig could hold.
+fn warn_unmatchable_primers(summary: &[PrimerSummary], options: &ScanOptions) {
+    if options.cancel.is_cancelled() {
+        return;
+    }
+    let names = summary
+        .iter()
+        .filter(|row| row.longer_than_every_contig)
+        .map(|row| format!("{} ({} bp)", row.primer, row.primer_len))
+        .collect::<Vec<_>>();
+    if !names.is_empty() {
+        options.warnings.warn(
+            "primer_longer_than_contigs",
+            format_args!(
+                "{} primer(s) are longer than every scanned contig and could never match: {}",
+                names.len(),
+                names.join(", ")
+            ),
+        );
+    }
+}
+
+/// Normal-approximation Poisson interval for the full-scan count behind `sampled_count` hits
+/// seen in a `sample_fraction` sample (see [`ScanOptions::sample_rate`]):
+/// `(n ± z·√n) / sample_fraction` with `z` the two-sided normal quantile of `confidence`,
+/// e.g. `z ≈ 1.96` (the familiar `n ± 2√n`) for `0.95`.
+///
+/// Hits are assumed to fall independently; clustered hits (repeats, tandem sites) vary more
+/// between samples than this interval allows.
+///
+/// # Panics
+/// When `sample_fraction` is not in `(0, 1]` or `confidence` not in `(0, 1)`.
+pub fn poisson_confidence_interval(
+    sampled_count: u64,
+    sample_fraction: f64,
+    confidence: f64,
+) -> (u64, u64) {
+    assert!(
+        sample_fraction > 0.0 && sample_fraction <= 1.0,
+        "sample fraction must be within (0, 1], got {sample_fraction}"
+    );
+    assert!(
+        confidence > 0.0 && confidence < 1.0,
+        "confidence must be within (0, 1), got {confidence}"
+    );
+    let z = upper_normal_quantile((1.0 - confidence) / 2.0);
+    let count = sampled_count as f64;
+    let half_width = z * count.sqrt();
+    (
+        ((count - half_width).max(0.0) / sample_fraction).floor() as u64,
+        ((count + half_width) / sample_fraction).ceil() as u64,
+    )
+}
+
+/// `z` with `P(Z > z) = tail` for a standard normal `Z` and `tail` in `(0, 0.5]`
+/// (Abramowitz & Stegun 26.2.23, absolute error below 4.5e-4).
+fn upper_normal_quantile(tail: f64) -> f64 {
+    let t = (-2.0 * tail.ln()).sqrt();
+    t - (2.515517 + 0.802853 * t + 0.010328 * t * t)
+        / (1.0 + 1.432788 * t + 0.189269 * t * t + 0.001308 * t * t * t)
+}
+
+/// Hex SHA-256 of `hits` in sorted order, one canonical TSV line per hit
+/// (`file contig primer primer_len start end strand mismatches matched`, then `tag` for
+/// tagged hits, so untagged results hash as before tags existed). `file` is the reference's
+/// base name, so the same scan hashes alike from any directory and on any platform.
+///
+/// Input order does not matter, so sorted and `--no-sort` results hash the same.
+pub fn compute_hit_hash(hits: &[Hit]) -> String {
+    hash_hits(hits, false)
+}
+
+/// [`compute_hit_hash`], leaving [`Hit::tag`] out when `ignore_tag` is set.
+pub(crate) fn hash_hits(hits: &[Hit], ignore_tag: bool) -> String {
+    let mut sorted = hits.iter().collect::<Vec<_>>();
+    sorted.sort_by(|a, b| compare_hits(a, b));
+    let mut hasher = HitHasher::new(ignore_tag);
+    for hit in sorted {
+        hasher.update(hit);
+    }
+    hasher.finish()
+}
+
+/// Incremental [`compute_hit_hash`] over hits that already arrive in sorted order.
+struct HitHasher {
+    sha: Sha256,
+    /// See [`ScanOptions::hash_ignores_tag`].
+    ignore_tag: bool,
+}
+
+impl HitHasher {
+    fn new(ignore_tag: bool) -> Self {
+        Self {
+            sha: Sha256::new(),
+            ignore_tag,
+        }
+    }
+
+    fn update(&mut self, hit: &Hit) {
+        let tag = hit.tag.as_deref().filter(|_| !self.ignore_tag);
+        self.sha.update(
+            format!(
+                "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}{}\n",
+                file_base_name(&hit.file),
+                hit.contig,
+                hit.primer,
+                hit.primer_len,
+                hit.start,
+                hit.end,
+                hit.strand,
+                hit.mismatches,
+                hit.matched,
+                tag.map(|tag| format!("\t{tag}")).unwrap_or_default()
+            )
+            .as_bytes(),
+        );
+    }
+
+    fn finish(self) -> String {
+        self.sha
+            .finalize()
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect()
+    }
+}
+
+/// The last component of a rendered reference path, whichever separator it uses.
+fn file_base_name(file: &str) -> &str {
+    file.rsplit(['/', '\\']).next().unwrap_or(file)
+}
+
+/// Removes hits equal in every field to an earlier one from `hits` (sorted by
+/// [`compare_hits`]) and returns them. Duplicates share a sort key, so only hits within a
+/// run of equal keys are compared.
+fn dedup_hits(hits: &mut Vec<Hit>) -> Vec<Hit> {
+    let mut kept: Vec<Hit> = Vec::with_capacity(hits.len());
+    let mut duplicates = Vec::new();
+    let mut run_start = 0;
+    for hit in hits.drain(..) {
+        if kept
+            .last()
+            .is_some_and(|last| compare_hits(last, &hit) != std::cmp::Ordering::Equal)
+        {
+            run_start = kept.len();
+        }
+        if kept[run_start..].contains(&hit) {
+            duplicates.push(hit);
+        } else {
+            kept.push(hit);
+        }
+    }
+    *hits = kept;
+    duplicates
+}
+
+/// Output order of sorted hits: file, contig, primer, start, end, strand, mismatches, and
+/// [`Hit::tag`] (untagged first), which only orders the passes of a merged result.
+///
+/// A hit is identified by its primer, contig, start, and strand, so hits of different primers
+/// at the same coordinates (nested or duplicated primers) are always separate records, never
+/// merged. `end` only differs for the same start with [`ScanOptions::overhang`], where every
+/// window hanging off the contig start has `start` 0.
+fn compare_hits(a: &Hit, b: &Hit) -> std::cmp::Ordering {
+    (
+        &a.file,
+        &a.contig,
+        &a.primer,
+        a.start,
+        a.end,
+        a.strand,
+        a.mismatches,
+        &a.tag,
+    )
+        .cmp(&(
+            &b.file,
+            &b.contig,
+            &b.primer,
+            b.start,
+            b.end,
+            b.strand,
+            b.mismatches,
+            &b.tag,
+        ))
+}
+
+/// Replaces every hit's contig with its [`sanitize_contig_name`] form, keeping the original.
+fn sanitize_hit_contigs(hits: &mut [Hit]) {
+    for hit in hits {
+        let sanitized = sanitize_contig_name(&hit.contig);
+        hit.original_contig = Some(std::mem::replace(&mut hit.contig, sanitized));
+    }
+}
+
+fn sanitize_hit_position_contigs(summary: &mut [PrimerSummary]) {
+    for (_, contig, _) in summary.iter_mut().flat_map(|row| {
+        row.first_hit_position
+            .as_mut()
+            .into_iter()
+            .chain(row.last_hit_position.as_mut())
+    }) {
+        *contig = sanitize_contig_name(contig);
+    }
+}
+
+/// Contig-local hit annotations; every step only looks at hits of one contig.
+fn postprocess_hits(hits: &mut Vec<Hit>, options: &ScanOptions) {
+    if options.collapse_strand_duplicates {
+        collapse_strand_duplicates(hits);
+    }
+    if let Some(transcripts) = &options.transcripts {
+        for hit in hits.iter_mut() {
+            transcripts.annotate(hit);
+        }
+    }
+}
+
+/// Merges `+`/`-` hits of the same primer at identical coordinates into one `=` row
+/// carrying the minimum mismatch count and both per-strand counts.
+fn collapse_strand_duplicates(hits: &mut Vec<Hit>) {
+    let mut seen: HashMap<(String, String, String, usize, usize), usize> = HashMap::new();
+    let mut collapsed: Vec<Hit> = Vec::with_capacity(hits.len());
+
+    for hit in hits.drain(..) {
+        let key = (
+            hit.file.clone(),
+            hit.contig.clone(),
+            hit.primer.clone(),
+            hit.start,
+            hit.end,
+        );
+        if let Some(&idx) = seen.get(&key) {
+            let kept = &mut collapsed[idx];
+            if kept.strand != hit.strand && kept.strand != '=' {
+                let (forward, reverse) = if kept.strand == '+' {
+                    (kept.mismatches, hit.mismatches)
+                } else {
+                    (hit.mismatches, kept.mismatches)
+                };
+                if hit.strand == '+' {
+                    kept.matched = hit.matched;
+                    kept.complexity = hit.complexity;
+                    kept.gc_skew = hit.gc_skew;
+                } else {
+                    kept.primer_reverse_complement = hit.primer_reverse_complement;
+                }
+                kept.strand = '=';
+                kept.mismatches = forward.min(reverse);
+                kept.fwd_mismatches = Some(forward);
+                kept.rev_mismatches = Some(reverse);
+                kept.amplicon_size = kept.amplicon_size.or(hit.amplicon_size);
+                kept.nearest_partner_distance = kept
+                    .nearest_partner_distance
+                    .or(hit.nearest_partner_distance);
+                kept.amplifiable |= hit.amplifiable;
+                // `None < Some(false) < Some(true)`: either strand extending is enough.
+                kept.extendable = kept.extendable.max(hit.extendable);
+                continue;
+            }
+        }
+        seen.insert(key, collapsed.len());
+        collapsed.push(hit);
+    }
+
+    *hits = collapsed;
 }
 
 fn scan_reference_file(
     reference: &Path,
     primers: &[Primer],
     options: &ScanOptions,
+    collector: &mut HitCollector,
 ) -> Result<FileScanResult> {
     let mut reader = open_reader(reference)?;
-    let file_name = reference.display().to_string();
-    let mut line = String::new();
+    let file_name = options.path_style.render(reference);
+    if fastq::looks_like_fastq(&mut reader)
+        .with_context(|| format!("failed reading reference '{}'", reference.display()))?
+    {
+        return fastq::scan_fastq_file(reader, &file_name, primers, options, collector);
+    }
+    let mut chunk = Vec::with_capacity(FASTA_READ_CHUNK_BYTES);
+    let mut header = Vec::new();
+    let mut at_line_start = true;
+    let mut in_header = false;
     let mut contig_name: Option<String> = None;
-    let mut sequence = String::new();
-    let mut collected_hits = Vec::new();
+    let mut sequence = ContigBuffer::new(options);
+    let mut contigs = Vec::new();
     let mut summary_acc = vec![SummaryAccumulator::default(); primers.len()];
     let mut total_hits = 0u64;
+    let mut covered_bases = 0u64;
+    let mut coerced_bases = 0usize;
+    let mut gap_map = GapMap::default();
+    let mut degapped = Vec::new();
+    // 1-based line number and the bytes of that line consumed by earlier chunks.
+    let mut line_number = 0usize;
+    let mut line_offset = 0usize;
+    let mut previous_chunk = (0usize, true);
     let max_contig_bases =
         read_limit_from_env("PRIMER_SCOUT_MAX_CONTIG_BASES", DEFAULT_MAX_CONTIG_BASES);
     let max_fasta_line_bytes = read_limit_from_env(
@@ -320,50 +2074,134 @@ fn scan_reference_file(
         DEFAULT_MAX_FASTA_LINE_BYTES,
     );
 
+    // Lines are consumed in bounded chunks so unwrapped (single-line) contigs never
+    // need a second line-sized buffer next to the contig sequence itself.
     loop {
-        line.clear();
-        let read_bytes = reader
-            .read_line(&mut line)
+        chunk.clear();
+        let read_bytes = (&mut reader)
+            .take(FASTA_READ_CHUNK_BYTES as u64)
+            .read_until(b'\n', &mut chunk)
             .with_context(|| format!("failed reading reference '{}'", reference.display()))?;
-        if read_bytes == 0 {
+        let eof = read_bytes == 0;
+        if eof && (at_line_start || !in_header) {
             break;
         }
-        if read_bytes > max_fasta_line_bytes {
-            bail!(
-                "FASTA line in '{}' exceeds safety limit of {} bytes (override with PRIMER_SCOUT_MAX_FASTA_LINE_BYTES)",
-                reference.display(),
-                max_fasta_line_bytes
-            );
+
+        let line_done = eof || chunk.ends_with(b"\n");
+        match previous_chunk {
+            (_, true) => {
+                line_number += 1;
+                line_offset = 0;
+            }
+            (len, false) => line_offset += len,
+        }
+        previous_chunk = (chunk.len(), line_done);
+        let mut fragment = chunk.as_slice();
+        if at_line_start {
+            fragment = fragment.trim_ascii_start();
+            if fragment.is_empty() && !line_done {
+                continue;
+            }
+            in_header = fragment.first() == Some(&b'>');
+            if in_header {
+                fragment = &fragment[1..];
+            }
+        }
+        // A chunk can end inside a line's trailing whitespace (e.g. the `\r` of a CRLF line
+        // longer than a chunk), so sequence fragments are trimmed even mid-line. Headers keep
+        // mid-line whitespace, which separates the name from the description.
+        if line_done || !in_header {
+            fragment = fragment.trim_ascii_end();
         }
+        at_line_start = line_done;
+
+        if in_header {
+            if header.len().saturating_add(fragment.len()) > max_fasta_line_bytes {
+                bail!(
+                    "FASTA header in '{}' exceeds safety limit of {} bytes (override with PRIMER_SCOUT_MAX_FASTA_LINE_BYTES)",
+                    reference.display(),
+                    max_fasta_line_bytes
+                );
+            }
+            header.extend_from_slice(fragment);
+            if !line_done {
+                continue;
+            }
 
-        let trimmed = line.trim_end_matches(['\n', '\r']).trim();
-        if let Some(header) = trimmed.strip_prefix('>') {
             if let Some(current_contig) = contig_name.take() {
-                let contig_result =
-                    scan_contig(&file_name, &current_contig, &sequence, primers, options)?;
+                report_coerced_bases(&file_name, &current_contig, coerced_bases, options);
+                contigs.push(ContigLength {
+                    file: file_name.clone(),
+                    contig: current_contig.clone(),
+                    length: sequence.len(),
+                    coerced_bases: std::mem::take(&mut coerced_bases),
+                });
+                let mut contig_result =
+                    scan_buffered_contig(&file_name, &current_contig, &sequence, primers, options)?;
                 total_hits += contig_result.total_hits;
-                collected_hits.extend(contig_result.hits);
-                for (acc, delta) in summary_acc
-                    .iter_mut()
-                    .zip(contig_result.summary.into_iter())
-                {
-                    acc.total_hits += delta.total_hits;
-                    acc.perfect_hits += delta.perfect_hits;
-                    acc.forward_hits += delta.forward_hits;
-                    acc.reverse_hits += delta.reverse_hits;
-                    acc.contigs_with_hits += delta.contigs_with_hits;
+                postprocess_hits(&mut contig_result.hits, options);
+                if options.compute_genome_coverage {
+                    covered_bases += regions::covered_bases(&contig_result.hits);
+                }
+                if options.keep_alignment_coords {
+                    translate_to_alignment(&mut contig_result, &gap_map);
+                }
+                collector.push(contig_result.hits)?;
+                for (acc, delta) in summary_acc.iter_mut().zip(contig_result.summary) {
+                    acc.merge(delta);
                 }
                 sequence.clear();
+                gap_map.clear();
+                if options.cancel.is_cancelled() {
+                    break;
+                }
             }
-            contig_name = Some(parse_contig_name(header));
-        } else if !trimmed.is_empty() {
+            if header.trim_ascii().is_empty() {
+                if options.fasta_strictness == FastaStrictness::Strict {
+                    bail!(
+                        "FASTA header on line {} of '{}' has no name",
+                        line_number,
+                        reference.display()
+                    );
+                }
+                options.warnings.warn(
+                    "empty_header",
+                    format_args!(
+                        "FASTA header on line {line_number} of '{file_name}' has no name; using 'unknown_contig'"
+                    ),
+                );
+            }
+            let mut name = parse_contig_name(&String::from_utf8_lossy(&header));
+            if let Some(aliases) = &options.contig_aliases {
+                match aliases.canonical(&name) {
+                    Some(canonical) => name = canonical.to_string(),
+                    None => {
+                        options.warnings.warn(
+                            "unaliased_contig",
+                            format_args!(
+                                "contig '{name}' in '{file_name}' has no entry in the contig aliases; keeping its name"
+                            ),
+                        );
+                    }
+                }
+            }
+            contig_name = Some(name);
+            header.clear();
+            header.shrink_to(FASTA_READ_CHUNK_BYTES);
+        } else if !fragment.is_empty() {
             if contig_name.is_none() {
                 bail!(
                     "invalid FASTA '{}': found sequence before header",
                     reference.display()
                 );
             }
-            let next_len = sequence.len().saturating_add(trimmed.len());
+            let bases = if options.degap {
+                gap_map.degap_into(fragment, sequence.len(), &mut degapped);
+                degapped.as_slice()
+            } else {
+                fragment
+            };
+            let next_len = sequence.len().saturating_add(bases.len());
             if next_len > max_contig_bases {
                 bail!(
                     "contig '{}' in '{}' exceeds safety limit of {} bases (override with PRIMER_SCOUT_MAX_CONTIG_BASES)",
@@ -372,48 +2210,154 @@ fn scan_reference_file(
                     max_contig_bases
                 );
             }
-            sequence.push_str(trimmed);
+            let invalid_base = |base: u8| {
+                iupac_mask(base).is_none() && !(options.degap && alignment::is_gap(base))
+            };
+            let invalid = fragment.iter().filter(|&&base| invalid_base(base)).count();
+            if invalid > 0 {
+                if options.fasta_strictness == FastaStrictness::Strict {
+                    let idx = fragment
+                        .iter()
+                        .position(|&base| invalid_base(base))
+                        .unwrap_or_default();
+                    let column =
+                        line_offset + (fragment.as_ptr() as usize - chunk.as_ptr() as usize) + idx;
+                    bail!(
+                        "invalid sequence character '{}' in contig '{}' at {}:{}:{} (not an IUPAC base)",
+                        fragment[idx].escape_ascii(),
+                        contig_name.as_deref().unwrap_or("unknown_contig"),
+                        reference.display(),
+                        line_number,
+                        column + 1
+                    );
+                }
+                coerced_bases += invalid;
+            }
+            sequence.extend(bases);
         }
     }
 
-    if let Some(current_contig) = contig_name {
-        let contig_result = scan_contig(&file_name, &current_contig, &sequence, primers, options)?;
+    if let Some(current_contig) = contig_name
+        && !options.cancel.is_cancelled()
+    {
+        report_coerced_bases(&file_name, &current_contig, coerced_bases, options);
+        contigs.push(ContigLength {
+            file: file_name.clone(),
+            contig: current_contig.clone(),
+            length: sequence.len(),
+            coerced_bases,
+        });
+        let mut contig_result =
+            scan_buffered_contig(&file_name, &current_contig, &sequence, primers, options)?;
         total_hits += contig_result.total_hits;
-        collected_hits.extend(contig_result.hits);
-        for (acc, delta) in summary_acc
-            .iter_mut()
-            .zip(contig_result.summary.into_iter())
-        {
-            acc.total_hits += delta.total_hits;
-            acc.perfect_hits += delta.perfect_hits;
-            acc.forward_hits += delta.forward_hits;
-            acc.reverse_hits += delta.reverse_hits;
-            acc.contigs_with_hits += delta.contigs_with_hits;
+        postprocess_hits(&mut contig_result.hits, options);
+        if options.compute_genome_coverage {
+            covered_bases += regions::covered_bases(&contig_result.hits);
+        }
+        if options.keep_alignment_coords {
+            translate_to_alignment(&mut contig_result, &gap_map);
+        }
+        collector.push(contig_result.hits)?;
+        for (acc, delta) in summary_acc.iter_mut().zip(contig_result.summary) {
+            acc.merge(delta);
         }
     }
 
     Ok(FileScanResult {
-        hits: collected_hits,
         summary: summary_acc,
         total_hits,
+        contigs,
+        covered_bases,
+        completed: !options.cancel.is_cancelled(),
     })
 }
 
+/// Moves a degapped contig's hits and first/last hit positions to alignment columns.
+fn translate_to_alignment(result: &mut ContigScanResult, gap_map: &GapMap) {
+    for hit in &mut result.hits {
+        gap_map.translate_hit(hit);
+    }
+    for acc in &mut result.summary {
+        for (_, _, start) in acc.first_hit.iter_mut().chain(acc.last_hit.iter_mut()) {
+            *start = gap_map.column(*start);
+        }
+    }
+}
+
+fn report_coerced_bases(file_name: &str, contig_name: &str, count: usize, options: &ScanOptions) {
+    if count > 0 {
+        options.warnings.warn(
+            "coerced_bases",
+            format_args!(
+                "contig '{contig_name}' in '{file_name}' has {count} non-IUPAC character(s) read as N"
+            ),
+        );
+    }
+}
+
+fn scan_buffered_contig(
+    file_name: &str,
+    contig_name: &str,
+    sequence: &ContigBuffer,
+    primers: &[Primer],
+    options: &ScanOptions,
+) -> Result<ContigScanResult> {
+    match sequence {
+        ContigBuffer::Raw(bytes) => scan_contig(file_name, contig_name, bytes, primers, options),
+        ContigBuffer::Packed(packed) => {
+            scan_masks(file_name, contig_name, packed, primers, options)
+        }
+    }
+}
+
 fn scan_contig(
     file_name: &str,
     contig_name: &str,
-    sequence: &str,
+    sequence: &[u8],
     primers: &[Primer],
     options: &ScanOptions,
 ) -> Result<ContigScanResult> {
-    let sequence_bytes: Vec<u8> = sequence.bytes().map(normalize_base).collect();
-    let sequence_masks: Vec<u8> = sequence_bytes
-        .iter()
-        .copied()
-        .map(mask_or_unknown)
-        .collect();
+    match options.memory_profile {
+        MemoryProfile::Fast => scan_masks(
+            file_name,
+            contig_name,
+            &DenseContig::from_raw(sequence, options.lowercase_mismatches),
+            primers,
+            options,
+        ),
+        MemoryProfile::Lean => scan_masks(
+            file_name,
+            contig_name,
+            &PackedContig::from_raw(sequence, options.lowercase_mismatches),
+            primers,
+            options,
+        ),
+    }
+}
 
-    if sequence_bytes.is_empty() {
+fn scan_masks<M: ContigMasks>(
+    file_name: &str,
+    contig_name: &str,
+    contig: &M,
+    primers: &[Primer],
+    options: &ScanOptions,
+) -> Result<ContigScanResult> {
+    if options
+        .regions
+        .as_ref()
+        .is_some_and(|regions| !regions.contains_contig(contig_name))
+    {
+        return Ok(ContigScanResult {
+            hits: Vec::new(),
+            summary: vec![SummaryAccumulator::default(); primers.len()],
+            total_hits: 0,
+        });
+    }
+    if contig.len() == 0 {
+        options.warnings.warn(
+            "empty_contig",
+            format_args!("contig '{contig_name}' in '{file_name}' has no sequence"),
+        );
         return Ok(ContigScanResult {
             hits: Vec::new(),
             summary: vec![SummaryAccumulator::default(); primers.len()],
@@ -421,21 +2365,60 @@ fn scan_contig(
         });
     }
 
-    let per_primer = primers
+    let prescan = ContigPrescan {
+        base_frequencies: base_frequencies(contig),
+        candidates: match options.algorithm {
+            ScanAlgorithm::Exhaustive => None,
+            ScanAlgorithm::Bloom => {
+                let seeds = prescreen::SeedIndex::new(
+                    primers,
+                    options.max_mismatches,
+                    options.scan_reverse_complement,
+                );
+                (!seeds.is_empty()).then(|| seeds.candidate_starts(contig))
+            }
+        },
+    };
+    let mut per_primer = primers
         .par_iter()
         .enumerate()
         .map(|(idx, primer)| {
             scan_primer_in_contig(
                 file_name,
                 contig_name,
-                &sequence_bytes,
-                &sequence_masks,
+                contig,
+                &prescan,
                 primer,
                 idx,
                 options,
             )
         })
         .collect::<Result<Vec<_>>>()?;
+    if options.deduplicate_iupac_equivalent_hits {
+        drop_iupac_instance_hits(&mut per_primer, primers, options);
+    }
+    if let Some(known_sites) = &options.known_sites {
+        for result in &mut per_primer {
+            let primer = &primers[result.primer_index];
+            result.summary.known_suppressed +=
+                drop_hits(result, primer, options, |hit| known_sites.contains(hit));
+        }
+    }
+    if let Some((forward_suffix, reverse_suffix)) = &options.pair_suffix_pattern {
+        annotate_contig_pairs(&mut per_primer, forward_suffix, reverse_suffix, options);
+        if options.only_amplifiable {
+            for result in &mut per_primer {
+                let primer = &primers[result.primer_index];
+                drop_hits(result, primer, options, |hit| !hit.amplifiable);
+            }
+        }
+    }
+    if options.extendable_only {
+        for result in &mut per_primer {
+            let primer = &primers[result.primer_index];
+            drop_hits(result, primer, options, |hit| hit.extendable != Some(true));
+        }
+    }
 
     let mut hits = Vec::new();
     let mut summary = vec![SummaryAccumulator::default(); primers.len()];
@@ -454,11 +2437,19 @@ fn scan_contig(
     })
 }
 
-fn scan_primer_in_contig(
+/// Per-contig values computed once before the per-primer scans.
+struct ContigPrescan {
+    base_frequencies: [f64; 4],
+    /// Window starts worth testing per primer under [`ScanAlgorithm::Bloom`]; `None` (for the
+    /// whole panel or one primer) tests every window.
+    candidates: Option<Vec<Option<Vec<std::ops::Range<usize>>>>>,
+}
+
+fn scan_primer_in_contig<M: ContigMasks>(
     file_name: &str,
     contig_name: &str,
-    sequence_bytes: &[u8],
-    sequence_masks: &[u8],
+    contig: &M,
+    prescan: &ContigPrescan,
     primer: &Primer,
     primer_index: usize,
     options: &ScanOptions,
@@ -466,7 +2457,7 @@ fn scan_primer_in_contig(
     if primer.is_empty() {
         bail!("primer '{}' has zero length", primer.name);
     }
-    if sequence_bytes.len() < primer.len() {
+    if contig.len() < primer.len() {
         return Ok(PerPrimerContigResult {
             primer_index,
             hits: Vec::new(),
@@ -476,38 +2467,83 @@ fn scan_primer_in_contig(
 
     let mut summary = SummaryAccumulator::default();
     let mut hits = Vec::new();
-
-    scan_orientation(
-        sequence_bytes,
-        sequence_masks,
-        primer,
-        &primer.masks,
-        '+',
-        options.max_mismatches,
+    let window_starts = match &options.regions {
+        Some(regions) => regions.window_starts(contig_name, contig.len(), primer.len()),
+        None => std::iter::once(0..contig.len() - primer.len() + 1).collect(),
+    };
+    summary.had_window |= window_starts.iter().any(|range| !range.is_empty());
+    let window_starts = match options.sample_rate {
+        Some(rate) => sample_window_starts(window_starts, contig_name, rate, options),
+        None => window_starts,
+    };
+    let windows = window_starts
+        .iter()
+        .map(ExactSizeIterator::len)
+        .sum::<usize>() as f64;
+    let (max_mismatches, base_frequencies) = (options.max_mismatches, &prescan.base_frequencies);
+    summary.expected_forward = windows
+        * match_probability(
+            primer.masks.iter().copied(),
+            base_frequencies,
+            max_mismatches,
+        );
+    if options.scan_reverse_complement && !primer.is_palindromic {
+        let reverse_masks = primer.combined_masks.iter().map(|mask| mask >> 4);
+        summary.expected_reverse =
+            windows * match_probability(reverse_masks, base_frequencies, max_mismatches);
+    }
+    // Expectations above cover every window; only the seeded candidates are tested.
+    let window_starts = match prescan
+        .candidates
+        .as_ref()
+        .and_then(|candidates| candidates[primer_index].as_deref())
+    {
+        Some(candidates) => intersect_ranges(&window_starts, candidates),
+        None => window_starts,
+    };
+    let builder = HitBuilder {
         file_name,
         contig_name,
+        primer,
+        options,
+        deadline: options
+            .time_limit_per_primer
+            .map(|limit| Instant::now() + limit),
+    };
+
+    let both_strands = options.scan_reverse_complement && !primer.is_palindromic;
+    scan_windows(
+        contig,
+        &window_starts,
+        both_strands,
+        &builder,
         &mut summary,
         &mut hits,
     );
-
-    if options.scan_reverse_complement && !primer.is_palindromic {
-        scan_orientation(
-            sequence_bytes,
-            sequence_masks,
-            primer,
-            &primer.reverse_masks,
-            '-',
-            options.max_mismatches,
-            file_name,
-            contig_name,
-            &mut summary,
-            &mut hits,
-        );
+    if options.overhang.is_some() && options.sample_rate.is_none() {
+        scan_overhangs(contig, both_strands, &builder, &mut summary, &mut hits);
+    }
+    if options.scan_reverse_complement && primer.is_palindromic {
+        // The `+` scan already covers the minus strand; report those sites on both strands.
+        summary.reverse_hits += summary.forward_hits;
     }
 
+    if summary.timed_out {
+        options.warnings.warn(
+            "primer_time_limit",
+            format_args!(
+                "primer '{}' exceeded the per-primer time limit on contig '{contig_name}' in \
+                 '{file_name}'; its hits and counts are incomplete",
+                primer.name
+            ),
+        );
+    }
     if summary.total_hits > 0 {
         summary.contigs_with_hits = 1;
     }
+    let position = |start: usize| (file_name.to_string(), contig_name.to_string(), start);
+    summary.first_hit = hits.iter().map(|hit| hit.start).min().map(position);
+    summary.last_hit = hits.iter().map(|hit| hit.start).max().map(position);
 
     Ok(PerPrimerContigResult {
         primer_index,
@@ -516,58 +2552,539 @@ fn scan_primer_in_contig(
     })
 }
 
-#[allow(clippy::too_many_arguments)]
-fn scan_orientation(
-    sequence_bytes: &[u8],
-    sequence_masks: &[u8],
-    primer: &Primer,
-    query_masks: &[u8],
-    strand: char,
+/// Starts in both `a` and `b`, each sorted and disjoint.
+fn intersect_ranges(
+    a: &[std::ops::Range<usize>],
+    b: &[std::ops::Range<usize>],
+) -> Vec<std::ops::Range<usize>> {
+    let (mut i, mut j) = (0, 0);
+    let mut out = Vec::new();
+    while i < a.len() && j < b.len() {
+        let (start, end) = (a[i].start.max(b[j].start), a[i].end.min(b[j].end));
+        if start < end {
+            out.push(start..end);
+        }
+        if a[i].end < b[j].end {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    out
+}
+
+/// A/C/G/T frequencies of `contig`, ignoring `N`, other IUPAC codes, and masked bases.
+fn base_frequencies<M: ContigMasks>(contig: &M) -> [f64; 4] {
+    let mut counts = [0u64; 4];
+    for idx in 0..contig.len() {
+        let mask = contig.mask(idx);
+        if mask.is_power_of_two() && mask < 0x10 {
+            counts[mask.trailing_zeros() as usize] += 1;
+        }
+    }
+    let total = counts.iter().sum::<u64>().max(1) as f64;
+    counts.map(|count| count as f64 / total)
+}
+
+/// Probability that a random window whose bases are drawn independently with
+/// `base_frequencies` (`[A, C, G, T]`, summing to 1) matches `sequence` (IUPAC codes allowed)
+/// with at most `max_mismatches` substitutions. An IUPAC position matches with the summed
+/// frequency of the bases it allows, so `N` always matches.
+pub fn random_window_match_probability(
+    sequence: &str,
+    base_frequencies: &[f64; 4],
     max_mismatches: usize,
-    file_name: &str,
+) -> Result<f64> {
+    let (normalized, _) = normalize_sequence(sequence)?;
+    let masks = to_masks(&normalized)?;
+    Ok(match_probability(
+        masks.into_iter(),
+        base_frequencies,
+        max_mismatches,
+    ))
+}
+
+/// [`random_window_match_probability`] over primer masks: the mismatch count follows a
+/// Poisson-binomial distribution, tracked up to `max_mismatches`.
+fn match_probability(
+    masks: impl Iterator<Item = u8>,
+    base_frequencies: &[f64; 4],
+    max_mismatches: usize,
+) -> f64 {
+    // `exactly[j]`: probability of exactly `j` mismatches among the positions so far.
+    let mut exactly = vec![0.0; max_mismatches + 1];
+    exactly[0] = 1.0;
+    for mask in masks {
+        let matches = (0..4)
+            .filter(|&bit| mask & (1 << bit) != 0)
+            .map(|bit| base_frequencies[bit])
+            .sum::<f64>();
+        for j in (0..=max_mismatches).rev() {
+            let one_more = if j > 0 { exactly[j - 1] } else { 0.0 };
+            exactly[j] = exactly[j] * matches + one_more * (1.0 - matches);
+        }
+    }
+    exactly.iter().sum()
+}
+
+/// The parts of `window_starts` inside the blocks [`ScanOptions::sample_rate`] keeps.
+fn sample_window_starts(
+    window_starts: Vec<std::ops::Range<usize>>,
     contig_name: &str,
+    rate: f64,
+    options: &ScanOptions,
+) -> Vec<std::ops::Range<usize>> {
+    let end = window_starts
+        .iter()
+        .map(|range| range.end)
+        .max()
+        .unwrap_or(0);
+    let mut rng = options.rng_for(&format!("sample_rate:{contig_name}"));
+    let kept = (0..end.div_ceil(SAMPLE_BLOCK_WINDOWS))
+        .map(|_| (rng.next_u64() as f64) < rate * u64::MAX as f64)
+        .collect::<Vec<_>>();
+    let mut sampled = Vec::new();
+    for range in window_starts.iter().filter(|range| !range.is_empty()) {
+        let first_block = range.start / SAMPLE_BLOCK_WINDOWS;
+        let blocks = &kept[first_block..=(range.end - 1) / SAMPLE_BLOCK_WINDOWS];
+        for (block, _) in (first_block..).zip(blocks).filter(|(_, kept)| **kept) {
+            let block_start = block * SAMPLE_BLOCK_WINDOWS;
+            sampled.push(
+                range.start.max(block_start)..range.end.min(block_start + SAMPLE_BLOCK_WINDOWS),
+            );
+        }
+    }
+    sampled
+}
+
+/// `(start, strand, mismatches)` of each window in `starts` within `max_mismatches` of
+/// `primer`, by start (a window's `+` before its `-`). With `both_strands`, one pass over the
+/// reference masks tests both strands using the primer's combined masks.
+///
+/// The one window loop behind both [`scan_windows`] and the lazy [`scan_one`].
+fn window_matches<M: ContigMasks>(
+    contig: M,
+    starts: impl Iterator<Item = usize>,
+    primer: &Primer,
+    both_strands: bool,
+    max_mismatches: usize,
+) -> impl Iterator<Item = (usize, char, usize)> {
+    starts.flat_map(move |start| {
+        let (forward, reverse) = if both_strands {
+            let (forward, reverse) = window_mismatches(
+                |idx| contig.mask(idx),
+                start,
+                &primer.combined_masks,
+                max_mismatches,
+            );
+            (forward, Some(reverse))
+        } else {
+            let forward =
+                forward_mismatches(|idx| contig.mask(idx), start, &primer.masks, max_mismatches);
+            (forward, None)
+        };
+        [
+            (forward <= max_mismatches).then_some((start, '+', forward)),
+            reverse
+                .filter(|&reverse| reverse <= max_mismatches)
+                .map(|reverse| (start, '-', reverse)),
+        ]
+        .into_iter()
+        .flatten()
+    })
+}
+
+/// Records the [`window_matches`] of `window_starts`, stopping on cancellation or at the
+/// primer's deadline. Reverse hits are appended after forward hits, matching the order of one
+/// pass per strand.
+fn scan_windows<M: ContigMasks>(
+    contig: &M,
+    window_starts: &[std::ops::Range<usize>],
+    both_strands: bool,
+    builder: &HitBuilder<'_>,
     summary: &mut SummaryAccumulator,
     hits: &mut Vec<Hit>,
 ) {
-    let window_len = query_masks.len();
-    let last_start = sequence_masks.len() - window_len;
-
-    for start in 0..=last_start {
-        let mut mismatches = 0usize;
-        for (offset, &query_mask) in query_masks.iter().enumerate() {
-            if (query_mask & sequence_masks[start + offset]) == 0 {
-                mismatches += 1;
-                if mismatches > max_mismatches {
-                    break;
+    let primer = builder.primer;
+    let mut timed_out = false;
+    let starts = window_starts.iter().cloned().flatten().take_while(|start| {
+        !(start.is_multiple_of(CANCEL_CHECK_INTERVAL) && builder.should_stop(&mut timed_out))
+    });
+    let matches = window_matches(
+        contig,
+        starts,
+        primer,
+        both_strands,
+        builder.options.max_mismatches,
+    );
+    let mut reverse_hits = Vec::new();
+    for (start, strand, mismatches) in matches {
+        let strand_hits = if strand == '+' {
+            &mut *hits
+        } else {
+            &mut reverse_hits
+        };
+        builder.push(
+            contig,
+            start..start + primer.len(),
+            strand,
+            mismatches,
+            summary,
+            strand_hits,
+        );
+    }
+    hits.append(&mut reverse_hits);
+    summary.timed_out |= timed_out;
+}
+
+/// Tests the windows hanging `1..` primer bases off either contig end, up to `max_mismatches`
+/// bases with [`OverhangScoring::Mismatch`] or half the primer with
+/// [`OverhangScoring::Exclude`]. An end is tested only when its base is inside
+/// [`ScanOptions::regions`] (if set).
+fn scan_overhangs<M: ContigMasks>(
+    contig: &M,
+    both_strands: bool,
+    builder: &HitBuilder<'_>,
+    summary: &mut SummaryAccumulator,
+    hits: &mut Vec<Hit>,
+) {
+    let (primer, options) = (builder.primer, builder.options);
+    let Some(scoring) = options.overhang else {
+        return;
+    };
+    let (primer_len, contig_len) = (primer.len(), contig.len());
+    let max_mismatches = options.max_mismatches;
+    let max_overhang = match scoring {
+        OverhangScoring::Mismatch => max_mismatches,
+        OverhangScoring::Exclude => primer_len / 2,
+    }
+    .min(primer_len - 1);
+    let scanned = |idx: usize| {
+        options.regions.as_ref().is_none_or(|regions| {
+            regions
+                .intervals(builder.contig_name)
+                .iter()
+                .any(|range| range.contains(&idx))
+        })
+    };
+    let (left_end, right_end) = (scanned(0), scanned(contig_len - 1));
+    let reverse_masks = primer
+        .combined_masks
+        .iter()
+        .map(|mask| mask >> 4)
+        .collect::<Vec<_>>();
+    let mut strands = vec![('+', primer.masks.as_slice())];
+    if both_strands {
+        strands.push(('-', reverse_masks.as_slice()));
+    }
+
+    let found = hits.len();
+    for overhang in 1..=max_overhang {
+        let overlap = primer_len - overhang;
+        let scored_overhang = match scoring {
+            OverhangScoring::Mismatch => overhang,
+            OverhangScoring::Exclude => 0,
+        };
+        for &(strand, masks) in &strands {
+            // Off the start, the primer's first `overhang` bases precede the contig; off the
+            // end, its last `overhang` bases follow it.
+            let sides = [
+                (left_end, 0, &masks[overhang..]),
+                (right_end, contig_len - overlap, &masks[..overlap]),
+            ];
+            for (tested, start, masks) in sides {
+                if !tested {
+                    continue;
+                }
+                let mismatches = scored_overhang
+                    + forward_mismatches(
+                        |idx| contig.mask(idx),
+                        start,
+                        masks,
+                        max_mismatches - scored_overhang,
+                    );
+                if mismatches <= max_mismatches {
+                    builder.push(
+                        contig,
+                        start..start + overlap,
+                        strand,
+                        mismatches,
+                        summary,
+                        hits,
+                    );
                 }
             }
         }
+    }
+    if hits.len() > found {
+        // Restore the window loops' layout: `+` hits by start, then `-` hits by start.
+        hits.sort_by_key(|hit| (hit.strand != '+', hit.start));
+    }
+}
 
-        if mismatches <= max_mismatches {
-            summary.total_hits += 1;
-            if mismatches == 0 {
-                summary.perfect_hits += 1;
-            }
-            if strand == '+' {
-                summary.forward_hits += 1;
+/// Per-primer context shared by the window loops for turning a passing window into a hit.
+struct HitBuilder<'a> {
+    file_name: &'a str,
+    contig_name: &'a str,
+    primer: &'a Primer,
+    options: &'a ScanOptions,
+    /// End of this primer's [`ScanOptions::time_limit_per_primer`] budget on the contig.
+    deadline: Option<Instant>,
+}
+
+impl HitBuilder<'_> {
+    /// True when the scan is cancelled or the primer ran out of time; the latter sets
+    /// `timed_out`.
+    fn should_stop(&self, timed_out: &mut bool) -> bool {
+        if self.options.cancel.is_cancelled() {
+            return true;
+        }
+        if self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            *timed_out = true;
+            return true;
+        }
+        false
+    }
+
+    /// Records the hit covering `span` of the contig: the full primer length, or less for a
+    /// window hanging off a contig end.
+    fn push<M: ContigMasks>(
+        &self,
+        contig: &M,
+        span: std::ops::Range<usize>,
+        strand: char,
+        mismatches: usize,
+        summary: &mut SummaryAccumulator,
+        hits: &mut Vec<Hit>,
+    ) {
+        let (primer, options) = (self.primer, self.options);
+        let (start, end) = (span.start, span.end);
+        let overhang = primer.len() - span.len();
+        let matched = contig.matched(start, end);
+        let complexity = sequence_complexity(&matched);
+        if options
+            .min_hit_complexity
+            .is_some_and(|min_complexity| complexity < min_complexity)
+        {
+            return;
+        }
+        // Bases hanging off the start have no counterpart on the contig.
+        let primer_offset = if start == 0 { overhang } else { 0 };
+        let (ambiguous_positions, matches_via_ambiguity) =
+            reference_ambiguity(contig, span.clone(), primer, strand, primer_offset);
+        if options
+            .max_ambiguous_positions
+            .is_some_and(|max_ambiguous| ambiguous_positions > max_ambiguous)
+        {
+            return;
+        }
+
+        summary.total_hits += 1;
+        summary.total_mismatches += mismatches as u64;
+        summary.total_mismatches_squared += (mismatches * mismatches) as u64;
+        summary.record_mismatches(mismatches);
+        if mismatches == 0 {
+            summary.perfect_hits += 1;
+        }
+        if strand == '+' {
+            summary.forward_hits += 1;
+        } else {
+            summary.reverse_hits += 1;
+        }
+
+        let mut hit = Hit {
+            file: self.file_name.to_string(),
+            contig: self.contig_name.to_string(),
+            primer: primer.name.clone(),
+            primer_len: primer.len(),
+            start,
+            end,
+            strand,
+            mismatches,
+            complexity,
+            matched_fraction: span.len() as f64 / primer.len() as f64,
+            amplicon_size: None,
+            nearest_partner_distance: None,
+            amplifiable: false,
+            primer_sequence: options
+                .include_primer_sequence
+                .then(|| primer.sequence.clone()),
+            primer_reverse_complement: (options.include_primer_sequence && strand == '-')
+                .then(|| primer.reverse_complement.clone()),
+            fwd_mismatches: None,
+            rev_mismatches: None,
+            gc_skew: options.include_gc_skew.then(|| gc_skew(&matched)),
+            transcript: None,
+            transcript_pos: None,
+            transcript_region: None,
+            contig_relative_position: options
+                .include_relative_position
+                .then(|| start as f64 / contig.len() as f64),
+            masked_sequence: options
+                .include_masked_sequence
+                .then(|| masked_sequence(&matched, primer, strand, primer_offset)),
+            matched,
+            overhang,
+            repeat_class: None,
+            ambiguous_positions,
+            matches_via_ambiguity,
+            extendable: None,
+            original_contig: None,
+            tag: options.tag.clone(),
+        };
+        if options.predict_extension {
+            hit.extendable = Some(extension::is_extendable(&hit, primer));
+        }
+        hits.push(hit);
+    }
+}
+
+/// Reference positions of `span` with more than one base in their mask, and how many of
+/// those match the primer (read on `strand`, from `primer_offset`).
+fn reference_ambiguity<M: ContigMasks>(
+    contig: &M,
+    span: std::ops::Range<usize>,
+    primer: &Primer,
+    strand: char,
+    primer_offset: usize,
+) -> (usize, usize) {
+    let (mut ambiguous, mut via_ambiguity) = (0, 0);
+    for (idx, &combined) in span.zip(&primer.combined_masks[primer_offset..]) {
+        let reference = contig.mask(idx);
+        if reference.count_ones() > 1 {
+            ambiguous += 1;
+            let query = match strand {
+                '-' => combined >> 4,
+                _ => combined & 0x0f,
+            };
+            via_ambiguity += usize::from(query & reference != 0);
+        }
+    }
+    (ambiguous, via_ambiguity)
+}
+
+/// `matched` lowercased where the primer (read on `strand`, from `primer_offset`) allows more
+/// than one base.
+fn masked_sequence(matched: &str, primer: &Primer, strand: char, primer_offset: usize) -> String {
+    let masks = primer.combined_masks[primer_offset..]
+        .iter()
+        .map(|&mask| match strand {
+            '-' => mask >> 4,
+            _ => mask & 0x0f,
+        });
+    matched
+        .chars()
+        .zip(masks)
+        .map(|(base, mask)| {
+            if mask.is_power_of_two() {
+                base.to_ascii_uppercase()
             } else {
-                summary.reverse_hits += 1;
+                base.to_ascii_lowercase()
             }
+        })
+        .collect()
+}
 
-            hits.push(Hit {
-                file: file_name.to_string(),
-                contig: contig_name.to_string(),
-                primer: primer.name.clone(),
-                primer_len: primer.len(),
-                start,
-                end: start + primer.len(),
-                strand,
-                mismatches,
-                matched: String::from_utf8_lossy(&sequence_bytes[start..start + primer.len()])
-                    .to_string(),
-            });
+/// True when `specific` is a different primer whose expansions all belong to `degenerate`.
+fn is_strict_iupac_instance(specific: &Primer, degenerate: &Primer) -> bool {
+    specific.masks.len() == degenerate.masks.len()
+        && specific.masks != degenerate.masks
+        && specific
+            .masks
+            .iter()
+            .zip(&degenerate.masks)
+            .all(|(&narrow, &wide)| narrow & !wide == 0)
+}
+
+/// Removes hits of primers that are strict IUPAC instances of another primer hitting the
+/// same site and strand, then takes them back out of the per-primer summaries.
+fn drop_iupac_instance_hits(
+    per_primer: &mut [PerPrimerContigResult],
+    primers: &[Primer],
+    options: &ScanOptions,
+) {
+    let mut sites: HashMap<(usize, usize, char), Vec<usize>> = HashMap::new();
+    for result in per_primer.iter() {
+        for hit in &result.hits {
+            sites
+                .entry((hit.start, hit.end, hit.strand))
+                .or_default()
+                .push(result.primer_index);
         }
     }
+    let shadowed = |primer_index: usize, hit: &Hit| {
+        sites[&(hit.start, hit.end, hit.strand)]
+            .iter()
+            .any(|&other| is_strict_iupac_instance(&primers[primer_index], &primers[other]))
+    };
+
+    for result in per_primer.iter_mut() {
+        let primer_index = result.primer_index;
+        drop_hits(result, &primers[primer_index], options, |hit| {
+            shadowed(primer_index, hit)
+        });
+    }
+}
+
+/// [`pairing::annotate_pairs`] over the hits of every primer on one contig, leaving each hit
+/// with its primer's result.
+fn annotate_contig_pairs(
+    per_primer: &mut [PerPrimerContigResult],
+    forward_suffix: &str,
+    reverse_suffix: &str,
+    options: &ScanOptions,
+) {
+    let lengths = per_primer
+        .iter()
+        .map(|result| result.hits.len())
+        .collect::<Vec<_>>();
+    let mut hits = per_primer
+        .iter_mut()
+        .flat_map(|result| std::mem::take(&mut result.hits))
+        .collect::<Vec<_>>();
+    pairing::annotate_pairs(
+        &mut hits,
+        forward_suffix,
+        reverse_suffix,
+        options.max_amplicon_size,
+        options.partner_window,
+    );
+    let mut hits = hits.into_iter();
+    for (result, len) in per_primer.iter_mut().zip(lengths) {
+        result.hits = hits.by_ref().take(len).collect();
+    }
+}
+
+/// Removes `result`'s hits for which `drop` is true and takes them back out of its summary.
+/// Returns how many were removed.
+fn drop_hits(
+    result: &mut PerPrimerContigResult,
+    primer: &Primer,
+    options: &ScanOptions,
+    drop: impl Fn(&Hit) -> bool,
+) -> u64 {
+    let (dropped, kept): (Vec<Hit>, Vec<Hit>) =
+        std::mem::take(&mut result.hits).into_iter().partition(drop);
+    result.hits = kept;
+    if dropped.is_empty() {
+        return 0;
+    }
+
+    let summary = &mut result.summary;
+    for hit in &dropped {
+        summary.remove_hit(
+            hit,
+            options.scan_reverse_complement && primer.is_palindromic,
+        );
+    }
+    summary.contigs_with_hits = u64::from(summary.total_hits > 0);
+    let position = |hit: &Hit| (hit.file.clone(), hit.contig.clone(), hit.start);
+    summary.first_hit = result.hits.iter().min_by_key(|hit| hit.start).map(position);
+    summary.last_hit = result.hits.iter().max_by_key(|hit| hit.start).map(position);
+    dropped.len() as u64
 }
 
 #[derive(Debug, Default, Clone)]
@@ -577,13 +3094,94 @@ struct SummaryAccumulator {
     forward_hits: u64,
     reverse_hits: u64,
     contigs_with_hits: u64,
+    first_hit: Option<(String, String, usize)>,
+    last_hit: Option<(String, String, usize)>,
+    /// At least one window of a contig (or region) was long enough to test this primer.
+    had_window: bool,
+    known_suppressed: u64,
+    timed_out: bool,
+    expected_forward: f64,
+    expected_reverse: f64,
+    total_mismatches: u64,
+    total_mismatches_squared: u64,
+    /// Hits per mismatch count, grown as higher counts appear.
+    counts_by_mismatch: Vec<u64>,
+}
+
+impl SummaryAccumulator {
+    /// Takes `hit` back out of the counts; `both_strands` when its `+` site was also counted
+    /// as a `-` hit (palindromic primer). Positions and contig counts are left to the caller.
+    fn remove_hit(&mut self, hit: &Hit, both_strands: bool) {
+        self.total_hits -= 1;
+        self.total_mismatches -= hit.mismatches as u64;
+        self.total_mismatches_squared -= (hit.mismatches * hit.mismatches) as u64;
+        self.counts_by_mismatch[hit.mismatches] -= 1;
+        if hit.mismatches == 0 {
+            self.perfect_hits -= 1;
+        }
+        if hit.strand == '+' {
+            self.forward_hits -= 1;
+            if both_strands {
+                self.reverse_hits -= 1;
+            }
+        } else {
+            self.reverse_hits -= 1;
+        }
+    }
+
+    fn record_mismatches(&mut self, mismatches: usize) {
+        if self.counts_by_mismatch.len() <= mismatches {
+            self.counts_by_mismatch.resize(mismatches + 1, 0);
+        }
+        self.counts_by_mismatch[mismatches] += 1;
+    }
+
+    /// Folds in a later contig or file; on equal starts the earlier-scanned hit is kept.
+    fn merge(&mut self, delta: SummaryAccumulator) {
+        self.total_hits += delta.total_hits;
+        self.perfect_hits += delta.perfect_hits;
+        self.forward_hits += delta.forward_hits;
+        self.reverse_hits += delta.reverse_hits;
+        self.contigs_with_hits += delta.contigs_with_hits;
+        self.had_window |= delta.had_window;
+        self.known_suppressed += delta.known_suppressed;
+        self.timed_out |= delta.timed_out;
+        self.expected_forward += delta.expected_forward;
+        self.expected_reverse += delta.expected_reverse;
+        self.total_mismatches += delta.total_mismatches;
+        self.total_mismatches_squared += delta.total_mismatches_squared;
+        if self.counts_by_mismatch.len() < delta.counts_by_mismatch.len() {
+            self.counts_by_mismatch
+                .resize(delta.counts_by_mismatch.len(), 0);
+        }
+        for (count, delta) in self
+            .counts_by_mismatch
+            .iter_mut()
+            .zip(delta.counts_by_mismatch)
+        {
+            *count += delta;
+        }
+        if let Some(first) = delta.first_hit
+            && self.first_hit.as_ref().is_none_or(|kept| first.2 < kept.2)
+        {
+            self.first_hit = Some(first);
+        }
+        if let Some(last) = delta.last_hit
+            && self.last_hit.as_ref().is_none_or(|kept| last.2 > kept.2)
+        {
+            self.last_hit = Some(last);
+        }
+    }
 }
 
 #[derive(Debug)]
 struct FileScanResult {
-    hits: Vec<Hit>,
     summary: Vec<SummaryAccumulator>,
     total_hits: u64,
+    contigs: Vec<ContigLength>,
+    /// Bases inside at least one hit, counted when `compute_genome_coverage` is on.
+    covered_bases: u64,
+    completed: bool,
 }
 
 #[derive(Debug)]
@@ -652,113 +3250,276 @@ fn is_header(name: &str, sequence: &str) -> bool {
         && (right == "sequence" || right == "primer" || right == "seq")
 }
 
-fn normalize_query(raw: &str) -> Result<String> {
-    let mut normalized = String::with_capacity(raw.len());
-    for ch in raw.chars() {
-        if ch.is_whitespace() {
-            continue;
-        }
-        let c = normalize_base(ch as u8) as char;
-        if iupac_mask(c as u8).is_none() {
-            bail!("unsupported base '{ch}' in primer sequence");
-        }
-        normalized.push(c);
-    }
-    Ok(normalized)
+fn mask_or_unknown(base: u8) -> u8 {
+    iupac_mask(base).unwrap_or(0b1111)
 }
 
-fn reverse_complement(sequence: &str) -> Result<String> {
-    let mut out = String::with_capacity(sequence.len());
-    for ch in sequence.bytes().rev() {
-        let comp = complement_base(ch)
-            .with_context(|| format!("unsupported base '{}' for reverse complement", ch as char))?;
-        out.push(comp as char);
+fn reference_mask(base: u8, lowercase_mismatches: bool) -> u8 {
+    if lowercase_mismatches && base.is_ascii_lowercase() {
+        0
+    } else {
+        mask_or_unknown(base)
     }
-    Ok(out)
 }
 
-fn to_masks(sequence: &str) -> Result<Vec<u8>> {
-    let mut out = Vec::with_capacity(sequence.len());
-    for ch in sequence.bytes() {
-        out.push(
-            iupac_mask(ch)
-                .with_context(|| format!("unsupported base '{}' in primer", ch as char))?,
-        );
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::tmp_path;
+
+    #[test]
+    fn reverse_complement_handles_iupac() {
+        let rc = crate::core::reverse_complement("ATGCRY").expect("reverse complement should work");
+        assert_eq!(rc, "RYGCAT");
     }
-    Ok(out)
-}
 
-fn normalize_base(base: u8) -> u8 {
-    match base {
-        b'u' | b'U' => b'T',
-        _ => base.to_ascii_uppercase(),
+    #[test]
+    fn load_primers_from_reader_honors_explicit_formats() {
+        let tsv = "name\tsequence\nfwd\tACGTACGT\nrev\tggccaatt\n";
+        let primers = load_primers_from_reader(tsv.as_bytes(), PrimerFormat::Tsv, "<stdin>")
+            .expect("load tsv");
+        let loaded = primers
+            .iter()
+            .map(|primer| (primer.name.as_str(), primer.sequence.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(loaded, vec![("fwd", "ACGTACGT"), ("rev", "GGCCAATT")]);
+
+        let jsonl = "{\"name\":\"a\",\"sequence\":\"ACGT\"}\n{\"sequence\":\"TTGG\"}\n";
+        let primers = load_primers_from_reader(jsonl.as_bytes(), PrimerFormat::Jsonl, "<stdin>")
+            .expect("load jsonl");
+        assert_eq!(primers[0].name, "a");
+        assert_eq!(primers[1].name, "primer_0002");
+
+        let pooled = "name\tsequence\tpool\np1\tACGT\tA\np2\tTTGG\t\n";
+        let primers = load_primers_from_reader(pooled.as_bytes(), PrimerFormat::Tsv, "<stdin>")
+            .expect("load pooled tsv");
+        let pools = primers
+            .iter()
+            .map(|primer| primer.pool.as_deref())
+            .collect::<Vec<_>>();
+        assert_eq!(pools, [Some("A"), None]);
+        let jsonl = "{\"name\":\"a\",\"sequence\":\"ACGT\",\"pool\":\"B\"}\n";
+        let primers = load_primers_from_reader(jsonl.as_bytes(), PrimerFormat::Jsonl, "<stdin>")
+            .expect("load pooled jsonl");
+        assert_eq!(primers[0].pool.as_deref(), Some("B"));
+
+        let err = load_primers_from_reader("ACGT\n".as_bytes(), PrimerFormat::Fasta, "<stdin>")
+            .expect_err("sequence before header");
+        assert!(err.to_string().contains("<stdin>"));
     }
-}
 
-fn mask_or_unknown(base: u8) -> u8 {
-    iupac_mask(base).unwrap_or(0b1111)
-}
+    #[test]
+    fn fasta_strictness_controls_malformed_references() {
+        let reference = tmp_path("strictness.fa");
+        let primers = vec![Primer::from_name_and_sequence("p1", "ACGT").expect("primer")];
+        let scan = |text: &str, strictness| {
+            std::fs::write(&reference, text).expect("write reference");
+            let options = ScanOptions {
+                fasta_strictness: strictness,
+                ..ScanOptions::default()
+            };
+            scan_references(std::slice::from_ref(&reference), &primers, &options)
+        };
 
-fn complement_base(base: u8) -> Option<u8> {
-    match normalize_base(base) {
-        b'A' => Some(b'T'),
-        b'C' => Some(b'G'),
-        b'G' => Some(b'C'),
-        b'T' => Some(b'A'),
-        b'R' => Some(b'Y'),
-        b'Y' => Some(b'R'),
-        b'S' => Some(b'S'),
-        b'W' => Some(b'W'),
-        b'K' => Some(b'M'),
-        b'M' => Some(b'K'),
-        b'B' => Some(b'V'),
-        b'D' => Some(b'H'),
-        b'H' => Some(b'D'),
-        b'V' => Some(b'B'),
-        b'N' => Some(b'N'),
-        _ => None,
+        let malformed = ">chr1 desc with spaces\nACGT12\nAC*GT-AC\n>\nACGT\n";
+        let lenient = scan(malformed, FastaStrictness::Lenient).expect("lenient scan");
+        let coerced = lenient
+            .contigs
+            .iter()
+            .map(|contig| (contig.contig.as_str(), contig.coerced_bases))
+            .collect::<Vec<_>>();
+        assert_eq!(coerced, vec![("chr1", 4), ("unknown_contig", 0)]);
+        assert_eq!(lenient.warnings.get("coerced_bases"), Some(&1));
+        assert_eq!(lenient.warnings.get("empty_header"), Some(&1));
+
+        let strict_err = |text: &str| {
+            scan(text, FastaStrictness::Strict)
+                .expect_err("strict scan rejects")
+                .to_string()
+        };
+        let digits = strict_err(">chr1\nACGT\nACG7T\n");
+        assert!(
+            digits.contains("'7'") && digits.contains(":3:4"),
+            "{digits}"
+        );
+        let stop = strict_err(">chr1\n  AC*T\n");
+        assert!(stop.contains("'*'") && stop.contains(":2:5"), "{stop}");
+        let gap = strict_err(">chr1\nAC-T\n");
+        assert!(gap.contains("'-'") && gap.contains(":2:3"), "{gap}");
+        let nameless = strict_err(">chr1\nACGT\n>  \nACGT\n");
+        assert!(nameless.contains("line 3"), "{nameless}");
+        assert!(scan(">chr1 any description\nacgtNRY\n", FastaStrictness::Strict).is_ok());
+
+        std::fs::remove_file(reference).expect("remove reference");
     }
-}
 
-fn iupac_mask(base: u8) -> Option<u8> {
-    match normalize_base(base) {
-        b'A' => Some(0b0001),
-        b'C' => Some(0b0010),
-        b'G' => Some(0b0100),
-        b'T' => Some(0b1000),
-        b'R' => Some(0b0101),
-        b'Y' => Some(0b1010),
-        b'S' => Some(0b0110),
-        b'W' => Some(0b1001),
-        b'K' => Some(0b1100),
-        b'M' => Some(0b0011),
-        b'B' => Some(0b1110),
-        b'D' => Some(0b1101),
-        b'H' => Some(0b1011),
-        b'V' => Some(0b0111),
-        b'N' => Some(0b1111),
-        _ => None,
+    #[test]
+    fn degap_reports_ungapped_or_alignment_coordinates() {
+        let reference = tmp_path("aligned.fa");
+        // Columns 0..14; ungapped ACGTTCAA. GTTCA is ungapped 2..7, columns 5..12.
+        std::fs::write(&reference, ">aln\n--AC-GTT..\nCA-A\n").expect("write reference");
+        let primers = vec![Primer::from_name_and_sequence("p1", "GTTCA").expect("primer")];
+        let scan = |keep_alignment_coords| {
+            let options = ScanOptions {
+                degap: true,
+                keep_alignment_coords,
+                ..ScanOptions::default()
+            };
+            scan_references(std::slice::from_ref(&reference), &primers, &options).expect("scan")
+        };
+
+        let ungapped = scan(false);
+        let spans = |result: &ScanResult| {
+            result
+                .hits
+                .iter()
+                .map(|hit| (hit.start, hit.end, hit.matched.clone()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(spans(&ungapped), vec![(2, 7, "GTTCA".to_string())]);
+        assert_eq!(ungapped.contigs[0].length, 8);
+        assert_eq!(ungapped.contigs[0].coerced_bases, 0);
+
+        let aligned = scan(true);
+        assert_eq!(spans(&aligned), vec![(5, 12, "GTTCA".to_string())]);
+        assert_eq!(
+            aligned.summary[0].first_hit_position,
+            Some((reference.display().to_string(), "aln".to_string(), 5))
+        );
+
+        std::fs::remove_file(reference).expect("remove reference");
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::io::Write;
-    use std::time::{SystemTime, UNIX_EPOCH};
+    #[test]
+    fn iupac_instance_hits_defer_to_the_degenerate_primer() {
+        assert_eq!(
+            iupac_expand("RAATGG").expect("expand"),
+            vec!["AAATGG", "GAATGG"]
+        );
+        assert_eq!(iupac_expand("NA").expect("expand").len(), 4);
+
+        let primer = |name: &str, sequence: &str| {
+            Primer::from_name_and_sequence(name, sequence).expect("primer")
+        };
+        let panel = vec![
+            primer("degenerate", "RAATGG"),
+            primer("specific", "GAATGG"),
+            primer("unrelated", "TTTTCC"),
+        ];
+        for (specific, degenerate) in [(&panel[1], &panel[0]), (&panel[0], &panel[1])] {
+            let expected = iupac_expand(&degenerate.sequence)
+                .expect("expand")
+                .contains(&specific.sequence);
+            assert_eq!(is_strict_iupac_instance(specific, degenerate), expected);
+        }
+        assert!(!is_strict_iupac_instance(&panel[0], &panel[0]));
 
-    fn tmp_path(name: &str) -> PathBuf {
-        let nanos = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .expect("clock should be after unix epoch")
-            .as_nanos();
-        std::env::temp_dir().join(format!("primer_scout_{nanos}_{name}"))
+        // GAATGG at 0 (both primers), AAATGG at 8 (degenerate only).
+        let sequence = "GAATGGCCAAATGGCC";
+        let scan = |dedup| {
+            let options = ScanOptions {
+                deduplicate_iupac_equivalent_hits: dedup,
+                scan_reverse_complement: false,
+                ..ScanOptions::default()
+            };
+            scan_sequence(sequence, "chr1", &panel, &options).expect("scan")
+        };
+        let sites = |result: &ScanResult| {
+            let mut sites = result
+                .hits
+                .iter()
+                .map(|hit| (hit.primer.clone(), hit.start))
+                .collect::<Vec<_>>();
+            sites.sort();
+            sites
+        };
+
+        let all = scan(false);
+        assert_eq!(all.total_hits, 3);
+        let deduplicated = scan(true);
+        assert_eq!(
+            sites(&deduplicated),
+            vec![("degenerate".to_string(), 0), ("degenerate".to_string(), 8)]
+        );
+        assert_eq!(deduplicated.total_hits, 2);
+        assert_eq!(deduplicated.summary[1].total_hits, 0);
+        assert_eq!(deduplicated.summary[1].perfect_hits, 0);
+        assert_eq!(deduplicated.summary[1].first_hit_position, None);
     }
 
     #[test]
-    fn reverse_complement_handles_iupac() {
-        let rc = reverse_complement("ATGCRY").expect("reverse complement should work");
-        assert_eq!(rc, "RYGCAT");
+    fn primer_name_regex_keeps_the_matching_subset() {
+        let panel = [
+            "EGFR_ex19_F",
+            "EGFR_ex19_R",
+            "KRAS_ex2_F",
+            "BRAF_V600_F",
+            "EGFR_ex21_F",
+        ]
+        .iter()
+        .map(|name| Primer::from_name_and_sequence(*name, "ACGTACGT").expect("primer"))
+        .collect::<Vec<_>>();
+        let names = |primers: Vec<Primer>| {
+            primers
+                .into_iter()
+                .map(|primer| primer.name)
+                .collect::<Vec<_>>()
+        };
+
+        let egfr = filter_primers_by_name_regex(panel.clone(), "^EGFR_").expect("filter");
+        assert_eq!(
+            names(egfr),
+            vec!["EGFR_ex19_F", "EGFR_ex19_R", "EGFR_ex21_F"]
+        );
+        let others = exclude_primers_by_name_regex(panel.clone(), "^EGFR_").expect("filter");
+        assert_eq!(names(others), vec!["KRAS_ex2_F", "BRAF_V600_F"]);
+        assert!(filter_primers_by_name_regex(panel.clone(), "^TP53").is_err());
+        assert!(filter_primers_by_name_regex(panel, "EGFR_(").is_err());
+    }
+
+    #[test]
+    fn normalization_records_rna_lowercase_and_whitespace_input() {
+        let primer =
+            |sequence: &str| Primer::from_name_and_sequence("p", sequence).expect("primer");
+
+        let dna = primer("ACGTAC");
+        assert_eq!(dna.original_sequence, None);
+        assert!(!dna.normalization.changed());
+
+        let rna = primer("ACGUAC");
+        assert_eq!(rna.sequence, "ACGTAC");
+        assert_eq!(rna.original_sequence.as_deref(), Some("ACGUAC"));
+        assert_eq!(rna.normalization.uracils_converted, 1);
+
+        let pasted = primer(" acg tAc\t");
+        assert_eq!(pasted.sequence, "ACGTAC");
+        assert_eq!(
+            pasted.normalization,
+            SequenceNormalization {
+                uracils_converted: 0,
+                lowercase_bases: 5,
+                whitespace_removed: 3,
+            }
+        );
+
+        let summary = build_summary(
+            std::slice::from_ref(&rna),
+            vec![SummaryAccumulator::default()],
+            &ScanOptions {
+                max_mismatches: 0,
+                ..ScanOptions::default()
+            },
+        );
+        let json = serde_json::to_string(&summary[0]).expect("json");
+        assert!(json.contains(r#""original_sequence":"ACGUAC""#), "{json}");
+
+        let guide = primer("acguuc");
+        assert!(check_rna_policy(&[dna.clone(), rna.clone()], RnaPolicy::Accept).is_ok());
+        assert!(check_rna_policy(std::slice::from_ref(&dna), RnaPolicy::Reject).is_ok());
+        let err = check_rna_policy(&[dna.clone(), rna], RnaPolicy::Reject).expect_err("U");
+        assert!(err.to_string().contains("contains U"), "{err}");
+        assert!(check_rna_policy(std::slice::from_ref(&guide), RnaPolicy::Expect).is_ok());
+        assert!(check_rna_policy(&[guide, dna], RnaPolicy::Expect).is_err());
     }
 
     #[test]
@@ -800,6 +3561,7 @@ mod tests {
             &ScanOptions {
                 max_mismatches: 0,
                 scan_reverse_complement: true,
+                ..ScanOptions::default()
             },
         )
         .expect("scan references");
@@ -829,19 +3591,23 @@ mod tests {
             name: "p".to_string(),
             sequence: "ATGC".to_string(),
             reverse_complement: "GCAT".to_string(),
+            original_sequence: None,
+            normalization: SequenceNormalization::default(),
+            pool: None,
             masks: vec![0b0001, 0b1000, 0b0100, 0b0010],
-            reverse_masks: vec![0b0100, 0b0010, 0b0001, 0b1000],
+            combined_masks: vec![0b0100_0001, 0b0010_1000, 0b0001_0100, 0b1000_0010],
             is_palindromic: false,
         };
 
         let result = scan_contig(
             "ref.fa",
             "chr1",
-            "ATGT",
+            b"ATGT",
             &[primer],
             &ScanOptions {
                 max_mismatches: 1,
                 scan_reverse_complement: false,
+                ..ScanOptions::default()
             },
         )
         .expect("scan contig");
@@ -850,6 +3616,1738 @@ mod tests {
         assert_eq!(result.hits[0].mismatches, 1);
     }
 
+    #[test]
+    fn paired_hits_report_amplicon_size() {
+        let primers = vec![
+            Primer::from_name_and_sequence("BRCA1_F", "AAGGT").expect("forward primer"),
+            Primer::from_name_and_sequence("BRCA1_R", "CAAGG").expect("reverse primer"),
+        ];
+        let mut options = ScanOptions {
+            pair_suffix_pattern: Some(("_F".to_string(), "_R".to_string())),
+            ..ScanOptions::default()
+        };
+
+        let result = scan_sequence("TTAAGGTCTCTCTCCTTGTT", "chr1", &primers, &options)
+            .expect("scan sequence");
+        assert_eq!(result.hits.len(), 2);
+        assert!(result.hits.iter().all(|h| h.amplicon_size == Some(16)));
+
+        options.max_amplicon_size = 10;
+        let result = scan_sequence("TTAAGGTCTCTCTCCTTGTT", "chr1", &primers, &options)
+            .expect("scan sequence");
+        assert!(result.hits.iter().all(|h| h.amplicon_size.is_none()));
+    }
+
+    #[test]
+    fn partner_distance_and_amplifiable_follow_orientation_and_spacing() {
+        let primers = vec![
+            Primer::from_name_and_sequence("T_F", "AAGGT").expect("forward primer"),
+            Primer::from_name_and_sequence("T_R", "CAAGG").expect("reverse primer"),
+        ];
+        let options = ScanOptions {
+            pair_suffix_pattern: Some(("_F".to_string(), "_R".to_string())),
+            max_amplicon_size: 30,
+            partner_window: 40,
+            ..ScanOptions::default()
+        };
+        let spacer = "CTCTCTCTCT";
+
+        // Converging pair 10 bp apart plus a lone forward site 50 bp further on.
+        let sequence = format!("AAGGT{spacer}CCTTG{spacer}{spacer}{spacer}{spacer}{spacer}AAGGT");
+        let result = scan_sequence(&sequence, "chr1", &primers, &options).expect("scan");
+        let mut hits = result.hits.clone();
+        hits.sort_by_key(|h| h.start);
+        assert_eq!(hits.len(), 3);
+        assert_eq!(hits[0].nearest_partner_distance, Some(15));
+        assert!(hits[0].amplifiable);
+        assert_eq!(hits[1].nearest_partner_distance, Some(-15));
+        assert!(hits[1].amplifiable);
+        assert_eq!(hits[2].nearest_partner_distance, None);
+        assert!(!hits[2].amplifiable);
+
+        // Divergent orientation (reverse site upstream of forward site) never amplifies.
+        let sequence = format!("CCTTG{spacer}AAGGT");
+        let result = scan_sequence(&sequence, "chr1", &primers, &options).expect("scan");
+        assert_eq!(result.hits.len(), 2);
+        assert!(result.hits.iter().all(|h| !h.amplifiable));
+        assert!(
+            result
+                .hits
+                .iter()
+                .all(|h| h.nearest_partner_distance.map(i64::abs) == Some(15))
+        );
+
+        // Same-orientation partner hits are not partners.
+        let sequence = format!("AAGGT{spacer}CAAGG");
+        let result = scan_sequence(&sequence, "chr1", &primers, &options).expect("scan");
+        assert!(
+            result
+                .hits
+                .iter()
+                .all(|h| h.nearest_partner_distance.is_none() && !h.amplifiable)
+        );
+    }
+
+    #[test]
+    fn only_amplifiable_drops_lone_hits_from_summaries_and_hash() {
+        let primers = vec![
+            Primer::from_name_and_sequence("T_F", "AAGGT").expect("forward primer"),
+            Primer::from_name_and_sequence("T_R", "CAAGG").expect("reverse primer"),
+        ];
+        let paired = ScanOptions {
+            pair_suffix_pattern: Some(("_F".to_string(), "_R".to_string())),
+            max_amplicon_size: 30,
+            ..ScanOptions::default()
+        };
+        let filtered = ScanOptions {
+            only_amplifiable: true,
+            ..paired.clone()
+        };
+        let spacer = "CTCTCTCTCT";
+        let sequence = format!("AAGGT{spacer}CCTTG{spacer}{spacer}{spacer}{spacer}{spacer}AAGGT");
+
+        let all = scan_sequence(&sequence, "chr1", &primers, &paired).expect("scan");
+        let kept = scan_sequence(&sequence, "chr1", &primers, &filtered).expect("scan");
+        let expected = all
+            .hits
+            .iter()
+            .filter(|hit| hit.amplifiable)
+            .cloned()
+            .collect::<Vec<_>>();
+        assert_eq!(kept.hits, expected);
+        assert_eq!(kept.total_hits, 2);
+        assert_eq!(kept.hit_hash, compute_hit_hash(&expected));
+        let forward = kept
+            .summary
+            .iter()
+            .find(|row| row.primer == "T_F")
+            .expect("row");
+        assert_eq!(forward.total_hits, 1);
+        assert_eq!(forward.forward_hits, 1);
+
+        let unpaired = ScanOptions {
+            only_amplifiable: true,
+            ..ScanOptions::default()
+        };
+        assert!(scan_sequence(&sequence, "chr1", &primers, &unpaired).is_err());
+    }
+
+    #[test]
+    fn rng_is_reproducible_for_a_seed() {
+        let draw = |mut rng: XorShift64| (0..4).map(|_| rng.next_u32()).collect::<Vec<_>>();
+        let options = ScanOptions::default();
+        assert_eq!(draw(options.rng()), draw(options.rng()));
+        assert_ne!(draw(options.rng()), draw(XorShift64::new(options.seed + 1)));
+
+        assert_eq!(
+            draw(options.rng_for("sample")),
+            draw(options.rng_for("sample"))
+        );
+        assert_ne!(
+            draw(options.rng_for("sample")),
+            draw(options.rng_for("demux"))
+        );
+        assert_ne!(draw(options.rng_for("sample")), draw(options.rng()));
+        let reseeded = ScanOptions {
+            seed: options.seed + 1,
+            ..ScanOptions::default()
+        };
+        assert_ne!(
+            draw(options.rng_for("sample")),
+            draw(reseeded.rng_for("sample"))
+        );
+    }
+
+    #[test]
+    fn single_line_contig_is_streamed_in_chunks() {
+        let reference = tmp_path("single_line.fa");
+        let primer = Primer::from_name_and_sequence("p1", "GATTACAGATTACA").expect("primer");
+        {
+            let mut rf = std::io::BufWriter::new(
+                std::fs::File::create(&reference).expect("create reference"),
+            );
+            writeln!(rf, ">chr_long description").expect("write header");
+            let filler = "C".repeat(10 * 1024 * 1024);
+            write!(rf, "{filler}GATTACAGATTACA{filler}").expect("write sequence");
+            writeln!(rf, "\r\n>chr_empty").expect("write second header");
+        }
+
+        let result = scan_references(
+            std::slice::from_ref(&reference),
+            &[primer],
+            &ScanOptions {
+                scan_reverse_complement: false,
+                ..ScanOptions::default()
+            },
+        )
+        .expect("scan single-line reference");
+
+        assert_eq!(result.total_hits, 1);
+        assert_eq!(result.hits[0].contig, "chr_long");
+        assert_eq!(result.hits[0].start, 10 * 1024 * 1024);
+        std::fs::remove_file(reference).expect("remove ref");
+    }
+
+    #[test]
+    fn crlf_line_longer_than_a_read_chunk_keeps_no_carriage_return() {
+        let reference = tmp_path("crlf_chunk.fa");
+        let primer = Primer::from_name_and_sequence("p1", "GATTACA").expect("primer");
+        // The first read chunk ends right on the `\r`, leaving `\n` for the next one.
+        let line = format!("{}GATTACA", "C".repeat(FASTA_READ_CHUNK_BYTES - 8));
+        std::fs::write(&reference, format!(">chr1\r\n{line}\r\n{line}\r\n"))
+            .expect("write reference");
+
+        for strictness in [FastaStrictness::Lenient, FastaStrictness::Strict] {
+            let options = ScanOptions {
+                scan_reverse_complement: false,
+                fasta_strictness: strictness,
+                ..ScanOptions::default()
+            };
+            let result = scan_references(
+                std::slice::from_ref(&reference),
+                std::slice::from_ref(&primer),
+                &options,
+            )
+            .expect("scan CRLF reference");
+            assert_eq!(result.contigs[0].length, 2 * line.len());
+            assert_eq!(result.contigs[0].coerced_bases, 0);
+            assert_eq!(result.total_hits, 2);
+            assert_eq!(result.hits[1].start, 2 * line.len() - 7);
+        }
+        std::fs::remove_file(reference).expect("remove ref");
+    }
+
+    #[test]
+    fn lean_profile_matches_fast_profile() {
+        const ALPHABET: &[u8] = b"ACGTACGTACGTacgtNRYSWKMBDHV";
+        let mut rng = XorShift64::new(99);
+        let sequence: String = (0..4000)
+            .map(|_| ALPHABET[rng.next_u32() as usize % ALPHABET.len()] as char)
+            .collect();
+        let primers = (0..6)
+            .map(|idx| {
+                let start = rng.next_u32() as usize % (sequence.len() - 12);
+                Primer::from_name_and_sequence(format!("p{idx}"), &sequence[start..start + 12])
+                    .expect("primer from reference window")
+            })
+            .collect::<Vec<_>>();
+
+        for k in 0..3 {
+            let fast = ScanOptions {
+                max_mismatches: k,
+                ..ScanOptions::default()
+            };
+            let lean = ScanOptions {
+                memory_profile: MemoryProfile::Lean,
+                ..fast.clone()
+            };
+            let expected = scan_sequence(&sequence, "chr1", &primers, &fast).expect("fast scan");
+            let actual = scan_sequence(&sequence, "chr1", &primers, &lean).expect("lean scan");
+            assert_eq!(expected.total_hits, actual.total_hits);
+            assert_eq!(
+                serde_json::to_string(&expected.hits).expect("serialize fast hits"),
+                serde_json::to_string(&actual.hits).expect("serialize lean hits")
+            );
+        }
+    }
+
+    #[test]
+    fn lowercase_reference_bases_count_as_mismatches_when_enabled() {
+        let primer = Primer::from_name_and_sequence("p1", "GATTACA").expect("primer");
+        let mut options = ScanOptions {
+            scan_reverse_complement: false,
+            ..ScanOptions::default()
+        };
+
+        let result = scan_sequence(
+            "CCGATtACACC",
+            "chr1",
+            std::slice::from_ref(&primer),
+            &options,
+        )
+        .expect("scan");
+        assert_eq!(result.total_hits, 1);
+
+        options.lowercase_mismatches = true;
+        for profile in [MemoryProfile::Fast, MemoryProfile::Lean] {
+            options.memory_profile = profile;
+            let result = scan_sequence(
+                "CCGATtACACC",
+                "chr1",
+                std::slice::from_ref(&primer),
+                &options,
+            )
+            .expect("scan");
+            assert_eq!(result.total_hits, 0);
+        }
+
+        options.max_mismatches = 1;
+        options.memory_profile = MemoryProfile::Fast;
+        let result = scan_sequence("CCGATtACACC", "chr1", &[primer], &options).expect("scan");
+        assert_eq!(result.total_hits, 1);
+        assert_eq!(result.hits[0].mismatches, 1);
+        assert_eq!(result.hits[0].matched, "GATTACA");
+    }
+
+    #[test]
+    fn sequence_complexity_separates_homopolymers_from_mixed_bases() {
+        assert_eq!(sequence_complexity("AAAAAAA"), 0.0);
+        assert!((sequence_complexity("ATGCATGC") - 1.0).abs() < 1e-12);
+        assert!((sequence_complexity("AATT") - 0.5).abs() < 1e-12);
+        assert_eq!(sequence_complexity("NNNN"), 0.0);
+    }
+
+    #[test]
+    fn low_complexity_primers_flags_repeats_but_not_diverse_primers() {
+        let primers = vec![
+            Primer::from_name_and_sequence("homopolymer", "AAAAAAAAAA").expect("primer"),
+            Primer::from_name_and_sequence("diverse", "ACGTTGCAACGGTCA").expect("primer"),
+            Primer::from_name_and_sequence("dinucleotide", "ATATATATAT").expect("primer"),
+        ];
+        assert!(sequence_complexity(&primers[1].sequence) > 0.9);
+        let flagged = low_complexity_primers(&primers, DEFAULT_MIN_PRIMER_COMPLEXITY);
+        assert_eq!(flagged, [(0, 0.0), (2, 0.5)]);
+        assert!(low_complexity_primers(&primers, 0.0).is_empty());
+    }
+
+    #[test]
+    fn exact_partition_scans_only_primers_without_ambiguity_codes() {
+        let primers = vec![
+            Primer::from_name_and_sequence("exact", "ACGTTG").expect("primer"),
+            Primer::from_name_and_sequence("degenerate", "ACGYTG").expect("primer"),
+            Primer::from_name_and_sequence("rna", "acguug").expect("primer"),
+            Primer::from_name_and_sequence("wildcard", "NCGTTG").expect("primer"),
+        ];
+        let (exact, deferred) = partition_exact_primers(primers);
+        let names = |primers: &[Primer]| {
+            primers
+                .iter()
+                .map(|primer| primer.name.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(&exact), ["exact", "rna"]);
+        assert_eq!(names(&deferred), ["degenerate", "wildcard"]);
+
+        let result = scan_sequence(
+            "TTACGTTGCCACGCTGAA",
+            "chr1",
+            &exact,
+            &ScanOptions::default(),
+        )
+        .expect("scan");
+        assert!(!result.hits.is_empty());
+        assert!(
+            result.hits.iter().all(|hit| hit.start == 2),
+            "{:?}",
+            result.hits
+        );
+        assert_eq!(
+            names(&exact),
+            result
+                .summary
+                .iter()
+                .map(|row| row.primer.clone())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn hit_hash_is_stable_and_detects_changed_hits() {
+        let primers = vec![Primer::from_name_and_sequence("p1", "ACGT").expect("primer")];
+        let options = ScanOptions {
+            max_mismatches: 1,
+            ..ScanOptions::default()
+        };
+        let first = scan_sequence("TTACGTGGACGAACGT", "chr1", &primers, &options).expect("scan");
+        let second = scan_sequence("TTACGTGGACGAACGT", "chr1", &primers, &options).expect("scan");
+        assert_eq!(first.hit_hash.len(), 64);
+        assert_eq!(first.hit_hash, second.hit_hash);
+
+        let mut reversed = first.hits.clone();
+        reversed.reverse();
+        assert_eq!(compute_hit_hash(&reversed), first.hit_hash);
+
+        let mut tampered = first.hits.clone();
+        tampered[0].mismatches += 1;
+        assert_ne!(compute_hit_hash(&tampered), first.hit_hash);
+
+        // Only the reference's base name is hashed, whatever directory or separator.
+        let mut moved = first.hits.clone();
+        for hit in &mut moved {
+            hit.file = format!("C:\\data\\{}", hit.file);
+        }
+        assert_eq!(compute_hit_hash(&moved), first.hit_hash);
+        for hit in &mut moved {
+            hit.file = "/srv/refs/other.fa".to_string();
+        }
+        assert_ne!(compute_hit_hash(&moved), first.hit_hash);
+    }
+
+    #[test]
+    fn tagged_passes_merge_without_losing_provenance() {
+        let primers = vec![Primer::from_name_and_sequence("p1", "GATTACA").expect("primer")];
+        let sequence = "TTGATTACATTGATTTCATT";
+        let pass = |max_mismatches, tag: &str| {
+            let options = ScanOptions {
+                max_mismatches,
+                tag: Some(tag.to_string()),
+                ..ScanOptions::default()
+            };
+            scan_sequence(sequence, "chr1", &primers, &options).expect("scan")
+        };
+        let mut merged = pass(0, "strict");
+        merged.merge(pass(1, "lenient")).expect("merge");
+
+        let hits = merged
+            .hits
+            .iter()
+            .map(|hit| (hit.start, hit.tag.as_deref()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            hits,
+            [
+                (2, Some("lenient")),
+                (2, Some("strict")),
+                (11, Some("lenient"))
+            ]
+        );
+        assert_eq!(merged.total_hits, 3);
+        let rows = merged
+            .summary
+            .iter()
+            .map(|row| (row.total_hits, row.tag.as_deref()))
+            .collect::<Vec<_>>();
+        assert_eq!(rows, [(1, Some("strict")), (2, Some("lenient"))]);
+        assert_eq!(merged.contigs.len(), 1);
+        assert_eq!(merged.hit_hash, compute_hit_hash(&merged.hits));
+        let json = serde_json::to_value(&merged.hits[0]).expect("serialize");
+        assert_eq!(json["tag"], "lenient");
+
+        // The tag is part of the hash identity unless the scan ignores it.
+        let untagged = scan_sequence(sequence, "chr1", &primers, &ScanOptions::default())
+            .expect("scan")
+            .hit_hash;
+        assert_ne!(pass(0, "strict").hit_hash, untagged);
+        let ignoring = ScanOptions {
+            tag: Some("strict".to_string()),
+            hash_ignores_tag: true,
+            ..ScanOptions::default()
+        };
+        assert_eq!(
+            scan_sequence(sequence, "chr1", &primers, &ignoring)
+                .expect("scan")
+                .hit_hash,
+            untagged
+        );
+        let bad = ScanOptions {
+            tag: Some("a\tb".to_string()),
+            ..ScanOptions::default()
+        };
+        assert!(scan_sequence(sequence, "chr1", &primers, &bad).is_err());
+    }
+
+    #[test]
+    fn gc_skew_ranges_from_all_c_to_all_g() {
+        assert_eq!(gc_skew("GGGGCCCC"), 0.0);
+        assert_eq!(gc_skew("GGGGAAAA"), 1.0);
+        assert_eq!(gc_skew("CCCCAAAA"), -1.0);
+        assert_eq!(gc_skew("ATAT"), 0.0);
+        assert!((gc_skew("ggc") - 1.0 / 3.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn min_hit_complexity_drops_low_complexity_hits() {
+        let primer = Primer::from_name_and_sequence("p1", "AAAANN").expect("primer");
+        let mut options = ScanOptions {
+            scan_reverse_complement: false,
+            ..ScanOptions::default()
+        };
+        let sequence = "AAAAAACCAAAAGT";
+
+        let result =
+            scan_sequence(sequence, "chr1", std::slice::from_ref(&primer), &options).expect("scan");
+        assert_eq!(result.total_hits, 4);
+
+        options.min_hit_complexity = Some(0.5);
+        let result = scan_sequence(sequence, "chr1", &[primer], &options).expect("scan");
+        assert_eq!(result.total_hits, 1);
+        assert_eq!(result.hits[0].matched, "AAAAGT");
+        assert_eq!(result.summary[0].total_hits, 1);
+    }
+
+    #[test]
+    fn palindromic_primer_hits_count_on_both_strands_once() {
+        let primers = vec![
+            Primer::from_name_and_sequence("ecori", "GAATTC").expect("palindromic primer"),
+            Primer::from_name_and_sequence("plain", "GAATTA").expect("plain primer"),
+        ];
+        let sequence = "CCGAATTCCCGAATTCCC";
+
+        let result =
+            scan_sequence(sequence, "chr1", &primers, &ScanOptions::default()).expect("scan");
+        assert_eq!(result.total_hits, 2);
+        assert_eq!(result.hits.len(), 2);
+        assert!(result.hits.iter().all(|h| h.strand == '+'));
+        let ecori = &result.summary[0];
+        assert_eq!(ecori.primer, "ecori");
+        assert!(ecori.is_palindromic);
+        assert_eq!(
+            (ecori.total_hits, ecori.forward_hits, ecori.reverse_hits),
+            (2, 2, 2)
+        );
+        assert!(!result.summary[1].is_palindromic);
+
+        let forward_only = ScanOptions {
+            scan_reverse_complement: false,
+            ..ScanOptions::default()
+        };
+        let result = scan_sequence(sequence, "chr1", &primers, &forward_only).expect("scan");
+        assert_eq!(result.total_hits, 2);
+        assert_eq!(
+            (
+                result.summary[0].forward_hits,
+                result.summary[0].reverse_hits
+            ),
+            (2, 0)
+        );
+    }
+
+    #[test]
+    fn primer_sequence_is_copied_into_hits_on_request() {
+        let primer = Primer::from_name_and_sequence("p1", "ATGC").expect("primer");
+        let mut options = ScanOptions::default();
+
+        let result = scan_sequence(
+            "TTTATGCCCGGCATTT",
+            "chr1",
+            std::slice::from_ref(&primer),
+            &options,
+        )
+        .expect("scan");
+        assert!(result.hits.iter().all(|h| h.primer_sequence.is_none()));
+
+        options.include_primer_sequence = true;
+        let result = scan_sequence("TTTATGCCCGGCATTT", "chr1", &[primer], &options).expect("scan");
+        let forward = result
+            .hits
+            .iter()
+            .find(|h| h.strand == '+')
+            .expect("forward hit");
+        assert_eq!(forward.primer_sequence.as_deref(), Some("ATGC"));
+        assert_eq!(forward.primer_reverse_complement, None);
+        let reverse = result
+            .hits
+            .iter()
+            .find(|h| h.strand == '-')
+            .expect("reverse hit");
+        assert_eq!(reverse.primer_sequence.as_deref(), Some("ATGC"));
+        assert_eq!(reverse.primer_reverse_complement.as_deref(), Some("GCAT"));
+        assert_eq!(reverse.matched, "GCAT");
+    }
+
+    #[test]
+    fn relative_position_is_start_over_contig_length() {
+        let primer = Primer::from_name_and_sequence("p1", "CGTGCATG").expect("primer");
+        let sequence = format!("{}CGTGCATG{}", "A".repeat(500), "A".repeat(492));
+        let mut options = ScanOptions {
+            scan_reverse_complement: false,
+            ..ScanOptions::default()
+        };
+
+        let result = scan_sequence(&sequence, "chr1", std::slice::from_ref(&primer), &options)
+            .expect("scan");
+        assert_eq!(result.hits[0].contig_relative_position, None);
+
+        options.include_relative_position = true;
+        let result = scan_sequence(&sequence, "chr1", &[primer], &options).expect("scan");
+        assert_eq!(result.hits.len(), 1);
+        assert_eq!(result.hits[0].start, 500);
+        let position = result.hits[0]
+            .contig_relative_position
+            .expect("relative position requested");
+        assert!((position - 0.5).abs() < 1e-9, "{position}");
+    }
+
+    #[test]
+    fn exhausted_time_limit_marks_primer_timed_out() {
+        let primers = vec![Primer::from_name_and_sequence("p1", "GATTACA").expect("primer")];
+        let sequence = "CCGATTACACCGATTACACC";
+        let mut options = ScanOptions {
+            time_limit_per_primer: Some(Duration::from_secs(3600)),
+            ..ScanOptions::default()
+        };
+        let result = scan_sequence(sequence, "chr1", &primers, &options).expect("scan");
+        assert_eq!(result.total_hits, 2);
+        assert!(!result.summary[0].timed_out);
+
+        // A spent budget stops the primer at its first deadline check, before any window.
+        options.time_limit_per_primer = Some(Duration::ZERO);
+        let result = scan_sequence(sequence, "chr1", &primers, &options).expect("scan");
+        assert!(result.hits.is_empty());
+        assert!(result.summary[0].timed_out);
+        assert_eq!(result.warnings.get("primer_time_limit"), Some(&1));
+    }
+
+    #[test]
+    fn poisson_interval_is_count_plus_minus_z_root_count_over_fraction() {
+        // 95%: z ≈ 1.96, so 100 ± 19.6 sampled hits at 1%.
+        let (low, high) = poisson_confidence_interval(100, 0.01, 0.95);
+        assert!((8035..=8045).contains(&low), "{low}");
+        assert!((11955..=11965).contains(&high), "{high}");
+        // One standard deviation: 400 ± 20 at a full sample.
+        let (low, high) = poisson_confidence_interval(400, 1.0, 0.6827);
+        assert!((379..=381).contains(&low), "{low}");
+        assert!((419..=421).contains(&high), "{high}");
+        assert_eq!(poisson_confidence_interval(0, 0.5, 0.95), (0, 0));
+        // The lower bound never goes negative.
+        assert_eq!(poisson_confidence_interval(1, 0.1, 0.99).0, 0);
+    }
+
+    #[test]
+    fn sampled_scan_estimates_the_full_count() {
+        let primers = vec![Primer::from_name_and_sequence("p1", "GATTACA").expect("primer")];
+        let sequence = "GATTACACCCCCCCCCCCCC".repeat(20_000);
+        let mut options = ScanOptions {
+            scan_reverse_complement: false,
+            ..ScanOptions::default()
+        };
+        let full = scan_sequence(&sequence, "chr1", &primers, &options).expect("scan");
+        assert_eq!(full.total_hits, 20_000);
+
+        options.sample_rate = Some(1.0);
+        let everything = scan_sequence(&sequence, "chr1", &primers, &options).expect("scan");
+        assert_eq!(everything.total_hits, full.total_hits);
+
+        options.sample_rate = Some(0.25);
+        let sampled = scan_sequence(&sequence, "chr1", &primers, &options).expect("scan");
+        let again = scan_sequence(&sequence, "chr1", &primers, &options).expect("scan");
+        assert_eq!(sampled.total_hits, again.total_hits);
+        assert!(sampled.total_hits < full.total_hits);
+        let (low, high) = poisson_confidence_interval(sampled.total_hits, 0.25, 0.99);
+        // Hits cluster in sampled blocks, so allow a wider margin than the interval alone.
+        let margin = (high - low) * 2;
+        assert!(
+            low.saturating_sub(margin) <= full.total_hits && full.total_hits <= high + margin,
+            "{low}..{high}"
+        );
+    }
+
+    #[test]
+    fn random_match_probability_follows_base_frequencies_and_mismatch_budget() {
+        let uniform = [0.25; 4];
+        let exact = random_window_match_probability("ACGT", &uniform, 0).expect("probability");
+        assert!((exact - 0.25f64.powi(4)).abs() < 1e-12, "{exact}");
+        // Exactly one of four positions mismatching: 4 * 0.75 * 0.25^3.
+        let one = random_window_match_probability("ACGT", &uniform, 1).expect("probability");
+        assert!(
+            (one - (exact + 4.0 * 0.75 * 0.25f64.powi(3))).abs() < 1e-12,
+            "{one}"
+        );
+        // `N` always matches, `S` (C/G) matches with the GC fraction.
+        let gc_rich = [0.1, 0.4, 0.4, 0.1];
+        let degenerate = random_window_match_probability("NSA", &gc_rich, 0).expect("probability");
+        assert!((degenerate - 0.8 * 0.1).abs() < 1e-12, "{degenerate}");
+        let budget = random_window_match_probability("NSA", &gc_rich, 5).expect("probability");
+        assert!((budget - 1.0).abs() < 1e-12, "{budget}");
+    }
+
+    #[test]
+    fn summary_reports_expected_random_hits() {
+        // 100 bases, A/T only: a GC-free primer expects hits, a GC one expects none.
+        let sequence = "AT".repeat(50);
+        let primers = vec![
+            Primer::from_name_and_sequence("at", "AATT").expect("primer"),
+            Primer::from_name_and_sequence("gc", "GGCA").expect("primer"),
+        ];
+        let result =
+            scan_sequence(&sequence, "chr1", &primers, &ScanOptions::default()).expect("scan");
+        // 97 windows per strand at 0.5^4; AATT is palindromic, so only one strand counts.
+        let at = &result.summary[0];
+        assert!(
+            (at.expected_random_hits - 97.0 / 16.0).abs() < 1e-9,
+            "{at:?}"
+        );
+        assert_eq!(result.summary[1].expected_random_hits, 0.0);
+    }
+
+    #[test]
+    fn summary_reports_mean_and_std_of_hit_mismatches() {
+        let primers = vec![
+            Primer::from_name_and_sequence("p1", "ACGTAC").expect("primer"),
+            Primer::from_name_and_sequence("p2", "GGGGGG").expect("primer"),
+        ];
+        let options = ScanOptions {
+            max_mismatches: 2,
+            scan_reverse_complement: false,
+            ..ScanOptions::default()
+        };
+        // A perfect site and a 2-mismatch site, in separate contigs to exercise the merge.
+        let path = tmp_path("mismatch_moments");
+        std::fs::write(&path, ">chr1\nTTACGTACTT\n>chr2\nTTTCGAACTT\n").expect("write reference");
+        let result =
+            scan_references(std::slice::from_ref(&path), &primers, &options).expect("scan");
+        let _ = std::fs::remove_file(&path);
+
+        let p1 = &result.summary[0];
+        assert_eq!(p1.total_hits, 2);
+        assert_eq!((p1.mean_mismatch, p1.std_mismatch), (1.0, 1.0));
+        let p2 = &result.summary[1];
+        assert_eq!(
+            (p2.total_hits, p2.mean_mismatch, p2.std_mismatch),
+            (0, 0.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn strand_bias_is_signed_share_of_forward_hits() {
+        assert_eq!(strand_bias(3, 3), 0.0);
+        assert_eq!(strand_bias(4, 0), 1.0);
+        assert_eq!(strand_bias(0, 4), -1.0);
+        assert_eq!(strand_bias(3, 1), 0.5);
+        assert_eq!(strand_bias(0, 0), 0.0);
+
+        let primers = vec![
+            Primer::from_name_and_sequence("p1", "ACGTAC").expect("primer"),
+            Primer::from_name_and_sequence("p2", "GGGGGG").expect("primer"),
+        ];
+        let path = tmp_path("strand_bias");
+        std::fs::write(&path, ">chr1\nTTACGTACTTACGTACTT\n").expect("write reference");
+        let result = scan_references(
+            std::slice::from_ref(&path),
+            &primers,
+            &ScanOptions::default(),
+        )
+        .expect("scan");
+        let _ = std::fs::remove_file(&path);
+        let biases = result
+            .summary
+            .iter()
+            .map(|row| row.strand_bias)
+            .collect::<Vec<_>>();
+        assert_eq!(biases, [1.0, 0.0]);
+        let json = serde_json::to_value(&result.summary[1]).expect("serialize");
+        assert_eq!(json["strand_bias"], 0.0);
+    }
+
+    #[test]
+    fn summary_breaks_hits_down_by_mismatch_count() {
+        let primers = vec![
+            Primer::from_name_and_sequence("p1", "GATTACA").expect("primer"),
+            Primer::from_name_and_sequence("p2", "GGGGGGG").expect("primer"),
+        ];
+        let options = ScanOptions {
+            max_mismatches: 2,
+            scan_reverse_complement: false,
+            ..ScanOptions::default()
+        };
+        // Sites with 0, 1, and 2 mismatches, plus a second perfect one, across contigs.
+        let path = tmp_path("mismatch_breakdown");
+        std::fs::write(
+            &path,
+            ">chr1\nCCGATTACACC\n>chr2\nCCGATTAGACC\n>chr3\nCCGTTTAGACCGATTACACC\n",
+        )
+        .expect("write reference");
+        let result =
+            scan_references(std::slice::from_ref(&path), &primers, &options).expect("scan");
+        let _ = std::fs::remove_file(&path);
+
+        let p1 = &result.summary[0];
+        assert_eq!(p1.per_mismatch_counts, vec![2, 1, 1]);
+        assert_eq!(p1.per_mismatch_counts.iter().sum::<u64>(), p1.total_hits);
+        assert_eq!(result.summary[1].per_mismatch_counts, vec![0, 0, 0]);
+
+        let columns = sink::TsvColumns {
+            mismatch_breakdown: true,
+            ..sink::TsvColumns::default()
+        };
+        let mut out = Vec::new();
+        let mut tsv = sink::TsvSink::new(&mut out, columns, false);
+        sink::HitSink::on_summary(&mut tsv, p1).expect("summary row");
+        let row = String::from_utf8(out).expect("utf8");
+        assert!(row.ends_with("\t2\t1\t1\n"), "{row}");
+    }
+
+    #[test]
+    fn overhanging_windows_are_scanned_at_both_contig_ends() {
+        let primers = vec![Primer::from_name_and_sequence("p1", "ACGTTGCA").expect("primer")];
+        // The primer's last 4 bases open the contig; its first 6 close it.
+        let sequence = format!("TGCA{}ACGTTG", "C".repeat(12));
+        let sites = |result: &ScanResult| {
+            result
+                .hits
+                .iter()
+                .map(|hit| {
+                    let (start, end, strand) = (hit.start, hit.end, hit.strand);
+                    (
+                        start,
+                        end,
+                        strand,
+                        hit.overhang,
+                        hit.mismatches,
+                        hit.matched.clone(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        let mut options = ScanOptions {
+            max_mismatches: 3,
+            scan_reverse_complement: false,
+            ..ScanOptions::default()
+        };
+        let result = scan_sequence(&sequence, "chr1", &primers, &options).expect("scan");
+        assert!(result.hits.is_empty());
+
+        // Off-contig bases as mismatches: 4 is over the budget, 2 is not.
+        options.overhang = Some(OverhangScoring::Mismatch);
+        let result = scan_sequence(&sequence, "chr1", &primers, &options).expect("scan");
+        assert_eq!(
+            sites(&result),
+            vec![(16, 22, '+', 2, 2, "ACGTTG".to_string())]
+        );
+        assert_eq!(result.hits[0].matched_fraction, 0.75);
+
+        // Unscored, up to half the primer hangs off.
+        options.overhang = Some(OverhangScoring::Exclude);
+        options.max_mismatches = 0;
+        let result = scan_sequence(&sequence, "chr1", &primers, &options).expect("scan");
+        assert_eq!(
+            sites(&result),
+            vec![
+                (0, 4, '+', 4, 0, "TGCA".to_string()),
+                (16, 22, '+', 2, 0, "ACGTTG".to_string()),
+            ]
+        );
+
+        // On the reverse-complemented contig the same sites are `-` hits at the other ends.
+        options.scan_reverse_complement = true;
+        let flipped = crate::core::reverse_complement(&sequence).expect("reverse complement");
+        let result = scan_sequence(&flipped, "chr1", &primers, &options).expect("scan");
+        assert_eq!(
+            sites(&result),
+            vec![
+                (0, 6, '-', 2, 0, "CAACGT".to_string()),
+                (18, 22, '-', 4, 0, "TGCA".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn dedup_removes_only_exact_duplicate_hits() {
+        let primers = vec![Primer::from_name_and_sequence("p1", "GATTACA").expect("primer")];
+        let result = scan_sequence(
+            "CCGATTACACCCCGATTACACC",
+            "chr1",
+            &primers,
+            &ScanOptions::default(),
+        )
+        .expect("scan");
+        assert_eq!(result.hits.len(), 2);
+
+        // The scan listed twice, plus a hit sharing the first one's sort key but not its end,
+        // sorted between the two copies of that hit.
+        let mut near_duplicate = result.hits[0].clone();
+        near_duplicate.end += 1;
+        let mut hits = result.hits.clone();
+        hits.push(near_duplicate.clone());
+        hits.extend(result.hits.iter().cloned());
+        hits.sort_by(compare_hits);
+
+        let duplicates = dedup_hits(&mut hits);
+        assert_eq!(duplicates, result.hits);
+        let mut expected = vec![
+            result.hits[0].clone(),
+            near_duplicate,
+            result.hits[1].clone(),
+        ];
+        expected.sort_by(compare_hits);
+        assert_eq!(hits, expected);
+        assert!(dedup_hits(&mut hits).is_empty());
+    }
+
+    #[test]
+    fn unsorted_output_emits_the_sorted_hits_in_discovery_order() {
+        let reference = tmp_path("unsorted.fa");
+        std::fs::write(
+            &reference,
+            ">chr2\nGATTACACCGGATCCGATTACA\n>chr1\nTGTAATCCGGATCC\n",
+        )
+        .expect("write reference");
+        // Listed out of name order, so discovery order differs from the sorted order.
+        let primers = vec![
+            Primer::from_name_and_sequence("zeta", "GGATCC").expect("primer"),
+            Primer::from_name_and_sequence("alpha", "GATTACA").expect("primer"),
+        ];
+        let sorted = scan_references(
+            std::slice::from_ref(&reference),
+            &primers,
+            &ScanOptions::default(),
+        )
+        .expect("sorted scan");
+        let unsorted_options = ScanOptions {
+            sort_output: false,
+            ..ScanOptions::default()
+        };
+        let unsorted = scan_references(
+            std::slice::from_ref(&reference),
+            &primers,
+            &unsorted_options,
+        )
+        .expect("unsorted scan");
+
+        assert_ne!(unsorted.hits, sorted.hits);
+        let mut resorted = unsorted.hits.clone();
+        resorted.sort_by(compare_hits);
+        assert_eq!(resorted, sorted.hits);
+        assert_eq!(unsorted.total_hits, sorted.total_hits);
+        assert_eq!(
+            format!("{:?}", unsorted.summary),
+            format!("{:?}", sorted.summary)
+        );
+
+        // Deduplication relies on duplicates being adjacent after the sort.
+        let dedup_unsorted = ScanOptions {
+            dedup_hits: true,
+            ..unsorted_options
+        };
+        let err = scan_references(std::slice::from_ref(&reference), &primers, &dedup_unsorted)
+            .expect_err("dedup_hits without sort_output");
+        assert!(
+            err.to_string().contains("dedup_hits requires sort_output"),
+            "{err}"
+        );
+        let _ = std::fs::remove_file(reference);
+    }
+
+    #[test]
+    fn sanitized_contig_names_replace_spaces_and_pipes() {
+        assert_eq!(
+            sanitize_contig_name("gi|12345|ref NC_000001.11 chr1:1-100"),
+            "gi_12345_ref_NC_000001.11_chr1:1-100"
+        );
+
+        let primers = vec![Primer::from_name_and_sequence("p1", "GATTACA").expect("primer")];
+        let options = ScanOptions {
+            sanitize_contig_names: true,
+            ..ScanOptions::default()
+        };
+        let result = scan_sequence("CCGATTACACC", "chr1 |alt", &primers, &options).expect("scan");
+        let hit = &result.hits[0];
+        assert_eq!(hit.contig, "chr1__alt");
+        assert_eq!(hit.original_contig.as_deref(), Some("chr1 |alt"));
+        assert_eq!(result.contigs[0].contig, "chr1__alt");
+        let row = &result.summary[0];
+        for position in [&row.first_hit_position, &row.last_hit_position] {
+            assert_eq!(
+                position.as_ref().map(|pos| pos.1.as_str()),
+                Some("chr1__alt")
+            );
+        }
+
+        let plain = scan_sequence(
+            "CCGATTACACC",
+            "chr1 |alt",
+            &primers,
+            &ScanOptions::default(),
+        )
+        .expect("scan");
+        assert_eq!(plain.hits[0].contig, "chr1 |alt");
+        assert_eq!(plain.hits[0].original_contig, None);
+    }
+
+    #[test]
+    fn ambiguous_reference_bases_are_counted_and_filtered() {
+        let primers = vec![Primer::from_name_and_sequence("p1", "GATTACA").expect("primer")];
+        // R admits the primer's A at 3; Y does not admit its A at 14 (a mismatch); Y and two
+        // Ns admit T and A at 24, 27, and 30.
+        let sequence = "CCGRTTACACCCCGYTTACACCCCNATYACNCC";
+        let mut options = ScanOptions {
+            max_mismatches: 1,
+            scan_reverse_complement: false,
+            ..ScanOptions::default()
+        };
+        let result = scan_sequence(sequence, "chr1", &primers, &options).expect("scan");
+        let counts = result
+            .hits
+            .iter()
+            .map(|hit| {
+                (
+                    hit.start,
+                    hit.mismatches,
+                    hit.ambiguous_positions,
+                    hit.matches_via_ambiguity,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(counts, vec![(2, 0, 1, 1), (13, 1, 1, 0), (24, 0, 3, 3)]);
+
+        options.max_ambiguous_positions = Some(1);
+        let result = scan_sequence(sequence, "chr1", &primers, &options).expect("scan");
+        let starts = result.hits.iter().map(|hit| hit.start).collect::<Vec<_>>();
+        assert_eq!(starts, vec![2, 13]);
+        assert_eq!(result.summary[0].total_hits, 2);
+    }
+
+    #[test]
+    fn validate_rejects_each_broken_invariant() {
+        let primers = vec![Primer::from_name_and_sequence("p1", "GATTACA").expect("primer")];
+        ScanOptions::default()
+            .validate(&primers)
+            .expect("defaults are valid");
+
+        let cases: Vec<(ScanOptions, &str)> = vec![
+            (
+                ScanOptions {
+                    spill_threshold: Some(10),
+                    dedup_hits: true,
+                    ..ScanOptions::default()
+                },
+                "spill_threshold cannot be combined",
+            ),
+            (
+                ScanOptions {
+                    spill_threshold: Some(0),
+                    ..ScanOptions::default()
+                },
+                "spill_threshold must be at least 1",
+            ),
+            (
+                ScanOptions {
+                    dedup_hits: true,
+                    sort_output: false,
+                    ..ScanOptions::default()
+                },
+                "dedup_hits requires sort_output",
+            ),
+            (
+                ScanOptions {
+                    keep_alignment_coords: true,
+                    ..ScanOptions::default()
+                },
+                "keep_alignment_coords requires degap",
+            ),
+            (
+                ScanOptions {
+                    degap: true,
+                    keep_alignment_coords: true,
+                    pair_suffix_pattern: Some(("_F".to_string(), "_R".to_string())),
+                    ..ScanOptions::default()
+                },
+                "keep_alignment_coords cannot be combined with pair_suffix_pattern",
+            ),
+            (
+                ScanOptions {
+                    min_hit_complexity: Some(1.5),
+                    ..ScanOptions::default()
+                },
+                "min_hit_complexity must be within [0, 1]",
+            ),
+            (
+                ScanOptions {
+                    sample_rate: Some(0.0),
+                    ..ScanOptions::default()
+                },
+                "sample_rate must be within (0, 1]",
+            ),
+            (
+                ScanOptions {
+                    pair_suffix_pattern: Some(("_F".to_string(), "_F".to_string())),
+                    ..ScanOptions::default()
+                },
+                "pair_suffix_pattern",
+            ),
+            (
+                ScanOptions {
+                    groups: vec![PrimerGroup {
+                        name: "egfr".to_string(),
+                        primers: vec!["p1".to_string(), "p9".to_string()],
+                    }],
+                    ..ScanOptions::default()
+                },
+                "primer 'p9' in group 'egfr'",
+            ),
+        ];
+        for (options, expected) in cases {
+            let err = options.validate(&primers).expect_err(expected);
+            assert!(err.to_string().contains(expected), "{err}");
+            // Every scan entry point goes through the same check.
+            let err = scan_sequence("GATTACA", "chr1", &primers, &options).expect_err(expected);
+            assert!(err.to_string().contains(expected), "{err}");
+        }
+    }
+
+    #[test]
+    fn contig_aliases_rename_listed_contigs_and_keep_the_rest() {
+        let reference = tmp_path("aliases.fa");
+        let aliases = tmp_path("aliases.tsv");
+        std::fs::write(
+            &reference,
+            ">1 primary\nAAGATTACAAA\n>NC_000002.12\nCCGATTACACC\n>scaffold_7\nGATTACA\n",
+        )
+        .expect("write reference");
+        std::fs::write(
+            &aliases,
+            "source\tcanonical\n# RefSeq and Ensembl names\n1\tchr1\nNC_000002.12\tchr2\n",
+        )
+        .expect("write aliases");
+        let primers = vec![Primer::from_name_and_sequence("p1", "GATTACA").expect("primer")];
+        let options = ScanOptions {
+            scan_reverse_complement: false,
+            contig_aliases: Some(aliases::load_contig_aliases(&aliases).expect("aliases")),
+            ..ScanOptions::default()
+        };
+
+        let result =
+            scan_references(std::slice::from_ref(&reference), &primers, &options).expect("scan");
+        let hits = result
+            .hits
+            .iter()
+            .map(|hit| (hit.contig.as_str(), hit.start))
+            .collect::<Vec<_>>();
+        assert_eq!(hits, vec![("chr1", 2), ("chr2", 2), ("scaffold_7", 0)]);
+        let contigs = result
+            .contigs
+            .iter()
+            .map(|contig| contig.contig.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(contigs, vec!["chr1", "chr2", "scaffold_7"]);
+        assert_eq!(result.warnings.get("unaliased_contig"), Some(&1));
+
+        std::fs::write(&aliases, "1\tchr1\n1\tchrUn\n").expect("write aliases");
+        let err = aliases::load_contig_aliases(&aliases).expect_err("conflicting aliases");
+        assert!(err.to_string().contains("earlier line maps it to 'chr1'"));
+        std::fs::remove_file(reference).expect("remove temp file");
+        std::fs::remove_file(aliases).expect("remove temp file");
+    }
+
+    #[test]
+    fn too_degenerate_primers_are_rejected() {
+        let primers = vec![
+            Primer::from_name_and_sequence("deg", "ACGRYNNNNNACGT").expect("primer"),
+            Primer::from_name_and_sequence("typo", "NNNNNNNNNNNNNNNNNNNN").expect("primer"),
+        ];
+        assert_eq!(primers[0].degeneracy(), 4 * 1024);
+        assert_eq!(primers[1].degeneracy(), 1 << 40);
+        check_degeneracy(&primers[..1], DEFAULT_MAX_DEGENERACY).expect("at the limit");
+        let err = check_degeneracy(&primers, DEFAULT_MAX_DEGENERACY).expect_err("too degenerate");
+        assert!(
+            err.to_string()
+                .contains("'typo' (primer 2 of the panel, 'NNNNNNNNNNNNNNNNNNNN') has degeneracy 1099511627776"),
+            "{err}"
+        );
+        let long_n = Primer::from_name_and_sequence("n40", &"N".repeat(40)).expect("primer");
+        assert_eq!(long_n.degeneracy(), u64::MAX);
+    }
+
+    #[test]
+    fn scan_one_yields_the_scan_sequence_hits_lazily() {
+        let primer = Primer::from_name_and_sequence("p1", "ACGTAC").expect("primer");
+        let reference = "TTACGTTCGGACGTACTTGTACGTAA";
+        let options = ScanOptions {
+            max_mismatches: 1,
+            ..ScanOptions::default()
+        };
+        let key = |hit: &Hit| (hit.start, hit.strand, hit.mismatches, hit.matched.clone());
+        let mut expected =
+            scan_sequence(reference, "chr1", std::slice::from_ref(&primer), &options)
+                .expect("scan")
+                .hits
+                .iter()
+                .map(key)
+                .collect::<Vec<_>>();
+        expected.sort();
+        let lazy = scan_one(&primer, reference.as_bytes(), &options)
+            .expect("scan_one")
+            .map(|hit| key(&hit))
+            .collect::<Vec<_>>();
+        assert!(lazy.iter().any(|hit| hit.1 == '-'));
+        assert!(lazy.is_sorted_by_key(|hit| hit.0));
+        let mut sorted = lazy.clone();
+        sorted.sort();
+        assert_eq!(sorted, expected);
+
+        let mut hits = scan_one(&primer, reference.as_bytes(), &options).expect("scan_one");
+        assert_eq!(
+            hits.next().map(|hit| (hit.start, hit.strand)),
+            Some((0, '-'))
+        );
+
+        // Options are checked up front, like every other scan entry point.
+        let loose = ScanOptions {
+            max_mismatches: primer.len(),
+            ..options.clone()
+        };
+        assert!(scan_one(&primer, reference.as_bytes(), &loose).is_err());
+        let invalid = ScanOptions {
+            dedup_hits: true,
+            sort_output: false,
+            ..options.clone()
+        };
+        assert!(scan_one(&primer, reference.as_bytes(), &invalid).is_err());
+    }
+
+    #[test]
+    fn path_style_forward_replaces_native_separators_only() {
+        let windows = r"refs\hg38\chr1.fa";
+        assert_eq!(
+            PathStyle::Forward.render_with_separator(windows, '\\'),
+            "refs/hg38/chr1.fa"
+        );
+        assert_eq!(
+            PathStyle::Native.render_with_separator(windows, '\\'),
+            windows
+        );
+        // On Unix a backslash is part of the file name, not a separator.
+        assert_eq!(
+            PathStyle::Forward.render_with_separator(r"refs/odd\name.fa", '/'),
+            r"refs/odd\name.fa"
+        );
+        assert_eq!(
+            PathStyle::Forward.render(&Path::new("refs").join("chr1.fa")),
+            "refs/chr1.fa"
+        );
+    }
+
+    #[test]
+    fn consensus_primer_unions_each_column() {
+        let consensus =
+            consensus_primer(&["ACGTAC", "GCGTAC", "ACATAC", "ACGuGN"]).expect("consensus");
+        assert_eq!(consensus, "RCRTRN");
+        assert_eq!(consensus_primer(&["acgt"]).expect("single"), "ACGT");
+        assert_eq!(
+            consensus_primer(&["AC", "CA", "GT", "TG"]).expect("all bases"),
+            "NN"
+        );
+
+        let err = consensus_primer(&["ACGTAC", "ACGTA"]).expect_err("unequal lengths");
+        assert!(err.to_string().contains("sequence 2"), "{err}");
+        assert!(consensus_primer(&[]).is_err());
+        assert!(consensus_primer(&["ACG-T", "ACGGT"]).is_err());
+    }
+
+    #[test]
+    fn hit_consumers_use_the_reported_span_not_the_primer_length() {
+        use crate::sink::{BedSink, GenbankFeatureSink, Gff3Sink, HitSink};
+
+        let primers = vec![
+            Primer::from_name_and_sequence("egfr_F", "ACGTACGT").expect("primer"),
+            Primer::from_name_and_sequence("egfr_R", "TTGGCCAA").expect("primer"),
+        ];
+        let template = scan_sequence("GGACGTACGTGG", "chr1", &primers, &ScanOptions::default())
+            .expect("scan")
+            .hits
+            .remove(0);
+        let hit = |primer: &str, start: usize, end: usize, strand: char| Hit {
+            primer: primer.to_string(),
+            start,
+            end,
+            strand,
+            matched: "A".repeat(end - start),
+            ..template.clone()
+        };
+        // Reference spans longer (insertion), shorter (deletion), and both, than the 8 bp
+        // primers: every consumer must read start..end, never start + primer_len.
+        for (forward_span, reverse_span) in [(9, 8), (7, 8), (9, 6)] {
+            let mut hits = vec![
+                hit("egfr_F", 100, 100 + forward_span, '+'),
+                hit("egfr_R", 300 - reverse_span, 300, '-'),
+            ];
+            pairing::annotate_pairs(&mut hits, "_F", "_R", 1_000, 1_000);
+            assert_eq!(hits[0].amplicon_size, Some(200));
+            assert!(hits.iter().all(|hit| hit.amplifiable));
+
+            let amplicons = amplicons::predicted_amplicons(&hits, "_F", "_R", 1_000);
+            let mut bed12 = Vec::new();
+            amplicons::write_amplicons_bed(&mut bed12, &amplicons).expect("write BED12");
+            assert_eq!(
+                String::from_utf8(bed12).expect("utf8"),
+                format!(
+                    "chr1\t100\t300\tegfr\t0\t+\t{}\t{}\t0\t2\t{forward_span},{reverse_span},\t0,{},\n",
+                    100 + forward_span,
+                    300 - reverse_span,
+                    200 - reverse_span
+                )
+            );
+
+            let contigs = [ContigLength {
+                file: template.file.clone(),
+                contig: "chr1".to_string(),
+                length: 400,
+                coerced_bases: 0,
+            }];
+            let gaps = |amplicons_only| {
+                regions::coverage_gaps(&contigs, &hits, amplicons_only, 1)
+                    .iter()
+                    .map(|gap| (gap.start, gap.end))
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(
+                gaps(false),
+                [
+                    (0, 100),
+                    (100 + forward_span, 300 - reverse_span),
+                    (300, 400)
+                ]
+            );
+            assert_eq!(gaps(true), [(0, 100), (300, 400)]);
+
+            let (mut bed, mut gff3, mut genbank) = (Vec::new(), Vec::new(), Vec::new());
+            let sinks: [Box<dyn HitSink + '_>; 3] = [
+                Box::new(BedSink::new(&mut bed)),
+                Box::new(Gff3Sink::new(&mut gff3)),
+                Box::new(GenbankFeatureSink::new(&mut genbank)),
+            ];
+            for mut sink in sinks {
+                sink.on_hit(&hits[0]).expect("hit");
+                sink.finish().expect("finish");
+            }
+            let text = |out: Vec<u8>| String::from_utf8(out).expect("utf8");
+            let end = 100 + forward_span;
+            assert!(text(bed).starts_with(&format!("chr1\t100\t{end}\t")));
+            assert!(text(gff3).contains(&format!("\t101\t{end}\t")));
+            assert!(text(genbank).contains(&format!("101..{end}")));
+        }
+    }
+
+    #[test]
+    fn masked_sequence_lowercases_bases_under_ambiguous_primer_positions() {
+        let primers = vec![Primer::from_name_and_sequence("p1", "ATNC").expect("primer")];
+        // ATGC on `+` at 2, GCAT (ATGC reverse-complemented) on `-` at 10, ATGG at 18.
+        let sequence = "CCATGCCCCCGCATCCCCATGGCC";
+        let mut options = ScanOptions {
+            max_mismatches: 1,
+            ..ScanOptions::default()
+        };
+        let result = scan_sequence(sequence, "chr1", &primers, &options).expect("scan");
+        assert!(result.hits.iter().all(|hit| hit.masked_sequence.is_none()));
+
+        options.include_masked_sequence = true;
+        let result = scan_sequence(sequence, "chr1", &primers, &options).expect("scan");
+        let masked = |start: usize, strand: char| {
+            result
+                .hits
+                .iter()
+                .find(|hit| hit.start == start && hit.strand == strand)
+                .and_then(|hit| hit.masked_sequence.clone())
+        };
+        assert_eq!(masked(2, '+').as_deref(), Some("ATgC"));
+        // The `-` primer reads GNAT, so the wildcard is the second reference base.
+        assert_eq!(masked(10, '-').as_deref(), Some("GcAT"));
+        // The mismatch at the last base stays uppercase.
+        assert_eq!(masked(18, '+').as_deref(), Some("ATgG"));
+    }
+
+    #[test]
+    fn duplicate_reference_paths_are_scanned_once() {
+        let reference = tmp_path("duplicate_ref.fa");
+        std::fs::write(&reference, ">chr1\nCCGATTACACC\n").expect("write reference");
+        let primers = vec![Primer::from_name_and_sequence("p1", "GATTACA").expect("primer")];
+        let options = ScanOptions {
+            max_mismatches: 0,
+            ..ScanOptions::default()
+        };
+
+        let result = scan_references(&[reference.clone(), reference.clone()], &primers, &options)
+            .expect("scan");
+        assert_eq!(result.total_hits, 1);
+        assert_eq!(result.summary[0].total_hits, 1);
+        assert_eq!(result.contigs.len(), 1);
+        assert_eq!(result.warnings.get("duplicate_reference"), Some(&1));
+
+        std::fs::remove_file(reference).expect("remove ref");
+    }
+
+    #[test]
+    fn shared_scanner_matches_scan_sequence_across_threads() {
+        let primers = vec![
+            Primer::from_name_and_sequence("p1", "ACGTA").expect("primer"),
+            Primer::from_name_and_sequence("p2", "GGCTT").expect("primer"),
+        ];
+        let options = ScanOptions {
+            max_mismatches: 1,
+            ..ScanOptions::default()
+        };
+        let scanner = Scanner::new(primers.clone(), options.clone()).expect("scanner");
+        let mut rng = XorShift64::new(11);
+        let sequences = (0..64)
+            .map(|_| {
+                (0..300)
+                    .map(|_| b"ACGT"[(rng.next_u32() & 3) as usize])
+                    .collect::<Vec<u8>>()
+            })
+            .collect::<Vec<_>>();
+
+        let concurrent = sequences
+            .par_iter()
+            .map(|sequence| scanner.scan("read", sequence).expect("scan").total_hits)
+            .collect::<Vec<_>>();
+        let sequential = sequences
+            .iter()
+            .map(|sequence| {
+                let text = std::str::from_utf8(sequence).expect("ascii");
+                scan_sequence(text, "read", &primers, &options)
+                    .expect("scan")
+                    .total_hits
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(concurrent, sequential);
+        assert!(Scanner::new(Vec::new(), ScanOptions::default()).is_err());
+    }
+
+    #[test]
+    fn scan_pool_bounds_parallelism_to_configured_threads() {
+        let primers = vec![Primer::from_name_and_sequence("p1", "ACGT").expect("primer")];
+        let scanner_with = |threads| {
+            let options = ScanOptions {
+                threads,
+                ..ScanOptions::default()
+            };
+            Scanner::new(primers.clone(), options).expect("scanner")
+        };
+        // The ids of every worker in the pool the probe runs on.
+        let workers = || {
+            rayon::broadcast(|_| std::thread::current().id())
+                .into_iter()
+                .collect::<HashSet<_>>()
+        };
+
+        // One pool per scanner: every call, and every clone, runs on the same two workers.
+        let bounded = scanner_with(Some(2));
+        let first = bounded.install(workers);
+        assert_eq!(first.len(), 2);
+        assert_eq!(bounded.install(workers), first);
+        assert_eq!(bounded.clone().install(workers), first);
+
+        // `None` stays on the caller's pool, and `Some` replaces it.
+        let ambient = rayon::ThreadPoolBuilder::new()
+            .num_threads(3)
+            .build()
+            .expect("ambient pool");
+        let ambient_workers = ambient.install(workers);
+        assert_eq!(ambient_workers.len(), 3);
+        assert_eq!(
+            ambient.install(|| scanner_with(None).install(workers)),
+            ambient_workers
+        );
+        let single = scanner_with(Some(1));
+        let single_workers = ambient.install(|| single.install(workers));
+        assert_eq!(single_workers.len(), 1);
+        assert!(single_workers.is_disjoint(&ambient_workers));
+
+        let bounded = ScanOptions {
+            threads: Some(1),
+            ..ScanOptions::default()
+        };
+        let sequence = "ACGTTGCAACGTAGGCTTAC";
+        assert_eq!(
+            scan_sequence(sequence, "chr1", &primers, &bounded)
+                .expect("bounded scan")
+                .hits,
+            scan_sequence(sequence, "chr1", &primers, &ScanOptions::default())
+                .expect("scan")
+                .hits
+        );
+    }
+
+    #[test]
+    fn auto_tuned_scan_matches_fixed_thread_scan() {
+        let reference = tmp_path("auto_tune.fa");
+        std::fs::write(
+            &reference,
+            ">chr1\nACGTTGCAACGTAGGCTTAC\n>chr2\nGTAAGCCTACGT\n",
+        )
+        .expect("write reference");
+        let primers = vec![Primer::from_name_and_sequence("p1", "ACGT").expect("primer")];
+        let references = vec![reference.clone()];
+
+        let fixed = scan_references(&references, &primers, &ScanOptions::default()).expect("scan");
+        let tuned = scan_references(
+            &references,
+            &primers,
+            &ScanOptions {
+                auto_tune_threads: true,
+                ..ScanOptions::default()
+            },
+        )
+        .expect("tuned scan");
+
+        assert_eq!(fixed.auto_tuned_threads, None);
+        let threads = tuned.auto_tuned_threads.expect("tuning ran");
+        assert!(threads >= 1);
+        assert_eq!(tuned.total_hits, fixed.total_hits);
+        assert_eq!(tuned.hits.len(), fixed.hits.len());
+
+        std::fs::remove_file(reference).expect("remove reference");
+    }
+
+    #[test]
+    fn primers_longer_than_every_contig_are_flagged() {
+        let primers = vec![
+            Primer::from_name_and_sequence("short", "ACGT").expect("primer"),
+            Primer::from_name_and_sequence("long", "ACGTACGTACGTACGT").expect("primer"),
+        ];
+        let result =
+            scan_sequence("TTACGTTT", "chr1", &primers, &ScanOptions::default()).expect("scan");
+
+        let long = result
+            .summary
+            .iter()
+            .find(|row| row.primer == "long")
+            .expect("long row");
+        let short = result
+            .summary
+            .iter()
+            .find(|row| row.primer == "short")
+            .expect("short row");
+        assert!(long.longer_than_every_contig);
+        assert!(!short.longer_than_every_contig);
+        assert_eq!(result.warnings.get("primer_longer_than_contigs"), Some(&1));
+    }
+
+    #[test]
+    fn group_summary_totals_member_primers() {
+        let groups_path = tmp_path("groups.tsv");
+        std::fs::write(
+            &groups_path,
+            "primer\tgroup\nb1\tBRCA1\nb2\tBRCA1\nt1\tTP53\n",
+        )
+        .expect("write groups");
+        let groups = groups::load_primer_groups(&groups_path).expect("load groups");
+        std::fs::remove_file(&groups_path).expect("remove groups");
+
+        let primers = vec![
+            Primer::from_name_and_sequence("b1", "GATTACA").expect("primer"),
+            Primer::from_name_and_sequence("b2", "CCGGTTA").expect("primer"),
+            Primer::from_name_and_sequence("t1", "GGGGGGG").expect("primer"),
+        ];
+        let options = ScanOptions {
+            max_mismatches: 0,
+            scan_reverse_complement: false,
+            groups,
+            ..ScanOptions::default()
+        };
+        let result =
+            scan_sequence("GATTACATTGATTACATTCCGGTTA", "chr1", &primers, &options).expect("scan");
+
+        let rows = result
+            .group_summaries
+            .iter()
+            .map(|g| {
+                (
+                    g.group.as_str(),
+                    g.total_hits,
+                    g.primers_with_hits,
+                    g.contigs_with_hits,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(rows, vec![("BRCA1", 3, 2, 1), ("TP53", 0, 0, 0)]);
+    }
+
+    #[test]
+    fn contig_summary_ranks_contigs_by_hits() {
+        let primers = vec![
+            Primer::from_name_and_sequence("p1", "GATTACA").expect("primer"),
+            Primer::from_name_and_sequence("p2", "CCGGTTA").expect("primer"),
+        ];
+        let options = ScanOptions {
+            max_mismatches: 1,
+            scan_reverse_complement: false,
+            ..ScanOptions::default()
+        };
+        // `decoy` repeats both sites (one of them with a mismatch); chr1 holds one site.
+        let path = tmp_path("contig_summary.fa");
+        std::fs::write(
+            &path,
+            ">chr1\nTTGATTACATT\n>decoy\nGATTACATGATTACATGATTTCACCGGTTA\n>chr2\nTTTTTTTTTT\n",
+        )
+        .expect("write reference");
+        let result =
+            scan_references(std::slice::from_ref(&path), &primers, &options).expect("scan");
+        let _ = std::fs::remove_file(&path);
+
+        let rows = regions::contig_summary(&result.hits)
+            .into_iter()
+            .map(|row| {
+                (
+                    row.contig,
+                    row.total_hits,
+                    row.distinct_primers,
+                    row.perfect_hits,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            rows,
+            vec![
+                ("decoy".to_string(), 4, 2, 3),
+                ("chr1".to_string(), 1, 1, 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn summary_tracks_first_and_last_hit_positions() {
+        let reference = tmp_path("positions_ref.fa");
+        std::fs::write(
+            &reference,
+            ">chr1\nCCCGATTACACCCCCCGATTACA\n>chr2\nGATTACACCC\n",
+        )
+        .expect("write reference");
+        let primers = vec![
+            Primer::from_name_and_sequence("hit", "GATTACA").expect("primer"),
+            Primer::from_name_and_sequence("miss", "GGGGGGG").expect("primer"),
+        ];
+        let options = ScanOptions {
+            max_mismatches: 0,
+            ..ScanOptions::default()
+        };
+        let result =
+            scan_references(std::slice::from_ref(&reference), &primers, &options).expect("scan");
+        let file = reference.display().to_string();
+
+        let hit = &result.summary[0];
+        assert_eq!(hit.total_hits, 3);
+        assert_eq!(
+            hit.first_hit_position,
+            Some((file.clone(), "chr2".to_string(), 0))
+        );
+        assert_eq!(hit.last_hit_position, Some((file, "chr1".to_string(), 16)));
+        assert_eq!(result.summary[1].first_hit_position, None);
+        assert_eq!(result.summary[1].last_hit_position, None);
+
+        std::fs::remove_file(reference).expect("remove ref");
+    }
+
+    #[test]
+    fn fasta_records_wrap_at_requested_width() {
+        let mut out = Vec::new();
+        write_fasta_record(&mut out, "seq", b"ACGTACGTAC", 4).expect("write wrapped");
+        write_fasta_record(&mut out, "flat", b"ACGTACGTAC", 0).expect("write unwrapped");
+        assert_eq!(
+            String::from_utf8(out).expect("utf8"),
+            ">seq\nACGT\nACGT\nAC\n>flat\nACGTACGTAC\n"
+        );
+    }
+
+    #[test]
+    fn regions_bed_limits_hits_to_merged_intervals() {
+        let mut chr1 = vec![b'C'; 100];
+        for start in [6, 16, 40, 80] {
+            chr1[start..start + 7].copy_from_slice(b"GATTACA");
+        }
+        let reference = tmp_path("regions_ref.fa");
+        let bed = tmp_path("regions.bed");
+        std::fs::write(
+            &reference,
+            format!(
+                ">chr1\n{}\n>chr2\nCCCCCGATTACACC\n>chr3\nGATTACA\n",
+                String::from_utf8_lossy(&chr1)
+            ),
+        )
+        .expect("write reference");
+        std::fs::write(
+            &bed,
+            "track name=panel\nchr1\t5\t22\nchr1\t18\t30\tspan\nchr1\t38\t45\nchr1\t78\t90\nchr2\t0\t3\n",
+        )
+        .expect("write bed");
+
+        let regions = regions::load_bed(&bed).expect("load bed");
+        assert_eq!(regions.intervals("chr1"), &[5..30, 38..45, 78..90]);
+
+        let primers = vec![Primer::from_name_and_sequence("p1", "GATTACA").expect("primer")];
+        let options = ScanOptions {
+            max_mismatches: 0,
+            regions: Some(regions),
+            ..ScanOptions::default()
+        };
+        let result =
+            scan_references(std::slice::from_ref(&reference), &primers, &options).expect("scan");
+
+        // 16..23 spans the two overlapping intervals and is found exactly once; 40..47 runs past
+        // its interval; chr2's hit lies outside its interval and chr3 is not listed.
+        let found: Vec<_> = result
+            .hits
+            .iter()
+            .map(|h| (h.contig.as_str(), h.start, h.end))
+            .collect();
+        assert_eq!(
+            found,
+            vec![("chr1", 6, 13), ("chr1", 16, 23), ("chr1", 80, 87)]
+        );
+        assert_eq!(result.total_hits, 3);
+
+        std::fs::remove_file(reference).expect("remove ref");
+        std::fs::remove_file(bed).expect("remove bed");
+    }
+
+    #[test]
+    fn regions_bed_hit_may_straddle_abutting_intervals() {
+        let reference = tmp_path("tiled_ref.fa");
+        let bed = tmp_path("tiled.bed");
+        std::fs::write(&reference, ">chr1\nCCCCCCCCGATTACACCCCCCC\n").expect("write reference");
+        // The site 8..15 crosses the 10 boundary between two tiles.
+        std::fs::write(&bed, "chr1\t0\t10\nchr1\t10\t22\n").expect("write bed");
+
+        let regions = regions::load_bed(&bed).expect("load bed");
+        assert_eq!(regions.intervals("chr1"), std::slice::from_ref(&(0..22)));
+        let primers = vec![Primer::from_name_and_sequence("p1", "GATTACA").expect("primer")];
+        let options = ScanOptions {
+            max_mismatches: 0,
+            regions: Some(regions),
+            ..ScanOptions::default()
+        };
+        let result =
+            scan_references(std::slice::from_ref(&reference), &primers, &options).expect("scan");
+        let found = result
+            .hits
+            .iter()
+            .map(|hit| (hit.start, hit.end))
+            .collect::<Vec<_>>();
+        assert_eq!(found, [(8, 15)]);
+
+        std::fs::remove_file(reference).expect("remove ref");
+        std::fs::remove_file(bed).expect("remove bed");
+    }
+
+    #[test]
+    fn collapse_strand_duplicates_merges_only_same_locus_pairs() {
+        let options = ScanOptions {
+            max_mismatches: 1,
+            collapse_strand_duplicates: true,
+            ..ScanOptions::default()
+        };
+
+        // Near-palindrome: AACGTA vs its reverse complement TACGTT differ, yet at AACGTT
+        // both strands land within one mismatch.
+        let near = Primer::from_name_and_sequence("near", "AACGTA").expect("near palindrome");
+        let result = scan_sequence("GGAACGTTGG", "chr1", std::slice::from_ref(&near), &options)
+            .expect("scan");
+        assert_eq!(result.hits.len(), 1);
+        let merged = &result.hits[0];
+        assert_eq!(merged.strand, '=');
+        assert_eq!((merged.start, merged.end), (2, 8));
+        assert_eq!(merged.fwd_mismatches, Some(1));
+        assert_eq!(merged.rev_mismatches, Some(1));
+        assert_eq!(merged.mismatches, 1);
+        assert_eq!(result.total_hits, 2);
+
+        // Exact palindromes are already scanned once and stay on `+`.
+        let exact = Primer::from_name_and_sequence("ecori", "GAATTC").expect("palindrome");
+        let result = scan_sequence("CCGAATTCCC", "chr1", &[exact], &options).expect("scan");
+        assert_eq!(result.hits.len(), 1);
+        assert_eq!(result.hits[0].strand, '+');
+        assert_eq!(result.hits[0].fwd_mismatches, None);
+
+        // Unrelated primers at the same locus, and strand hits at different loci, never merge.
+        let primers = vec![
+            Primer::from_name_and_sequence("a", "ATGC").expect("primer a"),
+            Primer::from_name_and_sequence("b", "ATGC").expect("primer b"),
+        ];
+        let result = scan_sequence("TTTATGCCCGGCATTT", "chr1", &primers, &options).expect("scan");
+        assert!(result.hits.iter().all(|h| h.strand != '='));
+        assert_eq!(result.hits.len() as u64, result.total_hits);
+    }
+
     #[test]
     fn parse_positive_usize_rejects_non_positive_values() {
         assert_eq!(parse_positive_usize("32"), Some(32));
@@ -0,0 +1,230 @@
+use std::collections::HashMap;
+
+use crate::Hit;
+
+#[derive(Debug, Default)]
+struct PairGroup {
+    forward_plus: Vec<usize>,
+    forward_minus: Vec<usize>,
+    reverse_plus: Vec<usize>,
+    reverse_minus: Vec<usize>,
+}
+
+/// Annotates hits of a named pair panel (`<target><forward_suffix>` / `<target><reverse_suffix>`).
+///
+/// Every hit gets the signed distance to the nearest opposite-orientation hit of its partner
+/// primer on the same contig (within `partner_window` bp) and is flagged `amplifiable` when a
+/// converging partner hit sits within `max_amplicon_size` bp. `+` hits of the forward primer
+/// paired with downstream `-` hits of the reverse primer also record their amplicon size.
+pub(crate) fn annotate_pairs(
+    hits: &mut [Hit],
+    forward_suffix: &str,
+    reverse_suffix: &str,
+    max_amplicon_size: usize,
+    partner_window: usize,
+) {
+    for group in pair_groups(hits, forward_suffix, reverse_suffix).into_values() {
+        annotate_converging(
+            hits,
+            &group.forward_plus,
+            &group.reverse_minus,
+            max_amplicon_size,
+            true,
+        );
+        annotate_converging(
+            hits,
+            &group.reverse_plus,
+            &group.forward_minus,
+            max_amplicon_size,
+            false,
+        );
+
+        annotate_nearest(
+            hits,
+            &group.forward_plus,
+            &group.reverse_minus,
+            partner_window,
+        );
+        annotate_nearest(
+            hits,
+            &group.reverse_minus,
+            &group.forward_plus,
+            partner_window,
+        );
+        annotate_nearest(
+            hits,
+            &group.forward_minus,
+            &group.reverse_plus,
+            partner_window,
+        );
+        annotate_nearest(
+            hits,
+            &group.reverse_plus,
+            &group.forward_minus,
+            partner_window,
+        );
+    }
+}
+
+/// One predicted product of a pair panel: the `+` hit of the primer binding its left end and
+/// the nearest downstream `-` hit of its partner, as indices into the scanned hits.
+pub(crate) struct ProductPair {
+    pub(crate) target: String,
+    pub(crate) left: usize,
+    pub(crate) right: usize,
+    /// `+` when the forward primer binds the left end, `-` when the reverse primer does.
+    pub(crate) strand: char,
+}
+
+/// Every `+` hit of a pair-panel primer paired with the nearest converging partner hit within
+/// `max_amplicon_size` bp, in both orientations. Order is unspecified.
+pub(crate) fn product_pairs(
+    hits: &[Hit],
+    forward_suffix: &str,
+    reverse_suffix: &str,
+    max_amplicon_size: usize,
+) -> Vec<ProductPair> {
+    let mut pairs = Vec::new();
+    for ((_, _, target), group) in pair_groups(hits, forward_suffix, reverse_suffix) {
+        for (plus, minus, strand) in [
+            (&group.forward_plus, &group.reverse_minus, '+'),
+            (&group.reverse_plus, &group.forward_minus, '-'),
+        ] {
+            pairs.extend(converging_pairs(hits, plus, minus, max_amplicon_size).map(
+                |(left, right)| ProductPair {
+                    target: target.clone(),
+                    left,
+                    right,
+                    strand,
+                },
+            ));
+        }
+    }
+    pairs
+}
+
+/// Hit indices per `(file, contig, target)` and primer orientation, each list sorted by start.
+fn pair_groups(
+    hits: &[Hit],
+    forward_suffix: &str,
+    reverse_suffix: &str,
+) -> HashMap<(String, String, String), PairGroup> {
+    let mut groups: HashMap<(String, String, String), PairGroup> = HashMap::new();
+    for (idx, hit) in hits.iter().enumerate() {
+        let (target, is_forward) = if let Some(target) = hit.primer.strip_suffix(forward_suffix) {
+            (target, true)
+        } else if let Some(target) = hit.primer.strip_suffix(reverse_suffix) {
+            (target, false)
+        } else {
+            continue;
+        };
+
+        let group = groups
+            .entry((hit.file.clone(), hit.contig.clone(), target.to_string()))
+            .or_default();
+        match (is_forward, hit.strand == '+') {
+            (true, true) => group.forward_plus.push(idx),
+            (true, false) => group.forward_minus.push(idx),
+            (false, true) => group.reverse_plus.push(idx),
+            (false, false) => group.reverse_minus.push(idx),
+        }
+    }
+
+    for group in groups.values_mut() {
+        for list in [
+            &mut group.forward_plus,
+            &mut group.forward_minus,
+            &mut group.reverse_plus,
+            &mut group.reverse_minus,
+        ] {
+            list.sort_by_key(|&idx| hits[idx].start);
+        }
+    }
+    groups
+}
+
+/// Pairs each `plus` hit with the first `minus` hit starting at or after it, when the pair
+/// spans at most `max_amplicon_size` bp. Both index lists must be sorted by start.
+fn converging_pairs<'a>(
+    hits: &'a [Hit],
+    plus: &'a [usize],
+    minus: &'a [usize],
+    max_amplicon_size: usize,
+) -> impl Iterator<Item = (usize, usize)> + 'a {
+    let mut next = 0usize;
+    plus.iter()
+        .map_while(move |&fwd| {
+            let start = hits[fwd].start;
+            while next < minus.len() && hits[minus[next]].start < start {
+                next += 1;
+            }
+            let rev = *minus.get(next)?;
+            Some((fwd, rev))
+        })
+        .filter(move |&(fwd, rev)| hits[rev].end - hits[fwd].start <= max_amplicon_size)
+}
+
+/// Marks `+`/`-` hit pairs that face each other within `max_amplicon_size` bp.
+/// Both index lists must be sorted by start.
+fn annotate_converging(
+    hits: &mut [Hit],
+    plus: &[usize],
+    minus: &[usize],
+    max_amplicon_size: usize,
+    record_size: bool,
+) {
+    if plus.is_empty() || minus.is_empty() {
+        return;
+    }
+
+    let pairs = converging_pairs(hits, plus, minus, max_amplicon_size).collect::<Vec<_>>();
+    for (fwd, rev) in pairs {
+        hits[fwd].amplifiable = true;
+        if record_size {
+            hits[fwd].amplicon_size = Some(hits[rev].end - hits[fwd].start);
+        }
+    }
+
+    let mut upstream = 0usize;
+    for &rev in minus {
+        let rev_start = hits[rev].start;
+        while upstream < plus.len() && hits[plus[upstream]].start <= rev_start {
+            upstream += 1;
+        }
+        if upstream == 0 {
+            continue;
+        }
+        let size = hits[rev].end - hits[plus[upstream - 1]].start;
+        if size <= max_amplicon_size {
+            hits[rev].amplifiable = true;
+            if record_size {
+                hits[rev].amplicon_size = Some(size);
+            }
+        }
+    }
+}
+
+/// Sorted merge recording, for each query hit, the signed distance to the closest partner hit.
+fn annotate_nearest(hits: &mut [Hit], queries: &[usize], partners: &[usize], window: usize) {
+    if partners.is_empty() {
+        return;
+    }
+
+    let mut next = 0usize;
+    for &query in queries {
+        let start = hits[query].start;
+        while next < partners.len() && hits[partners[next]].start < start {
+            next += 1;
+        }
+        let nearest = [next.checked_sub(1), (next < partners.len()).then_some(next)]
+            .into_iter()
+            .flatten()
+            .map(|idx| hits[partners[idx]].start as i64 - start as i64)
+            .min_by_key(|distance| distance.unsigned_abs());
+        if let Some(distance) = nearest
+            && distance.unsigned_abs() <= window as u64
+        {
+            hits[query].nearest_partner_distance = Some(distance);
+        }
+    }
+}
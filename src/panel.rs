@@ -0,0 +1,163 @@
+use anyhow::{Result, bail};
+use std::io::Write;
+
+use crate::{Primer, write_fasta_record};
+
+/// Layouts supported by `export-panel`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PanelFormat {
+    /// `name<tab>sequence` with a header row.
+    Tsv,
+    /// One `>name` record per primer.
+    Fasta,
+    /// IDT bulk upload CSV: Name, Sequence, Scale, Purification.
+    Idt,
+}
+
+/// Column defaults for the IDT bulk upload layout.
+#[derive(Debug, Clone)]
+pub struct IdtDefaults {
+    pub scale: String,
+    pub purification: String,
+}
+
+impl Default for IdtDefaults {
+    fn default() -> Self {
+        Self {
+            scale: "25nm".to_string(),
+            purification: "STD".to_string(),
+        }
+    }
+}
+
+/// Writes normalized primer sequences; the output loads back through `load_primers` unchanged.
+/// `fasta_line_width` wraps FASTA sequence lines (`0` = no wrapping).
+pub fn write_panel<W: Write>(
+    out: &mut W,
+    primers: &[Primer],
+    format: PanelFormat,
+    idt: &IdtDefaults,
+    fasta_line_width: usize,
+) -> Result<()> {
+    match format {
+        PanelFormat::Tsv => {
+            writeln!(out, "name\tsequence")?;
+            for primer in primers {
+                check_row_name(&primer.name, '\t', "TSV")?;
+                writeln!(out, "{}\t{}", primer.name, primer.sequence)?;
+            }
+        }
+        PanelFormat::Fasta => {
+            for primer in primers {
+                write_fasta_record(
+                    out,
+                    &primer.name,
+                    primer.sequence.as_bytes(),
+                    fasta_line_width,
+                )?;
+            }
+        }
+        PanelFormat::Idt => {
+            reject_delimiter(&idt.scale, ',', "IDT CSV")?;
+            reject_delimiter(&idt.purification, ',', "IDT CSV")?;
+            writeln!(out, "Name,Sequence,Scale,Purification")?;
+            for primer in primers {
+                check_row_name(&primer.name, ',', "IDT CSV")?;
+                writeln!(
+                    out,
+                    "{},{},{},{}",
+                    primer.name, primer.sequence, idt.scale, idt.purification
+                )?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Rejects names that would not load back as the same row: ones holding the delimiter, and
+/// `#`-leading ones, which the panel loader skips as comments.
+fn check_row_name(name: &str, delimiter: char, layout: &str) -> Result<()> {
+    reject_delimiter(name, delimiter, layout)?;
+    if name.starts_with('#') {
+        bail!(
+            "'{name}' starts with '#', which {layout} panels read as a comment, and cannot be exported"
+        );
+    }
+    Ok(())
+}
+
+fn reject_delimiter(value: &str, delimiter: char, layout: &str) -> Result<()> {
+    if value.contains(delimiter) {
+        bail!("'{value}' contains a {layout} delimiter {delimiter:?} and cannot be exported");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::tmp_path;
+    use crate::{DEFAULT_FASTA_LINE_WIDTH, load_primers};
+
+    #[test]
+    fn every_format_round_trips_through_load_primers() {
+        let primers = vec![
+            Primer::from_name_and_sequence("BRCA1 F", "acgu yrn").expect("primer 1"),
+            Primer::from_name_and_sequence("p2", "TTGCA").expect("primer 2"),
+        ];
+
+        for (format, ext) in [
+            (PanelFormat::Tsv, "tsv"),
+            (PanelFormat::Fasta, "fa"),
+            (PanelFormat::Idt, "csv"),
+        ] {
+            let mut buf = Vec::new();
+            write_panel(&mut buf, &primers, format, &IdtDefaults::default(), 3).expect("export");
+
+            let path = tmp_path(&format!("panel.{ext}"));
+            std::fs::write(&path, &buf).expect("write exported panel");
+            let loaded = load_primers(&path).expect("reload exported panel");
+            std::fs::remove_file(&path).expect("remove exported panel");
+
+            let expected: Vec<_> = primers.iter().map(|p| (&p.name, &p.sequence)).collect();
+            let actual: Vec<_> = loaded.iter().map(|p| (&p.name, &p.sequence)).collect();
+            assert_eq!(expected, actual, "{format:?} round trip");
+        }
+        assert_eq!(primers[0].sequence, "ACGTYRN");
+    }
+
+    #[test]
+    fn idt_export_rejects_comma_in_name() {
+        let primers = vec![Primer::from_name_and_sequence("a,b", "ACGT").expect("primer")];
+        let mut buf = Vec::new();
+        let err = write_panel(
+            &mut buf,
+            &primers,
+            PanelFormat::Idt,
+            &IdtDefaults::default(),
+            DEFAULT_FASTA_LINE_WIDTH,
+        );
+        assert!(err.is_err());
+    }
+
+    #[test]
+    fn delimited_exports_reject_comment_names() {
+        let primers = vec![Primer::from_name_and_sequence("#p1", "ACGT").expect("primer")];
+        for format in [PanelFormat::Tsv, PanelFormat::Idt] {
+            let mut buf = Vec::new();
+            let err = write_panel(&mut buf, &primers, format, &IdtDefaults::default(), 0)
+                .expect_err("comment-like name");
+            assert!(err.to_string().contains("starts with '#'"), "{err}");
+        }
+        let mut buf = Vec::new();
+        write_panel(
+            &mut buf,
+            &primers,
+            PanelFormat::Fasta,
+            &IdtDefaults::default(),
+            0,
+        )
+        .expect("FASTA headers keep the name");
+        assert_eq!(buf, b">#p1\nACGT\n");
+    }
+}
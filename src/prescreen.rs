@@ -0,0 +1,296 @@
+use std::collections::HashMap;
+use std::ops::Range;
+
+use crate::Primer;
+use crate::contig::ContigMasks;
+use crate::regions::merge_intervals;
+
+/// Bases per seed; a seed packs into 24 bits, two per base.
+const SEED_LEN: usize = 12;
+const SEED_CODE_MASK: u32 = (1 << (2 * SEED_LEN)) - 1;
+// Bloom filter sizing: ~1.7% false positives at 10 bits and 3 probes per seed.
+const BLOOM_BITS_PER_SEED: usize = 10;
+const BLOOM_PROBES: u32 = 3;
+
+/// Exact 12-mer seeds of a panel for [`crate::ScanAlgorithm::Bloom`].
+///
+/// Each primer (and its reverse complement, when that strand is scanned) is cut into
+/// `max_mismatches + 1` segments and one concrete (A/C/G/T only) 12-mer is taken from each:
+/// a window within the mismatch budget leaves at least one segment untouched, so it always
+/// contains one of its primer's seeds. Primers too short or too degenerate for that are not
+/// seeded and keep the exhaustive scan.
+#[derive(Debug)]
+pub(crate) struct SeedIndex {
+    bloom: BloomFilter,
+    /// Seed code -> `(primer index, offset of the seed in the window)`.
+    seeds: HashMap<u32, Vec<(usize, usize)>>,
+    primer_lens: Vec<usize>,
+    seeded: Vec<bool>,
+}
+
+impl SeedIndex {
+    pub(crate) fn new(primers: &[Primer], max_mismatches: usize, scan_reverse: bool) -> Self {
+        let mut seeds: HashMap<u32, Vec<(usize, usize)>> = HashMap::new();
+        let mut seeded = Vec::with_capacity(primers.len());
+        for (idx, primer) in primers.iter().enumerate() {
+            let forward = primer_seeds(&primer.masks, max_mismatches);
+            let reverse = if scan_reverse && !primer.is_palindromic {
+                let masks = primer
+                    .combined_masks
+                    .iter()
+                    .map(|mask| mask >> 4)
+                    .collect::<Vec<_>>();
+                primer_seeds(&masks, max_mismatches)
+            } else {
+                Some(Vec::new())
+            };
+            let (Some(forward), Some(reverse)) = (forward, reverse) else {
+                seeded.push(false);
+                continue;
+            };
+            for (code, offset) in forward.into_iter().chain(reverse) {
+                seeds.entry(code).or_default().push((idx, offset));
+            }
+            seeded.push(true);
+        }
+        let mut bloom = BloomFilter::with_capacity(seeds.len());
+        for &code in seeds.keys() {
+            bloom.insert(code);
+        }
+        Self {
+            bloom,
+            seeds,
+            primer_lens: primers.iter().map(Primer::len).collect(),
+            seeded,
+        }
+    }
+
+    /// True when no primer could be seeded, so the prescreen would only add work.
+    pub(crate) fn is_empty(&self) -> bool {
+        !self.seeded.contains(&true)
+    }
+
+    /// Window starts worth testing per primer on `contig`, as sorted disjoint ranges: every
+    /// start a seed occurrence implies, plus every window covering an ambiguous reference
+    /// base (those match several bases, so they cannot be looked up). `None` for unseeded
+    /// primers, which test every window.
+    pub(crate) fn candidate_starts<M: ContigMasks>(
+        &self,
+        contig: &M,
+    ) -> Vec<Option<Vec<Range<usize>>>> {
+        let mut starts = vec![Vec::new(); self.primer_lens.len()];
+        let mut ambiguous: Vec<Range<usize>> = Vec::new();
+        let (mut code, mut run) = (0u32, 0usize);
+        for idx in 0..contig.len() {
+            let mask = contig.mask(idx);
+            if mask.is_power_of_two() {
+                code = ((code << 2) | mask.trailing_zeros()) & SEED_CODE_MASK;
+                run += 1;
+            } else {
+                run = 0;
+                if mask.count_ones() > 1 {
+                    match ambiguous.last_mut() {
+                        Some(last) if last.end == idx => last.end += 1,
+                        _ => ambiguous.push(idx..idx + 1),
+                    }
+                }
+            }
+            if run < SEED_LEN || !self.bloom.contains(code) {
+                continue;
+            }
+            let Some(sites) = self.seeds.get(&code) else {
+                continue;
+            };
+            let seed_start = idx + 1 - SEED_LEN;
+            for &(primer, offset) in sites {
+                if let Some(start) = seed_start.checked_sub(offset)
+                    && start + self.primer_lens[primer] <= contig.len()
+                {
+                    starts[primer].push(start..start + 1);
+                }
+            }
+        }
+
+        starts
+            .into_iter()
+            .enumerate()
+            .map(|(primer, mut ranges)| {
+                if !self.seeded[primer] {
+                    return None;
+                }
+                let len = self.primer_lens[primer];
+                let starts_end = (contig.len() + 1).saturating_sub(len);
+                ranges.extend(
+                    ambiguous
+                        .iter()
+                        .map(|run| (run.start + 1).saturating_sub(len)..run.end.min(starts_end)),
+                );
+                Some(merge_intervals(ranges))
+            })
+            .collect()
+    }
+}
+
+/// One concrete seed `(code, offset)` per segment of `masks`, or `None` when a segment has
+/// no concrete 12-mer.
+fn primer_seeds(masks: &[u8], max_mismatches: usize) -> Option<Vec<(u32, usize)>> {
+    let segments = max_mismatches + 1;
+    let segment_len = masks.len() / segments;
+    if segment_len < SEED_LEN {
+        return None;
+    }
+    (0..segments)
+        .map(|segment| {
+            let start = segment * segment_len;
+            let end = if segment + 1 == segments {
+                masks.len()
+            } else {
+                start + segment_len
+            };
+            (start..=end - SEED_LEN).find_map(|offset| {
+                let window = &masks[offset..offset + SEED_LEN];
+                window.iter().all(|mask| mask.is_power_of_two()).then(|| {
+                    let code = window
+                        .iter()
+                        .fold(0u32, |code, mask| (code << 2) | mask.trailing_zeros());
+                    (code, offset)
+                })
+            })
+        })
+        .collect()
+}
+
+/// Bit array answering "possibly present" for seed codes, so most reference 12-mers are
+/// rejected without a hash-map lookup. Small enough to stay in cache for large panels.
+#[derive(Debug)]
+struct BloomFilter {
+    bits: Vec<u64>,
+    index_mask: u64,
+}
+
+impl BloomFilter {
+    fn with_capacity(seeds: usize) -> Self {
+        let bits = (seeds * BLOOM_BITS_PER_SEED).next_power_of_two().max(64);
+        Self {
+            bits: vec![0; bits / 64],
+            index_mask: bits as u64 - 1,
+        }
+    }
+
+    /// Double hashing over one 64-bit multiplicative hash of the code.
+    fn probes(&self, code: u32) -> impl Iterator<Item = u64> + '_ {
+        let hash = u64::from(code).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+        let (first, step) = (hash >> 32, (hash & 0xFFFF_FFFF) | 1);
+        (0..u64::from(BLOOM_PROBES))
+            .map(move |probe| first.wrapping_add(probe.wrapping_mul(step)) & self.index_mask)
+    }
+
+    fn insert(&mut self, code: u32) {
+        let probes = self.probes(code).collect::<Vec<_>>();
+        for bit in probes {
+            self.bits[(bit / 64) as usize] |= 1 << (bit % 64);
+        }
+    }
+
+    fn contains(&self, code: u32) -> bool {
+        self.probes(code)
+            .all(|bit| self.bits[(bit / 64) as usize] & (1 << (bit % 64)) != 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MemoryProfile, ScanAlgorithm, ScanOptions, XorShift64, scan_sequence};
+
+    #[test]
+    fn bloom_scan_matches_exhaustive_scan() {
+        let mut rng = XorShift64::new(11);
+        let mut reference = (0..20_000)
+            .map(|_| b"ACGT"[(rng.next_u32() & 3) as usize])
+            .collect::<Vec<_>>();
+        // Primers copied from the reference with up to two substitutions, one degenerate
+        // primer that cannot be seeded, and one too short to seed.
+        let mut primers = (0..40)
+            .map(|idx| {
+                let start = rng.next_u32() as usize % (reference.len() - 24);
+                let mut sequence = reference[start..start + 24].to_vec();
+                for _ in 0..idx % 3 {
+                    let pos = rng.next_u32() as usize % sequence.len();
+                    sequence[pos] = b"ACGT"[(rng.next_u32() & 3) as usize];
+                }
+                let sequence = String::from_utf8(sequence).expect("ascii");
+                Primer::from_name_and_sequence(format!("p{idx}"), &sequence).expect("primer")
+            })
+            .collect::<Vec<_>>();
+        primers.push(
+            Primer::from_name_and_sequence("deg", "ACGNNTACNNAGCTNNACGTNNAC").expect("primer"),
+        );
+        primers.push(Primer::from_name_and_sequence("short", "ACGTTGCA").expect("primer"));
+        // An N run, IUPAC codes, soft-masked bases, and a reverse-complement site.
+        reference[1000..1030].fill(b'N');
+        reference[5000] = b'R';
+        reference[7001] = b'y';
+        reference[9000..9024].make_ascii_lowercase();
+        let site = primers[0].reverse_complement.clone();
+        reference[12_000..12_024].copy_from_slice(site.as_bytes());
+        let reference = String::from_utf8(reference).expect("ascii");
+
+        for max_mismatches in 0..3 {
+            for memory_profile in [MemoryProfile::Fast, MemoryProfile::Lean] {
+                for lowercase_mismatches in [false, true] {
+                    let exhaustive = ScanOptions {
+                        max_mismatches,
+                        memory_profile,
+                        lowercase_mismatches,
+                        ..ScanOptions::default()
+                    };
+                    let bloom = ScanOptions {
+                        algorithm: ScanAlgorithm::Bloom,
+                        ..exhaustive.clone()
+                    };
+                    let expected =
+                        scan_sequence(&reference, "chr1", &primers, &exhaustive).expect("scan");
+                    let actual = scan_sequence(&reference, "chr1", &primers, &bloom).expect("scan");
+                    assert!(expected.total_hits >= primers.len() as u64 - 2);
+                    assert_eq!(actual.hits, expected.hits, "k {max_mismatches}");
+                    assert_eq!(actual.hit_hash, expected.hit_hash);
+                    let totals = |result: &crate::ScanResult| {
+                        result
+                            .summary
+                            .iter()
+                            .map(|row| (row.total_hits, row.expected_random_hits))
+                            .collect::<Vec<_>>()
+                    };
+                    assert_eq!(totals(&actual), totals(&expected));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn seeds_need_a_concrete_twelve_mer_per_segment() {
+        let masks = |sequence: &str| {
+            Primer::from_name_and_sequence("p", sequence)
+                .expect("primer")
+                .masks
+        };
+        let concrete = masks("ACGTACGTACGTTTGGCCAAGGTT");
+        assert_eq!(primer_seeds(&concrete, 0).map(|seeds| seeds.len()), Some(1));
+        assert_eq!(
+            primer_seeds(&concrete, 1)
+                .expect("two segments of 12")
+                .iter()
+                .map(|seed| seed.1)
+                .collect::<Vec<_>>(),
+            vec![0, 12]
+        );
+        assert_eq!(primer_seeds(&concrete, 2), None);
+        // An N in every 12-base window of the second half.
+        assert_eq!(primer_seeds(&masks("ACGTACGTACGTTTGGNCAAGGNT"), 1), None);
+
+        let mut bloom = BloomFilter::with_capacity(1);
+        bloom.insert(0xABCDEF);
+        assert!(bloom.contains(0xABCDEF));
+    }
+}
@@ -0,0 +1,222 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::Write;
+
+use crate::{Hit, Primer, iupac_mask};
+
+/// Per-position conservation of one primer across the scanned references (genomes).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PositionProfile {
+    pub primer: String,
+    /// References with at least one hit of the primer.
+    pub genomes: usize,
+    /// References scanned without any hit of the primer.
+    pub genomes_without_hit: usize,
+    /// Per primer position (5' to 3'), how many of the `genomes` mismatch there in their best
+    /// hit.
+    pub mismatched: Vec<usize>,
+    /// `mismatched` divided by `genomes`; all zero when no reference has a hit.
+    pub mismatch_frequency: Vec<f64>,
+}
+
+/// Primer positions (0-based, 5' to 3') where `hit` does not match `primer`. Positions hanging
+/// off a contig end count as mismatches; `=` hits are read on their better strand.
+pub fn mismatch_positions(hit: &Hit, primer: &Primer) -> Vec<usize> {
+    let len = primer.len();
+    let reverse = match hit.strand {
+        '-' => true,
+        '=' => hit.rev_mismatches < hit.fwd_mismatches,
+        _ => false,
+    };
+    // Window index of the first matched base: bases hanging off the start precede it.
+    let first = if hit.start == 0 { hit.overhang } else { 0 };
+    let matched = hit.matched.as_bytes();
+    (0..len)
+        .filter(|&idx| {
+            let reference = idx
+                .checked_sub(first)
+                .and_then(|offset| matched.get(offset))
+                .map(|&base| iupac_mask(base).unwrap_or(0b1111));
+            let combined = primer.combined_masks[idx];
+            let query = if reverse {
+                combined >> 4
+            } else {
+                combined & 0x0f
+            };
+            reference.is_none_or(|reference| reference & query == 0)
+        })
+        .map(|idx| if reverse { len - 1 - idx } else { idx })
+        .collect()
+}
+
+/// Aggregates, per primer, the mismatch positions of its best hit (fewest mismatches, first in
+/// `hits` order on ties) in each of `genomes`, the reference files as named in [`Hit::file`].
+/// Taking one hit per genome keeps multi-copy sites from outweighing single-copy genomes.
+pub fn position_profiles(
+    primers: &[Primer],
+    hits: &[Hit],
+    genomes: &[String],
+) -> Vec<PositionProfile> {
+    let mut best: HashMap<(&str, &str), &Hit> = HashMap::new();
+    for hit in hits {
+        let kept = best.entry((&hit.primer, &hit.file)).or_insert(hit);
+        if hit.mismatches < kept.mismatches {
+            *kept = hit;
+        }
+    }
+
+    primers
+        .iter()
+        .map(|primer| {
+            let mut mismatched = vec![0; primer.len()];
+            let mut with_hit = 0;
+            for genome in genomes {
+                let Some(hit) = best.get(&(primer.name.as_str(), genome.as_str())) else {
+                    continue;
+                };
+                with_hit += 1;
+                for position in mismatch_positions(hit, primer) {
+                    mismatched[position] += 1;
+                }
+            }
+            let mismatch_frequency = mismatched
+                .iter()
+                .map(|&count| match with_hit {
+                    0 => 0.0,
+                    genomes => count as f64 / genomes as f64,
+                })
+                .collect();
+            PositionProfile {
+                primer: primer.name.clone(),
+                genomes: with_hit,
+                genomes_without_hit: genomes.len() - with_hit,
+                mismatched,
+                mismatch_frequency,
+            }
+        })
+        .collect()
+}
+
+/// Writes profiles as a primer x position matrix: `primer`, `genomes`, `genomes_without_hit`,
+/// then one mismatch frequency column per position `1..` of the longest primer (`.` past a
+/// primer's end).
+pub fn write_position_profiles_tsv<W: Write>(
+    out: &mut W,
+    profiles: &[PositionProfile],
+) -> std::io::Result<()> {
+    let width = profiles
+        .iter()
+        .map(|profile| profile.mismatch_frequency.len())
+        .max()
+        .unwrap_or(0);
+    write!(out, "primer\tgenomes\tgenomes_without_hit")?;
+    for position in 1..=width {
+        write!(out, "\t{position}")?;
+    }
+    writeln!(out)?;
+    for profile in profiles {
+        write!(
+            out,
+            "{}\t{}\t{}",
+            profile.primer, profile.genomes, profile.genomes_without_hit
+        )?;
+        for position in 0..width {
+            match profile.mismatch_frequency.get(position) {
+                Some(frequency) => write!(out, "\t{frequency:.4}")?,
+                None => write!(out, "\t.")?,
+            }
+        }
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+/// Writes profiles as `{"genomes": [...], "primers": [PositionProfile, ...]}` for charting.
+pub fn write_position_profiles_json<W: Write>(
+    out: &mut W,
+    genomes: &[String],
+    profiles: &[PositionProfile],
+) -> serde_json::Result<()> {
+    serde_json::to_writer_pretty(
+        &mut *out,
+        &serde_json::json!({ "genomes": genomes, "primers": profiles }),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::tmp_path;
+    use crate::{ScanOptions, scan_references};
+
+    #[test]
+    fn profiles_count_best_hit_mismatches_per_genome() {
+        let primer = Primer::from_name_and_sequence("p1", "ACGTTGCAAC").expect("primer");
+        let rc = |site: &str| {
+            Primer::from_name_and_sequence("site", site)
+                .expect("site")
+                .reverse_complement
+        };
+        let flank = "T".repeat(12);
+        // Genome 1: perfect site, plus a 2-mismatch copy that must not count.
+        // Genome 2: mismatch at position 3, on the minus strand.
+        // Genome 3: mismatches at positions 3 and 8.
+        // Genome 4: no site.
+        let genomes = [
+            format!("{flank}ACGTTGCAAC{flank}AGGTTGCTAC{flank}"),
+            format!("{flank}{}{flank}", rc("ACCTTGCAAC")),
+            format!("{flank}ACCTTGCTAC{flank}"),
+            flank.repeat(3),
+        ];
+        let paths = genomes
+            .iter()
+            .enumerate()
+            .map(|(idx, genome)| {
+                let path = tmp_path(&format!("profile_genome{idx}.fa"));
+                std::fs::write(&path, format!(">chr1\n{genome}\n")).expect("write genome");
+                path
+            })
+            .collect::<Vec<_>>();
+        let options = ScanOptions {
+            max_mismatches: 2,
+            ..ScanOptions::default()
+        };
+        let primers = vec![primer];
+        let result = scan_references(&paths, &primers, &options).expect("scan");
+        let files = result
+            .files
+            .iter()
+            .map(|status| status.file.clone())
+            .collect::<Vec<_>>();
+
+        let profiles = position_profiles(&primers, &result.hits, &files);
+        let profile = &profiles[0];
+        assert_eq!((profile.genomes, profile.genomes_without_hit), (3, 1));
+        assert_eq!(profile.mismatched, [0, 0, 2, 0, 0, 0, 0, 1, 0, 0]);
+        assert!((profile.mismatch_frequency[2] - 2.0 / 3.0).abs() < 1e-9);
+
+        let mut tsv = Vec::new();
+        write_position_profiles_tsv(&mut tsv, &profiles).expect("write TSV");
+        let tsv = String::from_utf8(tsv).expect("utf8");
+        let mut lines = tsv.lines();
+        assert_eq!(
+            lines.next(),
+            Some("primer\tgenomes\tgenomes_without_hit\t1\t2\t3\t4\t5\t6\t7\t8\t9\t10")
+        );
+        assert_eq!(
+            lines.next(),
+            Some(
+                "p1\t3\t1\t0.0000\t0.0000\t0.6667\t0.0000\t0.0000\t0.0000\t0.0000\t0.3333\t0.0000\t0.0000"
+            )
+        );
+
+        let mut json = Vec::new();
+        write_position_profiles_json(&mut json, &files, &profiles).expect("write JSON");
+        let json: serde_json::Value = serde_json::from_slice(&json).expect("json");
+        assert_eq!(json["genomes"].as_array().map(Vec::len), Some(4));
+        assert_eq!(json["primers"][0]["mismatched"][7], 1);
+        for path in paths {
+            std::fs::remove_file(path).expect("remove genome");
+        }
+    }
+}
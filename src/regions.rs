@@ -0,0 +1,697 @@
+use anyhow::{Context, Result, bail};
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::io::{BufRead, Write};
+use std::ops::Range;
+use std::path::Path;
+
+use crate::{ContigLength, Hit, open_reader};
+
+/// Per-contig scan intervals (0-based, half-open), merged so no base is scanned twice.
+#[derive(Debug, Clone, Default)]
+pub struct RegionSet {
+    by_contig: HashMap<String, Vec<Range<usize>>>,
+}
+
+impl RegionSet {
+    /// Builds a set from `(contig, start, end)` intervals, merging overlapping and adjacent ones.
+    pub fn from_intervals<I, S>(intervals: I) -> Self
+    where
+        I: IntoIterator<Item = (S, usize, usize)>,
+        S: Into<String>,
+    {
+        let mut by_contig: HashMap<String, Vec<Range<usize>>> = HashMap::new();
+        for (contig, start, end) in intervals {
+            by_contig.entry(contig.into()).or_default().push(start..end);
+        }
+        for ranges in by_contig.values_mut() {
+            *ranges = merge_intervals(std::mem::take(ranges));
+        }
+        by_contig.retain(|_, ranges| !ranges.is_empty());
+        Self { by_contig }
+    }
+
+    /// Merged intervals of `contig`, sorted by start.
+    pub fn intervals(&self, contig: &str) -> &[Range<usize>] {
+        self.by_contig.get(contig).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn contains_contig(&self, contig: &str) -> bool {
+        self.by_contig.contains_key(contig)
+    }
+
+    /// Window start positions for a `window_len` query that fit entirely inside an interval.
+    pub(crate) fn window_starts(
+        &self,
+        contig: &str,
+        contig_len: usize,
+        window_len: usize,
+    ) -> Vec<Range<usize>> {
+        self.intervals(contig)
+            .iter()
+            .filter_map(|range| {
+                let end = range.end.min(contig_len);
+                (end >= range.start + window_len).then(|| range.start..end - window_len + 1)
+            })
+            .collect()
+    }
+}
+
+/// Named intervals per contig (repeats, exclusion regions), kept unmerged so each overlap
+/// reports its own name.
+#[derive(Debug, Clone, Default)]
+pub struct NamedRegions {
+    /// Intervals by contig, sorted by start.
+    by_contig: HashMap<String, Vec<(Range<usize>, String)>>,
+}
+
+impl NamedRegions {
+    /// Builds a set from `(contig, start, end, name)` intervals.
+    pub fn from_intervals<I, S>(intervals: I) -> Self
+    where
+        I: IntoIterator<Item = (S, usize, usize, S)>,
+        S: Into<String>,
+    {
+        let mut by_contig: HashMap<String, Vec<(Range<usize>, String)>> = HashMap::new();
+        for (contig, start, end, name) in intervals {
+            by_contig
+                .entry(contig.into())
+                .or_default()
+                .push((start..end, name.into()));
+        }
+        for intervals in by_contig.values_mut() {
+            intervals.sort_by_key(|(range, _)| (range.start, range.end));
+        }
+        Self { by_contig }
+    }
+
+    /// Names of the intervals sharing at least one base with `start..end` on `contig`, in
+    /// start order.
+    pub fn overlapping(&self, contig: &str, start: usize, end: usize) -> Vec<String> {
+        let Some(intervals) = self.by_contig.get(contig) else {
+            return Vec::new();
+        };
+        let candidates = intervals.partition_point(|(range, _)| range.start < end);
+        intervals[..candidates]
+            .iter()
+            .filter(|(range, _)| range.end > start && range.start < range.end)
+            .map(|(_, name)| name.clone())
+            .collect()
+    }
+}
+
+/// Reads named intervals from a BED file. With `require_name`, a line without a `name`
+/// column is an error; otherwise it is named `contig:start-end`.
+pub fn load_named_bed(path: &Path, require_name: bool) -> Result<NamedRegions> {
+    let records = read_bed_records(path, require_name)?;
+    Ok(NamedRegions::from_intervals(records.into_iter().map(
+        |record| {
+            let name = record
+                .name
+                .unwrap_or_else(|| format!("{}:{}-{}", record.contig, record.start, record.end));
+            (record.contig, record.start, record.end, name)
+        },
+    )))
+}
+
+/// Sets [`Hit::repeat_class`] of every hit overlapping a repeat to the repeat's name (several
+/// overlapping repeats are joined with `,` in start order); other hits get `None`.
+pub fn annotate_repeats(hits: &mut [Hit], repeats: &NamedRegions) {
+    for hit in hits {
+        let names = repeats.overlapping(&hit.contig, hit.start, hit.end);
+        hit.repeat_class = (!names.is_empty()).then(|| names.join(","));
+    }
+}
+
+/// Sorts and merges overlapping or adjacent intervals; empty intervals are dropped. Merging
+/// abutting intervals lets a window straddle a tiled BED boundary.
+pub fn merge_intervals(mut intervals: Vec<Range<usize>>) -> Vec<Range<usize>> {
+    intervals.retain(|range| range.start < range.end);
+    intervals.sort_by_key(|range| range.start);
+    let mut merged: Vec<Range<usize>> = Vec::with_capacity(intervals.len());
+    for range in intervals {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+/// Bases of one contig inside at least one of its `hits`.
+pub(crate) fn covered_bases(hits: &[Hit]) -> u64 {
+    merge_intervals(hits.iter().map(|hit| hit.start..hit.end).collect())
+        .iter()
+        .map(|range| range.len() as u64)
+        .sum()
+}
+
+/// `covered_bases` over the scanned bases of `contigs`: whole contigs, or only the parts
+/// inside `regions` when the scan was restricted. `0.0` when nothing was scanned.
+pub(crate) fn coverage_fraction(
+    covered_bases: u64,
+    contigs: &[ContigLength],
+    regions: Option<&RegionSet>,
+) -> f64 {
+    let scanned_bases = contigs
+        .iter()
+        .map(|contig| match regions {
+            Some(regions) => regions
+                .intervals(&contig.contig)
+                .iter()
+                .map(|range| range.end.min(contig.length).saturating_sub(range.start) as u64)
+                .sum(),
+            None => contig.length as u64,
+        })
+        .sum::<u64>();
+    if scanned_bases == 0 {
+        return 0.0;
+    }
+    covered_bases as f64 / scanned_bases as f64
+}
+
+/// Parts of `0..length` not covered by `merged`, which must be sorted and non-overlapping.
+pub fn complement_intervals(merged: &[Range<usize>], length: usize) -> Vec<Range<usize>> {
+    let mut gaps = Vec::new();
+    let mut cursor = 0usize;
+    for range in merged {
+        if range.start >= length {
+            break;
+        }
+        if range.start > cursor {
+            gaps.push(cursor..range.start);
+        }
+        cursor = cursor.max(range.end);
+    }
+    if cursor < length {
+        gaps.push(cursor..length);
+    }
+    gaps
+}
+
+/// An uncovered stretch of a scanned contig.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Gap {
+    pub file: String,
+    pub contig: String,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Reference stretches of at least `min_gap` bp covered by no hit, or with `amplicons_only`,
+/// by no predicted amplicon (hits carrying an `amplicon_size`). Gaps follow `contigs` order.
+pub fn coverage_gaps(
+    contigs: &[ContigLength],
+    hits: &[Hit],
+    amplicons_only: bool,
+    min_gap: usize,
+) -> Vec<Gap> {
+    let mut covered: HashMap<(&str, &str), Vec<Range<usize>>> = HashMap::new();
+    for hit in hits {
+        let interval = if amplicons_only {
+            match (hit.amplicon_size, hit.strand) {
+                (Some(size), '-') => hit.end.saturating_sub(size)..hit.end,
+                (Some(size), _) => hit.start..hit.start + size,
+                (None, _) => continue,
+            }
+        } else {
+            hit.start..hit.end
+        };
+        covered
+            .entry((hit.file.as_str(), hit.contig.as_str()))
+            .or_default()
+            .push(interval);
+    }
+
+    let mut gaps = Vec::new();
+    for contig in contigs {
+        let merged = covered
+            .get(&(contig.file.as_str(), contig.contig.as_str()))
+            .cloned()
+            .map(merge_intervals)
+            .unwrap_or_default();
+        gaps.extend(
+            complement_intervals(&merged, contig.length)
+                .into_iter()
+                .filter(|gap| gap.len() >= min_gap.max(1))
+                .map(|gap| Gap {
+                    file: contig.file.clone(),
+                    contig: contig.contig.clone(),
+                    start: gap.start,
+                    end: gap.end,
+                }),
+        );
+    }
+    gaps
+}
+
+/// Writes gaps as three-column BED.
+pub fn write_gaps_bed<W: Write>(out: &mut W, gaps: &[Gap]) -> std::io::Result<()> {
+    for gap in gaps {
+        writeln!(out, "{}\t{}\t{}", gap.contig, gap.start, gap.end)?;
+    }
+    Ok(())
+}
+
+/// Default `--hotspots` window width in bp.
+pub const DEFAULT_HOTSPOT_WINDOW: usize = 100_000;
+/// Default `--hotspots` minimum number of distinct primers per window.
+pub const DEFAULT_HOTSPOT_MIN_PRIMERS: usize = 3;
+
+/// A reference stretch bound by many distinct primers; a candidate design exclusion zone.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Hotspot {
+    pub file: String,
+    pub contig: String,
+    pub start: usize,
+    pub end: usize,
+    pub distinct_primers: usize,
+    pub hits: usize,
+    /// Names of the distinct primers with hits in the hotspot, sorted.
+    pub primers: Vec<String>,
+}
+
+/// Finds stretches where hits of at least `min_primers` distinct primers start within `window`
+/// bp of each other. Overlapping qualifying windows merge into one hotspot spanning their hits.
+/// Hotspots are ranked by distinct primers, then hit count, then position.
+pub fn find_hotspots(hits: &[Hit], window: usize, min_primers: usize) -> Vec<Hotspot> {
+    let mut by_contig: BTreeMap<(&str, &str), Vec<&Hit>> = BTreeMap::new();
+    for hit in hits {
+        by_contig
+            .entry((hit.file.as_str(), hit.contig.as_str()))
+            .or_default()
+            .push(hit);
+    }
+
+    let mut hotspots = Vec::new();
+    for ((file, contig), mut contig_hits) in by_contig {
+        contig_hits.sort_by_key(|hit| hit.start);
+
+        let mut qualifying = Vec::new();
+        let mut in_window: HashMap<&str, usize> = HashMap::new();
+        let mut left = 0usize;
+        for (right, hit) in contig_hits.iter().enumerate() {
+            *in_window.entry(hit.primer.as_str()).or_default() += 1;
+            while hit.start - contig_hits[left].start >= window.max(1) {
+                let primer = contig_hits[left].primer.as_str();
+                if let Some(count) = in_window.get_mut(primer) {
+                    *count -= 1;
+                    if *count == 0 {
+                        in_window.remove(primer);
+                    }
+                }
+                left += 1;
+            }
+            if in_window.len() >= min_primers.max(1) {
+                let end = contig_hits[left..=right]
+                    .iter()
+                    .map(|hit| hit.end)
+                    .max()
+                    .unwrap_or(hit.end);
+                qualifying.push(contig_hits[left].start..end);
+            }
+        }
+
+        for range in merge_intervals(qualifying) {
+            let members = contig_hits
+                .iter()
+                .filter(|hit| hit.start >= range.start && hit.end <= range.end)
+                .collect::<Vec<_>>();
+            let primers = members
+                .iter()
+                .map(|hit| hit.primer.as_str())
+                .collect::<BTreeSet<_>>();
+            hotspots.push(Hotspot {
+                file: file.to_string(),
+                contig: contig.to_string(),
+                start: range.start,
+                end: range.end,
+                distinct_primers: primers.len(),
+                hits: members.len(),
+                primers: primers.into_iter().map(str::to_string).collect(),
+            });
+        }
+    }
+
+    hotspots.sort_by(|a, b| {
+        b.distinct_primers
+            .cmp(&a.distinct_primers)
+            .then(b.hits.cmp(&a.hits))
+            .then_with(|| (&a.file, &a.contig, a.start).cmp(&(&b.file, &b.contig, b.start)))
+    });
+    hotspots
+}
+
+/// Writes hotspots as BED5: the name is `hotspot_<rank>`, the score the distinct primer count.
+pub fn write_hotspots_bed<W: Write>(out: &mut W, hotspots: &[Hotspot]) -> std::io::Result<()> {
+    for (rank, hotspot) in hotspots.iter().enumerate() {
+        writeln!(
+            out,
+            "{}\t{}\t{}\thotspot_{}\t{}",
+            hotspot.contig,
+            hotspot.start,
+            hotspot.end,
+            rank + 1,
+            hotspot.distinct_primers
+        )?;
+    }
+    Ok(())
+}
+
+/// Hits on one contig, across every primer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ContigSummary {
+    pub contig: String,
+    pub total_hits: u64,
+    pub distinct_primers: u64,
+    pub perfect_hits: u64,
+}
+
+/// Per-contig totals over `hits`, most-hit contig first (ties in order of first hit). Contigs
+/// of the same name in different references are counted together; contigs without hits are
+/// left out.
+pub fn contig_summary(hits: &[Hit]) -> Vec<ContigSummary> {
+    let mut rows: Vec<ContigSummary> = Vec::new();
+    let mut primers: Vec<HashSet<&str>> = Vec::new();
+    let mut index: HashMap<&str, usize> = HashMap::new();
+    for hit in hits {
+        let slot = *index.entry(hit.contig.as_str()).or_insert_with(|| {
+            rows.push(ContigSummary {
+                contig: hit.contig.clone(),
+                total_hits: 0,
+                distinct_primers: 0,
+                perfect_hits: 0,
+            });
+            primers.push(HashSet::new());
+            rows.len() - 1
+        });
+        rows[slot].total_hits += 1;
+        rows[slot].perfect_hits += u64::from(hit.mismatches == 0);
+        primers[slot].insert(hit.primer.as_str());
+    }
+    for (row, primers) in rows.iter_mut().zip(&primers) {
+        row.distinct_primers = primers.len() as u64;
+    }
+    rows.sort_by_key(|row| Reverse(row.total_hits));
+    rows
+}
+
+/// Reads the first three columns (`chrom`, `chromStart`, `chromEnd`) of a BED file.
+/// Blank, `#`, `track`, and `browser` lines are skipped.
+pub fn load_bed(path: &Path) -> Result<RegionSet> {
+    let intervals = read_bed_records(path, false)?
+        .into_iter()
+        .map(|record| (record.contig, record.start, record.end));
+    Ok(RegionSet::from_intervals(intervals))
+}
+
+/// One BED line: the three coordinate columns and, when present, the `name` column.
+pub(crate) struct BedRecord {
+    pub(crate) contig: String,
+    pub(crate) start: usize,
+    pub(crate) end: usize,
+    pub(crate) name: Option<String>,
+}
+
+/// Parses every data line of a BED file, in file order. With `require_name`, a line without
+/// a fourth (`name`) column is an error.
+pub(crate) fn read_bed_records(path: &Path, require_name: bool) -> Result<Vec<BedRecord>> {
+    let mut reader = open_reader(path)?;
+    let mut line = String::new();
+    let mut records = Vec::new();
+    let mut line_number = 0usize;
+
+    loop {
+        line.clear();
+        let read_bytes = reader
+            .read_line(&mut line)
+            .with_context(|| format!("failed reading BED file '{}'", path.display()))?;
+        if read_bytes == 0 {
+            break;
+        }
+        line_number += 1;
+
+        let trimmed = line.trim();
+        if trimmed.is_empty()
+            || trimmed.starts_with('#')
+            || trimmed.starts_with("track")
+            || trimmed.starts_with("browser")
+        {
+            continue;
+        }
+
+        let mut fields = trimmed.split_whitespace();
+        let (Some(contig), Some(start), Some(end)) = (fields.next(), fields.next(), fields.next())
+        else {
+            bail!(
+                "BED line {} in '{}' needs chrom, start, and end columns",
+                line_number,
+                path.display()
+            );
+        };
+        let name = fields.next();
+        if require_name && name.is_none() {
+            bail!(
+                "BED line {} in '{}' needs a name column",
+                line_number,
+                path.display()
+            );
+        }
+        let parse = |value: &str| {
+            value.parse::<usize>().with_context(|| {
+                format!(
+                    "invalid coordinate '{}' on BED line {} in '{}'",
+                    value,
+                    line_number,
+                    path.display()
+                )
+            })
+        };
+        let (start, end) = (parse(start)?, parse(end)?);
+        if start > end {
+            bail!(
+                "BED line {} in '{}' has start {} after end {}",
+                line_number,
+                path.display(),
+                start,
+                end
+            );
+        }
+        records.push(BedRecord {
+            contig: contig.to_string(),
+            start,
+            end,
+            name: name.map(str::to_string),
+        });
+    }
+
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::reverse_complement;
+    use crate::test_support::tmp_path;
+    use crate::{Primer, ScanOptions, scan_sequence};
+
+    #[test]
+    fn complement_covers_leading_inner_and_trailing_gaps() {
+        let merged = merge_intervals(vec![30..40, 10..20, 15..25, 25..28, 90..120]);
+        assert_eq!(merged, vec![10..28, 30..40, 90..120]);
+        assert_eq!(
+            complement_intervals(&merged, 100),
+            vec![0..10, 28..30, 40..90]
+        );
+        assert_eq!(complement_intervals(&[], 5), vec![0..5]);
+        assert!(
+            complement_intervals(&merged, 40)
+                .iter()
+                .all(|gap| gap.end <= 30)
+        );
+    }
+
+    #[test]
+    fn hotspots_rank_windows_by_distinct_primers() {
+        let hit = |contig: &str, primer: &str, start: usize| Hit {
+            file: "ref.fa".to_string(),
+            contig: contig.to_string(),
+            primer: primer.to_string(),
+            primer_len: 10,
+            start,
+            end: start + 10,
+            strand: '+',
+            mismatches: 0,
+            matched: "ACGTACGTAC".to_string(),
+            complexity: 1.0,
+            matched_fraction: 1.0,
+            amplicon_size: None,
+            nearest_partner_distance: None,
+            amplifiable: false,
+            primer_sequence: None,
+            primer_reverse_complement: None,
+            fwd_mismatches: None,
+            rev_mismatches: None,
+            gc_skew: None,
+            transcript: None,
+            transcript_pos: None,
+            transcript_region: None,
+            contig_relative_position: None,
+            masked_sequence: None,
+            overhang: 0,
+            repeat_class: None,
+            ambiguous_positions: 0,
+            matches_via_ambiguity: 0,
+            extendable: None,
+            original_contig: None,
+            tag: None,
+        };
+        let hits = vec![
+            // chr1: four primers within 300 bp, plus one primer repeating far away.
+            hit("chr1", "a", 1_000),
+            hit("chr1", "b", 1_100),
+            hit("chr1", "c", 1_200),
+            hit("chr1", "d", 1_300),
+            hit("chr1", "a", 1_310),
+            hit("chr1", "e", 50_000),
+            hit("chr1", "e", 50_010),
+            hit("chr1", "e", 50_020),
+            // chr2: three primers, but spread wider than the window.
+            hit("chr2", "a", 0),
+            hit("chr2", "b", 600),
+            hit("chr2", "c", 1_200),
+            // chr3: three primers close together.
+            hit("chr3", "a", 10),
+            hit("chr3", "b", 20),
+            hit("chr3", "c", 30),
+        ];
+
+        let hotspots = find_hotspots(&hits, 500, 3);
+        let spans = hotspots
+            .iter()
+            .map(|h| {
+                (
+                    h.contig.as_str(),
+                    h.start,
+                    h.end,
+                    h.distinct_primers,
+                    h.hits,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            spans,
+            vec![("chr1", 1_000, 1_320, 4, 5), ("chr3", 10, 40, 3, 3)]
+        );
+        assert_eq!(hotspots[0].primers, vec!["a", "b", "c", "d"]);
+
+        let mut bed = Vec::new();
+        write_hotspots_bed(&mut bed, &hotspots).expect("write bed");
+        assert_eq!(
+            String::from_utf8(bed).expect("utf8"),
+            "chr1\t1000\t1320\thotspot_1\t4\nchr3\t10\t40\thotspot_2\t3\n"
+        );
+    }
+
+    #[test]
+    fn tiled_amplicons_leave_only_the_deliberate_gaps() {
+        let panel = [
+            ("t1_F", "GCTGACCTGA", 0),
+            ("t1_R", "CGTTGCAGGT", 40),
+            ("t2_F", "GTCAGGTCCA", 100),
+            ("t2_R", "CAGTCCGTTG", 150),
+        ];
+        let mut reference = vec![b'A'; 200];
+        let mut primers = Vec::new();
+        for (name, sequence, site) in panel {
+            let bound = if name.ends_with("_R") {
+                reverse_complement(sequence).expect("reverse complement")
+            } else {
+                sequence.to_string()
+            };
+            reference[site..site + bound.len()].copy_from_slice(bound.as_bytes());
+            primers.push(Primer::from_name_and_sequence(name, sequence).expect("primer"));
+        }
+        let options = ScanOptions {
+            max_mismatches: 0,
+            pair_suffix_pattern: Some(("_F".to_string(), "_R".to_string())),
+            ..ScanOptions::default()
+        };
+        let reference = String::from_utf8(reference).expect("ascii reference");
+        let result = scan_sequence(&reference, "chr1", &primers, &options).expect("scan");
+
+        let spans = |gaps: Vec<Gap>| gaps.iter().map(|g| g.start..g.end).collect::<Vec<_>>();
+        assert_eq!(
+            spans(coverage_gaps(&result.contigs, &result.hits, true, 1)),
+            vec![50..100, 160..200]
+        );
+        assert_eq!(
+            spans(coverage_gaps(&result.contigs, &result.hits, true, 45)),
+            vec![50..100]
+        );
+        assert_eq!(
+            spans(coverage_gaps(&result.contigs, &result.hits, false, 1)),
+            vec![10..40, 50..100, 110..150, 160..200]
+        );
+    }
+
+    #[test]
+    fn genome_coverage_counts_hit_bases_over_scanned_bases() {
+        let sites = ["ACGTTGCAAC", "GGATCCTTAG", "CATGCATGAA", "TTTTGGGGCC"];
+        let filler = "A".repeat(15);
+        let sequence = format!("{filler}{}", sites.join(&filler));
+        assert_eq!(sequence.len(), 100);
+        let primers = sites
+            .iter()
+            .enumerate()
+            .map(|(idx, site)| {
+                Primer::from_name_and_sequence(format!("p{idx}"), site).expect("primer")
+            })
+            .collect::<Vec<_>>();
+        let options = ScanOptions {
+            scan_reverse_complement: false,
+            compute_genome_coverage: true,
+            ..ScanOptions::default()
+        };
+
+        let result = scan_sequence(&sequence, "chr1", &primers, &options).expect("scan");
+        assert_eq!(result.hits.len(), 4);
+        let fraction = result.genome_coverage_fraction.expect("coverage requested");
+        assert!((fraction - 0.4).abs() < 1e-9, "{fraction}");
+
+        let options = ScanOptions {
+            compute_genome_coverage: false,
+            ..options
+        };
+        let result = scan_sequence(&sequence, "chr1", &primers, &options).expect("scan");
+        assert_eq!(result.genome_coverage_fraction, None);
+    }
+
+    #[test]
+    fn hits_overlapping_a_repeat_get_its_class() {
+        let bed = tmp_path("repeats.bed");
+        // Alu covers the end of the first site; the satellite is on another contig.
+        std::fs::write(&bed, "chr1\t8\t12\tAluY\nchr2\t0\t100\tALR_Alpha\n").expect("write BED");
+        let primers = vec![Primer::from_name_and_sequence("p1", "GATTACA").expect("primer")];
+        let options = ScanOptions {
+            scan_reverse_complement: false,
+            repeat_library_bed: Some(bed.clone()),
+            ..ScanOptions::default()
+        };
+
+        let result =
+            scan_sequence("CCCCGATTACACCCCCGATTACACC", "chr1", &primers, &options).expect("scan");
+        let classes = result
+            .hits
+            .iter()
+            .map(|hit| (hit.start, hit.repeat_class.as_deref()))
+            .collect::<Vec<_>>();
+        assert_eq!(classes, vec![(4, Some("AluY")), (16, None)]);
+
+        std::fs::write(&bed, "chr1\t8\t12\n").expect("write BED");
+        let error = scan_sequence("GATTACA", "chr1", &primers, &options).expect_err("no name");
+        assert!(format!("{error:#}").contains("name column"), "{error:#}");
+        let _ = std::fs::remove_file(bed);
+    }
+}
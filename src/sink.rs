@@ -0,0 +1,1463 @@
+use anyhow::{Context, Result, bail};
+use crossterm::queue;
+use crossterm::style::{ResetColor, SetForegroundColor};
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::cli::primer_color;
+use crate::groups::SummarySubtotal;
+use crate::spill::{HitCollector, HitStream};
+use crate::{Hit, PrimerSummary, write_fasta_record};
+
+/// Destination for scan output, one record at a time.
+///
+/// The built-in TSV and NDJSON writers implement it, and [`crate::Scanner::scan_into`] drives
+/// any implementation, so downstream crates can add their own formats. This trait is part of
+/// the stable API: its methods will not change within a major version.
+pub trait HitSink {
+    /// Called once per hit, in output order.
+    fn on_hit(&mut self, hit: &Hit) -> Result<()>;
+    /// Called once per primer summary row, after every hit.
+    fn on_summary(&mut self, row: &PrimerSummary) -> Result<()>;
+    /// Called once after the last record; flush buffered output here.
+    fn finish(&mut self) -> Result<()>;
+}
+
+/// Optional columns of TSV hit and summary rows, appended in field order.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TsvColumns {
+    /// `amplicon_size`, `nearest_partner_distance`, `amplifiable`.
+    pub pairing: bool,
+    /// Wrap each row in its [`primer_color`].
+    pub color: bool,
+    /// `primer_sequence`, `primer_reverse_complement`.
+    pub primer_sequence: bool,
+    pub gc_skew: bool,
+    pub matched_fraction: bool,
+    /// `transcript`, `transcript_pos`, `transcript_region`.
+    pub transcripts: bool,
+    /// `contig_relative_position`.
+    pub relative_position: bool,
+    /// `masked_sequence`.
+    pub masked_sequence: bool,
+    /// `overhang`.
+    pub overhang: bool,
+    /// `repeat_class`.
+    pub repeat_class: bool,
+    /// `ambiguous_positions`, `matches_via_ambiguity`.
+    pub ambiguity: bool,
+    /// `extendable`.
+    pub extendable: bool,
+    /// Summary rows: `hits_k0`, `hits_k1`, ... after any extended-summary columns.
+    pub mismatch_breakdown: bool,
+    /// Summary rows: `strand_bias`, after the mismatch breakdown.
+    pub strand_bias: bool,
+    /// Summary rows: `known_suppressed`, after `strand_bias`.
+    pub known_suppressed: bool,
+    /// Summary rows: `timed_out`.
+    pub timed_out: bool,
+    /// Hit and summary rows: `tag`, last.
+    pub tag: bool,
+}
+
+/// Tab-separated hit and summary rows, `.` for missing values.
+pub struct TsvSink<W: Write> {
+    out: W,
+    columns: TsvColumns,
+    extended_summary: bool,
+    separator: char,
+    /// First field of every row, e.g. the `--group-by` group.
+    row_prefix: Option<String>,
+}
+
+impl<W: Write> TsvSink<W> {
+    /// `extended_summary` appends first/last hit positions, `expected_random_hits`,
+    /// `mean_mismatch`, and `std_mismatch` to summary rows.
+    pub fn new(out: W, columns: TsvColumns, extended_summary: bool) -> Self {
+        Self {
+            out,
+            columns,
+            extended_summary,
+            separator: '\t',
+            row_prefix: None,
+        }
+    }
+
+    /// The same rows separated by commas. Fields are not quoted, so names containing commas
+    /// shift the columns.
+    pub fn csv(out: W, columns: TsvColumns, extended_summary: bool) -> Self {
+        Self {
+            separator: ',',
+            ..Self::new(out, columns, extended_summary)
+        }
+    }
+
+    /// Writes `prefix` as an extra first field of the rows that follow; `None` stops.
+    pub fn set_row_prefix(&mut self, prefix: Option<String>) {
+        self.row_prefix = prefix;
+    }
+
+    /// A `__subtotal__` row closing a `--group-by` group: `primers total_hits perfect_hits
+    /// forward_hits reverse_hits . hits_per_primer` after the row prefix.
+    pub fn on_subtotal(&mut self, subtotal: &SummarySubtotal) -> Result<()> {
+        self.write_row_prefix()?;
+        let sep = self.separator;
+        writeln!(
+            self.out,
+            "__subtotal__{sep}{}{sep}{}{sep}{}{sep}{}{sep}{}{sep}.{sep}{:.4}",
+            subtotal.primers,
+            subtotal.total_hits,
+            subtotal.perfect_hits,
+            subtotal.forward_hits,
+            subtotal.reverse_hits,
+            subtotal.hits_per_primer
+        )?;
+        Ok(())
+    }
+
+    fn write_row_prefix(&mut self) -> Result<()> {
+        if let Some(prefix) = &self.row_prefix {
+            write!(self.out, "{prefix}{}", self.separator)?;
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> HitSink for TsvSink<W> {
+    fn on_hit(&mut self, hit: &Hit) -> Result<()> {
+        let (out, columns, sep) = (&mut self.out, &self.columns, self.separator);
+        if columns.color {
+            queue!(out, SetForegroundColor(primer_color(&hit.primer)))?;
+        }
+        if let Some(prefix) = &self.row_prefix {
+            write!(out, "{prefix}{sep}")?;
+        }
+        write!(
+            out,
+            "{}{sep}{}{sep}{}{sep}{}{sep}{}{sep}{}{sep}{}{sep}{}{sep}{}",
+            hit.file,
+            hit.contig,
+            hit.primer,
+            hit.primer_len,
+            hit.start,
+            hit.end,
+            hit.strand,
+            hit.mismatches,
+            hit.matched
+        )?;
+        if columns.pairing {
+            match hit.amplicon_size {
+                Some(size) => write!(out, "{sep}{size}")?,
+                None => write!(out, "{sep}.")?,
+            }
+            match hit.nearest_partner_distance {
+                Some(distance) => write!(out, "{sep}{distance}")?,
+                None => write!(out, "{sep}.")?,
+            }
+            write!(out, "{sep}{}", hit.amplifiable)?;
+        }
+        if columns.primer_sequence {
+            write!(
+                out,
+                "{sep}{}{sep}{}",
+                hit.primer_sequence.as_deref().unwrap_or("."),
+                hit.primer_reverse_complement.as_deref().unwrap_or(".")
+            )?;
+        }
+        if columns.gc_skew {
+            match hit.gc_skew {
+                Some(skew) => write!(out, "{sep}{skew:.4}")?,
+                None => write!(out, "{sep}.")?,
+            }
+        }
+        if columns.matched_fraction {
+            write!(out, "{sep}{:.4}", hit.matched_fraction)?;
+        }
+        if columns.transcripts {
+            write!(out, "{sep}{}", hit.transcript.as_deref().unwrap_or("."))?;
+            match hit.transcript_pos {
+                Some(position) => write!(out, "{sep}{position}")?,
+                None => write!(out, "{sep}.")?,
+            }
+            match hit.transcript_region {
+                Some(region) => write!(out, "{sep}{region}")?,
+                None => write!(out, "{sep}.")?,
+            }
+        }
+        if columns.relative_position {
+            match hit.contig_relative_position {
+                Some(position) => write!(out, "{sep}{position:.4}")?,
+                None => write!(out, "{sep}.")?,
+            }
+        }
+        if columns.masked_sequence {
+            write!(
+                out,
+                "{sep}{}",
+                hit.masked_sequence.as_deref().unwrap_or(".")
+            )?;
+        }
+        if columns.overhang {
+            write!(out, "{sep}{}", hit.overhang)?;
+        }
+        if columns.repeat_class {
+            write!(out, "{sep}{}", hit.repeat_class.as_deref().unwrap_or("."))?;
+        }
+        if columns.ambiguity {
+            write!(
+                out,
+                "{sep}{}{sep}{}",
+                hit.ambiguous_positions, hit.matches_via_ambiguity
+            )?;
+        }
+        if columns.extendable {
+            match hit.extendable {
+                Some(extendable) => write!(out, "{sep}{extendable}")?,
+                None => write!(out, "{sep}.")?,
+            }
+        }
+        if columns.tag {
+            write!(out, "{sep}{}", hit.tag.as_deref().unwrap_or("."))?;
+        }
+        if columns.color {
+            queue!(out, ResetColor)?;
+        }
+        writeln!(out)?;
+        Ok(())
+    }
+
+    fn on_summary(&mut self, row: &PrimerSummary) -> Result<()> {
+        self.write_row_prefix()?;
+        let (out, sep) = (&mut self.out, self.separator);
+        write!(
+            out,
+            "{}{sep}{}{sep}{}{sep}{}{sep}{}{sep}{}{sep}{}{sep}{}",
+            row.primer,
+            row.primer_len,
+            row.total_hits,
+            row.perfect_hits,
+            row.forward_hits,
+            row.reverse_hits,
+            row.contigs_with_hits,
+            row.is_palindromic
+        )?;
+        if self.extended_summary {
+            for position in [&row.first_hit_position, &row.last_hit_position] {
+                match position {
+                    Some((file, contig, start)) => {
+                        write!(out, "{sep}{file}{sep}{contig}{sep}{start}")?
+                    }
+                    None => write!(out, "{sep}.{sep}.{sep}.")?,
+                }
+            }
+            write!(
+                out,
+                "{sep}{:.4}{sep}{:.4}{sep}{:.4}",
+                row.expected_random_hits, row.mean_mismatch, row.std_mismatch
+            )?;
+        }
+        if self.columns.mismatch_breakdown {
+            for count in &row.per_mismatch_counts {
+                write!(out, "{sep}{count}")?;
+            }
+        }
+        if self.columns.strand_bias {
+            write!(out, "{sep}{:.4}", row.strand_bias)?;
+        }
+        if self.columns.known_suppressed {
+            write!(out, "{sep}{}", row.known_suppressed)?;
+        }
+        if self.columns.timed_out {
+            write!(out, "{sep}{}", row.timed_out)?;
+        }
+        if self.columns.tag {
+            write!(out, "{sep}{}", row.tag.as_deref().unwrap_or("."))?;
+        }
+        writeln!(out)?;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.out.flush()?;
+        Ok(())
+    }
+}
+
+/// One JSON object per hit or summary row (NDJSON).
+pub struct JsonSink<W: Write> {
+    out: W,
+    extended_summary: bool,
+}
+
+impl<W: Write> JsonSink<W> {
+    /// `extended_summary` adds `first_hit_position` / `last_hit_position`, `mean_mismatch`, and
+    /// `std_mismatch` to summary rows.
+    pub fn new(out: W, extended_summary: bool) -> Self {
+        Self {
+            out,
+            extended_summary,
+        }
+    }
+}
+
+impl<W: Write> HitSink for JsonSink<W> {
+    fn on_hit(&mut self, hit: &Hit) -> Result<()> {
+        writeln!(self.out, "{}", serde_json::to_string(hit)?)?;
+        Ok(())
+    }
+
+    fn on_summary(&mut self, row: &PrimerSummary) -> Result<()> {
+        #[derive(Serialize)]
+        struct ExtendedSummaryRow<'a> {
+            #[serde(flatten)]
+            row: &'a PrimerSummary,
+            first_hit_position: &'a Option<(String, String, usize)>,
+            last_hit_position: &'a Option<(String, String, usize)>,
+            mean_mismatch: f64,
+            std_mismatch: f64,
+        }
+
+        if self.extended_summary {
+            let extended_row = ExtendedSummaryRow {
+                row,
+                first_hit_position: &row.first_hit_position,
+                last_hit_position: &row.last_hit_position,
+                mean_mismatch: row.mean_mismatch,
+                std_mismatch: row.std_mismatch,
+            };
+            writeln!(self.out, "{}", serde_json::to_string(&extended_row)?)?;
+        } else {
+            writeln!(self.out, "{}", serde_json::to_string(row)?)?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.out.flush()?;
+        Ok(())
+    }
+}
+
+/// Text layouts of `--output`, named by `--output-format` or the output path's extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// [`TsvSink`] rows.
+    Tsv,
+    /// [`TsvSink::csv`] rows.
+    Csv,
+    /// [`JsonSink`] objects, one per line.
+    Ndjson,
+    /// [`BedSink`] hit intervals.
+    Bed,
+    /// [`SortedBedSink`] intervals with extra columns, ready for `bedToBigBed`.
+    BedSorted,
+    /// [`Gff3Sink`] primer-binding-site features.
+    Gff3,
+    /// [`GenbankFeatureSink`] `primer_bind` entries of a GenBank feature table.
+    GenbankFeatures,
+    /// Recognized so `.sam` paths are not silently written as TSV; not implemented yet.
+    Sam,
+}
+
+impl OutputFormat {
+    /// The format named by the extension of `path`, after stripping a trailing `.gz`
+    /// (case-insensitive); `None` for other extensions.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?.to_ascii_lowercase();
+        let name = name.strip_suffix(".gz").unwrap_or(&name);
+        let (_, extension) = name.rsplit_once('.')?;
+        match extension {
+            "tsv" => Some(Self::Tsv),
+            "csv" => Some(Self::Csv),
+            "json" | "ndjson" => Some(Self::Ndjson),
+            "bed" => Some(Self::Bed),
+            "gff3" => Some(Self::Gff3),
+            "sam" => Some(Self::Sam),
+            _ => None,
+        }
+    }
+}
+
+impl OutputFormat {
+    /// The format [`Self::from_path`] names for this format's files: `.bed` for
+    /// [`Self::BedSorted`].
+    fn extension_format(self) -> Self {
+        match self {
+            Self::BedSorted => Self::Bed,
+            other => other,
+        }
+    }
+}
+
+impl std::fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Tsv => "tsv",
+            Self::Csv => "csv",
+            Self::Ndjson => "ndjson",
+            Self::Bed => "bed",
+            Self::BedSorted => "bed-sorted",
+            Self::Gff3 => "gff3",
+            Self::GenbankFeatures => "genbank-features",
+            Self::Sam => "sam",
+        };
+        f.write_str(name)
+    }
+}
+
+/// The format to write `path` in: `explicit` when given, otherwise the one its extension
+/// names, otherwise TSV. An explicit format contradicting a recognized extension is an error
+/// unless `force`. [`OutputFormat::Sam`] is returned like the others; the caller rejects it.
+pub fn output_format(
+    path: &Path,
+    explicit: Option<OutputFormat>,
+    force: bool,
+) -> Result<OutputFormat> {
+    let inferred = OutputFormat::from_path(path);
+    let format = match (explicit, inferred) {
+        (Some(explicit), Some(inferred)) if explicit.extension_format() != inferred && !force => {
+            bail!(
+                "output format {explicit} contradicts the extension of '{}' ({inferred}); \
+             pass --force-format to write it anyway",
+                path.display()
+            )
+        }
+        (Some(explicit), _) => explicit,
+        (None, inferred) => inferred.unwrap_or(OutputFormat::Tsv),
+    };
+    Ok(format)
+}
+
+/// Hits as BED6: contig, start, end, primer, mismatches as the score, and strand (`.` for
+/// collapsed `=` hits). There is no BED layout for summary rows.
+///
+/// A row repeating the contig, span, and primer of the row before it (the same primer on both
+/// strands of one site) gets a `#2`, `#3`, ... suffix on its name, so names stay unique per
+/// interval. Sorted output puts such rows next to each other.
+pub struct BedSink<W: Write> {
+    out: W,
+    last: Option<(String, usize, usize, String)>,
+    repeats: usize,
+    /// Append [`BED_SORTED_EXTRA_FIELDS`].
+    extra_fields: bool,
+}
+
+impl<W: Write> BedSink<W> {
+    pub fn new(out: W) -> Self {
+        Self {
+            out,
+            last: None,
+            repeats: 0,
+            extra_fields: false,
+        }
+    }
+}
+
+impl<W: Write> HitSink for BedSink<W> {
+    fn on_hit(&mut self, hit: &Hit) -> Result<()> {
+        let strand = if hit.strand == '=' { '.' } else { hit.strand };
+        let repeated = self
+            .last
+            .as_ref()
+            .is_some_and(|(contig, start, end, primer)| {
+                (contig.as_str(), *start, *end, primer.as_str())
+                    == (hit.contig.as_str(), hit.start, hit.end, hit.primer.as_str())
+            });
+        if repeated {
+            self.repeats += 1;
+        } else {
+            self.repeats = 1;
+            self.last = Some((hit.contig.clone(), hit.start, hit.end, hit.primer.clone()));
+        }
+        write!(
+            self.out,
+            "{}\t{}\t{}\t{}",
+            hit.contig, hit.start, hit.end, hit.primer
+        )?;
+        if self.repeats > 1 {
+            write!(self.out, "#{}", self.repeats)?;
+        }
+        write!(self.out, "\t{}\t{strand}", hit.mismatches)?;
+        if self.extra_fields {
+            write!(self.out, "\t{}\t{}", hit.primer, hit.matched)?;
+        }
+        writeln!(self.out)?;
+        Ok(())
+    }
+
+    fn on_summary(&mut self, _row: &PrimerSummary) -> Result<()> {
+        bail!("summary rows cannot be written as BED; use tsv, csv or ndjson")
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.out.flush()?;
+        Ok(())
+    }
+}
+
+/// autoSql `(type, name, description)` of the columns [`SortedBedSink`] adds after BED6. The
+/// mismatch count is already the BED score.
+pub const BED_SORTED_EXTRA_FIELDS: &[(&str, &str, &str)] = &[
+    ("string", "primer", "Primer name"),
+    ("string", "matched", "Reference bases under the primer"),
+];
+
+/// Hits [`SortedBedSink`] holds in memory before spilling a sorted run to disk.
+pub const DEFAULT_SORTED_BED_RUN_HITS: usize = 1_000_000;
+
+/// [`BedSink`] rows plus [`BED_SORTED_EXTRA_FIELDS`], written at [`HitSink::finish`] sorted by
+/// `(contig, start)` as `bedToBigBed` requires (contigs in byte order, like
+/// `sort -k1,1 -k2,2n`), whatever order the hits arrive in.
+///
+/// An external sort: at most [`DEFAULT_SORTED_BED_RUN_HITS`] hits (see
+/// [`SortedBedSink::with_run_hits`]) are held in memory, and every full run is sorted and
+/// spilled to a temp directory, then merged at the end.
+pub struct SortedBedSink<W: Write> {
+    bed: BedSink<W>,
+    run_hits: usize,
+    hits: HitCollector,
+}
+
+impl<W: Write> SortedBedSink<W> {
+    pub fn new(out: W) -> Self {
+        Self {
+            bed: BedSink {
+                extra_fields: true,
+                ..BedSink::new(out)
+            },
+            run_hits: DEFAULT_SORTED_BED_RUN_HITS,
+            hits: HitCollector::ordered(Some(DEFAULT_SORTED_BED_RUN_HITS), compare_bed_hits),
+        }
+    }
+
+    /// Spills a sorted run once more than `hits` hits are held (at least 1).
+    pub fn with_run_hits(mut self, hits: usize) -> Self {
+        self.run_hits = hits.max(1);
+        self.hits = HitCollector::ordered(Some(self.run_hits), compare_bed_hits);
+        self
+    }
+}
+
+/// `bedToBigBed` order: contig (byte order), then start, with the rest of the interval breaking
+/// ties.
+fn compare_bed_hits(a: &Hit, b: &Hit) -> Ordering {
+    (&a.contig, a.start, a.end, &a.primer, a.strand)
+        .cmp(&(&b.contig, b.start, b.end, &b.primer, b.strand))
+}
+
+impl<W: Write> HitSink for SortedBedSink<W> {
+    fn on_hit(&mut self, hit: &Hit) -> Result<()> {
+        self.hits.push(vec![hit.clone()])
+    }
+
+    fn on_summary(&mut self, row: &PrimerSummary) -> Result<()> {
+        self.bed.on_summary(row)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        let collector = std::mem::replace(
+            &mut self.hits,
+            HitCollector::ordered(Some(self.run_hits), compare_bed_hits),
+        );
+        let (mut hits, spilled) = collector.finish();
+        hits.sort_by(compare_bed_hits);
+        for hit in HitStream::ordered(hits, spilled, compare_bed_hits)? {
+            self.bed.on_hit(&hit?)?;
+        }
+        self.bed.finish()
+    }
+}
+
+/// autoSql table definition for `bedToBigBed -type=bed6+N -as=<file>` of BED6 rows followed
+/// by `extra` `(type, name, description)` columns, such as [`BED_SORTED_EXTRA_FIELDS`].
+pub fn bed_autosql(extra: &[(&str, &str, &str)]) -> String {
+    let mut fields = vec![
+        ("string", "chrom", "Reference sequence (contig)"),
+        (
+            "uint",
+            "chromStart",
+            "Start of the primer-binding site (0-based)",
+        ),
+        ("uint", "chromEnd", "End of the primer-binding site"),
+        (
+            "string",
+            "name",
+            "Primer name, #N for repeats of one interval",
+        ),
+        ("uint", "score", "Mismatches against the primer"),
+        ("char[1]", "strand", "+ or -, . for both"),
+    ];
+    fields.extend_from_slice(extra);
+    let mut autosql =
+        String::from("table primerHits\n\"primer-scout primer-binding sites\"\n    (\n");
+    for (kind, name, description) in fields {
+        autosql.push_str(&format!("    {kind} {name}; \"{description}\"\n"));
+    }
+    autosql.push_str("    )\n");
+    autosql
+}
+
+/// Hits as FASTA, one record per hit: `><primer>_<contig>_<start>_<strand>` and the matched
+/// reference bases, wrapped at `line_width` (`0` for one line). There is no FASTA layout for
+/// summary rows.
+pub struct HitFastaSink<W: Write> {
+    out: W,
+    line_width: usize,
+}
+
+impl<W: Write> HitFastaSink<W> {
+    pub fn new(out: W, line_width: usize) -> Self {
+        Self { out, line_width }
+    }
+}
+
+impl<W: Write> HitSink for HitFastaSink<W> {
+    fn on_hit(&mut self, hit: &Hit) -> Result<()> {
+        let header = format!("{}_{}_{}_{}", hit.primer, hit.contig, hit.start, hit.strand);
+        write_fasta_record(
+            &mut self.out,
+            &header,
+            hit.matched.as_bytes(),
+            self.line_width,
+        )?;
+        Ok(())
+    }
+
+    fn on_summary(&mut self, _row: &PrimerSummary) -> Result<()> {
+        bail!("summary rows cannot be written as FASTA")
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.out.flush()?;
+        Ok(())
+    }
+}
+
+/// Hits as GFF3 `primer_binding_site` features (1-based, closed coordinates) with `Name` and
+/// `mismatches` attributes. There is no GFF3 layout for summary rows.
+pub struct Gff3Sink<W: Write> {
+    out: W,
+    wrote_header: bool,
+}
+
+impl<W: Write> Gff3Sink<W> {
+    pub fn new(out: W) -> Self {
+        Self {
+            out,
+            wrote_header: false,
+        }
+    }
+
+    fn write_header(&mut self) -> Result<()> {
+        if !self.wrote_header {
+            writeln!(self.out, "##gff-version 3")?;
+            self.wrote_header = true;
+        }
+        Ok(())
+    }
+}
+
+/// Percent-encodes the characters GFF3 reserves in column values.
+fn gff3_escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for ch in value.chars() {
+        if matches!(ch, ';' | '=' | '&' | ',' | '%') || ch.is_ascii_control() {
+            escaped.push_str(&format!("%{:02X}", ch as u32));
+        } else {
+            escaped.push(ch);
+        }
+    }
+    escaped
+}
+
+impl<W: Write> HitSink for Gff3Sink<W> {
+    fn on_hit(&mut self, hit: &Hit) -> Result<()> {
+        self.write_header()?;
+        let strand = if hit.strand == '=' { '.' } else { hit.strand };
+        write!(
+            self.out,
+            "{}\tprimer-scout\tprimer_binding_site\t{}\t{}\t.\t{strand}\t.\tName={};mismatches={}",
+            gff3_escape(&hit.contig),
+            hit.start + 1,
+            hit.end,
+            gff3_escape(&hit.primer),
+            hit.mismatches
+        )?;
+        if let Some(tag) = &hit.tag {
+            write!(self.out, ";tag={}", gff3_escape(tag))?;
+        }
+        writeln!(self.out)?;
+        Ok(())
+    }
+
+    fn on_summary(&mut self, _row: &PrimerSummary) -> Result<()> {
+        bail!("summary rows cannot be written as GFF3; use tsv, csv or ndjson")
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.write_header()?;
+        self.out.flush()?;
+        Ok(())
+    }
+}
+
+/// Hits as `primer_bind` entries of a GenBank feature table (1-based, closed coordinates,
+/// `complement(..)` for `-` hits) with `/label` and `/note` qualifiers, ready to paste into a
+/// record's FEATURES section. There is no layout for summary rows.
+pub struct GenbankFeatureSink<W: Write> {
+    out: W,
+}
+
+impl<W: Write> GenbankFeatureSink<W> {
+    pub fn new(out: W) -> Self {
+        Self { out }
+    }
+}
+
+/// Qualifier lines start at column 22, after the 5-space indent and 16-column feature key.
+const GENBANK_QUALIFIER_INDENT: &str = "                     ";
+
+/// The hit's span in GenBank location syntax.
+fn genbank_location(hit: &Hit) -> String {
+    let range = format!("{}..{}", hit.start + 1, hit.end);
+    if hit.strand == '-' {
+        format!("complement({range})")
+    } else {
+        range
+    }
+}
+
+impl<W: Write> HitSink for GenbankFeatureSink<W> {
+    fn on_hit(&mut self, hit: &Hit) -> Result<()> {
+        // `/label` takes a single token; `/note` is quoted, with `"` doubled.
+        let label = hit.primer.replace(char::is_whitespace, "_");
+        let mut note = format!(
+            "primer-scout hit on {}, {} mismatch(es)",
+            hit.contig, hit.mismatches
+        );
+        if let Some(tag) = &hit.tag {
+            note.push_str(&format!(", tag {tag}"));
+        }
+        let note = note.replace('"', "\"\"");
+        writeln!(
+            self.out,
+            "     {:<16}{}",
+            "primer_bind",
+            genbank_location(hit)
+        )?;
+        writeln!(self.out, "{GENBANK_QUALIFIER_INDENT}/label={label}")?;
+        writeln!(self.out, "{GENBANK_QUALIFIER_INDENT}/note=\"{note}\"")?;
+        Ok(())
+    }
+
+    fn on_summary(&mut self, _row: &PrimerSummary) -> Result<()> {
+        bail!("summary rows cannot be written as a GenBank feature table; use tsv, csv or ndjson")
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.out.flush()?;
+        Ok(())
+    }
+}
+
+/// Windows device names, reserved in every directory and with any extension.
+const WINDOWS_RESERVED_NAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// File stem for `name` that is valid on every platform: characters other than ASCII letters,
+/// digits, `.`, `_`, and `-` become `_`, and a Windows device name (`CON`, `nul.v2`, ...) gets
+/// a leading `_`.
+pub fn output_file_stem(name: &str) -> String {
+    let stem = name
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || matches!(ch, '.' | '_' | '-') {
+                ch
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+    let device = stem.split('.').next().unwrap_or_default();
+    if WINDOWS_RESERVED_NAMES
+        .iter()
+        .any(|reserved| reserved.eq_ignore_ascii_case(device))
+    {
+        format!("_{stem}")
+    } else {
+        stem
+    }
+}
+
+/// Builds the sink that writes one primer's file.
+pub type FileSinkFactory = Box<dyn FnMut(BufWriter<File>) -> Box<dyn HitSink + Send> + Send>;
+
+/// Files a [`PerPrimerSink`] keeps open at once unless set with
+/// [`PerPrimerSink::with_max_open_files`].
+pub const DEFAULT_MAX_OPEN_PRIMER_FILES: usize = 128;
+
+/// Writes each primer's hits to `<dir>/<primer>.<extension>`. At most
+/// [`DEFAULT_MAX_OPEN_PRIMER_FILES`] files stay open: the least recently written one is
+/// finished and closed to make room, then reopened for appending when its primer hits again,
+/// so large panels stay within the process's file limit. Primer names become file stems with
+/// [`output_file_stem`]; two primers mapping to the same file name are an error. Summary rows
+/// are not written.
+pub struct PerPrimerSink {
+    dir: PathBuf,
+    extension: &'static str,
+    make_sink: FileSinkFactory,
+    /// Every file written so far by stem, open or not.
+    files: HashMap<String, PrimerFile>,
+    /// Stems of the open files by their last write, oldest first.
+    open_by_use: BTreeMap<u64, String>,
+    writes: u64,
+    max_open_files: usize,
+}
+
+struct PrimerFile {
+    /// The primer that claimed the stem.
+    primer: String,
+    sink: Option<Box<dyn HitSink + Send>>,
+    last_write: u64,
+}
+
+impl PerPrimerSink {
+    /// `dir` must exist; `make_sink` wraps each newly created or reopened file (e.g. in a
+    /// [`TsvSink`]), so it must not write a preamble.
+    pub fn new(dir: PathBuf, extension: &'static str, make_sink: FileSinkFactory) -> Self {
+        Self {
+            dir,
+            extension,
+            make_sink,
+            files: HashMap::new(),
+            open_by_use: BTreeMap::new(),
+            writes: 0,
+            max_open_files: DEFAULT_MAX_OPEN_PRIMER_FILES,
+        }
+    }
+
+    /// Keeps at most `max_open_files` (at least one) files open at once.
+    pub fn with_max_open_files(mut self, max_open_files: usize) -> Self {
+        self.max_open_files = max_open_files.max(1);
+        self
+    }
+
+    /// Finishes and closes the least recently written open file.
+    fn close_least_recent(&mut self) -> Result<()> {
+        if let Some((_, stem)) = self.open_by_use.pop_first()
+            && let Some(mut sink) = self.files.get_mut(&stem).and_then(|file| file.sink.take())
+        {
+            sink.finish()?;
+        }
+        Ok(())
+    }
+}
+
+impl HitSink for PerPrimerSink {
+    fn on_hit(&mut self, hit: &Hit) -> Result<()> {
+        let stem = output_file_stem(&hit.primer);
+        let append = match self.files.get(&stem) {
+            Some(file) if file.primer != hit.primer => bail!(
+                "primers '{}' and '{}' would both be written to '{stem}.{}'",
+                file.primer,
+                hit.primer,
+                self.extension
+            ),
+            Some(file) if file.sink.is_some() => {
+                self.open_by_use.remove(&file.last_write);
+                None
+            }
+            Some(_) => Some(true),
+            None => Some(false),
+        };
+
+        if let Some(append) = append {
+            if self.open_by_use.len() >= self.max_open_files {
+                self.close_least_recent()?;
+            }
+            let path = self.dir.join(format!("{stem}.{}", self.extension));
+            let file = if append {
+                OpenOptions::new().append(true).open(&path)
+            } else {
+                File::create(&path)
+            }
+            .with_context(|| format!("failed to open '{}'", path.display()))?;
+            let sink = (self.make_sink)(BufWriter::new(file));
+            self.files
+                .entry(stem.clone())
+                .or_insert_with(|| PrimerFile {
+                    primer: hit.primer.clone(),
+                    sink: None,
+                    last_write: 0,
+                })
+                .sink = Some(sink);
+        }
+
+        self.writes += 1;
+        let file = self.files.get_mut(&stem).expect("file opened above");
+        file.last_write = self.writes;
+        self.open_by_use.insert(self.writes, stem);
+        file.sink.as_mut().expect("file opened above").on_hit(hit)
+    }
+
+    fn on_summary(&mut self, _row: &PrimerSummary) -> Result<()> {
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        while !self.open_by_use.is_empty() {
+            self.close_least_recent()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "async-output")]
+pub use channel::ChannelSink;
+
+#[cfg(feature = "async-output")]
+mod channel {
+    use anyhow::{Result, anyhow, bail};
+    use crossbeam_channel::{Sender, bounded};
+    use std::hash::{DefaultHasher, Hash, Hasher};
+    use std::thread::JoinHandle;
+
+    use super::{HitSink, output_file_stem};
+    use crate::{Hit, PrimerSummary};
+
+    enum Record {
+        Hit(Hit),
+        Summary(PrimerSummary),
+    }
+
+    /// Moves writing off the scanning thread: records go through bounded channels to writer
+    /// threads, each driving its own sink. Records are routed by the [`output_file_stem`] of
+    /// their primer name, so one primer's records keep their order and always reach the same
+    /// sink. Primers sharing a [`PerPrimerSink`](super::PerPrimerSink) file reach the same
+    /// sink too, which then rejects the clash.
+    pub struct ChannelSink {
+        senders: Vec<Sender<Record>>,
+        workers: Vec<JoinHandle<Result<()>>>,
+    }
+
+    impl ChannelSink {
+        /// Spawns `workers` writer threads (at least one) running `make_sink(worker_index)`.
+        /// Each channel holds up to `capacity` records, so a slow disk blocks the producer
+        /// instead of buffering without bound.
+        pub fn new(
+            workers: usize,
+            capacity: usize,
+            mut make_sink: impl FnMut(usize) -> Box<dyn HitSink + Send>,
+        ) -> Self {
+            let (senders, workers) = (0..workers.max(1))
+                .map(|worker| {
+                    let (sender, receiver) = bounded::<Record>(capacity);
+                    let mut sink = make_sink(worker);
+                    let handle = std::thread::spawn(move || {
+                        for record in receiver {
+                            match record {
+                                Record::Hit(hit) => sink.on_hit(&hit)?,
+                                Record::Summary(row) => sink.on_summary(&row)?,
+                            }
+                        }
+                        sink.finish()
+                    });
+                    (sender, handle)
+                })
+                .unzip();
+            Self { senders, workers }
+        }
+
+        fn send(&mut self, primer: &str, record: Record) -> Result<()> {
+            if self.senders.is_empty() {
+                bail!("output writers have already stopped");
+            }
+            let mut hasher = DefaultHasher::new();
+            output_file_stem(primer).hash(&mut hasher);
+            let worker = (hasher.finish() % self.senders.len() as u64) as usize;
+            if self.senders[worker].send(record).is_err() {
+                // The writer stopped early; its error explains why.
+                self.join()?;
+                bail!("output writer stopped early");
+            }
+            Ok(())
+        }
+
+        fn join(&mut self) -> Result<()> {
+            self.senders.clear();
+            let mut outcome = Ok(());
+            for worker in self.workers.drain(..) {
+                let result = worker
+                    .join()
+                    .unwrap_or_else(|_| Err(anyhow!("output writer thread panicked")));
+                if outcome.is_ok() {
+                    outcome = result;
+                }
+            }
+            outcome
+        }
+    }
+
+    impl HitSink for ChannelSink {
+        fn on_hit(&mut self, hit: &Hit) -> Result<()> {
+            self.send(&hit.primer, Record::Hit(hit.clone()))
+        }
+
+        fn on_summary(&mut self, row: &PrimerSummary) -> Result<()> {
+            self.send(&row.primer, Record::Summary(row.clone()))
+        }
+
+        /// Closes the channels and waits for every writer, returning the first error.
+        fn finish(&mut self) -> Result<()> {
+            self.join()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::tmp_path;
+    use crate::{Primer, ScanOptions, Scanner};
+
+    #[derive(Default)]
+    struct CountingSink {
+        hits: usize,
+        perfect_hits: usize,
+        summaries: usize,
+        finished: bool,
+    }
+
+    impl HitSink for CountingSink {
+        fn on_hit(&mut self, hit: &Hit) -> Result<()> {
+            assert!(!self.finished && self.summaries == 0, "hits come first");
+            self.hits += 1;
+            self.perfect_hits += usize::from(hit.mismatches == 0);
+            Ok(())
+        }
+
+        fn on_summary(&mut self, _row: &PrimerSummary) -> Result<()> {
+            self.summaries += 1;
+            Ok(())
+        }
+
+        fn finish(&mut self) -> Result<()> {
+            self.finished = true;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn custom_sink_receives_every_hit_and_summary() {
+        let reference = tmp_path("sink.fa");
+        std::fs::write(&reference, ">chr1\nACGTACGTTTGCAACGT\n>chr2\nACGAACGT\n")
+            .expect("write reference");
+        let scanner = Scanner::new(
+            vec![
+                Primer::from_name_and_sequence("p1", "ACGT").expect("primer"),
+                Primer::from_name_and_sequence("p2", "TTGC").expect("primer"),
+            ],
+            ScanOptions {
+                max_mismatches: 1,
+                ..ScanOptions::default()
+            },
+        )
+        .expect("scanner");
+
+        let mut sink = CountingSink::default();
+        let result = scanner
+            .scan_into(std::slice::from_ref(&reference), &mut sink)
+            .expect("scan");
+
+        assert!(sink.finished);
+        assert_eq!(sink.hits as u64, result.total_hits);
+        assert_eq!(
+            sink.perfect_hits as u64,
+            result
+                .summary
+                .iter()
+                .map(|row| row.perfect_hits)
+                .sum::<u64>()
+        );
+        assert_eq!(sink.summaries, 2);
+
+        assert!(result.hits.is_empty());
+
+        let mut tsv = TsvSink::new(Vec::new(), TsvColumns::default(), false);
+        scanner
+            .scan_into(std::slice::from_ref(&reference), &mut tsv)
+            .expect("scan");
+        let text = String::from_utf8(tsv.out).expect("utf8");
+        assert_eq!(text.lines().count(), sink.hits + sink.summaries);
+
+        std::fs::remove_file(reference).expect("remove reference");
+    }
+
+    #[test]
+    fn per_primer_sink_writes_one_file_per_primer() {
+        let dir = tmp_path("per_primer");
+        std::fs::create_dir_all(&dir).expect("create dir");
+        let reference = dir.join("ref.fa");
+        std::fs::write(&reference, ">chr1\nACGTACGTTTGCAACGT\n").expect("write reference");
+        let scanner = Scanner::new(
+            vec![
+                Primer::from_name_and_sequence("EGFR/1", "ACGT").expect("primer"),
+                Primer::from_name_and_sequence("p2", "TTGC").expect("primer"),
+            ],
+            ScanOptions::default(),
+        )
+        .expect("scanner");
+        let file_sink = || {
+            PerPrimerSink::new(
+                dir.clone(),
+                "tsv",
+                Box::new(|out| Box::new(TsvSink::new(out, TsvColumns::default(), false))),
+            )
+        };
+
+        let result = scanner
+            .scan_into(std::slice::from_ref(&reference), &mut file_sink())
+            .expect("scan");
+        let lines = |name: &str| {
+            std::fs::read_to_string(dir.join(name))
+                .expect("read primer file")
+                .lines()
+                .count()
+        };
+        assert_eq!(lines("EGFR_1.tsv"), 3);
+        assert_eq!(lines("p2.tsv"), 2);
+        assert_eq!(result.total_hits, 5);
+
+        #[cfg(feature = "async-output")]
+        {
+            std::fs::remove_file(dir.join("p2.tsv")).expect("remove p2 file");
+            let mut sink = ChannelSink::new(2, 1, |_| Box::new(file_sink()));
+            scanner
+                .scan_into(std::slice::from_ref(&reference), &mut sink)
+                .expect("scan");
+            assert_eq!(lines("EGFR_1.tsv"), 3);
+            assert_eq!(lines("p2.tsv"), 2);
+        }
+
+        // With one open file, every switch of primer closes a file and reopens the other one
+        // for appending.
+        let hits = scanner
+            .scan_references(std::slice::from_ref(&reference))
+            .expect("scan")
+            .hits;
+        let mut sink = file_sink().with_max_open_files(1);
+        for hit in hits.iter().chain(&hits) {
+            sink.on_hit(hit).expect("write hit");
+        }
+        sink.finish().expect("finish");
+        assert_eq!(lines("EGFR_1.tsv"), 6);
+        assert_eq!(lines("p2.tsv"), 4);
+        let rows = std::fs::read_to_string(dir.join("p2.tsv")).expect("read p2 file");
+        let rows = rows.lines().collect::<Vec<_>>();
+        assert_eq!(rows[..2], rows[2..]);
+
+        let clash = Scanner::new(
+            vec![
+                Primer::from_name_and_sequence("EGFR/1", "ACGT").expect("primer"),
+                Primer::from_name_and_sequence("EGFR:1", "TTGC").expect("primer"),
+            ],
+            ScanOptions::default(),
+        )
+        .expect("scanner");
+        let err = clash
+            .scan_into(std::slice::from_ref(&reference), &mut file_sink())
+            .expect_err("file name clash");
+        assert!(err.to_string().contains("would both be written"), "{err}");
+
+        // Background writers must see the clash too, however many there are.
+        #[cfg(feature = "async-output")]
+        for workers in 2..=8 {
+            let mut sink = ChannelSink::new(workers, 1, |_| Box::new(file_sink()));
+            let err = clash
+                .scan_into(std::slice::from_ref(&reference), &mut sink)
+                .expect_err("file name clash across writers");
+            assert!(err.to_string().contains("would both be written"), "{err}");
+        }
+
+        std::fs::remove_dir_all(dir).expect("remove dir");
+    }
+
+    #[test]
+    fn nested_and_identical_coordinate_hits_stay_distinct_in_every_format() {
+        // `long` and `short` share their 3' end; `twin` is `short` under another name, so it
+        // hits the exact same coordinates.
+        let reference = "TTTTTCCAGTGATTACAGGCTAGCATGCAGTTTTT";
+        let primers = vec![
+            Primer::from_name_and_sequence("long", "CCAGTGATTACAGGCTAGCATGCAG").expect("primer"),
+            Primer::from_name_and_sequence("short", "GATTACAGGCTAGCATGCAG").expect("primer"),
+            Primer::from_name_and_sequence("twin", "GATTACAGGCTAGCATGCAG").expect("primer"),
+        ];
+        for options in [
+            ScanOptions::default(),
+            ScanOptions {
+                collapse_strand_duplicates: true,
+                dedup_hits: true,
+                ..ScanOptions::default()
+            },
+        ] {
+            let result = crate::scan_sequence(reference, "chr1", &primers, &options).expect("scan");
+            let hits = result
+                .hits
+                .iter()
+                .map(|hit| (hit.primer.as_str(), hit.start, hit.end, hit.strand))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                hits,
+                vec![
+                    ("long", 5, 30, '+'),
+                    ("short", 10, 30, '+'),
+                    ("twin", 10, 30, '+'),
+                ]
+            );
+            assert!(result.summary.iter().all(|row| row.total_hits == 1));
+        }
+
+        let mut hits = crate::scan_sequence(reference, "chr1", &primers, &ScanOptions::default())
+            .expect("scan")
+            .hits;
+        // The same primer on both strands of one site.
+        let mut minus = hits[1].clone();
+        minus.strand = '-';
+        minus.mismatches = 3;
+        hits.insert(2, minus);
+        let render = |sink: &mut dyn HitSink| {
+            for hit in &hits {
+                sink.on_hit(hit).expect("hit");
+            }
+            sink.finish().expect("finish");
+        };
+        let lines = |out: Vec<u8>| {
+            String::from_utf8(out)
+                .expect("utf8")
+                .lines()
+                .map(str::to_string)
+                .collect::<Vec<_>>()
+        };
+
+        let mut tsv = TsvSink::new(Vec::new(), TsvColumns::default(), false);
+        render(&mut tsv);
+        assert_eq!(lines(tsv.out).len(), 4);
+        let mut csv = TsvSink::csv(Vec::new(), TsvColumns::default(), false);
+        render(&mut csv);
+        assert_eq!(lines(csv.out).len(), 4);
+        let mut json = JsonSink::new(Vec::new(), false);
+        render(&mut json);
+        let primers_in_json = lines(json.out)
+            .iter()
+            .map(|line| {
+                let value: serde_json::Value = serde_json::from_str(line).expect("json");
+                value["primer"].as_str().expect("primer").to_string()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(primers_in_json, vec!["long", "short", "short", "twin"]);
+
+        let mut bed = BedSink::new(Vec::new());
+        render(&mut bed);
+        assert_eq!(
+            lines(bed.out),
+            vec![
+                "chr1\t5\t30\tlong\t0\t+",
+                "chr1\t10\t30\tshort\t0\t+",
+                "chr1\t10\t30\tshort#2\t3\t-",
+                "chr1\t10\t30\ttwin\t0\t+",
+            ]
+        );
+        let mut gff3 = Gff3Sink::new(Vec::new());
+        render(&mut gff3);
+        let gff3 = lines(gff3.out);
+        assert_eq!(gff3.len(), 5);
+        assert!(gff3[4].ends_with("Name=twin;mismatches=0"), "{}", gff3[4]);
+        let mut genbank = GenbankFeatureSink::new(Vec::new());
+        render(&mut genbank);
+        let labels = lines(genbank.out)
+            .into_iter()
+            .filter_map(|line| line.trim().strip_prefix("/label=").map(str::to_string))
+            .collect::<Vec<_>>();
+        assert_eq!(labels, vec!["long", "short", "short", "twin"]);
+    }
+
+    #[test]
+    fn sorted_bed_sink_orders_by_contig_then_start() {
+        let primers = vec![Primer::from_name_and_sequence("p1", "ACGT").expect("primer")];
+        let result = crate::scan_sequence(
+            "TTACGTGGACGAACGT",
+            "chr1",
+            &primers,
+            &ScanOptions {
+                max_mismatches: 1,
+                ..ScanOptions::default()
+            },
+        )
+        .expect("scan");
+        let mut hits = result.hits;
+        for contig in ["chr2", "chr10"] {
+            let mut other = hits[0].clone();
+            other.contig = contig.to_string();
+            hits.insert(0, other);
+        }
+        hits.reverse();
+
+        let mut sink = SortedBedSink::new(Vec::new());
+        for hit in &hits {
+            sink.on_hit(hit).expect("hit");
+        }
+        sink.finish().expect("finish");
+        let text = String::from_utf8(sink.bed.out).expect("utf8");
+        let rows = text
+            .lines()
+            .map(|row| row.split('\t').collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let order = rows
+            .iter()
+            .map(|row| (row[0], row[1].parse::<usize>().expect("start")))
+            .collect::<Vec<_>>();
+        let mut sorted = order.clone();
+        sorted.sort();
+        assert_eq!(order, sorted);
+        assert_eq!(order.first().map(|row| row.0), Some("chr1"));
+        assert_eq!(order.last().map(|row| row.0), Some("chr2"));
+        assert!(
+            rows.iter()
+                .all(|row| row.len() == 6 + BED_SORTED_EXTRA_FIELDS.len())
+        );
+        let first = hits
+            .iter()
+            .filter(|hit| hit.contig == "chr1")
+            .min_by_key(|hit| hit.start)
+            .expect("chr1 hit");
+        // The mismatches are the score, not repeated after it.
+        assert_eq!(rows[0][4], first.mismatches.to_string());
+        assert_eq!(&rows[0][6..], ["p1", first.matched.as_str()]);
+
+        // Spilling a run per hit merges back to the same rows.
+        let mut spilling = SortedBedSink::new(Vec::new()).with_run_hits(1);
+        for hit in &hits {
+            spilling.on_hit(hit).expect("hit");
+        }
+        spilling.finish().expect("finish");
+        assert_eq!(String::from_utf8(spilling.bed.out).expect("utf8"), text);
+
+        let autosql = bed_autosql(BED_SORTED_EXTRA_FIELDS);
+        let fields = autosql
+            .lines()
+            .filter_map(|line| line.trim().split_once(';'))
+            .map(|(declaration, _)| declaration.rsplit(' ').next().expect("field name"))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            fields,
+            [
+                "chrom",
+                "chromStart",
+                "chromEnd",
+                "name",
+                "score",
+                "strand",
+                "primer",
+                "matched"
+            ]
+        );
+    }
+
+    #[test]
+    fn output_file_stem_avoids_windows_reserved_names() {
+        for (name, stem) in [
+            ("EGFR/1", "EGFR_1"),
+            ("a<b>:c|d?e*f\"g\\h", "a_b__c_d_e_f_g_h"),
+            ("CON", "_CON"),
+            ("nul", "_nul"),
+            ("com7.v2", "_com7.v2"),
+            ("Lpt1_F", "Lpt1_F"),
+            ("CONSENSUS", "CONSENSUS"),
+            ("COM10", "COM10"),
+        ] {
+            assert_eq!(output_file_stem(name), stem, "{name}");
+        }
+    }
+
+    #[test]
+    fn output_format_follows_the_extension() {
+        for (path, format) in [
+            ("hits.bed", OutputFormat::Bed),
+            ("hits.gff3", OutputFormat::Gff3),
+            ("hits.sam", OutputFormat::Sam),
+            ("hits.json", OutputFormat::Ndjson),
+            ("hits.ndjson", OutputFormat::Ndjson),
+            ("hits.csv", OutputFormat::Csv),
+            ("out/hits.TSV", OutputFormat::Tsv),
+            ("hits.bed.gz", OutputFormat::Bed),
+        ] {
+            assert_eq!(
+                OutputFormat::from_path(Path::new(path)),
+                Some(format),
+                "{path}"
+            );
+            let resolved = output_format(Path::new(path), None, false).expect("format");
+            assert_eq!(resolved, format, "{path}");
+        }
+        for path in ["hits.txt", "hits", "hits.gz"] {
+            assert_eq!(OutputFormat::from_path(Path::new(path)), None, "{path}");
+            let resolved = output_format(Path::new(path), None, false).expect("format");
+            assert_eq!(resolved, OutputFormat::Tsv, "{path}");
+        }
+    }
+
+    #[test]
+    fn explicit_output_format_must_match_the_extension_unless_forced() {
+        let path = Path::new("hits.bed");
+        let err = output_format(path, Some(OutputFormat::Tsv), false).expect_err("conflict");
+        assert!(err.to_string().contains("--force-format"), "{err}");
+        assert_eq!(
+            output_format(path, Some(OutputFormat::Tsv), true).expect("forced"),
+            OutputFormat::Tsv
+        );
+        assert_eq!(
+            output_format(path, Some(OutputFormat::Bed), false).expect("same format"),
+            OutputFormat::Bed
+        );
+        assert_eq!(
+            output_format(Path::new("hits.txt"), Some(OutputFormat::Gff3), false)
+                .expect("unknown extension"),
+            OutputFormat::Gff3
+        );
+    }
+
+    #[test]
+    fn genbank_features_use_one_based_locations_and_complement() {
+        let primers = vec![Primer::from_name_and_sequence("M13 fwd", "GATTACA").expect("primer")];
+        // GATTACA on `+` at 2, its reverse complement TGTAATC on `-` at 11.
+        let result = crate::scan_sequence(
+            "CCGATTACACCTGTAATCCC",
+            "pUC19",
+            &primers,
+            &ScanOptions::default(),
+        )
+        .expect("scan");
+
+        let mut sink = GenbankFeatureSink::new(Vec::new());
+        for hit in &result.hits {
+            sink.on_hit(hit).expect("feature");
+        }
+        assert!(sink.on_summary(&result.summary[0]).is_err());
+        let table = String::from_utf8(sink.out).expect("utf8");
+        assert_eq!(
+            table,
+            "     primer_bind     3..9\n\
+             \x20                    /label=M13_fwd\n\
+             \x20                    /note=\"primer-scout hit on pUC19, 0 mismatch(es)\"\n\
+             \x20    primer_bind     complement(12..18)\n\
+             \x20                    /label=M13_fwd\n\
+             \x20                    /note=\"primer-scout hit on pUC19, 0 mismatch(es)\"\n"
+        );
+    }
+}
@@ -0,0 +1,279 @@
+use anyhow::{Context, Result};
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{Hit, compare_hits};
+
+/// Sorted hit runs written to a private temp directory, removed when the last handle drops.
+#[derive(Debug)]
+pub struct SpilledHits {
+    dir: PathBuf,
+    runs: Vec<PathBuf>,
+}
+
+impl SpilledHits {
+    fn create() -> Result<Self> {
+        // Concurrent scans in one process would otherwise share (and delete) each other's runs.
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        let id = NEXT.fetch_add(1, AtomicOrdering::Relaxed);
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_nanos())
+            .unwrap_or_default();
+        let dir = std::env::temp_dir().join(format!(
+            "primer_scout_spill_{}_{nanos}_{id}",
+            std::process::id()
+        ));
+        std::fs::create_dir(&dir)
+            .with_context(|| format!("failed to create spill directory '{}'", dir.display()))?;
+        Ok(Self {
+            dir,
+            runs: Vec::new(),
+        })
+    }
+
+    /// Number of sorted runs on disk.
+    pub fn run_count(&self) -> usize {
+        self.runs.len()
+    }
+
+    /// Writes one run of already sorted hits as NDJSON.
+    fn write_run(&mut self, hits: &[Hit]) -> Result<()> {
+        let path = self.dir.join(format!("run_{:05}.ndjson", self.runs.len()));
+        let file = File::create(&path)
+            .with_context(|| format!("failed to create spill run '{}'", path.display()))?;
+        let mut out = BufWriter::new(file);
+        for hit in hits {
+            serde_json::to_writer(&mut out, hit)?;
+            out.write_all(b"\n")?;
+        }
+        out.flush()
+            .with_context(|| format!("failed writing spill run '{}'", path.display()))?;
+        self.runs.push(path);
+        Ok(())
+    }
+}
+
+impl Drop for SpilledHits {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// The order spilled runs are sorted and merged in.
+pub(crate) type HitOrder = fn(&Hit, &Hit) -> Ordering;
+
+/// Accumulates post-processed contig hits, spilling a sorted run once more than `threshold`
+/// hits are held in memory.
+pub(crate) struct HitCollector {
+    hits: Vec<Hit>,
+    threshold: Option<usize>,
+    order: HitOrder,
+    spilled: Option<SpilledHits>,
+}
+
+impl HitCollector {
+    pub(crate) fn new(threshold: Option<usize>) -> Self {
+        Self::ordered(threshold, compare_hits)
+    }
+
+    /// Like [`HitCollector::new`], with runs sorted by `order` instead of [`compare_hits`].
+    pub(crate) fn ordered(threshold: Option<usize>, order: HitOrder) -> Self {
+        Self {
+            hits: Vec::new(),
+            threshold,
+            order,
+            spilled: None,
+        }
+    }
+
+    pub(crate) fn push(&mut self, hits: Vec<Hit>) -> Result<()> {
+        self.hits.extend(hits);
+        if self.threshold.is_some_and(|limit| self.hits.len() > limit) {
+            self.hits.sort_by(self.order);
+            let spilled = match &mut self.spilled {
+                Some(spilled) => spilled,
+                None => self.spilled.insert(SpilledHits::create()?),
+            };
+            spilled.write_run(&self.hits)?;
+            self.hits.clear();
+        }
+        Ok(())
+    }
+
+    /// The hits still in memory and the spilled runs, if any.
+    pub(crate) fn finish(self) -> (Vec<Hit>, Option<Arc<SpilledHits>>) {
+        (self.hits, self.spilled.map(Arc::new))
+    }
+}
+
+type HitSource = Box<dyn Iterator<Item = Result<Hit>> + Send>;
+
+struct HeapEntry {
+    hit: Hit,
+    source: usize,
+    order: HitOrder,
+}
+
+impl Ord for HeapEntry {
+    // Reversed so the max-heap pops the smallest hit; earlier sources win ties.
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.order)(&other.hit, &self.hit).then_with(|| other.source.cmp(&self.source))
+    }
+}
+
+impl PartialOrd for HeapEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for HeapEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for HeapEntry {}
+
+/// Sorted hits of a scan: a k-way merge of every spilled run and the in-memory run.
+/// Without spilled runs this simply yields the in-memory hits.
+pub struct HitStream {
+    sources: Vec<HitSource>,
+    heap: BinaryHeap<HeapEntry>,
+    order: HitOrder,
+    _spilled: Option<Arc<SpilledHits>>,
+}
+
+impl HitStream {
+    pub(crate) fn new(in_memory: Vec<Hit>, spilled: Option<Arc<SpilledHits>>) -> Result<Self> {
+        Self::ordered(in_memory, spilled, compare_hits)
+    }
+
+    /// Like [`HitStream::new`], merging runs sorted by `order`; `in_memory` must be sorted by
+    /// it too.
+    pub(crate) fn ordered(
+        in_memory: Vec<Hit>,
+        spilled: Option<Arc<SpilledHits>>,
+        order: HitOrder,
+    ) -> Result<Self> {
+        let mut sources: Vec<HitSource> = Vec::new();
+        for path in spilled.iter().flat_map(|spilled| &spilled.runs) {
+            let file = File::open(path)
+                .with_context(|| format!("failed to open spill run '{}'", path.display()))?;
+            let path = path.clone();
+            sources.push(Box::new(BufReader::new(file).lines().map(move |line| {
+                let line =
+                    line.with_context(|| format!("failed reading spill run '{}'", path.display()))?;
+                serde_json::from_str(&line)
+                    .with_context(|| format!("corrupt spill run '{}'", path.display()))
+            })));
+        }
+        sources.push(Box::new(in_memory.into_iter().map(Ok)));
+
+        let mut stream = Self {
+            sources,
+            heap: BinaryHeap::new(),
+            order,
+            _spilled: spilled,
+        };
+        for source in 0..stream.sources.len() {
+            stream.refill(source)?;
+        }
+        Ok(stream)
+    }
+
+    fn refill(&mut self, source: usize) -> Result<()> {
+        if let Some(hit) = self.sources[source].next() {
+            self.heap.push(HeapEntry {
+                hit: hit?,
+                source,
+                order: self.order,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl Iterator for HitStream {
+    type Item = Result<Hit>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let HeapEntry { hit, source, .. } = self.heap.pop()?;
+        Some(self.refill(source).map(|()| hit))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SpilledHits;
+    use crate::test_support::tmp_path;
+    use crate::{Primer, ScanOptions, scan_references};
+
+    #[test]
+    fn spilled_scan_streams_the_same_sorted_hits() {
+        let reference = tmp_path("spill.fa");
+        std::fs::write(
+            &reference,
+            ">chr2\nACGTACGTTTACGT\n>chr1\nACGTGGACGTACGAACGT\n>chr3\nTTTT\n>chr0\nACGTACGT\n",
+        )
+        .expect("write reference");
+        let primers = vec![
+            Primer::from_name_and_sequence("p2", "ACGT").expect("primer"),
+            Primer::from_name_and_sequence("p1", "TACG").expect("primer"),
+        ];
+        let references = vec![reference.clone()];
+
+        let mut in_memory =
+            scan_references(&references, &primers, &ScanOptions::default()).expect("scan");
+        let mut spilled = scan_references(
+            &references,
+            &primers,
+            &ScanOptions {
+                spill_threshold: Some(2),
+                ..ScanOptions::default()
+            },
+        )
+        .expect("spilled scan");
+
+        assert!(in_memory.spilled_hits.is_none());
+        assert!(spilled.spilled_hits.as_ref().expect("spilled").run_count() > 1);
+        assert_eq!(spilled.hit_hash, in_memory.hit_hash);
+        let key = |hit: crate::Hit| (hit.contig, hit.primer, hit.start, hit.strand);
+        let expected = in_memory
+            .hit_stream()
+            .expect("stream")
+            .map(|hit| key(hit.expect("hit")))
+            .collect::<Vec<_>>();
+        let actual = spilled
+            .hit_stream()
+            .expect("stream")
+            .map(|hit| key(hit.expect("hit")))
+            .collect::<Vec<_>>();
+        assert_eq!(actual, expected);
+        assert_eq!(actual.len() as u64, spilled.total_hits);
+
+        std::fs::remove_file(reference).expect("remove reference");
+    }
+
+    #[test]
+    fn spill_directories_are_private_to_each_spill() {
+        let first = SpilledHits::create().expect("first spill");
+        let second = SpilledHits::create().expect("second spill");
+        assert_ne!(first.dir, second.dir);
+
+        let second_dir = second.dir.clone();
+        let first_dir = first.dir.clone();
+        drop(first);
+        assert!(!first_dir.exists());
+        assert!(second_dir.is_dir());
+        drop(second);
+        assert!(!second_dir.exists());
+    }
+}
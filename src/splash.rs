@@ -4,11 +4,50 @@ use std::time::Duration;
 
 const ESC: &str = "\x1b[";
 const RESET: &str = "\x1b[0m";
-const CYAN: &str = "\x1b[36m";
-const BLUE: &str = "\x1b[94m";
-const YELLOW: &str = "\x1b[93m";
-const DIM: &str = "\x1b[2m";
-const BOLD: &str = "\x1b[1m";
+
+/// Escape sequences used by the frames; every one is empty for terminals without ANSI support.
+struct Palette {
+    clear: &'static str,
+    reset: &'static str,
+    cyan: &'static str,
+    blue: &'static str,
+    yellow: &'static str,
+    dim: &'static str,
+    bold: &'static str,
+}
+
+const ANSI: Palette = Palette {
+    clear: "\x1b[2J\x1b[H",
+    reset: RESET,
+    cyan: "\x1b[36m",
+    blue: "\x1b[94m",
+    yellow: "\x1b[93m",
+    dim: "\x1b[2m",
+    bold: "\x1b[1m",
+};
+
+const PLAIN: Palette = Palette {
+    clear: "",
+    reset: "",
+    cyan: "",
+    blue: "",
+    yellow: "",
+    dim: "",
+    bold: "",
+};
+
+/// Whether stdout interprets ANSI escapes. On Windows this enables virtual terminal
+/// processing, which consoles before Windows 10 lack.
+fn ansi_supported() -> bool {
+    #[cfg(windows)]
+    {
+        crossterm::ansi_support::supports_ansi()
+    }
+    #[cfg(not(windows))]
+    {
+        true
+    }
+}
 
 pub fn show_dna_splash(
     command_name: &str,
@@ -18,42 +57,73 @@ pub fn show_dna_splash(
         return Ok(());
     }
 
+    let total_frames = 18usize;
+    if !ansi_supported() {
+        // No animation without cursor control: print the final frame once, uncolored.
+        let mut out = io::stdout().lock();
+        render_frame(
+            &mut out,
+            &PLAIN,
+            total_frames,
+            command_name,
+            true,
+            update_info,
+        )?;
+        return out.flush();
+    }
+
     let _cursor_guard = CursorGuard;
     let mut out = io::stdout().lock();
     write!(out, "{ESC}?25l")?;
 
-    let total_frames = 18usize;
     for phase in 0..total_frames {
-        render_frame(&mut out, phase, command_name, false, update_info)?;
+        render_frame(&mut out, &ANSI, phase, command_name, false, update_info)?;
         out.flush()?;
         thread::sleep(Duration::from_millis(55));
     }
 
-    render_frame(&mut out, total_frames, command_name, true, update_info)?;
+    render_frame(
+        &mut out,
+        &ANSI,
+        total_frames,
+        command_name,
+        true,
+        update_info,
+    )?;
     out.flush()?;
     Ok(())
 }
 
 fn render_frame<W: Write>(
     out: &mut W,
+    palette: &Palette,
     phase: usize,
     command_name: &str,
     final_frame: bool,
     update_info: Option<&crate::update::UpdateInfo>,
 ) -> io::Result<()> {
-    write!(out, "{ESC}2J{ESC}H")?;
-    writeln!(out, "{BOLD}{CYAN}primer-scout{RESET} {BLUE}startup{RESET}")?;
+    let Palette {
+        clear,
+        reset,
+        cyan,
+        blue,
+        yellow,
+        dim,
+        bold,
+    } = *palette;
+    write!(out, "{clear}")?;
+    writeln!(out, "{bold}{cyan}primer-scout{reset} {blue}startup{reset}")?;
     writeln!(
         out,
-        "{DIM}Fast primer off-target scanning for FASTA references{RESET}"
+        "{dim}Fast primer off-target scanning for FASTA references{reset}"
     )?;
     writeln!(out)?;
 
     for (row, line) in helix_lines(phase).into_iter().enumerate() {
         if row % 2 == 0 {
-            writeln!(out, "  {CYAN}{line}{RESET}")?;
+            writeln!(out, "  {cyan}{line}{reset}")?;
         } else {
-            writeln!(out, "  {BLUE}{line}{RESET}")?;
+            writeln!(out, "  {blue}{line}{reset}")?;
         }
     }
 
@@ -61,23 +131,23 @@ fn render_frame<W: Write>(
     if final_frame {
         writeln!(
             out,
-            "{BOLD}Ready:{RESET} run scans with `{command_name} --primers <file.tsv> --reference <ref.fa> --summary`"
+            "{bold}Ready:{reset} run scans with `{command_name} --primers <file.tsv> --reference <ref.fa> --summary`"
         )?;
         writeln!(
             out,
-            "{DIM}Tip: `{command_name} --help` for full command options.{RESET}"
+            "{dim}Tip: `{command_name} --help` for full command options.{reset}"
         )?;
         if let Some(update) = update_info {
             writeln!(
                 out,
-                "{YELLOW}{BOLD}Update available!{RESET} {YELLOW}v{}{RESET}",
+                "{yellow}{bold}Update available!{reset} {yellow}v{}{reset}",
                 update.latest_version
             )?;
-            writeln!(out, "{YELLOW}Run: {}{RESET}", update.install_command)?;
+            writeln!(out, "{yellow}Run: {}{reset}", update.install_command)?;
         }
     } else {
         let dots = ".".repeat((phase % 4) + 1);
-        writeln!(out, "{DIM}Initializing helix renderer{dots}{RESET}")?;
+        writeln!(out, "{dim}Initializing helix renderer{dots}{reset}")?;
     }
     Ok(())
 }
@@ -120,3 +190,21 @@ impl Drop for CursorGuard {
         let _ = out.flush();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_frames_carry_no_escape_sequences() {
+        let render = |palette: &Palette| {
+            let mut out = Vec::new();
+            render_frame(&mut out, palette, 18, "primer-scout", true, None).expect("render");
+            String::from_utf8(out).expect("utf8 frame")
+        };
+        let plain = render(&PLAIN);
+        assert!(!plain.contains('\x1b'), "{plain:?}");
+        assert!(plain.contains("Ready: run scans with `primer-scout"));
+        assert!(render(&ANSI).starts_with("\x1b[2J\x1b[H"));
+    }
+}
@@ -0,0 +1,24 @@
+use anyhow::Result;
+use std::ffi::OsString;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A fresh path under the temp dir ending in `name`, unique across tests and test processes.
+pub(crate) fn tmp_path(name: &str) -> PathBuf {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("clock should be after unix epoch")
+        .as_nanos();
+    let id = NEXT.fetch_add(1, Ordering::Relaxed);
+    std::env::temp_dir().join(format!(
+        "primer_scout_{}_{nanos}_{id}_{name}",
+        std::process::id()
+    ))
+}
+
+/// Runs the CLI on `args`, which exclude the program name.
+pub(crate) fn run_cli(args: impl IntoIterator<Item = OsString>) -> Result<()> {
+    crate::cli::run_from_args(std::iter::once(OsString::from("primer-scout")).chain(args))
+}
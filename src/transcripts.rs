@@ -0,0 +1,538 @@
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::io::BufRead;
+use std::ops::Range;
+use std::path::Path;
+
+use crate::{Hit, open_reader};
+
+/// Where a hit falls relative to the exons of its transcript.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TranscriptRegion {
+    /// Entirely inside one exon; `transcript_pos` is set.
+    Exonic,
+    /// Overlaps an exon and extends into an intron or past the transcript end, so the genomic
+    /// match is not contiguous in the spliced transcript.
+    JunctionSpanning,
+    /// Inside the transcript span but overlapping no exon.
+    Intronic,
+}
+
+impl fmt::Display for TranscriptRegion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Exonic => "exonic",
+            Self::JunctionSpanning => "junction_spanning",
+            Self::Intronic => "intronic",
+        })
+    }
+}
+
+/// One spliced transcript: sorted, non-overlapping genomic exons (0-based, half-open).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transcript {
+    pub name: String,
+    pub contig: String,
+    pub reverse: bool,
+    pub exons: Vec<Range<usize>>,
+}
+
+impl Transcript {
+    fn span(&self) -> Range<usize> {
+        self.exons.first().map_or(0, |exon| exon.start)
+            ..self.exons.last().map_or(0, |exon| exon.end)
+    }
+
+    fn spliced_len(&self) -> usize {
+        self.exons.iter().map(|exon| exon.len()).sum()
+    }
+
+    /// Region of `start..end` in this transcript and, when exonic, the 0-based transcript
+    /// position of its 5'-most base in transcript orientation.
+    fn locate(&self, start: usize, end: usize) -> Option<(TranscriptRegion, Option<usize>)> {
+        let span = self.span();
+        if start >= span.end || end <= span.start {
+            return None;
+        }
+        let mut preceding = 0usize;
+        let mut overlaps_exon = false;
+        for exon in &self.exons {
+            if exon.start <= start && end <= exon.end {
+                let offset = preceding + (start - exon.start);
+                let position = if self.reverse {
+                    self.spliced_len() - offset - (end - start)
+                } else {
+                    offset
+                };
+                return Some((TranscriptRegion::Exonic, Some(position)));
+            }
+            overlaps_exon |= exon.start < end && start < exon.end;
+            preceding += exon.len();
+        }
+        let region = if overlaps_exon {
+            TranscriptRegion::JunctionSpanning
+        } else {
+            TranscriptRegion::Intronic
+        };
+        Some((region, None))
+    }
+}
+
+/// The transcripts of one contig, sorted by span start.
+#[derive(Debug, Clone, Default)]
+struct ContigTranscripts {
+    transcripts: Vec<Transcript>,
+    /// Largest span end of `transcripts[..=i]`, so a lookup can stop at the first transcript
+    /// before which nothing reaches the hit.
+    max_end: Vec<usize>,
+}
+
+/// Transcript models per contig, used to add spliced coordinates to hits.
+#[derive(Debug, Clone, Default)]
+pub struct TranscriptSet {
+    by_contig: HashMap<String, ContigTranscripts>,
+}
+
+impl TranscriptSet {
+    pub fn from_transcripts(transcripts: impl IntoIterator<Item = Transcript>) -> Self {
+        let mut grouped: HashMap<String, Vec<Transcript>> = HashMap::new();
+        for transcript in transcripts {
+            grouped
+                .entry(transcript.contig.clone())
+                .or_default()
+                .push(transcript);
+        }
+        let by_contig = grouped
+            .into_iter()
+            .map(|(contig, mut transcripts)| {
+                transcripts.sort_by_key(|transcript| transcript.span().start);
+                let max_end = transcripts
+                    .iter()
+                    .scan(0, |max_end, transcript| {
+                        *max_end = transcript.span().end.max(*max_end);
+                        Some(*max_end)
+                    })
+                    .collect();
+                let contig_transcripts = ContigTranscripts {
+                    transcripts,
+                    max_end,
+                };
+                (contig, contig_transcripts)
+            })
+            .collect();
+        Self { by_contig }
+    }
+
+    /// Sets `transcript`, `transcript_pos`, and `transcript_region` on a hit. With several
+    /// overlapping transcripts, an exonic placement beats a junction-spanning one, which beats
+    /// an intronic one; ties go to the transcript starting first.
+    ///
+    /// Walks back from the last transcript starting before the hit end only while an earlier
+    /// transcript can still reach the hit, so a lookup costs the overlapping transcripts, not
+    /// the whole contig.
+    pub fn annotate(&self, hit: &mut Hit) {
+        let Some(ContigTranscripts {
+            transcripts,
+            max_end,
+        }) = self.by_contig.get(&hit.contig)
+        else {
+            return;
+        };
+        let candidates =
+            transcripts.partition_point(|transcript| transcript.span().start < hit.end);
+        let best = (0..candidates)
+            .rev()
+            .take_while(|&index| max_end[index] > hit.start)
+            .filter_map(|index| {
+                let (region, position) = transcripts[index].locate(hit.start, hit.end)?;
+                Some((region, index, position))
+            })
+            .min_by_key(|(region, index, _)| (*region, *index));
+        if let Some((region, index, position)) = best {
+            let transcript = &transcripts[index];
+            hit.transcript = Some(transcript.name.clone());
+            hit.transcript_pos = position;
+            hit.transcript_region = Some(region);
+        }
+    }
+}
+
+/// Loads exon structures from BED12, or from GFF3/GTF `exon` rows when the file name ends in
+/// `.gff`, `.gff3`, or `.gtf` (optionally `.gz`).
+pub fn load_transcripts(path: &Path) -> Result<TranscriptSet> {
+    let name = path.to_string_lossy().to_ascii_lowercase();
+    let name = name.strip_suffix(".gz").unwrap_or(&name);
+    let transcripts = if [".gff", ".gff3", ".gtf"]
+        .iter()
+        .any(|extension| name.ends_with(extension))
+    {
+        load_gff_exons(path)?
+    } else {
+        load_bed12(path)?
+    };
+    if transcripts.is_empty() {
+        bail!("no transcripts found in '{}'", path.display());
+    }
+    Ok(TranscriptSet::from_transcripts(transcripts))
+}
+
+/// Data lines of an annotation file with their 1-based line numbers; blank, `#`, `track`, and
+/// `browser` lines are skipped.
+fn data_lines(path: &Path) -> Result<Vec<(usize, String)>> {
+    let reader = open_reader(path)?;
+    let mut lines = Vec::new();
+    for (line_idx, line) in reader.lines().enumerate() {
+        let line =
+            line.with_context(|| format!("failed reading transcripts '{}'", path.display()))?;
+        let trimmed = line.trim();
+        if trimmed.is_empty()
+            || trimmed.starts_with('#')
+            || trimmed.starts_with("track")
+            || trimmed.starts_with("browser")
+        {
+            continue;
+        }
+        lines.push((line_idx + 1, trimmed.to_string()));
+    }
+    Ok(lines)
+}
+
+fn parse_number(value: &str, line_number: usize, path: &Path) -> Result<usize> {
+    value.trim().parse::<usize>().with_context(|| {
+        format!(
+            "invalid number '{}' on line {} of '{}'",
+            value,
+            line_number,
+            path.display()
+        )
+    })
+}
+
+fn parse_strand(value: &str, line_number: usize, path: &Path) -> Result<bool> {
+    match value {
+        "+" | "." => Ok(false),
+        "-" => Ok(true),
+        other => bail!(
+            "invalid strand '{}' on line {} of '{}'",
+            other,
+            line_number,
+            path.display()
+        ),
+    }
+}
+
+/// Sorts exons and rejects overlapping ones.
+fn finish_transcript(mut transcript: Transcript, path: &Path) -> Result<Transcript> {
+    transcript.exons.sort_by_key(|exon| exon.start);
+    if let Some(pair) = transcript
+        .exons
+        .windows(2)
+        .find(|pair| pair[1].start < pair[0].end)
+    {
+        bail!(
+            "transcript '{}' in '{}' has overlapping exons {}..{} and {}..{}",
+            transcript.name,
+            path.display(),
+            pair[0].start,
+            pair[0].end,
+            pair[1].start,
+            pair[1].end
+        );
+    }
+    Ok(transcript)
+}
+
+fn load_bed12(path: &Path) -> Result<Vec<Transcript>> {
+    let mut transcripts = Vec::new();
+    for (line_number, line) in data_lines(path)? {
+        let fields = line.split('\t').map(str::trim).collect::<Vec<_>>();
+        if fields.len() < 12 {
+            bail!(
+                "BED12 line {} in '{}' has {} columns, expected 12",
+                line_number,
+                path.display(),
+                fields.len()
+            );
+        }
+        let chrom_start = parse_number(fields[1], line_number, path)?;
+        let chrom_end = parse_number(fields[2], line_number, path)?;
+        let block_count = parse_number(fields[9], line_number, path)?;
+        let list = |column: &str| {
+            column
+                .split(',')
+                .filter(|value| !value.is_empty())
+                .map(|value| parse_number(value, line_number, path))
+                .collect::<Result<Vec<_>>>()
+        };
+        let (sizes, starts) = (list(fields[10])?, list(fields[11])?);
+        if sizes.len() != block_count || starts.len() != block_count {
+            bail!(
+                "BED12 line {} in '{}' lists {} block sizes and {} block starts for {} blocks",
+                line_number,
+                path.display(),
+                sizes.len(),
+                starts.len(),
+                block_count
+            );
+        }
+        let exons = starts
+            .iter()
+            .zip(&sizes)
+            .map(|(&start, &size)| chrom_start + start..chrom_start + start + size)
+            .collect::<Vec<_>>();
+        if exons
+            .iter()
+            .any(|exon| exon.is_empty() || exon.end > chrom_end)
+        {
+            bail!(
+                "BED12 line {} in '{}' has a block outside {}..{}",
+                line_number,
+                path.display(),
+                chrom_start,
+                chrom_end
+            );
+        }
+        transcripts.push(finish_transcript(
+            Transcript {
+                name: fields[3].to_string(),
+                contig: fields[0].to_string(),
+                reverse: parse_strand(fields[5], line_number, path)?,
+                exons,
+            },
+            path,
+        )?);
+    }
+    Ok(transcripts)
+}
+
+/// Groups GFF3 (`Parent=`) or GTF (`transcript_id "..."`) exon rows into transcripts, in order
+/// of first appearance. GFF coordinates are 1-based and inclusive.
+fn load_gff_exons(path: &Path) -> Result<Vec<Transcript>> {
+    let mut order = Vec::new();
+    let mut by_name: HashMap<String, Transcript> = HashMap::new();
+    for (line_number, line) in data_lines(path)? {
+        let fields = line.split('\t').collect::<Vec<_>>();
+        if fields.len() < 9 {
+            bail!(
+                "GFF line {} in '{}' has {} columns, expected 9",
+                line_number,
+                path.display(),
+                fields.len()
+            );
+        }
+        if fields[2] != "exon" {
+            continue;
+        }
+        let start = parse_number(fields[3], line_number, path)?;
+        let end = parse_number(fields[4], line_number, path)?;
+        if start == 0 || start > end {
+            bail!(
+                "GFF line {} in '{}' has invalid exon {}..{}",
+                line_number,
+                path.display(),
+                start,
+                end
+            );
+        }
+        let Some(name) = transcript_id(fields[8]) else {
+            bail!(
+                "GFF exon on line {} in '{}' has no Parent or transcript_id",
+                line_number,
+                path.display()
+            );
+        };
+        let reverse = parse_strand(fields[6], line_number, path)?;
+        let transcript = by_name.entry(name.clone()).or_insert_with(|| {
+            order.push(name.clone());
+            Transcript {
+                name,
+                contig: fields[0].to_string(),
+                reverse,
+                exons: Vec::new(),
+            }
+        });
+        if transcript.contig != fields[0] || transcript.reverse != reverse {
+            bail!(
+                "GFF line {} in '{}' places an exon of '{}' on a different contig or strand",
+                line_number,
+                path.display(),
+                transcript.name
+            );
+        }
+        transcript.exons.push(start - 1..end);
+    }
+    order
+        .into_iter()
+        .map(|name| finish_transcript(by_name.remove(&name).expect("ordered name"), path))
+        .collect()
+}
+
+fn transcript_id(attributes: &str) -> Option<String> {
+    attributes.split(';').find_map(|attribute| {
+        let attribute = attribute.trim();
+        if let Some(parent) = attribute.strip_prefix("Parent=") {
+            return parent.split(',').next().map(str::to_string);
+        }
+        attribute
+            .strip_prefix("transcript_id")
+            .map(|value| value.trim().trim_matches('"').to_string())
+            .filter(|value| !value.is_empty())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::tmp_path;
+
+    #[test]
+    fn maps_hits_into_spliced_coordinates() {
+        // Exons 100..110, 200..220, 300..305: spliced length 35.
+        let exons = vec![100..110, 200..220, 300..305];
+        let forward = Transcript {
+            name: "tx_plus".to_string(),
+            contig: "chr1".to_string(),
+            reverse: false,
+            exons: exons.clone(),
+        };
+        let reverse = Transcript {
+            name: "tx_minus".to_string(),
+            reverse: true,
+            ..forward.clone()
+        };
+
+        // Second exon, 5 bp in: 10 + 5 = 15 on `+`; on `-` the 4 bp hit ends at spliced 19,
+        // so its 5'-most transcript base is 35 - 19 = 16.
+        assert_eq!(
+            forward.locate(205, 209),
+            Some((TranscriptRegion::Exonic, Some(15)))
+        );
+        assert_eq!(
+            reverse.locate(205, 209),
+            Some((TranscriptRegion::Exonic, Some(16)))
+        );
+        assert_eq!(
+            forward.locate(300, 305),
+            Some((TranscriptRegion::Exonic, Some(30)))
+        );
+        assert_eq!(
+            forward.locate(108, 112),
+            Some((TranscriptRegion::JunctionSpanning, None))
+        );
+        assert_eq!(
+            forward.locate(150, 160),
+            Some((TranscriptRegion::Intronic, None))
+        );
+        assert_eq!(forward.locate(90, 100), None);
+        assert_eq!(forward.locate(305, 310), None);
+
+        assert_eq!(
+            transcript_id("ID=exon1;Parent=tx1,tx2"),
+            Some("tx1".to_string())
+        );
+        assert_eq!(
+            transcript_id("gene_id \"g1\"; transcript_id \"tx9\";"),
+            Some("tx9".to_string())
+        );
+    }
+
+    #[test]
+    fn annotate_matches_a_scan_of_every_transcript() {
+        // One long transcript enclosing a run of short ones, plus one sharing a short one's
+        // start, so the walk back must not stop at the short transcripts.
+        let transcript = |name: &str, exons: Vec<Range<usize>>| Transcript {
+            name: name.to_string(),
+            contig: "chr1".to_string(),
+            reverse: false,
+            exons,
+        };
+        let mut transcripts = vec![transcript("long", vec![0..10, 990..1000])];
+        for index in 0..20 {
+            let start = 40 * index + 20;
+            transcripts.push(transcript(
+                &format!("short{index}"),
+                vec![start..start + 5, start + 8..start + 15],
+            ));
+        }
+        transcripts.push(transcript("twin", vec![100..104, 110..130]));
+        let set = TranscriptSet::from_transcripts(transcripts.clone());
+        transcripts.sort_by_key(|transcript| transcript.span().start);
+
+        let primer = crate::Primer::from_name_and_sequence("p1", "ACGTAC").expect("primer");
+        let options = crate::ScanOptions {
+            scan_reverse_complement: false,
+            ..crate::ScanOptions::default()
+        };
+        let template = crate::scan_sequence("ACGTAC", "chr1", &[primer], &options)
+            .expect("scan")
+            .hits
+            .remove(0);
+        for start in (0..1000).step_by(3) {
+            let mut hit = Hit {
+                start,
+                end: start + 6,
+                ..template.clone()
+            };
+            set.annotate(&mut hit);
+            let expected = transcripts
+                .iter()
+                .filter_map(|transcript| {
+                    let (region, position) = transcript.locate(start, start + 6)?;
+                    Some((region, position, transcript.name.clone()))
+                })
+                .min_by_key(|(region, _, _)| *region);
+            assert_eq!(
+                hit.transcript_region.map(|region| (
+                    region,
+                    hit.transcript_pos,
+                    hit.transcript.clone().expect("name")
+                )),
+                expected,
+                "hit at {start}"
+            );
+        }
+    }
+
+    #[test]
+    fn bed12_and_gff_load_the_same_exons() {
+        let bed = tmp_path("tx.bed");
+        let gff = tmp_path("tx.gff3");
+        std::fs::write(
+            &bed,
+            "chr1\t100\t305\ttx1\t0\t-\t100\t305\t0\t3\t10,20,5,\t0,100,200,\n",
+        )
+        .expect("write bed");
+        std::fs::write(
+            &gff,
+            "##gff-version 3\nchr1\tsrc\tmRNA\t101\t305\t.\t-\t.\tID=tx1\n\
+             chr1\tsrc\texon\t301\t305\t.\t-\t.\tParent=tx1\n\
+             chr1\tsrc\texon\t101\t110\t.\t-\t.\tParent=tx1\n\
+             chr1\tsrc\texon\t201\t220\t.\t-\t.\tParent=tx1\n",
+        )
+        .expect("write gff");
+
+        let from_bed = load_transcripts(&bed).expect("bed12");
+        let from_gff = load_transcripts(&gff).expect("gff");
+        let expected = Transcript {
+            name: "tx1".to_string(),
+            contig: "chr1".to_string(),
+            reverse: true,
+            exons: vec![100..110, 200..220, 300..305],
+        };
+        assert_eq!(
+            from_bed.by_contig["chr1"].transcripts,
+            vec![expected.clone()]
+        );
+        assert_eq!(from_gff.by_contig["chr1"].transcripts, vec![expected]);
+
+        std::fs::write(&bed, "chr1\t100\t305\ttx1\t0\t+\n").expect("write bed");
+        let err = load_transcripts(&bed).expect_err("bed6 is not bed12");
+        assert!(err.to_string().contains("line 1"), "{err}");
+
+        std::fs::remove_file(bed).expect("remove bed");
+        std::fs::remove_file(gff).expect("remove gff");
+    }
+}
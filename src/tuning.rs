@@ -0,0 +1,138 @@
+use anyhow::{Context, Result};
+use std::io::BufRead;
+use std::num::NonZeroUsize;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::warnings::WarningLog;
+use crate::{Primer, ScanOptions, open_reader, scan_contig};
+
+/// Length of the synthetic sequence every candidate thread count scans once.
+pub(crate) const CALIBRATION_BASES: usize = 100_000;
+
+/// Picks the worker count with the best measured throughput on a calibration scan.
+///
+/// Candidates are 1, 2, 4, ... up to (and including) the available parallelism. The
+/// calibration sequence tiles the first contig of `reference`, so its base composition
+/// resembles the real input; an empty reference falls back to random bases from the
+/// run seed. Ties go to the smaller count.
+pub(crate) fn tune_threads(
+    reference: &Path,
+    primers: &[Primer],
+    options: &ScanOptions,
+) -> Result<usize> {
+    let sequence = calibration_sequence(reference, options)?;
+    let calibration = ScanOptions {
+        regions: None,
+        transcripts: None,
+        groups: Vec::new(),
+        warnings: Arc::new(WarningLog::new(0)),
+        ..options.clone()
+    };
+
+    let mut best = (1usize, Duration::MAX);
+    for threads in candidate_thread_counts(available_threads()) {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .context("failed to create calibration thread pool")?;
+        let started = Instant::now();
+        pool.install(|| {
+            scan_contig(
+                "calibration",
+                "calibration",
+                &sequence,
+                primers,
+                &calibration,
+            )
+        })?;
+        let elapsed = started.elapsed();
+        if elapsed < best.1 {
+            best = (threads, elapsed);
+        }
+    }
+    Ok(best.0)
+}
+
+fn candidate_thread_counts(available: usize) -> Vec<usize> {
+    let available = available.max(1);
+    let mut counts = std::iter::successors(Some(1usize), |count| count.checked_mul(2))
+        .take_while(|count| *count < available)
+        .collect::<Vec<_>>();
+    counts.push(available);
+    counts
+}
+
+fn available_threads() -> usize {
+    std::thread::available_parallelism()
+        .map(NonZeroUsize::get)
+        .unwrap_or(1)
+}
+
+fn calibration_sequence(reference: &Path, options: &ScanOptions) -> Result<Vec<u8>> {
+    let prefix = first_contig_prefix(reference, CALIBRATION_BASES)?;
+    if prefix.is_empty() {
+        let mut rng = options.rng_for("calibration");
+        return Ok((0..CALIBRATION_BASES)
+            .map(|_| b"ACGT"[(rng.next_u32() & 3) as usize])
+            .collect());
+    }
+    Ok(prefix
+        .iter()
+        .copied()
+        .cycle()
+        .take(CALIBRATION_BASES)
+        .collect())
+}
+
+/// Up to `limit` sequence bytes of the first FASTA record, read without buffering whole lines.
+fn first_contig_prefix(reference: &Path, limit: usize) -> Result<Vec<u8>> {
+    let mut reader = open_reader(reference)?;
+    let mut prefix = Vec::with_capacity(limit);
+    let mut headers_seen = 0usize;
+    let mut in_header = false;
+    let mut at_line_start = true;
+
+    'read: loop {
+        let buffer = reader
+            .fill_buf()
+            .with_context(|| format!("failed reading reference '{}'", reference.display()))?;
+        if buffer.is_empty() {
+            break;
+        }
+        let consumed = buffer.len();
+        for &byte in buffer {
+            if at_line_start && byte == b'>' {
+                headers_seen += 1;
+                if headers_seen > 1 {
+                    break 'read;
+                }
+                in_header = true;
+            } else if byte == b'\n' {
+                in_header = false;
+            } else if !in_header && headers_seen == 1 && !byte.is_ascii_whitespace() {
+                prefix.push(byte);
+                if prefix.len() == limit {
+                    break 'read;
+                }
+            }
+            at_line_start = byte == b'\n';
+        }
+        reader.consume(consumed);
+    }
+    Ok(prefix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn candidates_double_up_to_available_parallelism() {
+        assert_eq!(candidate_thread_counts(1), vec![1]);
+        assert_eq!(candidate_thread_counts(4), vec![1, 2, 4]);
+        assert_eq!(candidate_thread_counts(6), vec![1, 2, 4, 6]);
+        assert_eq!(candidate_thread_counts(0), vec![1]);
+    }
+}
@@ -0,0 +1,115 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::sync::Mutex;
+
+/// How many warnings of one category are printed before the rest are only counted.
+pub const DEFAULT_WARNING_LIMIT: usize = 5;
+
+/// Thread-safe warning sink that prints each category at most `limit` times.
+///
+/// Every warning is counted, so `counts` stays complete even when output is suppressed.
+#[derive(Debug)]
+pub struct WarningLog {
+    limit: usize,
+    counts: Mutex<BTreeMap<&'static str, u64>>,
+}
+
+impl Default for WarningLog {
+    fn default() -> Self {
+        Self::new(DEFAULT_WARNING_LIMIT)
+    }
+}
+
+impl WarningLog {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            counts: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Records a warning and prints it to stderr unless `category` already reached the limit.
+    /// Returns whether the warning was printed.
+    pub fn warn(&self, category: &'static str, message: impl Display) -> bool {
+        let printed = {
+            let mut counts = self.counts.lock().unwrap_or_else(|err| err.into_inner());
+            let count = counts.entry(category).or_default();
+            *count += 1;
+            *count <= self.limit as u64
+        };
+        if printed {
+            eprintln!("warning: {message}");
+        }
+        printed
+    }
+
+    /// Total warnings recorded per category, including suppressed ones.
+    pub fn counts(&self) -> BTreeMap<String, u64> {
+        self.counts
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .iter()
+            .map(|(category, count)| (category.to_string(), *count))
+            .collect()
+    }
+
+    /// One `… and N more` line per category that exceeded the limit.
+    pub fn suppressed_lines(&self) -> Vec<String> {
+        self.counts
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .iter()
+            .filter(|&(_, &count)| count > self.limit as u64)
+            .map(|(category, count)| {
+                format!(
+                    "… and {} more {category} warnings",
+                    group_thousands(count - self.limit as u64)
+                )
+            })
+            .collect()
+    }
+
+    /// Prints the suppressed-warning lines to stderr.
+    pub fn finish(&self) {
+        for line in self.suppressed_lines() {
+            eprintln!("warning: {line}");
+        }
+    }
+}
+
+fn group_thousands(value: u64) -> String {
+    let digits = value.to_string();
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+    for (idx, digit) in digits.chars().enumerate() {
+        if idx > 0 && (digits.len() - idx).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(digit);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rayon::prelude::*;
+
+    #[test]
+    fn flood_of_one_category_is_capped_and_fully_counted() {
+        let log = WarningLog::new(5);
+        let printed = (0..12_350u32)
+            .into_par_iter()
+            .filter(|idx| log.warn("empty_contig", format!("contig {idx} is empty")))
+            .count();
+        log.warn("other", "printed once");
+
+        assert_eq!(printed, 5);
+        let counts = log.counts();
+        assert_eq!(counts.get("empty_contig"), Some(&12_350));
+        assert_eq!(counts.get("other"), Some(&1));
+        assert_eq!(
+            log.suppressed_lines(),
+            vec!["… and 12,345 more empty_contig warnings".to_string()]
+        );
+    }
+}
@@ -0,0 +1,109 @@
+//! Golden-file test for `--amplicons-bed`: `tests/golden/amplicons.bed` pins the BED12 rows
+//! written for a small pair panel, and every row is checked against the BED12 rules.
+//!
+//! Regenerate the golden file with
+//! `PRIMER_SCOUT_UPDATE_GOLDEN=1 cargo test --test amplicons_bed`, then review the diff.
+
+use primer_scout::amplicons::{predicted_amplicons, write_amplicons_bed};
+use primer_scout::{Primer, ScanOptions, scan_sequence};
+use std::path::Path;
+
+fn primer(name: &str, sequence: &str) -> Primer {
+    Primer::from_name_and_sequence(name, sequence).expect("primer")
+}
+
+fn revcomp(sequence: &str) -> String {
+    primer("site", sequence).reverse_complement
+}
+
+/// Panel and reference with one product per layout:
+/// - egfr: forward orientation, 20 bp insert.
+/// - kras: reverse orientation (reverse primer on the left), so the amplicon is on `-`.
+/// - tp53: primer sites overlapping by 2 bp, so there is no insert.
+fn golden_bed() -> String {
+    let (egfr_f, egfr_r_site) = ("ACGTTGCAAC", "GGATCCTTAG");
+    let (kras_r, kras_f_site) = ("TTGGCCAATG", "CAGTCAGTCA");
+    let (tp53_f, tp53_r_site) = ("GATGGACTCT", "CTCCTGAGTG");
+    let spacer = "A".repeat(20);
+    let sequence = format!(
+        "{spacer}{egfr_f}CCCCCCCCCCCCCCCCCCCC{egfr_r_site}{spacer}{kras_r}CCCCC{kras_f_site}{spacer}{}{}{spacer}",
+        tp53_f,
+        &tp53_r_site[2..]
+    );
+    let panel = vec![
+        primer("egfr_F", egfr_f),
+        primer("egfr_R", &revcomp(egfr_r_site)),
+        primer("kras_F", &revcomp(kras_f_site)),
+        primer("kras_R", kras_r),
+        primer("tp53_F", tp53_f),
+        primer("tp53_R", &revcomp(&format!("CT{}", &tp53_r_site[2..]))),
+    ];
+    let options = ScanOptions {
+        pair_suffix_pattern: Some(("_F".to_string(), "_R".to_string())),
+        ..ScanOptions::default()
+    };
+    let result = scan_sequence(&sequence, "chr7", &panel, &options).expect("scan");
+
+    let amplicons = predicted_amplicons(&result.hits, "_F", "_R", options.max_amplicon_size);
+    let mut out = Vec::new();
+    write_amplicons_bed(&mut out, &amplicons).expect("write BED");
+    String::from_utf8(out).expect("utf8 BED")
+}
+
+fn list(field: &str) -> Vec<usize> {
+    let values = field.strip_suffix(',').expect("trailing comma");
+    values
+        .split(',')
+        .map(|value| value.parse().expect("list value"))
+        .collect()
+}
+
+/// The BED12 rules genome browsers rely on.
+fn check_bed12_row(line: &str) {
+    let fields = line.split('\t').collect::<Vec<_>>();
+    assert_eq!(fields.len(), 12, "{line}");
+    let number = |idx: usize| fields[idx].parse::<usize>().expect("numeric field");
+    let (start, end, thick_start, thick_end) = (number(1), number(2), number(6), number(7));
+    assert!(start < end, "{line}");
+    assert!(matches!(fields[5], "+" | "-"), "{line}");
+    assert!(
+        start <= thick_start && thick_start <= thick_end && thick_end <= end,
+        "{line}"
+    );
+
+    let block_count = number(9);
+    let (sizes, starts) = (list(fields[10]), list(fields[11]));
+    assert_eq!(
+        (sizes.len(), starts.len()),
+        (block_count, block_count),
+        "{line}"
+    );
+    assert_eq!(starts[0], 0, "first block must start at chromStart: {line}");
+    assert_eq!(
+        starts[block_count - 1] + sizes[block_count - 1],
+        end - start,
+        "last block must end at chromEnd: {line}"
+    );
+    for idx in 1..block_count {
+        assert!(
+            starts[idx - 1] + sizes[idx - 1] <= starts[idx],
+            "blocks must ascend without overlap: {line}"
+        );
+    }
+}
+
+#[test]
+fn amplicon_bed_matches_golden_file() {
+    let actual = golden_bed();
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden/amplicons.bed");
+    if std::env::var_os("PRIMER_SCOUT_UPDATE_GOLDEN").is_some() {
+        std::fs::write(&path, &actual).expect("write golden file");
+    }
+    let expected = std::fs::read_to_string(&path).expect("read golden file");
+    assert_eq!(actual, expected);
+
+    assert_eq!(actual.lines().count(), 3);
+    for line in actual.lines() {
+        check_bed12_row(line);
+    }
+}
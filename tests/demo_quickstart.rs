@@ -0,0 +1,69 @@
+//! The documented quickstart commands, run end to end against `primer-scout demo` output in a
+//! fresh directory (as after `cargo install`, with no repository checkout).
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn temp_dir() -> PathBuf {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("clock should be after unix epoch")
+        .as_nanos();
+    std::env::temp_dir().join(format!("primer_scout_{nanos}_demo"))
+}
+
+fn primer_scout(dir: &Path, args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_primer-scout"))
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .expect("run primer-scout");
+    assert!(
+        output.status.success(),
+        "{args:?}: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).expect("utf8 output")
+}
+
+#[test]
+fn quickstart_commands_run_against_materialized_demo() {
+    let dir = temp_dir();
+    std::fs::create_dir_all(&dir).expect("create temp dir");
+    primer_scout(&dir, &["demo"]);
+    let demo = [
+        "--primers",
+        "data/demo_primers.tsv",
+        "--reference",
+        "data/demo.fa",
+    ];
+    let quickstart = |extra: &[&str]| primer_scout(&dir, &[&demo[..], extra].concat());
+
+    let count = quickstart(&["--count-only"]);
+    assert_eq!(count.trim(), "27");
+
+    let summary = quickstart(&["--summary"]);
+    let primers = summary
+        .lines()
+        .map(|row| row.split('\t').next().expect("primer column"))
+        .collect::<Vec<_>>();
+    assert_eq!(primers, ["p_ambig", "p_atgc", "p_gatc"]);
+
+    let hits = quickstart(&["--max-mismatches", "1"]);
+    let rows = hits
+        .lines()
+        .map(|row| row.split('\t').collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    let has = |check: &dyn Fn(&Vec<&str>) -> bool| rows.iter().any(check);
+    assert!(has(&|row| row[6] == "+" && row[7] == "0"), "{hits}");
+    assert!(has(&|row| row[6] == "-" && row[7] == "0"), "{hits}");
+    assert!(has(&|row| row[7] == "1"), "{hits}");
+    // The degenerate TGYA primer (Y = C or T) matches TGCA exactly.
+    assert!(
+        has(&|row| row[2] == "p_ambig" && row[8] == "TGCA"),
+        "{hits}"
+    );
+
+    std::fs::remove_dir_all(dir).expect("remove temp dir");
+}
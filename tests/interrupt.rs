@@ -0,0 +1,88 @@
+//! Ctrl+C during a scan must still flush the completed part and exit with status 130.
+#![cfg(unix)]
+
+use primer_scout::XorShift64;
+use std::fmt::Write as _;
+use std::io::{BufRead, BufReader, Read};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn random_bases(rng: &mut XorShift64, len: usize) -> String {
+    (0..len)
+        .map(|_| ['A', 'C', 'G', 'T'][(rng.next_u32() & 3) as usize])
+        .collect()
+}
+
+fn temp_dir() -> PathBuf {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("clock should be after unix epoch")
+        .as_nanos();
+    std::env::temp_dir().join(format!("primer_scout_{nanos}_interrupt"))
+}
+
+#[test]
+fn sigint_flushes_partial_output_and_exits_130() {
+    let dir = temp_dir();
+    std::fs::create_dir_all(&dir).expect("create temp dir");
+    let mut rng = XorShift64::new(5);
+
+    // The nameless first contig makes the scan warn on stderr once it is reading the
+    // reference, which is the signal to interrupt it.
+    let mut reference = String::from(">\nACGT\n");
+    for idx in 0..4 {
+        writeln!(
+            reference,
+            ">chr{idx}\n{}",
+            random_bases(&mut rng, 1_000_000)
+        )
+        .expect("fasta");
+    }
+    let mut panel = String::from("name\tsequence\n");
+    for idx in 0..64 {
+        writeln!(panel, "p{idx}\t{}", random_bases(&mut rng, 20)).expect("panel");
+    }
+    let reference_path = dir.join("reference.fa");
+    let panel_path = dir.join("panel.tsv");
+    std::fs::write(&reference_path, reference).expect("write reference");
+    std::fs::write(&panel_path, panel).expect("write panel");
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_primer-scout"))
+        .arg("--primers")
+        .arg(&panel_path)
+        .arg("--reference")
+        .arg(&reference_path)
+        .args(["--max-mismatches", "4", "--threads", "1", "--count-only"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("spawn primer-scout");
+
+    let mut stderr_lines = BufReader::new(child.stderr.take().expect("piped stderr"));
+    let mut stderr = String::new();
+    while !stderr.contains("has no name") {
+        let read = stderr_lines.read_line(&mut stderr).expect("read stderr");
+        assert!(read > 0, "scan ended before it started reading: {stderr}");
+    }
+    let status = Command::new("kill")
+        .args(["-INT", &child.id().to_string()])
+        .status()
+        .expect("send SIGINT");
+    assert!(status.success());
+
+    stderr_lines
+        .read_to_string(&mut stderr)
+        .expect("read stderr");
+    let output = child.wait_with_output().expect("wait for primer-scout");
+    assert_eq!(output.status.code(), Some(130), "stderr: {stderr}");
+    assert!(stderr.contains("interrupted"), "stderr: {stderr}");
+    assert!(stderr.contains(": partial"), "stderr: {stderr}");
+    let count = String::from_utf8_lossy(&output.stdout);
+    count
+        .trim()
+        .parse::<u64>()
+        .expect("partial count is still written");
+
+    std::fs::remove_dir_all(dir).expect("remove temp dir");
+}
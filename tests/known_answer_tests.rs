@@ -0,0 +1,124 @@
+//! Known-answer tests: each `tests/kat/*.json` fixture pins the exact hits `scan_sequence`
+//! must report for one primer against one reference sequence.
+//!
+//! Regenerate fixtures from the current engine output with
+//! `PRIMER_SCOUT_UPDATE_KAT=1 cargo test --test known_answer_tests`, then review the diff.
+
+use primer_scout::{Primer, ScanOptions, scan_sequence};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Fixture {
+    description: String,
+    primer: String,
+    reference_sequence: String,
+    scan_options: FixtureOptions,
+    expected_hits: Vec<ExpectedHit>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct FixtureOptions {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_mismatches: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    scan_reverse_complement: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    lowercase_mismatches: Option<bool>,
+}
+
+impl FixtureOptions {
+    fn to_scan_options(&self) -> ScanOptions {
+        let defaults = ScanOptions::default();
+        ScanOptions {
+            max_mismatches: self.max_mismatches.unwrap_or(defaults.max_mismatches),
+            scan_reverse_complement: self
+                .scan_reverse_complement
+                .unwrap_or(defaults.scan_reverse_complement),
+            lowercase_mismatches: self
+                .lowercase_mismatches
+                .unwrap_or(defaults.lowercase_mismatches),
+            ..defaults
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct ExpectedHit {
+    start: usize,
+    end: usize,
+    strand: char,
+    mismatches: usize,
+    matched: String,
+}
+
+fn fixture_paths() -> Vec<PathBuf> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/kat");
+    let mut paths = std::fs::read_dir(&dir)
+        .expect("read tests/kat")
+        .map(|entry| entry.expect("read fixture entry").path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect::<Vec<_>>();
+    paths.sort();
+    paths
+}
+
+fn actual_hits(fixture: &Fixture) -> Vec<ExpectedHit> {
+    let primer = Primer::from_name_and_sequence("kat", &fixture.primer).expect("fixture primer");
+    let result = scan_sequence(
+        &fixture.reference_sequence,
+        "kat",
+        &[primer],
+        &fixture.scan_options.to_scan_options(),
+    )
+    .expect("scan fixture");
+    result
+        .hits
+        .into_iter()
+        .map(|hit| ExpectedHit {
+            start: hit.start,
+            end: hit.end,
+            strand: hit.strand,
+            mismatches: hit.mismatches,
+            matched: hit.matched,
+        })
+        .collect()
+}
+
+#[test]
+fn known_answer_fixtures_match_scan_output() {
+    let update = std::env::var_os("PRIMER_SCOUT_UPDATE_KAT").is_some();
+    let paths = fixture_paths();
+    assert!(!paths.is_empty(), "no fixtures under tests/kat");
+
+    for path in paths {
+        let text = std::fs::read_to_string(&path).expect("read fixture");
+        let mut fixture: Fixture = serde_json::from_str(&text)
+            .unwrap_or_else(|err| panic!("invalid fixture {}: {err}", path.display()));
+        let actual = actual_hits(&fixture);
+
+        if update {
+            fixture.expected_hits = actual;
+            let json = serde_json::to_string_pretty(&fixture).expect("serialize fixture");
+            std::fs::write(&path, json + "\n").expect("write fixture");
+            continue;
+        }
+
+        assert_eq!(
+            fixture.expected_hits.len(),
+            actual.len(),
+            "{}: hit count ({})",
+            path.display(),
+            fixture.description
+        );
+        for (idx, (expected, actual)) in fixture.expected_hits.iter().zip(&actual).enumerate() {
+            assert_eq!(
+                expected,
+                actual,
+                "{}: hit {idx} ({})",
+                path.display(),
+                fixture.description
+            );
+        }
+    }
+}
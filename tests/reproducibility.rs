@@ -0,0 +1,58 @@
+//! Same seed, same files: `gen_synthetic` output must be byte-identical across runs and releases.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn generate(dir: &Path, tag: &str, seed: u64) -> (Vec<u8>, Vec<u8>) {
+    let reference = dir.join(format!("{tag}.fa"));
+    let primers = dir.join(format!("{tag}.tsv"));
+    let status = Command::new(env!("CARGO_BIN_EXE_gen_synthetic"))
+        .arg("--reference-out")
+        .arg(&reference)
+        .arg("--primers-out")
+        .arg(&primers)
+        .args(["--bases", "5000", "--primer-count", "8", "--seed"])
+        .arg(seed.to_string())
+        .status()
+        .expect("run gen_synthetic");
+    assert!(status.success());
+    (
+        std::fs::read(reference).expect("read reference"),
+        std::fs::read(primers).expect("read primers"),
+    )
+}
+
+fn temp_dir() -> PathBuf {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("clock should be after unix epoch")
+        .as_nanos();
+    std::env::temp_dir().join(format!("primer_scout_{nanos}_reproducibility"))
+}
+
+#[test]
+fn same_seed_generates_identical_files() {
+    let dir = temp_dir();
+    let first = generate(&dir, "first", 7);
+    let second = generate(&dir, "second", 7);
+    let reseeded = generate(&dir, "reseeded", 8);
+
+    assert_eq!(first, second);
+    assert_ne!(first.0, reseeded.0);
+    assert_ne!(first.1, reseeded.1);
+
+    // Benchmark baselines were generated by earlier releases; their seeds must keep working.
+    let reference = String::from_utf8(first.0).expect("utf8 reference");
+    assert!(
+        reference.starts_with(">synthetic_chr1\nCGAGGGGGTCGAAGGGTACCACGCAGTATC"),
+        "{reference}"
+    );
+    let primers = String::from_utf8(first.1).expect("utf8 primers");
+    assert!(
+        primers.starts_with("name\tsequence\np0001\tCGTCACGGATATGTAGCCTC\n"),
+        "{primers}"
+    );
+
+    std::fs::remove_dir_all(dir).expect("remove temp dir");
+}